@@ -1,5 +1,6 @@
 fn main() {
     let mut config = capnpc::CompilerCommand::new();
+    config.file("proto/disable_register.capnp");
     config.file("proto/fww_register.capnp");
     config.file("proto/gcounter.capnp");
     config.file("proto/gset.capnp");
@@ -136,7 +136,7 @@ async fn main() {
                         );
                     } else {
                         // Check if this is an update or new insert
-                        let exists = map.entries.iter().any(|(k, _)| k == &update.entity.id);
+                        let exists = map.contains_key(&update.entity.id);
                         let action = if exists { "UPDATE" } else { "INSERT" };
                         
                         map.insert(
@@ -330,7 +330,7 @@ async fn game_loop(state: Arc<AppState>) {
 
         // Move Monsters (Dynamic speed based on age)
         // We iterate all monsters every tick, but only move them based on probability
-        if tick_count % 5 == 0 {
+        if tick_count.is_multiple_of(5) {
             // Broadcast Stats
             let stats = StatsUpdate {
                 stats: ServerStats {
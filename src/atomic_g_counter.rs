@@ -0,0 +1,141 @@
+// Copyright (c) 2026 Adrian Robinson. All rights reserved.
+// Licensed under the MIT License. See LICENSE file in the project root for full license information.
+
+use crate::g_counter::{saturate_i64, GCounter};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// AtomicGCounter: A G-Counter that can be incremented through a shared reference.
+///
+/// Intended for hot counters on a single ingest node, where taking a write lock per
+/// increment would serialize every writer. Per-node counts are held in `AtomicI64`s; the
+/// map of nodes is only write-locked the first time a node id is seen.
+///
+/// The vector clock is not maintained on the hot path. Each node only counts its
+/// increments, and [`AtomicGCounter::snapshot`] turns those counts into clock entries
+/// stamped with the snapshot time. Use the snapshot for merging and serialization.
+///
+/// # Example
+///
+/// ```
+/// use crdt_data_types::AtomicGCounter;
+/// use std::sync::Arc;
+///
+/// let counter = Arc::new(AtomicGCounter::new());
+/// let handles: Vec<_> = (0..4)
+///     .map(|_| {
+///         let counter = Arc::clone(&counter);
+///         std::thread::spawn(move || counter.increment("node_a", 1))
+///     })
+///     .collect();
+/// for handle in handles {
+///     handle.join().unwrap();
+/// }
+///
+/// assert_eq!(counter.snapshot().value(), 4);
+/// ```
+#[derive(Debug, Default)]
+pub struct AtomicGCounter {
+    nodes: RwLock<HashMap<String, Arc<NodeSlot>>>,
+}
+
+#[derive(Debug, Default)]
+struct NodeSlot {
+    /// Total increments applied by the node.
+    count: AtomicI64,
+    /// Number of increment operations, i.e. the node's logical clock.
+    ops: AtomicU64,
+}
+
+impl AtomicGCounter {
+    /// Creates a new, empty counter.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Seeds an atomic counter from an existing G-Counter, carrying over its logical clocks.
+    pub fn from_gcounter(counter: &GCounter) -> Self {
+        let nodes = counter
+            .counters
+            .iter()
+            .map(|(node_id, count)| {
+                let ops = counter.vclock.clocks.get(node_id).map_or(0, |(c, _)| *c);
+                let slot = NodeSlot {
+                    count: AtomicI64::new(*count),
+                    ops: AtomicU64::new(ops),
+                };
+                (node_id.clone(), Arc::new(slot))
+            })
+            .collect();
+        Self {
+            nodes: RwLock::new(nodes),
+        }
+    }
+
+    /// Increments the counter for a specific node by a given delta.
+    ///
+    /// Negative deltas are ignored, as in [`GCounter::increment`]. A node's count
+    /// saturates at `i64::MAX`.
+    pub fn increment(&self, node_id: &str, delta: i64) {
+        if delta < 0 {
+            return;
+        }
+        let slot = self.slot(node_id);
+        // Saturate rather than wrap, so an overflowing node never reads as negative
+        let _ = slot
+            .count
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |count| {
+                Some(count.saturating_add(delta))
+            });
+        slot.ops.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Returns the total aggregated value, saturating at the `i64` bounds.
+    ///
+    /// Increments racing with this call may or may not be included.
+    pub fn value(&self) -> i64 {
+        let nodes = self.nodes.read().unwrap_or_else(|e| e.into_inner());
+        let total: i128 = nodes
+            .values()
+            .map(|slot| slot.count.load(Ordering::Relaxed) as i128)
+            .sum();
+        saturate_i64(total)
+    }
+
+    /// Copies the current counts into a regular [`GCounter`] for merging or serialization.
+    pub fn snapshot(&self) -> GCounter {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+
+        let nodes = self.nodes.read().unwrap_or_else(|e| e.into_inner());
+        let mut counter = GCounter::new();
+        for (node_id, slot) in nodes.iter() {
+            counter
+                .counters
+                .push((node_id.clone(), slot.count.load(Ordering::Relaxed)));
+            let ops = slot.ops.load(Ordering::Relaxed);
+            if ops > 0 {
+                counter.vclock.clocks.insert(node_id.clone(), (ops, now));
+            }
+        }
+        counter.counters.sort_by(|a, b| a.0.cmp(&b.0));
+        counter
+    }
+
+    fn slot(&self, node_id: &str) -> Arc<NodeSlot> {
+        if let Some(slot) = self
+            .nodes
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(node_id)
+        {
+            return Arc::clone(slot);
+        }
+        let mut nodes = self.nodes.write().unwrap_or_else(|e| e.into_inner());
+        Arc::clone(nodes.entry(node_id.to_string()).or_default())
+    }
+}
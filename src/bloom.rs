@@ -0,0 +1,90 @@
+// Copyright (c) 2026 Adrian Robinson. All rights reserved.
+// Licensed under the MIT License. See LICENSE file in the project root for full license information.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+/// Local bloom filter used by the sets to answer most negative `contains` queries
+/// without searching their element list.
+///
+/// Never serialized. Elements are only ever added, so a removed element may still test
+/// positive; callers must confirm positives against the real set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct BloomFilter {
+    bits: Vec<u64>,
+    num_bits: u64,
+    num_hashes: u32,
+}
+
+impl BloomFilter {
+    /// Sizes the filter for `expected` elements at a false-positive rate of `fp_rate`,
+    /// clamped to `[1e-9, 0.5]`.
+    pub(crate) fn new(expected: usize, fp_rate: f64) -> Self {
+        let n = expected.max(1) as f64;
+        let p = if fp_rate.is_nan() { 0.5 } else { fp_rate.clamp(1e-9, 0.5) };
+        let ln2 = std::f64::consts::LN_2;
+        let num_bits = ((-n * p.ln()) / (ln2 * ln2)).ceil().max(64.0) as u64;
+        let num_hashes = ((num_bits as f64 / n) * ln2).round().clamp(1.0, 32.0) as u32;
+        Self {
+            bits: vec![0; num_bits.div_ceil(64) as usize],
+            num_bits,
+            num_hashes,
+        }
+    }
+
+    pub(crate) fn insert<T: Hash>(&mut self, item: &T) {
+        for bit in self.positions(item) {
+            self.bits[(bit / 64) as usize] |= 1 << (bit % 64);
+        }
+    }
+
+    /// Returns false if `item` was definitely never inserted.
+    pub(crate) fn might_contain<T: Hash>(&self, item: &T) -> bool {
+        self.positions(item)
+            .all(|bit| self.bits[(bit / 64) as usize] & (1 << (bit % 64)) != 0)
+    }
+
+    /// Adds every element of `other`, if both filters have the same dimensions.
+    ///
+    /// Returns false (leaving `self` unchanged) on a dimension mismatch.
+    pub(crate) fn union(&mut self, other: &Self) -> bool {
+        if self.num_bits != other.num_bits || self.num_hashes != other.num_hashes {
+            return false;
+        }
+        for (word, other_word) in self.bits.iter_mut().zip(&other.bits) {
+            *word |= other_word;
+        }
+        true
+    }
+
+    /// Double hashing: bit `i` is `h1 + i * h2`.
+    fn positions<T: Hash>(&self, item: &T) -> impl Iterator<Item = u64> {
+        let mut hasher = DefaultHasher::new();
+        item.hash(&mut hasher);
+        let h1 = hasher.finish();
+        h1.hash(&mut hasher);
+        let h2 = hasher.finish() | 1;
+        let num_bits = self.num_bits;
+        (0..self.num_hashes as u64).map(move |i| h1.wrapping_add(i.wrapping_mul(h2)) % num_bits)
+    }
+}
+
+/// Brings a set's filter up to date after a merge brought in `elements`.
+///
+/// Unions the other replica's filter when the dimensions match, and otherwise re-adds
+/// every element.
+pub(crate) fn refresh_after_merge<'a, T: Hash + 'a>(
+    filter: &mut Option<BloomFilter>,
+    other: Option<&BloomFilter>,
+    elements: impl Iterator<Item = &'a T>,
+) {
+    let Some(filter) = filter else {
+        return;
+    };
+    if other.is_some_and(|other| filter.union(other)) {
+        return;
+    }
+    for element in elements {
+        filter.insert(element);
+    }
+}
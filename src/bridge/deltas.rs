@@ -3,6 +3,7 @@ use crate::*;
 use crate::enums::CrdtType;
 use crate::deltas::*; 
 use crate::deltas_capnp::delta;
+use crate::sync::CausalMut;
 use serde_json::Value;
 
 /// Apply a delta operation to an existing CRDT state.
@@ -46,12 +47,7 @@ pub fn apply_json_delta(
             let delta_struct: PNCounterDelta = serde_json::from_value(delta.clone())
                 .map_err(|e| CrdtError::InvalidInput(format!("Invalid PNCounter delta: {}", e)))?;
             
-            let amount = match delta_struct {
-                PNCounterDelta::Direct(v) => v,
-                PNCounterDelta::Object { increment } => increment,
-            };
-
-            crdt.increment(node_id, amount);
+            delta_struct.apply(&mut crdt, node_id)?;
             serde_json::to_value(crdt).map_err(|e| CrdtError::InvalidInput(e.to_string()))
         }
         CrdtType::GSet => {
@@ -216,9 +212,21 @@ pub fn apply_json_delta(
             }
             serde_json::to_value(crdt).map_err(|e| CrdtError::InvalidInput(e.to_string()))
         }
+        #[cfg(feature = "probabilistic")]
+        CrdtType::HyperLogLog
+        | CrdtType::CountMinSketch
+        | CrdtType::RoaringBitmap
+        | CrdtType::TDigest
+        | CrdtType::TopK => Err(unsupported_delta(crdt_type)),
     }
 }
 
+/// Sketches merge as whole states and have no delta format.
+#[cfg(feature = "probabilistic")]
+fn unsupported_delta(crdt_type: CrdtType) -> CrdtError {
+    CrdtError::InvalidInput(format!("Deltas are not supported for {}", crdt_type))
+}
+
 /// Apply a JSON delta to a Cap'n Proto binary state, returning new Cap'n Proto bytes.
 pub fn apply_bytes_delta(
         crdt_type: CrdtType,
@@ -257,12 +265,7 @@ pub fn apply_bytes_delta(
             let delta_struct: PNCounterDelta = serde_json::from_value(delta.clone())
                 .map_err(|e| CrdtError::InvalidInput(format!("Invalid PNCounter delta: {}", e)))?;
 
-            let amount = match delta_struct {
-                PNCounterDelta::Direct(v) => v,
-                PNCounterDelta::Object { increment } => increment,
-            };
-
-            crdt.increment(node_id, amount);
+            delta_struct.apply(&mut crdt, node_id)?;
             Ok(crdt.to_capnp_bytes())
         }
         CrdtType::GSet => {
@@ -428,6 +431,12 @@ pub fn apply_bytes_delta(
             }
                 Ok(crdt.to_capnp_bytes())
         }
+        #[cfg(feature = "probabilistic")]
+        CrdtType::HyperLogLog
+        | CrdtType::CountMinSketch
+        | CrdtType::RoaringBitmap
+        | CrdtType::TDigest
+        | CrdtType::TopK => Err(unsupported_delta(crdt_type)),
     }
 }
 
@@ -898,5 +907,141 @@ pub fn apply_batch_capnp_deltas(
             }
             Ok(crdt.to_capnp_bytes())
         }
+        #[cfg(feature = "probabilistic")]
+        CrdtType::HyperLogLog
+        | CrdtType::CountMinSketch
+        | CrdtType::RoaringBitmap
+        | CrdtType::TDigest
+        | CrdtType::TopK => Err(unsupported_delta(crdt_type)),
+    }
+}
+
+/// Apply a Cap'n Proto delta at most once and in order, using the dot it carries.
+///
+/// A delta stamped with `dotNode`/`dotCounter` names the first vclock counter of that node
+/// it consumes, so it must be exactly one past the counter the state has reached:
+///
+/// - a dot the state already covers is a redelivery, and the state is returned unchanged,
+///   which makes at-least-once delivery safe;
+/// - a dot further ahead means an earlier delta is missing, and is rejected with
+///   `CrdtError::InvalidInput` so it can be retried once the gap is filled.
+///
+/// Applying a delta always covers its dot, even when its operations tick nothing (such as
+/// removing an absent element). A delta whose operations tick several times (one per
+/// item) consumes that many counters, and the next delta's dot follows the last of them.
+///
+/// The dot's node must be `node_id`, since applying the delta advances that node's clock.
+/// Deltas without a dot are applied as by [`apply_capnp_delta`].
+pub fn apply_capnp_delta_idempotent(
+    crdt_type: CrdtType,
+    current_state_bytes: Option<&[u8]>,
+    delta_bytes: &[u8],
+    node_id: &str,
+) -> Result<Vec<u8>, CrdtError> {
+    let mut delta_slice = delta_bytes;
+    let message_reader = capnp::serialize::read_message(
+        &mut delta_slice,
+        capnp::message::ReaderOptions::new()
+    ).map_err(|e| CrdtError::Deserialization(e.to_string()))?;
+    let delta_reader = message_reader.get_root::<delta::Reader>()
+        .map_err(|e| CrdtError::Deserialization(e.to_string()))?;
+
+    let dot_counter = delta_reader.get_dot_counter();
+    if dot_counter == 0 {
+        return apply_capnp_delta(crdt_type, current_state_bytes, delta_bytes, node_id);
+    }
+    let dot_node = delta_reader
+        .get_dot_node()
+        .map_err(|e| CrdtError::Deserialization(e.to_string()))?
+        .to_str()
+        .map_err(|e| CrdtError::Deserialization(e.to_string()))?;
+    if dot_node != node_id {
+        return Err(CrdtError::InvalidInput(format!(
+            "Delta dot node {} does not match applying node {}",
+            dot_node, node_id
+        )));
+    }
+
+    match crdt_type {
+        CrdtType::GCounter => apply_dotted::<GCounter>(crdt_type, current_state_bytes, delta_bytes, node_id, dot_counter),
+        CrdtType::PNCounter => apply_dotted::<PNCounter>(crdt_type, current_state_bytes, delta_bytes, node_id, dot_counter),
+        CrdtType::GSet => apply_dotted::<GSet<String>>(crdt_type, current_state_bytes, delta_bytes, node_id, dot_counter),
+        CrdtType::ORSet => apply_dotted::<ORSet<String>>(crdt_type, current_state_bytes, delta_bytes, node_id, dot_counter),
+        CrdtType::LWWSet => apply_dotted::<LWWSet<String>>(crdt_type, current_state_bytes, delta_bytes, node_id, dot_counter),
+        CrdtType::LWWRegister => apply_dotted::<LWWRegister<String>>(crdt_type, current_state_bytes, delta_bytes, node_id, dot_counter),
+        CrdtType::FWWRegister => apply_dotted::<FWWRegister<String>>(crdt_type, current_state_bytes, delta_bytes, node_id, dot_counter),
+        CrdtType::MVRegister => apply_dotted::<MVRegister<String>>(crdt_type, current_state_bytes, delta_bytes, node_id, dot_counter),
+        CrdtType::LWWMap => apply_dotted::<LWWMap<String, String>>(crdt_type, current_state_bytes, delta_bytes, node_id, dot_counter),
+        CrdtType::ORMap => apply_dotted::<ORMap<String, String>>(crdt_type, current_state_bytes, delta_bytes, node_id, dot_counter),
+        #[cfg(feature = "probabilistic")]
+        CrdtType::HyperLogLog
+        | CrdtType::CountMinSketch
+        | CrdtType::RoaringBitmap
+        | CrdtType::TDigest
+        | CrdtType::TopK => Err(unsupported_delta(crdt_type)),
+    }
+}
+
+/// Applies a delta whose dot is `(node_id, dot_counter)` to a state decoded as `C`.
+fn apply_dotted<C: Crdt + CausalMut>(
+    crdt_type: CrdtType,
+    current_state_bytes: Option<&[u8]>,
+    delta_bytes: &[u8],
+    node_id: &str,
+    dot_counter: u64,
+) -> Result<Vec<u8>, CrdtError> {
+    let seen = match current_state_bytes {
+        Some(bytes) => C::from_capnp_bytes(bytes)?.vclock().clocks.get(node_id).map_or(0, |(c, _)| *c),
+        None => 0,
+    };
+    if dot_counter <= seen {
+        // Duplicate delivery; re-encode so the result matches a normal apply's output
+        return apply_batch_capnp_deltas(crdt_type, current_state_bytes, &[], node_id);
+    }
+    if dot_counter > seen + 1 {
+        return Err(CrdtError::InvalidInput(format!(
+            "Delta dot {}:{} is out of order: the state has only reached {}",
+            node_id, dot_counter, seen
+        )));
+    }
+
+    let applied = apply_capnp_delta(crdt_type, current_state_bytes, delta_bytes, node_id)?;
+    let mut state = C::from_capnp_bytes(&applied)?;
+    if state.vclock().covers(node_id, dot_counter) {
+        return Ok(applied);
+    }
+    // The delta ticked nothing; record it so a redelivery is still recognised
+    state.vclock_mut().increment(node_id);
+    Ok(state.to_capnp_bytes())
+}
+
+/// Resulting state bytes plus the `(index, error)` of each delta rejected by a lenient batch.
+pub type LenientBatchResult = (Vec<u8>, Vec<(usize, CrdtError)>);
+
+/// Apply a batch of Cap'n Proto deltas, skipping (rather than aborting on) invalid ones.
+///
+/// Returns the state after applying every valid delta, plus the index and error of each
+/// delta that was rejected. Each delta is applied atomically: a rejected delta leaves no
+/// partial effect. Errors decoding `current_state_bytes` itself still fail the whole call.
+pub fn apply_batch_capnp_deltas_lenient(
+    crdt_type: CrdtType,
+    current_state_bytes: Option<&[u8]>,
+    deltas_bytes: &[&[u8]],
+    node_id: &str,
+) -> Result<LenientBatchResult, CrdtError> {
+    // Fast path: the whole batch is valid
+    if let Ok(state) = apply_batch_capnp_deltas(crdt_type, current_state_bytes, deltas_bytes, node_id) {
+        return Ok((state, Vec::new()));
+    }
+
+    // Normalise the starting state so a bad initial state surfaces as an error here
+    let mut state = apply_batch_capnp_deltas(crdt_type, current_state_bytes, &[], node_id)?;
+    let mut failures = Vec::new();
+    for (idx, delta_bytes) in deltas_bytes.iter().enumerate() {
+        match apply_capnp_delta(crdt_type, Some(&state), delta_bytes, node_id) {
+            Ok(next) => state = next,
+            Err(e) => failures.push((idx, e)),
+        }
     }
+    Ok((state, failures))
 }
@@ -0,0 +1,85 @@
+// Copyright (c) 2026 Adrian Robinson. All rights reserved.
+// Licensed under the MIT License. See LICENSE file in the project root for full license information.
+
+//! Flat per-node JSON for counters.
+//!
+//! The canonical counter JSON nests per-node totals under `counters` next to a `vclock`.
+//! Front-ends usually want just `{"node_a": 10, "node_b": 5}`. The flat form is a view:
+//! it drops the vclock, and for `PNCounter` it holds each node's net value, so
+//! `from_flat_json` builds a fresh state rather than inverting `to_flat_json`.
+
+use crate::enums::CrdtType;
+use crate::traits::CrdtError;
+use crate::{GCounter, PNCounter};
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashMap};
+
+/// Converts canonical `GCounter` or `PNCounter` JSON to a flat `{node: count}` object.
+///
+/// `PNCounter` nodes map to their net value (increments minus decrements).
+pub fn to_flat_json(crdt_type: CrdtType, json_value: &Value) -> Result<Value, CrdtError> {
+    let totals: BTreeMap<String, i64> = match crdt_type {
+        CrdtType::GCounter => parse::<GCounter>(json_value)?.counters.into_iter().collect(),
+        CrdtType::PNCounter => {
+            let pn = parse::<PNCounter>(json_value)?;
+            let mut totals: BTreeMap<String, i64> = pn.positive.counters.into_iter().collect();
+            for (node_id, count) in pn.negative.counters {
+                let total = totals.entry(node_id).or_insert(0);
+                *total = total.saturating_sub(count);
+            }
+            totals
+        }
+        _ => return Err(unsupported(crdt_type)),
+    };
+    Ok(Value::Object(
+        totals.into_iter().map(|(node_id, count)| (node_id, Value::from(count))).collect(),
+    ))
+}
+
+/// Builds canonical `GCounter` or `PNCounter` JSON from a flat `{node: count}` object.
+///
+/// Each node's vclock entry is advanced once. `GCounter` counts must be non-negative;
+/// `PNCounter` counts are split into increments and decrements by sign.
+pub fn from_flat_json(crdt_type: CrdtType, flat: &Value) -> Result<Value, CrdtError> {
+    let counts = flat_counts(flat)?;
+    let crdt = match crdt_type {
+        CrdtType::GCounter => serde_json::to_value(GCounter::from_node_counts(counts)?),
+        CrdtType::PNCounter => {
+            let (positive, negative): (HashMap<_, _>, HashMap<_, _>) =
+                counts.into_iter().partition(|(_, count)| *count >= 0);
+            let negative = negative
+                .into_iter()
+                .map(|(node_id, count)| (node_id, count.saturating_neg()))
+                .collect();
+            serde_json::to_value(PNCounter::from_node_counts(positive, negative)?)
+        }
+        _ => return Err(unsupported(crdt_type)),
+    };
+    crdt.map_err(|e| CrdtError::Serialization(e.to_string()))
+}
+
+fn parse<T: serde::de::DeserializeOwned>(json_value: &Value) -> Result<T, CrdtError> {
+    T::deserialize(json_value)
+        .map_err(|e| CrdtError::InvalidInput(format!("JSON parse error: {}", e)))
+}
+
+fn flat_counts(flat: &Value) -> Result<HashMap<String, i64>, CrdtError> {
+    let object: &Map<String, Value> = flat.as_object().ok_or_else(|| {
+        CrdtError::InvalidInput(format!("Expected a flat object of node counts, got {}", flat))
+    })?;
+    object
+        .iter()
+        .map(|(node_id, count)| {
+            count.as_i64().map(|count| (node_id.clone(), count)).ok_or_else(|| {
+                CrdtError::InvalidInput(format!(
+                    "Expected an integer count for node {}, got {}",
+                    node_id, count
+                ))
+            })
+        })
+        .collect()
+}
+
+fn unsupported(crdt_type: CrdtType) -> CrdtError {
+    CrdtError::InvalidInput(format!("Flat JSON is not supported for {}", crdt_type))
+}
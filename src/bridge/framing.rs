@@ -0,0 +1,108 @@
+// Copyright (c) 2026 Adrian Robinson. All rights reserved.
+// Licensed under the MIT License. See LICENSE file in the project root for full license information.
+
+//! Self-describing envelope for Cap'n Proto CRDT blobs.
+//!
+//! Cap'n Proto roots carry no schema identifier, so a blob separated from its type tag
+//! cannot be decoded. A framed blob prefixes the payload with a small header:
+//!
+//! ```text
+//! b"CRDT" | version (1 byte) | type discriminant (1 byte) | payload
+//! ```
+
+use crate::enums::CrdtType;
+use crate::traits::CrdtError;
+
+const MAGIC: &[u8; 4] = b"CRDT";
+const VERSION: u8 = 1;
+const HEADER_LEN: usize = MAGIC.len() + 2;
+
+/// Stable on-the-wire discriminant for each type. Never renumber.
+fn discriminant(crdt_type: CrdtType) -> u8 {
+    match crdt_type {
+        CrdtType::GCounter => 1,
+        CrdtType::PNCounter => 2,
+        CrdtType::GSet => 3,
+        CrdtType::ORSet => 4,
+        CrdtType::LWWSet => 5,
+        CrdtType::LWWRegister => 6,
+        CrdtType::FWWRegister => 7,
+        CrdtType::MVRegister => 8,
+        CrdtType::LWWMap => 9,
+        CrdtType::ORMap => 10,
+        #[cfg(feature = "probabilistic")]
+        CrdtType::HyperLogLog => 11,
+        #[cfg(feature = "probabilistic")]
+        CrdtType::CountMinSketch => 12,
+        #[cfg(feature = "probabilistic")]
+        CrdtType::RoaringBitmap => 13,
+        #[cfg(feature = "probabilistic")]
+        CrdtType::TDigest => 14,
+        #[cfg(feature = "probabilistic")]
+        CrdtType::TopK => 15,
+    }
+}
+
+fn from_discriminant(tag: u8) -> Result<CrdtType, CrdtError> {
+    Ok(match tag {
+        1 => CrdtType::GCounter,
+        2 => CrdtType::PNCounter,
+        3 => CrdtType::GSet,
+        4 => CrdtType::ORSet,
+        5 => CrdtType::LWWSet,
+        6 => CrdtType::LWWRegister,
+        7 => CrdtType::FWWRegister,
+        8 => CrdtType::MVRegister,
+        9 => CrdtType::LWWMap,
+        10 => CrdtType::ORMap,
+        #[cfg(feature = "probabilistic")]
+        11 => CrdtType::HyperLogLog,
+        #[cfg(feature = "probabilistic")]
+        12 => CrdtType::CountMinSketch,
+        #[cfg(feature = "probabilistic")]
+        13 => CrdtType::RoaringBitmap,
+        #[cfg(feature = "probabilistic")]
+        14 => CrdtType::TDigest,
+        #[cfg(feature = "probabilistic")]
+        15 => CrdtType::TopK,
+        _ => {
+            return Err(CrdtError::Deserialization(format!(
+                "Unknown framed CRDT type discriminant: {}",
+                tag
+            )))
+        }
+    })
+}
+
+/// Wraps Cap'n Proto bytes in a header identifying their CRDT type.
+pub fn frame(crdt_type: CrdtType, payload: &[u8]) -> Vec<u8> {
+    let mut framed = Vec::with_capacity(HEADER_LEN + payload.len());
+    framed.extend_from_slice(MAGIC);
+    framed.push(VERSION);
+    framed.push(discriminant(crdt_type));
+    framed.extend_from_slice(payload);
+    framed
+}
+
+/// Splits a framed blob into its CRDT type and the Cap'n Proto payload.
+pub fn unframe(bytes: &[u8]) -> Result<(CrdtType, &[u8]), CrdtError> {
+    if bytes.len() < HEADER_LEN || &bytes[..MAGIC.len()] != MAGIC {
+        return Err(CrdtError::Deserialization(
+            "Not a framed CRDT blob".to_string(),
+        ));
+    }
+    let version = bytes[MAGIC.len()];
+    if version != VERSION {
+        return Err(CrdtError::Deserialization(format!(
+            "Unsupported CRDT frame version: {}",
+            version
+        )));
+    }
+    let crdt_type = from_discriminant(bytes[MAGIC.len() + 1])?;
+    Ok((crdt_type, &bytes[HEADER_LEN..]))
+}
+
+/// Reads the CRDT type from a framed blob's header without touching the payload.
+pub fn detect_type(bytes: &[u8]) -> Result<CrdtType, CrdtError> {
+    unframe(bytes).map(|(crdt_type, _)| crdt_type)
+}
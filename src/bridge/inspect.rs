@@ -0,0 +1,67 @@
+// Copyright (c) 2026 Adrian Robinson. All rights reserved.
+// Licensed under the MIT License. See LICENSE file in the project root for full license information.
+
+use crate::traits::CrdtError;
+use capnp::message::{ReaderOptions, ReaderSegments};
+use capnp::serialize;
+
+/// Size metrics for a Cap'n Proto message, gathered without decoding it into a CRDT.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MessageInfo {
+    /// Number of segments in the message.
+    pub segment_count: usize,
+    /// Total words across all segments, as framed on the wire.
+    pub total_words: usize,
+    /// Words reachable from the root, i.e. what a full traversal reads.
+    pub traversed_words: u64,
+    /// Whether a full traversal fits within `ReaderOptions::new()`'s traversal limit.
+    pub within_default_traversal_limit: bool,
+}
+
+/// How many times its own size a message may cost to traverse while being measured.
+///
+/// Without aliased pointers a traversal reads each word at most once; the slack covers
+/// pointer overhead while keeping a hostile message from making `inspect` walk far more
+/// data than it was handed.
+const MAX_TRAVERSAL_AMPLIFICATION: usize = 8;
+
+/// Inspects a Cap'n Proto message's segments and traversal cost.
+///
+/// The message is walked once under a limit of `MAX_TRAVERSAL_AMPLIFICATION` times its
+/// size to measure it (a message over that limit is an error), then once under the
+/// default limit to check it would be accepted by the crate's readers.
+pub fn inspect(bytes: &[u8]) -> Result<MessageInfo, CrdtError> {
+    let mut bounded = ReaderOptions::new();
+    bounded.traversal_limit_in_words(Some(
+        (bytes.len() / 8 + 1).saturating_mul(MAX_TRAVERSAL_AMPLIFICATION),
+    ));
+    let reader = serialize::read_message(bytes, bounded)
+        .map_err(|e| CrdtError::Deserialization(e.to_string()))?;
+    let traversed_words = reader
+        .get_root::<capnp::any_pointer::Reader>()
+        .and_then(|root| root.target_size())
+        .map_err(|e| CrdtError::Deserialization(e.to_string()))?
+        .word_count;
+
+    let segments = reader.into_segments();
+    let segment_count = segments.len();
+    let total_words = (0..segment_count as u32)
+        .filter_map(|id| segments.get_segment(id))
+        .map(|segment| segment.len() / 8)
+        .sum();
+
+    let within_default_traversal_limit = serialize::read_message(bytes, ReaderOptions::new())
+        .and_then(|limited| {
+            limited
+                .get_root::<capnp::any_pointer::Reader>()
+                .and_then(|root| root.target_size())
+        })
+        .is_ok();
+
+    Ok(MessageInfo {
+        segment_count,
+        total_words,
+        traversed_words,
+        within_default_traversal_limit,
+    })
+}
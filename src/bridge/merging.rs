@@ -1,6 +1,7 @@
 use crate::traits::{CrdtError};
 use crate::*;
 use crate::enums::CrdtType;
+use super::shape::check_shape;
 use serde_json::Value;
 
 /// Merges multiple JSON values representing CRDT states into a single JSON value.
@@ -8,6 +9,9 @@ pub fn merge_json_values(crdt_type: CrdtType, values: &[Value]) -> Result<Value,
     if values.is_empty() {
         return Ok(Value::Null);
     }
+    for value in values {
+        check_shape(crdt_type, value)?;
+    }
 
     match crdt_type {
         CrdtType::GCounter => {
@@ -110,9 +114,36 @@ pub fn merge_json_values(crdt_type: CrdtType, values: &[Value]) -> Result<Value,
             }
             serde_json::to_value(base).map_err(|e| CrdtError::InvalidInput(e.to_string()))
         }
+        #[cfg(feature = "probabilistic")]
+        CrdtType::HyperLogLog => merge_sketch_json::<HyperLogLog>(values),
+        #[cfg(feature = "probabilistic")]
+        CrdtType::CountMinSketch => merge_sketch_json::<CountMinSketch>(values),
+        #[cfg(feature = "probabilistic")]
+        CrdtType::RoaringBitmap => merge_sketch_json::<RoaringBitmap>(values),
+        #[cfg(feature = "probabilistic")]
+        CrdtType::TDigest => merge_sketch_json::<TDigest>(values),
+        #[cfg(feature = "probabilistic")]
+        CrdtType::TopK => merge_sketch_json::<TopK>(values),
     }
 }
 
+/// Merges sketches through their Cap'n Proto readers, which report mismatched dimensions
+/// as `CrdtError::Merge` where the in-place `merge` methods would panic.
+#[cfg(feature = "probabilistic")]
+fn merge_sketch_json<C: Crdt>(values: &[Value]) -> Result<Value, CrdtError> {
+    let buffers = values
+        .iter()
+        .map(|val| {
+            serde_json::from_value::<C>(val.clone())
+                .map(|crdt| crdt.to_capnp_bytes())
+                .map_err(|e| CrdtError::InvalidInput(e.to_string()))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let readers: Vec<_> = buffers.iter().map(|b| C::Reader::from_bytes(b)).collect();
+    let merged = C::merge_from_readers(&readers)?;
+    serde_json::to_value(merged).map_err(|e| CrdtError::InvalidInput(e.to_string()))
+}
+
 /// Additively merge accumulated delta state into current state.
 /// For counters: adds values using add_state.
 /// For sets and registers: falls back to standard merge (union/LWW).
@@ -4,6 +4,15 @@
 pub mod serialization;
 pub mod merging;
 pub mod deltas;
+pub mod flat;
+pub mod framing;
+pub mod inspect;
+pub mod query;
+pub mod shape;
+pub mod schema;
+
+pub use inspect::MessageInfo;
+pub use serialization::to_json;
 
 use crate::enums::CrdtType;
 use crate::traits::CrdtError;
@@ -90,6 +99,17 @@ impl SerdeCapnpBridge {
         deltas::apply_capnp_delta(crdt_type, current_state_bytes, delta_bytes, node_id)
     }
 
+    /// Apply a Cap'n Proto delta at most once, skipping it if the state already observed
+    /// its dot.
+    pub fn apply_capnp_delta_idempotent(
+        crdt_type: CrdtType,
+        current_state_bytes: Option<&[u8]>,
+        delta_bytes: &[u8],
+        node_id: &str,
+    ) -> Result<Vec<u8>, CrdtError> {
+        deltas::apply_capnp_delta_idempotent(crdt_type, current_state_bytes, delta_bytes, node_id)
+    }
+
     /// Apply a batch of Cap'n Proto deltas to a Cap'n Proto binary state.
     pub fn apply_batch_capnp_deltas(
         crdt_type: CrdtType,
@@ -99,4 +119,69 @@ impl SerdeCapnpBridge {
     ) -> Result<Vec<u8>, CrdtError> {
         deltas::apply_batch_capnp_deltas(crdt_type, current_state_bytes, deltas_bytes, node_id)
     }
+
+    /// Apply a batch of Cap'n Proto deltas, collecting per-delta failures instead of aborting.
+    ///
+    /// Returns the resulting state and the `(index, error)` of each rejected delta.
+    pub fn apply_batch_capnp_deltas_lenient(
+        crdt_type: CrdtType,
+        current_state_bytes: Option<&[u8]>,
+        deltas_bytes: &[&[u8]],
+        node_id: &str,
+    ) -> Result<deltas::LenientBatchResult, CrdtError> {
+        deltas::apply_batch_capnp_deltas_lenient(crdt_type, current_state_bytes, deltas_bytes, node_id)
+    }
+
+    /// Wraps Cap'n Proto bytes in a self-describing header carrying the CRDT type.
+    pub fn frame(crdt_type: CrdtType, capnp_bytes: &[u8]) -> Vec<u8> {
+        framing::frame(crdt_type, capnp_bytes)
+    }
+
+    /// Splits a framed blob into its CRDT type and the Cap'n Proto payload.
+    pub fn unframe(bytes: &[u8]) -> Result<(CrdtType, &[u8]), CrdtError> {
+        framing::unframe(bytes)
+    }
+
+    /// Detects the CRDT type of a framed blob (see [`SerdeCapnpBridge::frame`]).
+    ///
+    /// Unframed Cap'n Proto bytes cannot be identified, since message roots carry no
+    /// schema id; those are rejected with `CrdtError::Deserialization`.
+    pub fn detect_type(bytes: &[u8]) -> Result<CrdtType, CrdtError> {
+        framing::detect_type(bytes)
+    }
+
+    /// Reports segment count, size and traversal cost of a Cap'n Proto message without
+    /// building the CRDT, e.g. to tune `ReaderOptions`.
+    pub fn inspect(bytes: &[u8]) -> Result<MessageInfo, CrdtError> {
+        inspect::inspect(bytes)
+    }
+
+    /// Returns true if the `GSet` or `ORSet` in `bytes` contains `element`, without
+    /// converting the whole set to JSON.
+    pub fn set_contains(crdt_type: CrdtType, bytes: &[u8], element: &Value) -> Result<bool, CrdtError> {
+        query::set_contains(crdt_type, bytes, element)
+    }
+
+    /// Returns the value under `key` in the `LWWMap` in `bytes`, without converting the
+    /// whole map to JSON.
+    pub fn map_get(crdt_type: CrdtType, bytes: &[u8], key: &Value) -> Result<Option<Value>, CrdtError> {
+        query::map_get(crdt_type, bytes, key)
+    }
+
+    /// Converts canonical `GCounter` or `PNCounter` JSON to a flat `{node: count}` object,
+    /// dropping the vclock. `PNCounter` nodes map to their net value.
+    pub fn to_flat_json(crdt_type: CrdtType, json_value: &Value) -> Result<Value, CrdtError> {
+        flat::to_flat_json(crdt_type, json_value)
+    }
+
+    /// Builds canonical `GCounter` or `PNCounter` JSON from a flat `{node: count}` object.
+    pub fn from_flat_json(crdt_type: CrdtType, flat: &Value) -> Result<Value, CrdtError> {
+        flat::from_flat_json(crdt_type, flat)
+    }
+
+    /// Returns a JSON Schema document describing the JSON this bridge accepts for
+    /// `crdt_type`, e.g. for validating request bodies at an API gateway.
+    pub fn json_schema(crdt_type: CrdtType) -> Value {
+        schema::json_schema(crdt_type)
+    }
 }
@@ -0,0 +1,51 @@
+// Copyright (c) 2026 Adrian Robinson. All rights reserved.
+// Licensed under the MIT License. See LICENSE file in the project root for full license information.
+
+//! Point lookups on serialized CRDTs.
+//!
+//! `capnp_bytes_to_json` rebuilds the whole CRDT to answer any question about it. These
+//! answer a single membership or key lookup straight from the bytes, decoding entries
+//! one at a time and never touching dots, vclocks or non-matching values.
+
+use crate::enums::CrdtType;
+use crate::traits::CrdtError;
+use crate::{GSetReader, LWWMapReader, ORSetReader};
+use serde_json::Value;
+
+/// Returns true if the set in `bytes` contains `element`.
+///
+/// Supports `GSet` and `ORSet`, whose bridge elements are strings.
+pub fn set_contains(crdt_type: CrdtType, bytes: &[u8], element: &Value) -> Result<bool, CrdtError> {
+    let element = string_arg(element, "element")?;
+    match crdt_type {
+        CrdtType::GSet => GSetReader::<String>::new(bytes).contains(&element),
+        CrdtType::ORSet => ORSetReader::<String>::new(bytes).contains(&element),
+        _ => Err(CrdtError::InvalidInput(format!(
+            "Membership queries are not supported for {}",
+            crdt_type
+        ))),
+    }
+}
+
+/// Returns the value stored under `key` in the map in `bytes`, or `None` if absent.
+///
+/// Supports `LWWMap`, whose bridge keys and values are strings.
+pub fn map_get(crdt_type: CrdtType, bytes: &[u8], key: &Value) -> Result<Option<Value>, CrdtError> {
+    let key = string_arg(key, "key")?;
+    match crdt_type {
+        CrdtType::LWWMap => Ok(LWWMapReader::<String, String>::new(bytes)
+            .get(&key)?
+            .map(Value::String)),
+        _ => Err(CrdtError::InvalidInput(format!(
+            "Key lookups are not supported for {}",
+            crdt_type
+        ))),
+    }
+}
+
+fn string_arg(value: &Value, name: &str) -> Result<String, CrdtError> {
+    value
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| CrdtError::InvalidInput(format!("Expected a string {}, got {}", name, value)))
+}
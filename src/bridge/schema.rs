@@ -0,0 +1,234 @@
+// Copyright (c) 2026 Adrian Robinson. All rights reserved.
+// Licensed under the MIT License. See LICENSE file in the project root for full license information.
+
+//! JSON Schema documents for the bridge's JSON representations.
+//!
+//! The schemas describe the same shapes the bridge accepts (elements, keys and values are
+//! strings) so that gateways can validate request bodies before they reach the bridge.
+//! Fields that serde fills with defaults are optional; the CRDT-specific rules enforced by
+//! `validate_json` (e.g. dots covered by the vclock) are beyond what a schema can express.
+
+use crate::enums::CrdtType;
+use serde_json::{json, Value};
+
+const DRAFT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// Returns a JSON Schema (draft 2020-12) document for `crdt_type`'s JSON representation.
+///
+/// The probabilistic sketches are described only as objects, since their fields are
+/// implementation details of the underlying sketch crates.
+pub fn json_schema(crdt_type: CrdtType) -> Value {
+    let body = match crdt_type {
+        CrdtType::GCounter => gcounter(),
+        CrdtType::PNCounter => object(
+            json!({
+                "positive": reference("GCounter"),
+                "negative": reference("GCounter"),
+                "vclock": reference("VectorClock"),
+            }),
+            &["positive", "negative"],
+        ),
+        CrdtType::GSet => object(
+            json!({
+                "elements": { "type": "array", "items": { "type": "string" } },
+                "vclock": reference("VectorClock"),
+            }),
+            &["elements"],
+        ),
+        CrdtType::ORSet => orset(json!({ "type": "string" })),
+        CrdtType::LWWSet => object(
+            json!({
+                "add_set": lww_set_side(),
+                "remove_set": lww_set_side(),
+                "vclock": reference("VectorClock"),
+                "tie_break": reference("TieBreak"),
+                "tie_bias": { "enum": ["AddWins", "RemoveWins"] },
+            }),
+            &["add_set", "remove_set"],
+        ),
+        CrdtType::LWWRegister | CrdtType::FWWRegister => {
+            let mut properties = json!({
+                "value": { "type": "string" },
+                "timestamp": { "type": "integer", "minimum": 0 },
+                "node_id": { "type": "string" },
+                "vclock": reference("VectorClock"),
+            });
+            if crdt_type == CrdtType::LWWRegister {
+                properties["tie_break"] = reference("TieBreak");
+            }
+            object(properties, &["value", "timestamp", "node_id"])
+        }
+        CrdtType::MVRegister => object(
+            json!({
+                "entries": {
+                    "type": "object",
+                    "description": "Concurrent values, each with the dots that wrote it",
+                    "additionalProperties": reference("Dots"),
+                },
+                "vclock": reference("VectorClock"),
+            }),
+            &["entries"],
+        ),
+        CrdtType::LWWMap => object(
+            json!({
+                "entries": {
+                    "type": "object",
+                    "description": "Key to [value, timestamp, node_id]",
+                    "additionalProperties": tuple(&[
+                        json!({ "type": "string" }),
+                        json!({ "type": "integer", "minimum": 0 }),
+                        json!({ "type": "string" }),
+                    ]),
+                },
+                "vclock": reference("VectorClock"),
+                "ties": {
+                    "type": "array",
+                    "items": tuple(&[
+                        json!({ "type": "string" }),
+                        json!({ "type": "array", "items": { "type": "string" } }),
+                    ]),
+                },
+                "multi_value": { "type": "boolean" },
+                "tie_break": reference("TieBreak"),
+                "expired": {
+                    "type": "array",
+                    "items": tuple(&[
+                        json!({ "type": "string" }),
+                        json!({ "type": "integer", "minimum": 0 }),
+                    ]),
+                },
+                "ttl": object(
+                    json!({
+                        "secs": { "type": "integer", "minimum": 0 },
+                        "nanos": { "type": "integer", "minimum": 0 },
+                    }),
+                    &["secs", "nanos"],
+                ),
+            }),
+            &["entries"],
+        ),
+        CrdtType::ORMap => object(
+            json!({
+                "elements": orset(tuple(&[json!({ "type": "string" }), json!({ "type": "string" })])),
+                "vclock": reference("VectorClock"),
+                "key_clocks": {
+                    "type": "array",
+                    "description": "[key, vclock] pairs, sorted by key",
+                    "items": tuple(&[json!({ "type": "string" }), reference("VectorClock")]),
+                },
+            }),
+            &["elements"],
+        ),
+        #[cfg(feature = "probabilistic")]
+        CrdtType::HyperLogLog
+        | CrdtType::CountMinSketch
+        | CrdtType::RoaringBitmap
+        | CrdtType::TDigest
+        | CrdtType::TopK => json!({ "type": "object" }),
+    };
+
+    let mut schema = json!({
+        "$schema": DRAFT,
+        "title": crdt_type.to_string(),
+        "$defs": definitions(),
+    });
+    if let (Value::Object(schema), Value::Object(body)) = (&mut schema, body) {
+        schema.extend(body);
+    }
+    schema
+}
+
+/// Shared definitions, referenced as `#/$defs/<name>`.
+fn definitions() -> Value {
+    json!({
+        "VectorClock": object(
+            json!({
+                "clocks": {
+                    "type": "object",
+                    "description": "Node id to [logical counter, epoch seconds]",
+                    "additionalProperties": tuple(&[
+                        json!({ "type": "integer", "minimum": 0 }),
+                        json!({ "type": "integer", "minimum": 0 }),
+                    ]),
+                },
+                "logical_only": { "type": "boolean" },
+            }),
+            &["clocks"],
+        ),
+        "GCounter": gcounter(),
+        "Dots": {
+            "type": "array",
+            "description": "[node_id, counter] dots",
+            "items": tuple(&[
+                json!({ "type": "string" }),
+                json!({ "type": "integer", "minimum": 0 }),
+            ]),
+        },
+        "TieBreak": { "enum": ["HigherNodeWins", "LowerNodeWins"] },
+    })
+}
+
+fn gcounter() -> Value {
+    object(
+        json!({
+            "counters": {
+                "type": "object",
+                "additionalProperties": { "type": "integer" },
+            },
+            "vclock": reference("VectorClock"),
+        }),
+        &["counters"],
+    )
+}
+
+/// An OR-Set over elements matching `element`; ORMap nests one over `[key, value]` pairs.
+fn orset(element: Value) -> Value {
+    let entries = json!({
+        "type": "array",
+        "items": object(
+            json!({ "element": element, "observations": reference("Dots") }),
+            &["element", "observations"],
+        ),
+    });
+    object(
+        json!({
+            "elements": entries,
+            "vclock": reference("VectorClock"),
+            "policy": { "enum": ["AddWins", "RemoveWins"] },
+            "removals": entries,
+        }),
+        &["elements"],
+    )
+}
+
+fn lww_set_side() -> Value {
+    json!({
+        "type": "object",
+        "description": "Element to [timestamp, node_id]",
+        "additionalProperties": tuple(&[
+            json!({ "type": "integer", "minimum": 0 }),
+            json!({ "type": "string" }),
+        ]),
+    })
+}
+
+fn object(properties: Value, required: &[&str]) -> Value {
+    json!({
+        "type": "object",
+        "properties": properties,
+        "required": required,
+    })
+}
+
+fn tuple(items: &[Value]) -> Value {
+    json!({
+        "type": "array",
+        "prefixItems": items,
+        "items": false,
+        "minItems": items.len(),
+    })
+}
+
+fn reference(name: &str) -> Value {
+    json!({ "$ref": format!("#/$defs/{}", name) })
+}
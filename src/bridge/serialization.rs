@@ -1,80 +1,79 @@
-use crate::traits::{Crdt, CrdtError};
+use crate::trace::{self, ElementCount};
+use crate::traits::{Crdt, CrdtError, CrdtKind};
 use crate::*;
 use crate::enums::CrdtType;
+use super::shape::check_shape;
 use serde_json::Value;
 
 /// Converts a JSON value to Cap'n Proto bytes for a specific CRDT type.
+///
+/// The JSON shape is checked with [`check_shape`] first, so mistyped fields are reported
+/// as `CrdtError::Validation` naming the field.
 pub fn json_to_capnp_bytes(crdt_type: CrdtType, json_value: Value) -> Result<Vec<u8>, CrdtError> {
+    check_shape(crdt_type, &json_value)?;
+    encode_json(crdt_type, json_value)
+}
+
+fn encode_json(crdt_type: CrdtType, json_value: Value) -> Result<Vec<u8>, CrdtError> {
     match crdt_type {
-        CrdtType::GCounter => {
-            let crdt: GCounter = serde_json::from_value(json_value)
-                .map_err(|e| CrdtError::InvalidInput(format!("JSON parse error: {}", e)))?;
-            crdt.validate()?;
-            Ok(crdt.to_capnp_bytes())
-        }
-        CrdtType::PNCounter => {
-            let crdt: PNCounter = serde_json::from_value(json_value)
-                .map_err(|e| CrdtError::InvalidInput(format!("JSON parse error: {}", e)))?;
-            crdt.validate()?;
-            Ok(crdt.to_capnp_bytes())
-        }
-        CrdtType::GSet => {
-            let crdt: GSet<String> = serde_json::from_value(json_value)
-                .map_err(|e| CrdtError::InvalidInput(format!("JSON parse error: {}", e)))?;
-            crdt.validate()?;
-            Ok(crdt.to_capnp_bytes())
-        }
-        CrdtType::ORSet => {
-            let crdt: ORSet<String> = serde_json::from_value(json_value)
-                .map_err(|e| CrdtError::InvalidInput(format!("JSON parse error: {}", e)))?;
-            crdt.validate()?;
-            Ok(crdt.to_capnp_bytes())
-        }
-        CrdtType::LWWRegister => {
-            let crdt: LWWRegister<String> = serde_json::from_value(json_value)
-                .map_err(|e| CrdtError::InvalidInput(format!("JSON parse error: {}", e)))?;
-            crdt.validate()?;
-            Ok(crdt.to_capnp_bytes())
-        }
-        CrdtType::FWWRegister => {
-            let crdt: FWWRegister<String> = serde_json::from_value(json_value)
-                .map_err(|e| CrdtError::InvalidInput(format!("JSON parse error: {}", e)))?;
-            crdt.validate()?;
-            Ok(crdt.to_capnp_bytes())
-        }
-        CrdtType::MVRegister => {
-            let crdt: MVRegister<String> = serde_json::from_value(json_value)
-                .map_err(|e| CrdtError::InvalidInput(format!("JSON parse error: {}", e)))?;
-            crdt.validate()?;
-            Ok(crdt.to_capnp_bytes())
-        }
-        CrdtType::LWWMap => {
-            let crdt: LWWMap<String, String> = serde_json::from_value(json_value)
-                .map_err(|e| CrdtError::InvalidInput(format!("JSON parse error: {}", e)))?;
-            crdt.validate()?;
-            Ok(crdt.to_capnp_bytes())
-        }
-        CrdtType::ORMap => {
-            let crdt: ORMap<String, String> = serde_json::from_value(json_value)
-                .map_err(|e| CrdtError::InvalidInput(format!("JSON parse error: {}", e)))?;
-            crdt.validate()?;
-            Ok(crdt.to_capnp_bytes())
-        }
-        CrdtType::LWWSet => {
-            let crdt: LWWSet<String> = serde_json::from_value(json_value)
-                .map_err(|e| CrdtError::InvalidInput(format!("JSON parse error: {}", e)))?;
-            crdt.validate()?;
-            Ok(crdt.to_capnp_bytes())
-        }
+        CrdtType::GCounter => typed_json_to_capnp_bytes::<GCounter>(crdt_type, json_value),
+        CrdtType::PNCounter => typed_json_to_capnp_bytes::<PNCounter>(crdt_type, json_value),
+        CrdtType::GSet => typed_json_to_capnp_bytes::<GSet<String>>(crdt_type, json_value),
+        CrdtType::ORSet => typed_json_to_capnp_bytes::<ORSet<String>>(crdt_type, json_value),
+        CrdtType::LWWRegister => typed_json_to_capnp_bytes::<LWWRegister<String>>(crdt_type, json_value),
+        CrdtType::FWWRegister => typed_json_to_capnp_bytes::<FWWRegister<String>>(crdt_type, json_value),
+        CrdtType::MVRegister => typed_json_to_capnp_bytes::<MVRegister<String>>(crdt_type, json_value),
+        CrdtType::LWWMap => typed_json_to_capnp_bytes::<LWWMap<String, String>>(crdt_type, json_value),
+        CrdtType::ORMap => typed_json_to_capnp_bytes::<ORMap<String, String>>(crdt_type, json_value),
+        CrdtType::LWWSet => typed_json_to_capnp_bytes::<LWWSet<String>>(crdt_type, json_value),
+        #[cfg(feature = "probabilistic")]
+        CrdtType::HyperLogLog => typed_json_to_capnp_bytes::<HyperLogLog>(crdt_type, json_value),
+        #[cfg(feature = "probabilistic")]
+        CrdtType::CountMinSketch => typed_json_to_capnp_bytes::<CountMinSketch>(crdt_type, json_value),
+        #[cfg(feature = "probabilistic")]
+        CrdtType::RoaringBitmap => typed_json_to_capnp_bytes::<RoaringBitmap>(crdt_type, json_value),
+        #[cfg(feature = "probabilistic")]
+        CrdtType::TDigest => typed_json_to_capnp_bytes::<TDigest>(crdt_type, json_value),
+        #[cfg(feature = "probabilistic")]
+        CrdtType::TopK => typed_json_to_capnp_bytes::<TopK>(crdt_type, json_value),
     }
 }
 
+/// Parses and validates `json_value` as `C`, then serializes it.
+fn typed_json_to_capnp_bytes<C: Crdt + ElementCount>(
+    crdt_type: CrdtType,
+    json_value: Value,
+) -> Result<Vec<u8>, CrdtError> {
+    trace::observe("json_to_capnp_bytes", crdt_type, 1, || {
+        let crdt: C = serde_json::from_value(json_value)
+            .map_err(|e| CrdtError::InvalidInput(format!("JSON parse error: {}", e)))?;
+        crdt.validate()?;
+        Ok(crdt)
+    })
+}
+
+#[cfg(feature = "probabilistic")]
+fn sketch_capnp_bytes_to_json<C: Crdt>(bytes: &[u8]) -> Result<Value, CrdtError> {
+    let crdt = C::from_capnp_bytes(bytes)?;
+    serde_json::to_value(crdt).map_err(|e| CrdtError::InvalidInput(e.to_string()))
+}
+
 /// Validates a JSON value against a specific CRDT's internal rules.
 pub fn validate_json(crdt_type: CrdtType, json_value: Value) -> Result<(), CrdtError> {
     // Reuse conversion logic for validation
     json_to_capnp_bytes(crdt_type, json_value).map(|_| ())
 }
 
+/// Converts Cap'n Proto bytes to JSON for a statically known CRDT type.
+///
+/// The typed counterpart of [`capnp_bytes_to_json`]: the bytes are decoded as `C` itself,
+/// so a type mismatch is caught by `C`'s reader rather than by a runtime `CrdtType`.
+pub fn to_json<C: CrdtKind + Crdt>(bytes: &[u8]) -> Result<Value, CrdtError> {
+    let crdt = C::from_capnp_bytes(bytes)?;
+    serde_json::to_value(crdt)
+        .map_err(|e| CrdtError::InvalidInput(format!("{} to JSON: {}", C::TYPE, e)))
+}
+
 /// Converts Cap'n Proto bytes back to a JSON value for a specific CRDT type.
 pub fn capnp_bytes_to_json(crdt_type: CrdtType, bytes: &[u8]) -> Result<Value, CrdtError> {
     match crdt_type {
@@ -128,5 +127,15 @@ pub fn capnp_bytes_to_json(crdt_type: CrdtType, bytes: &[u8]) -> Result<Value, C
             let crdt = LWWSet::<String>::merge_from_readers(&[reader])?;
             serde_json::to_value(crdt).map_err(|e| CrdtError::InvalidInput(e.to_string()))
         }
+        #[cfg(feature = "probabilistic")]
+        CrdtType::HyperLogLog => sketch_capnp_bytes_to_json::<HyperLogLog>(bytes),
+        #[cfg(feature = "probabilistic")]
+        CrdtType::CountMinSketch => sketch_capnp_bytes_to_json::<CountMinSketch>(bytes),
+        #[cfg(feature = "probabilistic")]
+        CrdtType::RoaringBitmap => sketch_capnp_bytes_to_json::<RoaringBitmap>(bytes),
+        #[cfg(feature = "probabilistic")]
+        CrdtType::TDigest => sketch_capnp_bytes_to_json::<TDigest>(bytes),
+        #[cfg(feature = "probabilistic")]
+        CrdtType::TopK => sketch_capnp_bytes_to_json::<TopK>(bytes),
     }
 }
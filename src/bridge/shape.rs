@@ -0,0 +1,150 @@
+// Copyright (c) 2026 Adrian Robinson. All rights reserved.
+// Licensed under the MIT License. See LICENSE file in the project root for full license information.
+
+//! Structural pre-validation of bridge JSON.
+//!
+//! serde reports a mistyped element deep inside the parse ("invalid type: integer `1`,
+//! expected a string at line 1 column 14"). Checking the shape first lets the bridge name
+//! the offending field and the expected type instead. Only fields that are present are
+//! checked; missing fields are left to serde and its defaults.
+
+use crate::enums::CrdtType;
+use crate::traits::CrdtError;
+use serde_json::{Map, Value};
+
+/// Checks that `json` has the shape the bridge expects for `crdt_type`.
+///
+/// The bridge instantiates the generic types with `String` elements, keys and values, so
+/// those positions must hold JSON strings. Returns `CrdtError::Validation` naming the
+/// first field that does not match.
+pub fn check_shape(crdt_type: CrdtType, json: &Value) -> Result<(), CrdtError> {
+    let root = object(json, "$")?;
+    match crdt_type {
+        CrdtType::GCounter => gcounter(root, "$")?,
+        CrdtType::PNCounter => {
+            for part in ["positive", "negative"] {
+                if let Some(value) = root.get(part) {
+                    let path = format!("$.{}", part);
+                    gcounter(object(value, &path)?, &path)?;
+                }
+            }
+        }
+        CrdtType::GSet => {
+            if let Some(elements) = root.get("elements") {
+                each(elements, "$.elements", string)?;
+            }
+        }
+        CrdtType::ORSet => orset(root, "$")?,
+        CrdtType::LWWRegister | CrdtType::FWWRegister => {
+            if let Some(value) = root.get("value") {
+                string(value, "$.value")?;
+            }
+        }
+        CrdtType::MVRegister => {
+            if let Some(entries) = root.get("entries") {
+                object(entries, "$.entries")?;
+            }
+        }
+        CrdtType::LWWMap => {
+            if let Some(entries) = root.get("entries") {
+                for (key, entry) in object(entries, "$.entries")? {
+                    let path = format!("$.entries.{}", key);
+                    match array(entry, &path)?.first() {
+                        Some(value) => string(value, &format!("{}[0]", path))?,
+                        None => return Err(mismatch(&path, "a [value, timestamp, node_id] array", entry)),
+                    }
+                }
+            }
+        }
+        CrdtType::ORMap => {
+            if let Some(elements) = root.get("elements") {
+                orset(object(elements, "$.elements")?, "$.elements")?;
+            }
+        }
+        CrdtType::LWWSet => {
+            for part in ["add_set", "remove_set"] {
+                if let Some(value) = root.get(part) {
+                    object(value, &format!("$.{}", part))?;
+                }
+            }
+        }
+        // The sketches have no type parameters; serde's messages are already specific
+        #[cfg(feature = "probabilistic")]
+        CrdtType::HyperLogLog
+        | CrdtType::CountMinSketch
+        | CrdtType::RoaringBitmap
+        | CrdtType::TDigest
+        | CrdtType::TopK => {}
+    }
+
+    if let Some(vclock) = root.get("vclock") {
+        let vclock = object(vclock, "$.vclock")?;
+        if let Some(clocks) = vclock.get("clocks") {
+            object(clocks, "$.vclock.clocks")?;
+        }
+    }
+    Ok(())
+}
+
+fn gcounter(root: &Map<String, Value>, path: &str) -> Result<(), CrdtError> {
+    if let Some(counters) = root.get("counters") {
+        for (node, count) in object(counters, &format!("{}.counters", path))? {
+            if !count.is_i64() {
+                return Err(mismatch(&format!("{}.counters.{}", path, node), "an integer", count));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// ORSet elements, and ORMap entries (whose elements are `[key, value]` pairs).
+fn orset(root: &Map<String, Value>, path: &str) -> Result<(), CrdtError> {
+    let Some(elements) = root.get("elements") else {
+        return Ok(());
+    };
+    each(elements, &format!("{}.elements", path), |entry, path| {
+        match object(entry, path)?.get("element") {
+            Some(Value::Array(pair)) if pair.len() == 2 => {
+                string(&pair[0], &format!("{}.element[0]", path))?;
+                string(&pair[1], &format!("{}.element[1]", path))
+            }
+            Some(element) => string(element, &format!("{}.element", path)),
+            None => Ok(()),
+        }
+    })
+}
+
+fn each(
+    value: &Value,
+    path: &str,
+    check: impl Fn(&Value, &str) -> Result<(), CrdtError>,
+) -> Result<(), CrdtError> {
+    for (idx, item) in array(value, path)?.iter().enumerate() {
+        check(item, &format!("{}[{}]", path, idx))?;
+    }
+    Ok(())
+}
+
+fn object<'a>(value: &'a Value, path: &str) -> Result<&'a Map<String, Value>, CrdtError> {
+    value.as_object().ok_or_else(|| mismatch(path, "an object", value))
+}
+
+fn array<'a>(value: &'a Value, path: &str) -> Result<&'a Vec<Value>, CrdtError> {
+    value.as_array().ok_or_else(|| mismatch(path, "an array", value))
+}
+
+fn string(value: &Value, path: &str) -> Result<(), CrdtError> {
+    value.as_str().map(|_| ()).ok_or_else(|| mismatch(path, "a string", value))
+}
+
+fn mismatch(path: &str, expected: &str, found: &Value) -> CrdtError {
+    let kind = match found {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    };
+    CrdtError::Validation(format!("{}: expected {}, found {}", path, expected, kind))
+}
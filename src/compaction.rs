@@ -28,7 +28,8 @@
 //! ```
 
 use crate::bridge::SerdeCapnpBridge;
-use crate::traits::{Crdt, CrdtError};
+use crate::trace::{self, ElementCount};
+use crate::traits::{Crdt, CrdtError, CrdtReader};
 use crate::enums::CrdtType;
 use crate::*;
 use serde_json::Value;
@@ -88,84 +89,116 @@ pub fn compact_capnp_bytes(crdt_type: CrdtType, buffers: &[&[u8]]) -> Result<Vec
         return Ok(Vec::new());
     }
 
+    compact_nonempty(crdt_type, buffers)
+}
+
+fn compact_nonempty(crdt_type: CrdtType, buffers: &[&[u8]]) -> Result<Vec<u8>, CrdtError> {
     match crdt_type {
-        CrdtType::GCounter => {
-            let readers: Vec<_> = buffers.iter().map(|b| GCounterReader::new(b)).collect();
-            let merged = GCounter::merge_from_readers(&readers)?;
-            Ok(merged.to_capnp_bytes())
-        }
-        CrdtType::PNCounter => {
-            let readers: Vec<_> = buffers.iter().map(|b| PNCounterReader::new(b)).collect();
-            let merged = PNCounter::merge_from_readers(&readers)?;
-            Ok(merged.to_capnp_bytes())
-        }
-        CrdtType::GSet => {
-            let readers: Vec<_> = buffers
-                .iter()
-                .map(|b| GSetReader::<String>::new(b))
-                .collect();
-            let merged = GSet::<String>::merge_from_readers(&readers)?;
-            Ok(merged.to_capnp_bytes())
-        }
-        CrdtType::ORSet => {
-            let readers: Vec<_> = buffers
-                .iter()
-                .map(|b| ORSetReader::<String>::new(b))
-                .collect();
-            let merged = ORSet::<String>::merge_from_readers(&readers)?;
-            Ok(merged.to_capnp_bytes())
-        }
-        CrdtType::LWWRegister => {
-            let readers: Vec<_> = buffers
-                .iter()
-                .map(|b| LWWRegisterReader::<String>::new(b))
-                .collect();
-            let merged = LWWRegister::<String>::merge_from_readers(&readers)?;
-            Ok(merged.to_capnp_bytes())
-        }
-        CrdtType::FWWRegister => {
-            let readers: Vec<_> = buffers
-                .iter()
-                .map(|b| FWWRegisterReader::<String>::new(b))
-                .collect();
-            let merged = FWWRegister::<String>::merge_from_readers(&readers)?;
-            Ok(merged.to_capnp_bytes())
-        }
-        CrdtType::MVRegister => {
-            let readers: Vec<_> = buffers
-                .iter()
-                .map(|b| MVRegisterReader::<String>::new(b))
-                .collect();
-            let merged = MVRegister::<String>::merge_from_readers(&readers)?;
-            Ok(merged.to_capnp_bytes())
-        }
-        CrdtType::LWWMap => {
-            let readers: Vec<_> = buffers
-                .iter()
-                .map(|b| LWWMapReader::<String, String>::new(b))
-                .collect();
-            let merged = LWWMap::<String, String>::merge_from_readers(&readers)?;
-            Ok(merged.to_capnp_bytes())
-        }
-        CrdtType::ORMap => {
-            let readers: Vec<_> = buffers
-                .iter()
-                .map(|b| ORMapReader::<String, String>::new(b))
-                .collect();
-            let merged = ORMap::<String, String>::merge_from_readers(&readers)?;
-            Ok(merged.to_capnp_bytes())
-        }
-        CrdtType::LWWSet => {
-            let readers: Vec<_> = buffers
-                .iter()
-                .map(|b| LWWSetReader::<String>::new(b))
-                .collect();
-            let merged = LWWSet::<String>::merge_from_readers(&readers)?;
-            Ok(merged.to_capnp_bytes())
-        }
+        CrdtType::GCounter => compact_typed::<GCounter>(crdt_type, buffers),
+        CrdtType::PNCounter => compact_typed::<PNCounter>(crdt_type, buffers),
+        CrdtType::GSet => compact_typed::<GSet<String>>(crdt_type, buffers),
+        CrdtType::ORSet => compact_typed::<ORSet<String>>(crdt_type, buffers),
+        CrdtType::LWWRegister => compact_typed::<LWWRegister<String>>(crdt_type, buffers),
+        CrdtType::FWWRegister => compact_typed::<FWWRegister<String>>(crdt_type, buffers),
+        CrdtType::MVRegister => compact_typed::<MVRegister<String>>(crdt_type, buffers),
+        CrdtType::LWWMap => compact_typed::<LWWMap<String, String>>(crdt_type, buffers),
+        CrdtType::ORMap => compact_typed::<ORMap<String, String>>(crdt_type, buffers),
+        CrdtType::LWWSet => compact_typed::<LWWSet<String>>(crdt_type, buffers),
+        #[cfg(feature = "probabilistic")]
+        CrdtType::HyperLogLog => compact_typed::<HyperLogLog>(crdt_type, buffers),
+        #[cfg(feature = "probabilistic")]
+        CrdtType::CountMinSketch => compact_typed::<CountMinSketch>(crdt_type, buffers),
+        #[cfg(feature = "probabilistic")]
+        CrdtType::RoaringBitmap => compact_typed::<RoaringBitmap>(crdt_type, buffers),
+        #[cfg(feature = "probabilistic")]
+        CrdtType::TDigest => compact_typed::<TDigest>(crdt_type, buffers),
+        #[cfg(feature = "probabilistic")]
+        CrdtType::TopK => compact_typed::<TopK>(crdt_type, buffers),
+    }
+}
+
+/// Merges a stream of Cap'n Proto blobs into a single compacted blob.
+///
+/// The streaming counterpart of [`compact_capnp_bytes`]: blobs are decoded and folded in
+/// one at a time, so only the running result and the current blob are held in memory.
+/// Dispatches on `crdt_type` with the same element types as the bridge. An empty iterator
+/// yields an empty buffer.
+///
+/// Blobs whose merge settings disagree (tie-break direction, conflict policy) are
+/// rejected with the same `CrdtError::Merge` as [`compact_capnp_bytes`], rather than
+/// joined.
+///
+/// # Example
+///
+/// ```
+/// use crdt_data_types::{merge_all, Crdt, CrdtType, GCounter};
+///
+/// let blobs = (0..3).map(|i| {
+///     let mut gc = GCounter::new();
+///     gc.increment(&format!("node_{}", i), 10);
+///     gc.to_capnp_bytes()
+/// });
+///
+/// let merged = merge_all(CrdtType::GCounter, blobs).unwrap();
+/// assert_eq!(GCounter::from_capnp_bytes(&merged).unwrap().value(), 30);
+/// ```
+pub fn merge_all<I>(crdt_type: CrdtType, blobs: I) -> Result<Vec<u8>, CrdtError>
+where
+    I: IntoIterator<Item = Vec<u8>>,
+{
+    match crdt_type {
+        CrdtType::GCounter => fold_blobs::<GCounter, _>(blobs),
+        CrdtType::PNCounter => fold_blobs::<PNCounter, _>(blobs),
+        CrdtType::GSet => fold_blobs::<GSet<String>, _>(blobs),
+        CrdtType::ORSet => fold_blobs::<ORSet<String>, _>(blobs),
+        CrdtType::LWWRegister => fold_blobs::<LWWRegister<String>, _>(blobs),
+        CrdtType::FWWRegister => fold_blobs::<FWWRegister<String>, _>(blobs),
+        CrdtType::MVRegister => fold_blobs::<MVRegister<String>, _>(blobs),
+        CrdtType::LWWMap => fold_blobs::<LWWMap<String, String>, _>(blobs),
+        CrdtType::ORMap => fold_blobs::<ORMap<String, String>, _>(blobs),
+        CrdtType::LWWSet => fold_blobs::<LWWSet<String>, _>(blobs),
+        #[cfg(feature = "probabilistic")]
+        CrdtType::HyperLogLog => fold_blobs::<HyperLogLog, _>(blobs),
+        #[cfg(feature = "probabilistic")]
+        CrdtType::CountMinSketch => fold_blobs::<CountMinSketch, _>(blobs),
+        #[cfg(feature = "probabilistic")]
+        CrdtType::RoaringBitmap => fold_blobs::<RoaringBitmap, _>(blobs),
+        #[cfg(feature = "probabilistic")]
+        CrdtType::TDigest => fold_blobs::<TDigest, _>(blobs),
+        #[cfg(feature = "probabilistic")]
+        CrdtType::TopK => fold_blobs::<TopK, _>(blobs),
     }
 }
 
+/// Decodes the first blob and merges the rest into it, checking each against the running
+/// result's merge settings.
+fn fold_blobs<C, I>(blobs: I) -> Result<Vec<u8>, CrdtError>
+where
+    C: Crdt,
+    I: IntoIterator<Item = Vec<u8>>,
+{
+    let mut blobs = blobs.into_iter();
+    let Some(first) = blobs.next() else {
+        return Ok(Vec::new());
+    };
+    let mut merged = C::from_capnp_bytes(&first)?;
+    for blob in blobs {
+        let next = C::from_capnp_bytes(&blob)?;
+        merged.ensure_mergeable(&next)?;
+        merged.merge(&next);
+    }
+    Ok(merged.to_capnp_bytes())
+}
+
+/// Merges `buffers` as `C` through its zero-copy readers.
+fn compact_typed<C: Crdt + ElementCount>(
+    crdt_type: CrdtType,
+    buffers: &[&[u8]],
+) -> Result<Vec<u8>, CrdtError> {
+    let readers: Vec<_> = buffers.iter().map(|b| C::Reader::from_bytes(b)).collect();
+    trace::observe("compact", crdt_type, buffers.len(), || C::merge_from_readers(&readers))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -201,6 +234,49 @@ mod tests {
         assert_eq!(merged.value(), 30);
     }
 
+    #[test]
+    fn test_merge_all_orset_blobs() {
+        let blobs: Vec<Vec<u8>> = ["apple", "banana", "cherry"]
+            .iter()
+            .enumerate()
+            .map(|(i, fruit)| {
+                let mut set = ORSet::new();
+                set.insert(&format!("node_{}", i), fruit.to_string());
+                set.to_capnp_bytes()
+            })
+            .collect();
+
+        let merged = merge_all(CrdtType::ORSet, blobs.clone()).unwrap();
+        let merged = ORSet::<String>::from_capnp_bytes(&merged).unwrap();
+        assert_eq!(merged.len(), 3);
+        assert!(merged.contains(&"banana".to_string()));
+
+        let slices: Vec<&[u8]> = blobs.iter().map(|b| b.as_slice()).collect();
+        let via_readers = compact_capnp_bytes(CrdtType::ORSet, &slices).unwrap();
+        assert_eq!(ORSet::<String>::from_capnp_bytes(&via_readers).unwrap(), merged);
+
+        assert!(merge_all(CrdtType::ORSet, Vec::new()).unwrap().is_empty());
+        assert!(merge_all(CrdtType::ORSet, vec![b"garbage".to_vec()]).is_err());
+    }
+
+    #[test]
+    fn test_merge_all_rejects_mismatched_settings_like_compact() {
+        let add_wins = ORSet::<String>::new().to_capnp_bytes();
+        let remove_wins = ORSet::<String>::new().with_policy(ConflictPolicy::RemoveWins).to_capnp_bytes();
+        let lower = LWWRegister::new("a".to_string(), 1, "node_a")
+            .with_tie_break(TieBreak::LowerNodeWins)
+            .to_capnp_bytes();
+        let higher = LWWRegister::new("b".to_string(), 1, "node_b").to_capnp_bytes();
+
+        for (crdt_type, blobs) in [(CrdtType::ORSet, [add_wins, remove_wins]), (CrdtType::LWWRegister, [lower, higher])] {
+            let slices: Vec<&[u8]> = blobs.iter().map(|b| b.as_slice()).collect();
+            let expected = compact_capnp_bytes(crdt_type, &slices).unwrap_err();
+            let streamed = merge_all(crdt_type, blobs).unwrap_err();
+            assert!(matches!(streamed, CrdtError::Merge(_)));
+            assert_eq!(streamed.to_string(), expected.to_string());
+        }
+    }
+
     #[test]
     fn test_compact_empty() {
         let result = compact_json_values(CrdtType::GCounter, &[]).unwrap();
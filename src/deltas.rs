@@ -1,3 +1,8 @@
+use crate::deltas_capnp::delta;
+use crate::dot::Dot;
+use crate::enums::CrdtType;
+use crate::pn_counter::PNCounter;
+use crate::traits::CrdtError;
 use serde::Deserialize;
 use std::collections::HashMap;
 
@@ -8,11 +13,45 @@ pub enum GCounterDelta {
     Object { increment: i64 },
 }
 
+/// A PNCounter delta: a signed amount, `{"increment": n}` (negative decrements), or an
+/// explicit `{"decrement": n}`.
+///
+/// Objects with any other field, including both `increment` and `decrement`, are rejected
+/// rather than matched by their first recognised field.
 #[derive(Debug, Deserialize)]
-#[serde(untagged)]
+#[serde(untagged, deny_unknown_fields)]
 pub enum PNCounterDelta {
     Direct(i64),
     Object { increment: i64 },
+    Decrement { decrement: i64 },
+}
+
+impl PNCounterDelta {
+    /// Applies the delta, routing explicit decrements to `PNCounter::decrement`.
+    ///
+    /// Rejects a negative `decrement` (ambiguous intent) and `i64::MIN`, whose magnitude
+    /// cannot be represented.
+    pub fn apply(self, crdt: &mut PNCounter, node_id: &str) -> Result<(), CrdtError> {
+        match self {
+            PNCounterDelta::Direct(amount) | PNCounterDelta::Object { increment: amount } => {
+                if amount == i64::MIN {
+                    return Err(CrdtError::InvalidInput(
+                        "PNCounter delta magnitude out of range".into(),
+                    ));
+                }
+                crdt.increment(node_id, amount);
+            }
+            PNCounterDelta::Decrement { decrement } => {
+                if decrement < 0 {
+                    return Err(CrdtError::InvalidInput(
+                        "PNCounter decrement must be non-negative".into(),
+                    ));
+                }
+                crdt.decrement(node_id, decrement);
+            }
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -72,3 +111,237 @@ where
     pub set: Option<HashMap<K, V>>,
     pub remove: Option<Vec<K>>,
 }
+
+// ============================================================================
+// Cap'n Proto Delta Builder
+// ============================================================================
+
+/// Builds serialized `deltas_capnp::Delta` messages for `apply_capnp_delta`.
+///
+/// # Example
+///
+/// ```
+/// use crdt_data_types::deltas::DeltaBuilder;
+/// use crdt_data_types::{CrdtType, SerdeCapnpBridge};
+///
+/// let delta = DeltaBuilder::orset_add(["a", "b"]).build();
+/// let state = SerdeCapnpBridge::apply_capnp_delta(CrdtType::ORSet, None, &delta, "node_a").unwrap();
+/// let json = SerdeCapnpBridge::capnp_bytes_to_json(CrdtType::ORSet, &state).unwrap();
+/// assert_eq!(json["elements"].as_array().unwrap().len(), 2);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeltaBuilder {
+    payload: DeltaPayload,
+    dot: Option<Dot>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum DeltaPayload {
+    GCounter(i64),
+    PNCounter(i64),
+    GSet(Vec<String>),
+    ORSet { add: Vec<String>, remove: Vec<String> },
+    LWWSet { add: Vec<String>, remove: Vec<String>, timestamp: u64 },
+    LWWRegister { value: String, timestamp: u64 },
+    FWWRegister { value: String, timestamp: u64 },
+    MVRegister(String),
+    LWWMap { set: Vec<(String, String)>, remove: Vec<String>, timestamp: u64 },
+    ORMap { set: Vec<(String, String)>, remove: Vec<String> },
+}
+
+fn strings<I, S>(items: I) -> Vec<String>
+where
+    I: IntoIterator<Item = S>,
+    S: Into<String>,
+{
+    items.into_iter().map(Into::into).collect()
+}
+
+fn string_pairs<I, K, V>(entries: I) -> Vec<(String, String)>
+where
+    I: IntoIterator<Item = (K, V)>,
+    K: Into<String>,
+    V: Into<String>,
+{
+    entries.into_iter().map(|(k, v)| (k.into(), v.into())).collect()
+}
+
+impl DeltaBuilder {
+    fn new(payload: DeltaPayload) -> Self {
+        Self { payload, dot: None }
+    }
+
+    /// Increments a GCounter by `amount`.
+    pub fn gcounter(amount: i64) -> Self {
+        Self::new(DeltaPayload::GCounter(amount))
+    }
+
+    /// Adds a signed `amount` to a PNCounter.
+    pub fn pncounter(amount: i64) -> Self {
+        Self::new(DeltaPayload::PNCounter(amount))
+    }
+
+    /// Adds items to a GSet.
+    pub fn gset<I: IntoIterator<Item = S>, S: Into<String>>(items: I) -> Self {
+        Self::new(DeltaPayload::GSet(strings(items)))
+    }
+
+    /// Adds items to an ORSet.
+    pub fn orset_add<I: IntoIterator<Item = S>, S: Into<String>>(items: I) -> Self {
+        Self::new(DeltaPayload::ORSet { add: strings(items), remove: Vec::new() })
+    }
+
+    /// Removes items from an ORSet.
+    pub fn orset_remove<I: IntoIterator<Item = S>, S: Into<String>>(items: I) -> Self {
+        Self::new(DeltaPayload::ORSet { add: Vec::new(), remove: strings(items) })
+    }
+
+    /// Adds items to an LWWSet at `timestamp`.
+    pub fn lww_set_add<I: IntoIterator<Item = S>, S: Into<String>>(items: I, timestamp: u64) -> Self {
+        Self::new(DeltaPayload::LWWSet { add: strings(items), remove: Vec::new(), timestamp })
+    }
+
+    /// Removes items from an LWWSet at `timestamp`.
+    pub fn lww_set_remove<I: IntoIterator<Item = S>, S: Into<String>>(items: I, timestamp: u64) -> Self {
+        Self::new(DeltaPayload::LWWSet { add: Vec::new(), remove: strings(items), timestamp })
+    }
+
+    /// Writes an LWWRegister value at `timestamp`.
+    pub fn lww_register(value: impl Into<String>, timestamp: u64) -> Self {
+        Self::new(DeltaPayload::LWWRegister { value: value.into(), timestamp })
+    }
+
+    /// Writes an FWWRegister value at `timestamp`.
+    pub fn fww_register(value: impl Into<String>, timestamp: u64) -> Self {
+        Self::new(DeltaPayload::FWWRegister { value: value.into(), timestamp })
+    }
+
+    /// Sets an MVRegister value.
+    pub fn mv_register(value: impl Into<String>) -> Self {
+        Self::new(DeltaPayload::MVRegister(value.into()))
+    }
+
+    /// Sets LWWMap entries at `timestamp`.
+    pub fn lww_map_set<I, K, V>(entries: I, timestamp: u64) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<String>,
+    {
+        Self::new(DeltaPayload::LWWMap { set: string_pairs(entries), remove: Vec::new(), timestamp })
+    }
+
+    /// Removes LWWMap keys.
+    pub fn lww_map_remove<I: IntoIterator<Item = S>, S: Into<String>>(keys: I, timestamp: u64) -> Self {
+        Self::new(DeltaPayload::LWWMap { set: Vec::new(), remove: strings(keys), timestamp })
+    }
+
+    /// Sets ORMap entries.
+    pub fn or_map_set<I, K, V>(entries: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<String>,
+    {
+        Self::new(DeltaPayload::ORMap { set: string_pairs(entries), remove: Vec::new() })
+    }
+
+    /// Removes ORMap keys.
+    pub fn or_map_remove<I: IntoIterator<Item = S>, S: Into<String>>(keys: I) -> Self {
+        Self::new(DeltaPayload::ORMap { set: Vec::new(), remove: strings(keys) })
+    }
+
+    /// Tags the delta with a dot for `apply_capnp_delta_idempotent`.
+    ///
+    /// `counter` is the first vclock counter of `node_id` the delta consumes: one past the
+    /// counter the target state has reached for that node.
+    pub fn with_dot(mut self, node_id: impl Into<String>, counter: u64) -> Self {
+        self.dot = Some(Dot::new(node_id, counter));
+        self
+    }
+
+    /// The CRDT type this delta applies to.
+    pub fn crdt_type(&self) -> CrdtType {
+        match self.payload {
+            DeltaPayload::GCounter(_) => CrdtType::GCounter,
+            DeltaPayload::PNCounter(_) => CrdtType::PNCounter,
+            DeltaPayload::GSet(_) => CrdtType::GSet,
+            DeltaPayload::ORSet { .. } => CrdtType::ORSet,
+            DeltaPayload::LWWSet { .. } => CrdtType::LWWSet,
+            DeltaPayload::LWWRegister { .. } => CrdtType::LWWRegister,
+            DeltaPayload::FWWRegister { .. } => CrdtType::FWWRegister,
+            DeltaPayload::MVRegister(_) => CrdtType::MVRegister,
+            DeltaPayload::LWWMap { .. } => CrdtType::LWWMap,
+            DeltaPayload::ORMap { .. } => CrdtType::ORMap,
+        }
+    }
+
+    /// Serializes the delta message.
+    pub fn build(&self) -> Vec<u8> {
+        let mut message = capnp::message::Builder::new_default();
+        {
+            let mut root = message.init_root::<delta::Builder>();
+            match &self.payload {
+                DeltaPayload::GCounter(amount) => root.set_g_counter(*amount),
+                DeltaPayload::PNCounter(amount) => root.set_pn_counter(*amount),
+                DeltaPayload::GSet(items) => write_texts(root.reborrow().init_g_set(items.len() as u32), items),
+                DeltaPayload::ORSet { add, remove } => {
+                    let mut orset = root.reborrow().init_or_set();
+                    write_texts(orset.reborrow().init_add(add.len() as u32), add);
+                    write_texts(orset.init_remove(remove.len() as u32), remove);
+                }
+                DeltaPayload::LWWSet { add, remove, timestamp } => {
+                    let mut lww_set = root.reborrow().init_lww_set();
+                    lww_set.set_timestamp(*timestamp);
+                    write_texts(lww_set.reborrow().init_add(add.len() as u32), add);
+                    write_texts(lww_set.init_remove(remove.len() as u32), remove);
+                }
+                DeltaPayload::LWWRegister { value, timestamp } => {
+                    let mut reg = root.reborrow().init_lww_register();
+                    reg.set_value(value.as_str().into());
+                    reg.set_timestamp(*timestamp);
+                }
+                DeltaPayload::FWWRegister { value, timestamp } => {
+                    let mut reg = root.reborrow().init_fww_register();
+                    reg.set_value(value.as_str().into());
+                    reg.set_timestamp(*timestamp);
+                }
+                DeltaPayload::MVRegister(value) => root.set_mv_register(value.as_str().into()),
+                DeltaPayload::LWWMap { set, remove, timestamp } => {
+                    let mut map = root.reborrow().init_lww_map();
+                    map.set_timestamp(*timestamp);
+                    let mut entries = map.reborrow().init_set(set.len() as u32);
+                    for (idx, (key, value)) in set.iter().enumerate() {
+                        let mut entry = entries.reborrow().get(idx as u32);
+                        entry.set_key(key.as_str().into());
+                        entry.set_value(value.as_str().into());
+                    }
+                    write_texts(map.init_remove(remove.len() as u32), remove);
+                }
+                DeltaPayload::ORMap { set, remove } => {
+                    let mut map = root.reborrow().init_or_map();
+                    let mut entries = map.reborrow().init_set(set.len() as u32);
+                    for (idx, (key, value)) in set.iter().enumerate() {
+                        let mut entry = entries.reborrow().get(idx as u32);
+                        entry.set_key(key.as_str().into());
+                        entry.set_value(value.as_str().into());
+                    }
+                    write_texts(map.init_remove(remove.len() as u32), remove);
+                }
+            }
+            if let Some(dot) = &self.dot {
+                root.set_dot_node(dot.node_id.as_str().into());
+                root.set_dot_counter(dot.counter);
+            }
+        }
+        let mut bytes = Vec::new();
+        capnp::serialize::write_message(&mut bytes, &message).expect("Delta serialization fail");
+        bytes
+    }
+}
+
+fn write_texts(mut list: capnp::text_list::Builder<'_>, items: &[String]) {
+    for (idx, item) in items.iter().enumerate() {
+        list.set(idx as u32, item.as_str().into());
+    }
+}
@@ -0,0 +1,317 @@
+// Copyright (c) 2026 Adrian Robinson. All rights reserved.
+// Licensed under the MIT License. See LICENSE file in the project root for full license information.
+
+use crate::disable_register_capnp;
+use crate::traits::{Crdt, CrdtError, CrdtReader};
+use crate::vector_clock::VectorClock;
+use capnp::message::{Builder, HeapAllocator, ReaderOptions};
+use capnp::serialize;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::io::Write;
+
+/// Disable-Register: a register with a permanent, remove-wins "deleted" state.
+///
+/// While live, the register behaves like an LWW-Register: the write with the highest
+/// timestamp wins, with the greater node id breaking ties. Once any replica calls
+/// [`disable`](DisableRegister::disable), the tombstone is permanent: merges keep the
+/// register disabled regardless of concurrent or later sets, in the manner of a
+/// two-phase set.
+///
+/// # Key Properties
+///
+/// - **Remove-Wins**: A disable beats every set, concurrent or not.
+/// - **Permanent**: A disabled register can never hold a value again.
+/// - **Last-Write-Wins**: Among sets on a live register, the highest timestamp wins.
+///
+/// # Algebraic Properties
+///
+/// - **Commutativity**: Yes.
+/// - **Associativity**: Yes.
+/// - **Idempotence**: Yes.
+///
+/// # Example
+///
+/// ```
+/// use crdt_data_types::DisableRegister;
+///
+/// let mut a = DisableRegister::new("draft".to_string(), 100, "node_a");
+/// let mut b = a.clone();
+///
+/// a.disable("node_a");
+/// b.set("final".to_string(), 200, "node_b");
+///
+/// a.merge(&b);
+/// assert!(a.is_disabled());
+/// assert_eq!(a.get(), None);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound(serialize = "T: Serialize", deserialize = "T: DeserializeOwned"))]
+pub struct DisableRegister<T> {
+    /// The current value; always `None` once disabled.
+    pub value: Option<T>,
+    /// Timestamp of the winning write.
+    pub timestamp: u64,
+    /// Identifier of the node that performed the winning write.
+    pub node_id: String,
+    /// Permanent tombstone: set once, never cleared.
+    pub disabled: bool,
+    /// Vector clock for tracking causal history.
+    #[serde(default)]
+    pub vclock: VectorClock,
+}
+
+impl<T> Default for DisableRegister<T> {
+    fn default() -> Self {
+        Self {
+            value: None,
+            timestamp: 0,
+            node_id: String::new(),
+            disabled: false,
+            vclock: VectorClock::new(),
+        }
+    }
+}
+
+impl<T: Clone + Serialize + DeserializeOwned + Send + Sync + 'static> DisableRegister<T> {
+    /// Creates a live register holding an initial value.
+    pub fn new(value: T, timestamp: u64, node_id: impl Into<String>) -> Self {
+        let node_id = node_id.into();
+        let mut vclock = VectorClock::new();
+        vclock.increment(&node_id);
+        Self {
+            value: Some(value),
+            timestamp,
+            node_id,
+            disabled: false,
+            vclock,
+        }
+    }
+
+    /// Writes `value` if it is newer than the current write. No-op once disabled.
+    pub fn set(&mut self, value: T, timestamp: u64, node_id: impl Into<String>) {
+        if self.disabled {
+            return;
+        }
+        let node_id = node_id.into();
+        if self.value.is_none() || Self::newer(timestamp, &node_id, self.timestamp, &self.node_id) {
+            self.value = Some(value);
+            self.timestamp = timestamp;
+            self.node_id = node_id.clone();
+            self.vclock.increment(&node_id);
+        }
+    }
+
+    /// Permanently disables the register, discarding its value.
+    pub fn disable(&mut self, node_id: impl Into<String>) {
+        if self.disabled {
+            return;
+        }
+        let node_id = node_id.into();
+        self.clear();
+        self.vclock.increment(&node_id);
+    }
+
+    /// Returns `true` once any replica has disabled the register.
+    pub fn is_disabled(&self) -> bool {
+        self.disabled
+    }
+
+    /// Returns the current value, or `None` if unset or disabled.
+    pub fn get(&self) -> Option<&T> {
+        self.value.as_ref()
+    }
+
+    /// Merges another register into this one; a disable on either side wins.
+    pub fn merge(&mut self, other: &Self) {
+        if self.disabled || other.disabled {
+            self.clear();
+        } else if let Some(value) = &other.value {
+            if self.value.is_none()
+                || Self::newer(other.timestamp, &other.node_id, self.timestamp, &self.node_id)
+            {
+                self.value = Some(value.clone());
+                self.timestamp = other.timestamp;
+                self.node_id = other.node_id.clone();
+            }
+        }
+        self.vclock.merge(&other.vclock);
+    }
+
+    fn newer(ts: u64, node_id: &str, cur_ts: u64, cur_node_id: &str) -> bool {
+        ts > cur_ts || (ts == cur_ts && node_id > cur_node_id)
+    }
+
+    fn clear(&mut self) {
+        self.disabled = true;
+        self.value = None;
+        self.timestamp = 0;
+        self.node_id.clear();
+    }
+}
+
+// ============================================================================
+// Zero-Copy Reader
+// ============================================================================
+
+pub struct DisableRegisterReader<'a, T> {
+    bytes: &'a [u8],
+    _phantom: std::marker::PhantomData<T>,
+}
+
+impl<'a, T: Clone + Serialize + DeserializeOwned + Send + Sync + 'static>
+    DisableRegisterReader<'a, T>
+{
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self {
+            bytes,
+            _phantom: std::marker::PhantomData,
+        }
+    }
+
+    /// Reads only the tombstone flag, without decoding the value.
+    pub fn is_disabled(&self) -> Result<bool, CrdtError> {
+        let reader = serialize::read_message(self.bytes, ReaderOptions::new())
+            .map_err(|e: capnp::Error| CrdtError::Deserialization(e.to_string()))?;
+        let reg = reader
+            .get_root::<disable_register_capnp::disable_register::Reader>()
+            .map_err(|e: capnp::Error| CrdtError::Deserialization(e.to_string()))?;
+        Ok(reg.get_disabled())
+    }
+
+    fn to_register(&self) -> Result<DisableRegister<T>, CrdtError> {
+        let reader = serialize::read_message(self.bytes, ReaderOptions::new())
+            .map_err(|e: capnp::Error| CrdtError::Deserialization(e.to_string()))?;
+        let reg = reader
+            .get_root::<disable_register_capnp::disable_register::Reader>()
+            .map_err(|e: capnp::Error| CrdtError::Deserialization(e.to_string()))?;
+
+        let value = if reg.has_value() {
+            let value_bytes = reg
+                .get_value()
+                .map_err(|e: capnp::Error| CrdtError::Deserialization(e.to_string()))?;
+            Some(
+                bincode::deserialize(value_bytes)
+                    .map_err(|e: bincode::Error| CrdtError::Deserialization(e.to_string()))?,
+            )
+        } else {
+            None
+        };
+
+        let node_id = reg
+            .get_node_id()
+            .map_err(|e: capnp::Error| CrdtError::Deserialization(e.to_string()))?
+            .to_string()
+            .map_err(|e: std::str::Utf8Error| CrdtError::Deserialization(e.to_string()))?;
+
+        let vclock = if reg.has_vclock() {
+            let vc_bytes = reg
+                .get_vclock()
+                .map_err(|e: capnp::Error| CrdtError::Deserialization(e.to_string()))?;
+            VectorClock::merge_from_readers(&[crate::vector_clock::VectorClockReader::new(
+                vc_bytes,
+            )])?
+        } else {
+            VectorClock::new()
+        };
+
+        Ok(DisableRegister {
+            value,
+            timestamp: reg.get_timestamp(),
+            node_id,
+            disabled: reg.get_disabled(),
+            vclock,
+        })
+    }
+}
+
+impl<'a, T: Clone + Serialize + DeserializeOwned + Send + Sync + 'static> CrdtReader<'a>
+    for DisableRegisterReader<'a, T>
+{
+    fn from_bytes(bytes: &'a [u8]) -> Self {
+        Self::new(bytes)
+    }
+
+    fn is_empty(&self) -> Result<bool, CrdtError> {
+        Ok(self.to_register()?.is_empty())
+    }
+}
+
+// ============================================================================
+// CRDT Trait Implementation
+// ============================================================================
+
+impl<T: Clone + Serialize + DeserializeOwned + Send + Sync + 'static> Crdt for DisableRegister<T> {
+    type Reader<'a> = DisableRegisterReader<'a, T>;
+
+    fn merge_from_readers(readers: &[Self::Reader<'_>]) -> Result<Self, CrdtError> {
+        let mut result = DisableRegister::default();
+        for reader in readers {
+            result.merge(&reader.to_register()?);
+        }
+        Ok(result)
+    }
+
+    fn merge(&mut self, other: &Self) {
+        self.merge(other);
+    }
+
+    fn dominates(&self, other: &Self) -> bool {
+        // A disabled register is already cleared; otherwise `other` must not be disabled
+        // or hold a newer write
+        self.vclock.dominates(&other.vclock)
+            && (self.disabled
+                || (!other.disabled
+                    && other.value.as_ref().is_none_or(|_| {
+                        self.value.is_some()
+                            && !Self::newer(other.timestamp, &other.node_id, self.timestamp, &self.node_id)
+                    })))
+    }
+
+    fn merge_bytes(&mut self, bytes: &[u8]) -> Result<(), CrdtError> {
+        let other = DisableRegisterReader::new(bytes).to_register()?;
+        self.merge(&other);
+        Ok(())
+    }
+
+    fn write_to<W: Write>(&self, writer: &mut W) -> Result<(), CrdtError> {
+        let mut message = Builder::new(HeapAllocator::new());
+        self.build_capnp(&mut message)?;
+        serialize::write_message(writer, &message)
+            .map_err(|e| CrdtError::Serialization(e.to_string()))
+    }
+
+    fn build_capnp(&self, message: &mut Builder<HeapAllocator>) -> Result<(), CrdtError> {
+        let mut reg =
+            message.init_root::<disable_register_capnp::disable_register::Builder>();
+        if let Some(value) = &self.value {
+            let bytes = bincode::serialize(value)
+                .map_err(|e| CrdtError::Serialization(e.to_string()))?;
+            reg.set_value(&bytes);
+        }
+        reg.set_timestamp(self.timestamp);
+        reg.set_node_id(self.node_id.as_str().into());
+        reg.set_disabled(self.disabled);
+        let vclock_bytes = self.vclock.to_capnp_bytes();
+        reg.set_vclock(&vclock_bytes);
+        Ok(())
+    }
+
+    fn to_capnp_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        self.write_to(&mut buf).expect("DisableRegister serialization fail");
+        buf
+    }
+
+    fn is_empty(&self) -> bool {
+        self.value.is_none() && !self.disabled
+    }
+
+    fn validate(&self) -> Result<(), CrdtError> {
+        if self.disabled && self.value.is_some() {
+            return Err(CrdtError::Validation(
+                "DisableRegister holds a value while disabled".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,83 @@
+// Copyright (c) 2026 Adrian Robinson. All rights reserved.
+// Licensed under the MIT License. See LICENSE file in the project root for full license information.
+
+use crate::vector_clock::VectorClock;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+
+/// Dot: A single update in a node's history, identified by `(node_id, counter)`.
+///
+/// The counter is the node's logical clock entry after the update, never a wall-clock
+/// timestamp. Dots order by node id, then counter.
+///
+/// Serializes as a `[node_id, counter]` pair, so formats written with plain tuples are
+/// unchanged.
+///
+/// # Example
+///
+/// ```
+/// use crdt_data_types::{Dot, VectorClock};
+///
+/// let mut vclock = VectorClock::new();
+/// vclock.increment("node_a");
+///
+/// assert!(Dot::new("node_a", 1).is_dominated_by(&vclock));
+/// assert!(!Dot::new("node_a", 2).is_dominated_by(&vclock));
+/// assert!(!Dot::new("node_b", 1).is_dominated_by(&vclock));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct Dot {
+    pub node_id: String,
+    pub counter: u64,
+}
+
+impl Dot {
+    /// Creates the dot `(node_id, counter)`.
+    pub fn new(node_id: impl Into<String>, counter: u64) -> Self {
+        Self {
+            node_id: node_id.into(),
+            counter,
+        }
+    }
+
+    /// Returns true if `vclock` has observed this dot.
+    pub fn is_dominated_by(&self, vclock: &VectorClock) -> bool {
+        vclock.covers(&self.node_id, self.counter)
+    }
+}
+
+impl From<(String, u64)> for Dot {
+    fn from((node_id, counter): (String, u64)) -> Self {
+        Self { node_id, counter }
+    }
+}
+
+impl From<(&str, u64)> for Dot {
+    fn from((node_id, counter): (&str, u64)) -> Self {
+        Self::new(node_id, counter)
+    }
+}
+
+impl From<Dot> for (String, u64) {
+    fn from(dot: Dot) -> Self {
+        (dot.node_id, dot.counter)
+    }
+}
+
+impl fmt::Display for Dot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {})", self.node_id, self.counter)
+    }
+}
+
+impl Serialize for Dot {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        (&self.node_id, self.counter).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Dot {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        <(String, u64)>::deserialize(deserializer).map(Self::from)
+    }
+}
@@ -21,8 +21,12 @@ use crate::traits::CrdtError;
 /// let type_enum = CrdtType::from_str("gcounter").unwrap();
 /// assert_eq!(type_enum, CrdtType::GCounter);
 /// ```
+///
+/// The probabilistic variants only exist with the `probabilistic` feature, and new types
+/// are added over time, so the enum is `#[non_exhaustive]`: match it with a wildcard arm.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
 #[serde(into = "String")]
+#[non_exhaustive]
 pub enum CrdtType {
     GCounter,
     PNCounter,
@@ -34,6 +38,32 @@ pub enum CrdtType {
     MVRegister,
     LWWMap,
     ORMap,
+    #[cfg(feature = "probabilistic")]
+    HyperLogLog,
+    #[cfg(feature = "probabilistic")]
+    CountMinSketch,
+    #[cfg(feature = "probabilistic")]
+    RoaringBitmap,
+    #[cfg(feature = "probabilistic")]
+    TDigest,
+    #[cfg(feature = "probabilistic")]
+    TopK,
+}
+
+impl CrdtType {
+    /// Returns true for the probabilistic sketches, which support state merges through
+    /// the bridge but not deltas.
+    pub fn is_probabilistic(&self) -> bool {
+        match self {
+            #[cfg(feature = "probabilistic")]
+            CrdtType::HyperLogLog
+            | CrdtType::CountMinSketch
+            | CrdtType::RoaringBitmap
+            | CrdtType::TDigest
+            | CrdtType::TopK => true,
+            _ => false,
+        }
+    }
 }
 
 impl fmt::Display for CrdtType {
@@ -49,6 +79,16 @@ impl fmt::Display for CrdtType {
             CrdtType::MVRegister => write!(f, "MVRegister"),
             CrdtType::LWWMap => write!(f, "LWWMap"),
             CrdtType::ORMap => write!(f, "ORMap"),
+            #[cfg(feature = "probabilistic")]
+            CrdtType::HyperLogLog => write!(f, "HyperLogLog"),
+            #[cfg(feature = "probabilistic")]
+            CrdtType::CountMinSketch => write!(f, "CountMinSketch"),
+            #[cfg(feature = "probabilistic")]
+            CrdtType::RoaringBitmap => write!(f, "RoaringBitmap"),
+            #[cfg(feature = "probabilistic")]
+            CrdtType::TDigest => write!(f, "TDigest"),
+            #[cfg(feature = "probabilistic")]
+            CrdtType::TopK => write!(f, "TopK"),
         }
     }
 }
@@ -68,6 +108,11 @@ impl FromStr for CrdtType {
             "mvregister" => Ok(CrdtType::MVRegister),
             "lwwmap" => Ok(CrdtType::LWWMap),
             "ormap" => Ok(CrdtType::ORMap),
+            #[cfg(feature = "probabilistic")]
+            other => ProbabilisticCrdtType::from_str(other)
+                .map(CrdtType::from)
+                .map_err(|_| CrdtError::InvalidInput(format!("Unknown CRDT type: {}", s))),
+            #[cfg(not(feature = "probabilistic"))]
             _ => Err(CrdtError::InvalidInput(format!("Unknown CRDT type: {}", s))),
         }
     }
@@ -100,6 +145,19 @@ pub enum ProbabilisticCrdtType {
     TopK,
 }
 
+#[cfg(feature = "probabilistic")]
+impl From<ProbabilisticCrdtType> for CrdtType {
+    fn from(t: ProbabilisticCrdtType) -> Self {
+        match t {
+            ProbabilisticCrdtType::HyperLogLog => CrdtType::HyperLogLog,
+            ProbabilisticCrdtType::CountMinSketch => CrdtType::CountMinSketch,
+            ProbabilisticCrdtType::RoaringBitmap => CrdtType::RoaringBitmap,
+            ProbabilisticCrdtType::TDigest => CrdtType::TDigest,
+            ProbabilisticCrdtType::TopK => CrdtType::TopK,
+        }
+    }
+}
+
 #[cfg(feature = "probabilistic")]
 impl fmt::Display for ProbabilisticCrdtType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -128,3 +186,123 @@ impl FromStr for ProbabilisticCrdtType {
         }
     }
 }
+
+/// Node-id tie-break direction for Last-Write-Wins types.
+///
+/// When two writes carry the same timestamp, the node identifiers decide the winner.
+/// The direction is part of each LWW type's serialized state; every replica of a given
+/// CRDT must use the same one.
+///
+/// # Usage
+///
+/// ```
+/// use crdt_data_types::{LWWRegister, TieBreak};
+///
+/// let mut reg = LWWRegister::new("a".to_string(), 100, "node_a").with_tie_break(TieBreak::LowerNodeWins);
+/// reg.set("b".to_string(), 100, "node_b");
+/// assert_eq!(reg.value, "a");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum TieBreak {
+    /// The lexicographically greater node id wins (the crate's historical behaviour).
+    #[default]
+    HigherNodeWins,
+    /// The lexicographically lower node id wins.
+    LowerNodeWins,
+}
+
+impl TieBreak {
+    /// Returns true if `candidate` beats `current` on a timestamp tie.
+    ///
+    /// Equal node ids never win, so callers fall through to their own value comparison.
+    pub fn prefers(self, candidate: &str, current: &str) -> bool {
+        match self {
+            TieBreak::HigherNodeWins => candidate > current,
+            TieBreak::LowerNodeWins => candidate < current,
+        }
+    }
+
+    /// Direction that results from merging two states.
+    ///
+    /// Configurations are expected to agree; if they do not, `LowerNodeWins` takes
+    /// precedence so that merging stays deterministic regardless of order.
+    pub fn join(self, other: Self) -> Self {
+        if self == TieBreak::LowerNodeWins || other == TieBreak::LowerNodeWins {
+            TieBreak::LowerNodeWins
+        } else {
+            TieBreak::HigherNodeWins
+        }
+    }
+
+    pub(crate) fn is_default(&self) -> bool {
+        *self == TieBreak::HigherNodeWins
+    }
+}
+
+/// Resolution of a concurrent add and remove of the same element in an `ORSet`.
+///
+/// The policy is part of the set's serialized state; every replica of a given set must
+/// use the same one. An `LWWSet` uses it too, to settle an add and a remove carrying the
+/// same timestamp (see `LWWSet::with_tie_bias`).
+///
+/// # Usage
+///
+/// ```
+/// use crdt_data_types::{ConflictPolicy, ORSet};
+///
+/// let mut a = ORSet::new().with_policy(ConflictPolicy::RemoveWins);
+/// a.insert("node_a", "alice".to_string());
+/// let mut b = a.clone();
+///
+/// a.insert("node_a", "alice".to_string()); // Concurrent re-grant...
+/// b.remove_by("node_b", &"alice".to_string()); // ...and revoke
+///
+/// a.merge(&b);
+/// assert!(!a.contains(&"alice".to_string()));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum ConflictPolicy {
+    /// A concurrent add survives the remove (the crate's historical behaviour).
+    #[default]
+    AddWins,
+    /// A concurrent remove that observed the element suppresses it, even if an add
+    /// happened at the same time. Adds that observed the remove still win.
+    RemoveWins,
+}
+
+impl ConflictPolicy {
+    /// Policy that results from merging two states.
+    ///
+    /// Configurations are expected to agree; if they do not, `RemoveWins` takes
+    /// precedence so that merging stays deterministic regardless of order.
+    pub fn join(self, other: Self) -> Self {
+        if self == ConflictPolicy::RemoveWins || other == ConflictPolicy::RemoveWins {
+            ConflictPolicy::RemoveWins
+        } else {
+            ConflictPolicy::AddWins
+        }
+    }
+
+    pub(crate) fn is_default(&self) -> bool {
+        *self == ConflictPolicy::AddWins
+    }
+}
+
+/// Errors if the given merge settings (tie-break directions, conflict policies) are not
+/// all identical. `what` names the setting in the error message.
+pub(crate) fn ensure_uniform<T, I>(what: &str, settings: I) -> Result<(), CrdtError>
+where
+    T: PartialEq + fmt::Debug,
+    I: IntoIterator<Item = T>,
+{
+    let mut settings = settings.into_iter();
+    if let Some(first) = settings.next() {
+        if let Some(other) = settings.find(|s| *s != first) {
+            return Err(CrdtError::Merge(format!(
+                "Mismatched {}: {:?} vs {:?}",
+                what, first, other
+            )));
+        }
+    }
+    Ok(())
+}
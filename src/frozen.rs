@@ -0,0 +1,81 @@
+// Copyright (c) 2026 Adrian Robinson. All rights reserved.
+// Licensed under the MIT License. See LICENSE file in the project root for full license information.
+
+use crate::traits::{Crdt, CrdtError, CrdtReader};
+use std::sync::{Arc, OnceLock};
+
+/// FrozenView: An immutable snapshot of a CRDT.
+///
+/// Produced by [`Crdt::freeze`] or [`Shared::freeze`](crate::Shared::freeze). The view
+/// holds the serialized Cap'n Proto bytes behind an `Arc` and decodes them on first
+/// access, so taking a snapshot under a lock costs one serialization and reads can then
+/// proceed without the lock. Clones share both the bytes and the decoded value.
+///
+/// Read the decoded CRDT through [`try_get`](Self::try_get), or query the bytes in place
+/// with [`reader`](Self::reader).
+///
+/// # Example
+///
+/// ```
+/// use crdt_data_types::{Crdt, ORSet};
+///
+/// let mut set = ORSet::new();
+/// set.insert("node_a", "apple".to_string());
+/// let view = set.freeze();
+///
+/// set.remove(&"apple".to_string());
+/// assert!(view.try_get().unwrap().contains(&"apple".to_string()));
+/// ```
+pub struct FrozenView<C> {
+    bytes: Arc<[u8]>,
+    decoded: Arc<OnceLock<C>>,
+}
+
+impl<C> Clone for FrozenView<C> {
+    fn clone(&self) -> Self {
+        Self {
+            bytes: Arc::clone(&self.bytes),
+            decoded: Arc::clone(&self.decoded),
+        }
+    }
+}
+
+impl<C: std::fmt::Debug> std::fmt::Debug for FrozenView<C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FrozenView")
+            .field("len", &self.bytes.len())
+            .field("decoded", &self.decoded.get())
+            .finish()
+    }
+}
+
+impl<C: Crdt> FrozenView<C> {
+    /// Wraps bytes produced by `to_capnp_bytes()`.
+    ///
+    /// Crate-private so that views only come from `freeze`.
+    pub(crate) fn from_bytes(bytes: Vec<u8>) -> Self {
+        Self {
+            bytes: bytes.into(),
+            decoded: Arc::new(OnceLock::new()),
+        }
+    }
+
+    /// The serialized snapshot.
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// A zero-copy reader over the snapshot.
+    pub fn reader(&self) -> C::Reader<'_> {
+        C::Reader::from_bytes(&self.bytes)
+    }
+
+    /// Returns the decoded snapshot, decoding it on first use.
+    pub fn try_get(&self) -> Result<&C, CrdtError> {
+        if let Some(value) = self.decoded.get() {
+            return Ok(value);
+        }
+        let value = C::from_capnp_bytes(&self.bytes)?;
+        Ok(self.decoded.get_or_init(|| value))
+    }
+}
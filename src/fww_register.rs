@@ -2,11 +2,13 @@
 // Licensed under the MIT License. See LICENSE file in the project root for full license information.
 
 use crate::fww_register_capnp;
+use crate::node_id::NodeId;
 use crate::traits::{Crdt, CrdtError, CrdtReader};
 use crate::vector_clock::VectorClock;
 use capnp::message::{Builder, HeapAllocator, ReaderOptions};
 use capnp::serialize;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::io::Write;
 
 /// FWW-Register: A First-Write-Wins Register CRDT.
 ///
@@ -79,6 +81,12 @@ impl<T: Clone + Serialize + DeserializeOwned + Send + Sync + 'static> FWWRegiste
         }
     }
 
+    /// Like [`new`](Self::new), but rejects an empty or oversized `node_id` with
+    /// [`CrdtError::InvalidInput`]; see [`NodeId`].
+    pub fn try_new(value: T, timestamp: u64, node_id: &str) -> Result<Self, CrdtError> {
+        Ok(Self::new(value, timestamp, NodeId::new(node_id)?))
+    }
+
     /// Updates the register with a new value and timestamp if it's "earlier".
     pub fn set(&mut self, value: T, timestamp: u64, node_id: impl Into<String>) {
         let node_id = node_id.into();
@@ -102,6 +110,14 @@ impl<T: Clone + Serialize + DeserializeOwned + Send + Sync + 'static> FWWRegiste
         }
         self.vclock.merge(&other.vclock);
     }
+
+    /// Compares the observable value and the logical vclock counters, ignoring wall-clock timestamps.
+    pub fn semantically_eq(&self, other: &Self) -> bool
+    where
+        T: PartialEq,
+    {
+        self.value == other.value && self.vclock.logically_eq(&other.vclock)
+    }
 }
 
 // ============================================================================
@@ -163,6 +179,10 @@ impl<'a, T: Clone + Serialize + DeserializeOwned + Send + Sync + 'static> FWWReg
 impl<'a, T: Clone + Serialize + DeserializeOwned + Send + Sync + 'static> CrdtReader<'a>
     for FWWRegisterReader<'a, T>
 {
+    fn from_bytes(bytes: &'a [u8]) -> Self {
+        Self::new(bytes)
+    }
+
     fn is_empty(&self) -> Result<bool, CrdtError> {
         Ok(self.to_register()?.timestamp == u64::MAX)
     }
@@ -188,20 +208,45 @@ impl<T: Clone + Default + Serialize + DeserializeOwned + Send + Sync + 'static>
         Ok(result)
     }
 
-    fn to_capnp_bytes(&self) -> Vec<u8> {
+    fn merge(&mut self, other: &Self) {
+        self.merge(other);
+    }
+
+    fn dominates(&self, other: &Self) -> bool {
+        // `merge` only replaces the value when `other`'s write came first
+        self.vclock.dominates(&other.vclock)
+            && !(other.timestamp < self.timestamp
+                || (other.timestamp == self.timestamp && other.node_id < self.node_id))
+    }
+
+    fn merge_bytes(&mut self, bytes: &[u8]) -> Result<(), CrdtError> {
+        let other = FWWRegisterReader::new(bytes).to_register()?;
+        self.merge(&other);
+        Ok(())
+    }
+
+    fn write_to<W: Write>(&self, writer: &mut W) -> Result<(), CrdtError> {
         let mut message = Builder::new(HeapAllocator::new());
-        {
-            let mut reg = message.init_root::<fww_register_capnp::fww_register::Builder>();
-            let bytes =
-                bincode::serialize(&self.value).expect("FWWRegister value serialization fail");
-            reg.set_value(&bytes);
-            reg.set_timestamp(self.timestamp);
-            reg.set_node_id(self.node_id.as_str().into());
-            let vclock_bytes = self.vclock.to_capnp_bytes();
-            reg.set_vclock(&vclock_bytes);
-        }
+        self.build_capnp(&mut message)?;
+        serialize::write_message(writer, &message)
+            .map_err(|e| CrdtError::Serialization(e.to_string()))
+    }
+
+    fn build_capnp(&self, message: &mut Builder<HeapAllocator>) -> Result<(), CrdtError> {
+        let mut reg = message.init_root::<fww_register_capnp::fww_register::Builder>();
+        let bytes =
+            bincode::serialize(&self.value).expect("FWWRegister value serialization fail");
+        reg.set_value(&bytes);
+        reg.set_timestamp(self.timestamp);
+        reg.set_node_id(self.node_id.as_str().into());
+        let vclock_bytes = self.vclock.to_capnp_bytes();
+        reg.set_vclock(&vclock_bytes);
+        Ok(())
+    }
+
+    fn to_capnp_bytes(&self) -> Vec<u8> {
         let mut buf = Vec::new();
-        serialize::write_message(&mut buf, &message).expect("FWWRegister serialization fail");
+        self.write_to(&mut buf).expect("FWWRegister serialization fail");
         buf
     }
 
@@ -0,0 +1,119 @@
+// Copyright (c) 2026 Adrian Robinson. All rights reserved.
+// Licensed under the MIT License. See LICENSE file in the project root for full license information.
+
+use crate::g_counter::GCounter;
+use crate::g_set::GSet;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::hash::Hash;
+
+/// GCountedSet: A G-Set that also counts how many times each element was added.
+///
+/// Membership behaves exactly like a [`GSet`]. Alongside it, every element carries a
+/// [`GCounter`] keyed by node, which each `insert` bumps by one. `count` is therefore the
+/// number of adds across all nodes.
+///
+/// # Add-count Semantics
+///
+/// Counts merge like G-Counters: per node, the larger count wins, and the per-node
+/// counts are summed. Merging the same state twice is a no-op, and adds of the same
+/// element by different nodes all count. A node must not re-create its own history
+/// (e.g. after losing local state), since its counts would be masked by the larger
+/// counts already replicated.
+///
+/// # Example
+///
+/// ```
+/// use crdt_data_types::GCountedSet;
+///
+/// let mut set1 = GCountedSet::new();
+/// set1.insert("node_a", "apple".to_string());
+/// set1.insert("node_a", "apple".to_string());
+///
+/// let mut set2 = GCountedSet::new();
+/// set2.insert("node_b", "apple".to_string());
+///
+/// set1.merge(&set2);
+/// set1.merge(&set2);
+/// assert_eq!(set1.len(), 1);
+/// assert_eq!(set1.count(&"apple".to_string()), 3);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "T: Serialize",
+    deserialize = "T: DeserializeOwned + Eq + Hash + Ord"
+))]
+pub struct GCountedSet<T: Eq + Hash + Ord> {
+    /// Set membership.
+    pub set: GSet<T>,
+    /// Per-element add counts, sorted by element.
+    pub counts: Vec<(T, GCounter)>,
+}
+
+impl<T: Eq + Hash + Ord> Default for GCountedSet<T> {
+    fn default() -> Self {
+        Self {
+            set: GSet::new(),
+            counts: Vec::new(),
+        }
+    }
+}
+
+impl<T: Clone + Eq + Hash + Ord + Serialize + DeserializeOwned + Send + Sync + 'static>
+    GCountedSet<T>
+{
+    /// Creates a new, empty set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an element, recording one more add by `node_id`.
+    pub fn insert(&mut self, node_id: &str, element: T) {
+        let idx = match self.counts.binary_search_by(|(e, _)| e.cmp(&element)) {
+            Ok(idx) => idx,
+            Err(idx) => {
+                self.counts.insert(idx, (element.clone(), GCounter::new()));
+                idx
+            }
+        };
+        self.counts[idx].1.increment(node_id, 1);
+        self.set.insert(node_id, element);
+    }
+
+    /// Returns the total number of times `element` was added across all nodes.
+    pub fn count(&self, element: &T) -> i64 {
+        self.counts
+            .binary_search_by(|(e, _)| e.cmp(element))
+            .map_or(0, |idx| self.counts[idx].1.value())
+    }
+
+    /// Returns true if the set contains the element.
+    pub fn contains(&self, element: &T) -> bool {
+        self.set.contains(element)
+    }
+
+    /// Iterator over `(element, count)` pairs in ascending element order.
+    pub fn iter(&self) -> impl Iterator<Item = (&T, i64)> {
+        self.counts.iter().map(|(e, counter)| (e, counter.value()))
+    }
+
+    /// Number of distinct elements.
+    pub fn len(&self) -> usize {
+        self.counts.len()
+    }
+
+    /// Returns true if no element has been added.
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    /// Merges another set into this one: union of elements, G-Counter merge of counts.
+    pub fn merge(&mut self, other: &Self) {
+        self.set.merge(&other.set);
+        for (element, counter) in &other.counts {
+            match self.counts.binary_search_by(|(e, _)| e.cmp(element)) {
+                Ok(idx) => self.counts[idx].1.merge(counter),
+                Err(idx) => self.counts.insert(idx, (element.clone(), counter.clone())),
+            }
+        }
+    }
+}
@@ -2,11 +2,14 @@
 // Licensed under the MIT License. See LICENSE file in the project root for full license information.
 
 use crate::gcounter_capnp;
-use crate::traits::{Crdt, CrdtError, CrdtReader};
+use crate::size_estimate::{data_size, struct_size, text_size, MESSAGE_OVERHEAD, WORD};
+use crate::traits::{merge_is_noop, Crdt, CrdtError, CrdtReader};
 use crate::vector_clock::VectorClock;
 use capnp::message::{Builder, HeapAllocator, ReaderOptions};
 use capnp::serialize;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Write;
 
 /// G-Counter: A Grow-only Counter CRDT.
 ///
@@ -98,6 +101,30 @@ impl GCounter {
         Self::default()
     }
 
+    /// Builds a counter from existing per-node totals, e.g. when migrating from another system.
+    ///
+    /// Each node's vclock entry is advanced once, rather than once per increment.
+    ///
+    /// # Errors
+    /// Returns `CrdtError::InvalidInput` if any count is negative.
+    pub fn from_node_counts(counts: HashMap<String, i64>) -> Result<Self, CrdtError> {
+        let mut counter = Self::new();
+        let mut counters: Vec<(String, i64)> = Vec::with_capacity(counts.len());
+        for (node_id, count) in counts {
+            if count < 0 {
+                return Err(CrdtError::InvalidInput(format!(
+                    "Negative count {} for node {}",
+                    count, node_id
+                )));
+            }
+            counter.vclock.increment(&node_id);
+            counters.push((node_id, count));
+        }
+        counters.sort_by(|a, b| a.0.cmp(&b.0));
+        counter.counters = counters;
+        Ok(counter)
+    }
+
     /// Increments the counter for a specific node by a given delta.
     ///
     /// # Arguments
@@ -121,15 +148,27 @@ impl GCounter {
     }
 
     /// Returns the total aggregated value of the counter.
+    ///
+    /// The sum is accumulated in `i128` and saturates at the `i64` bounds rather than wrapping.
     pub fn value(&self) -> i64 {
-        self.counters.iter().map(|(_, v)| v).sum()
+        saturate_i64(self.value_i128())
+    }
+
+    /// Returns the exact sum of all per-node counters without saturation.
+    pub(crate) fn value_i128(&self) -> i128 {
+        self.counters.iter().map(|(_, v)| *v as i128).sum()
     }
 
     /// Merges another G-Counter into this one.
     ///
     /// This implementation uses a linear scan merge of the sorted vectors,
     /// which is significantly faster and more cache-friendly than HashMap merging.
+    /// Counters take the per-node max regardless of either vclock, so states built with
+    /// an empty or stale vclock still converge; the vclocks are joined separately.
     pub fn merge(&mut self, other: &Self) {
+        if other.is_identity() {
+            return;
+        }
         let mut new_counters = Vec::with_capacity(self.counters.len() + other.counters.len());
         let mut i = 0;
         let mut j = 0;
@@ -166,6 +205,22 @@ impl GCounter {
         self.vclock.merge(&other.vclock);
     }
 
+    /// Iterator over the `(node_id, count)` pairs that advanced beyond a peer's clock.
+    ///
+    /// A node is yielded when our vclock counter for it exceeds the peer's in `clock`
+    /// (a missing entry counts as zero). Merging just these counts into the peer brings it
+    /// up to date, so this is the counter delta to gossip.
+    pub fn advanced_since<'a>(
+        &'a self,
+        clock: &'a VectorClock,
+    ) -> impl Iterator<Item = (&'a str, i64)> + 'a {
+        let counter = |vc: &VectorClock, node: &str| vc.clocks.get(node).map_or(0, |(c, _)| *c);
+        self.counters
+            .iter()
+            .filter(move |(node, _)| counter(&self.vclock, node) > counter(clock, node))
+            .map(|(node, count)| (node.as_str(), *count))
+    }
+
     /// Additively merge another counter's values into this one.
     /// Unlike `merge()` which uses max semantics, this ADDS the values.
     pub fn add_state(&mut self, other: &Self) {
@@ -173,6 +228,22 @@ impl GCounter {
             self.increment(node_id, *count);
         }
     }
+
+    /// Zeroes a node's contribution (e.g. a compromised or decommissioned node).
+    ///
+    /// This is a privileged, local administrative operation. Merge takes the per-node
+    /// maximum, so any replica that still holds the node's counter will restore it on the
+    /// next merge; the eviction must be applied on every replica to converge.
+    pub fn evict_node(&mut self, node_id: &str) {
+        if let Ok(idx) = self.counters.binary_search_by(|(k, _)| k.as_str().cmp(node_id)) {
+            self.counters.remove(idx);
+        }
+    }
+}
+
+/// Clamps an `i128` accumulator to the `i64` range.
+pub(crate) fn saturate_i64(value: i128) -> i64 {
+    value.clamp(i64::MIN as i128, i64::MAX as i128) as i64
 }
 
 // ============================================================================
@@ -229,6 +300,10 @@ impl<'a> GCounterReader<'a> {
 }
 
 impl<'a> CrdtReader<'a> for GCounterReader<'a> {
+    fn from_bytes(bytes: &'a [u8]) -> Self {
+        Self::new(bytes)
+    }
+
     fn is_empty(&self) -> Result<bool, CrdtError> {
         Ok(self.to_gcounter()?.counters.is_empty())
     }
@@ -302,24 +377,62 @@ impl Crdt for GCounter {
         Ok(result)
     }
 
-    fn to_capnp_bytes(&self) -> Vec<u8> {
+    fn is_identity(&self) -> bool {
+        *self == Self::default()
+    }
+
+    fn merge(&mut self, other: &Self) {
+        self.merge(other);
+    }
+
+    fn dominates(&self, other: &Self) -> bool {
+        self.vclock.dominates(&other.vclock) && merge_is_noop(self, other)
+    }
+
+    fn merge_bytes(&mut self, bytes: &[u8]) -> Result<(), CrdtError> {
+        let other = GCounterReader::new(bytes).to_gcounter()?;
+        self.merge(&other);
+        Ok(())
+    }
+
+    fn write_to<W: Write>(&self, writer: &mut W) -> Result<(), CrdtError> {
         let mut message = Builder::new(HeapAllocator::new());
-        {
-            let mut gcounter = message.init_root::<gcounter_capnp::g_counter::Builder>();
-            let mut entries = gcounter.reborrow().init_entries(self.counters.len() as u32);
-            for (idx, (node_id, count)) in self.counters.iter().enumerate() {
-                let mut entry = entries.reborrow().get(idx as u32);
-                entry.set_node_id(node_id.as_str().into());
-                entry.set_count(*count);
-            }
-            let vclock_bytes = self.vclock.to_capnp_bytes();
-            gcounter.set_vclock(&vclock_bytes);
+        self.build_capnp(&mut message)?;
+        serialize::write_message(writer, &message)
+            .map_err(|e| CrdtError::Serialization(e.to_string()))
+    }
+
+    fn build_capnp(&self, message: &mut Builder<HeapAllocator>) -> Result<(), CrdtError> {
+        let mut gcounter = message.init_root::<gcounter_capnp::g_counter::Builder>();
+        let mut entries = gcounter.reborrow().init_entries(self.counters.len() as u32);
+        for (idx, (node_id, count)) in self.counters.iter().enumerate() {
+            let mut entry = entries.reborrow().get(idx as u32);
+            entry.set_node_id(node_id.as_str().into());
+            entry.set_count(*count);
         }
+        let vclock_bytes = self.vclock.to_capnp_bytes();
+        gcounter.set_vclock(&vclock_bytes);
+        Ok(())
+    }
+
+    fn to_capnp_bytes(&self) -> Vec<u8> {
         let mut buf = Vec::new();
-        serialize::write_message(&mut buf, &message).expect("GCounter serialization fail");
+        self.write_to(&mut buf).expect("GCounter serialization fail");
         buf
     }
 
+    fn estimated_capnp_size(&self) -> usize {
+        MESSAGE_OVERHEAD
+            + struct_size(0, 2)
+            + WORD
+            + self
+                .counters
+                .iter()
+                .map(|(node, _)| struct_size(1, 1) + text_size(node.len()))
+                .sum::<usize>()
+            + data_size(self.vclock.estimated_capnp_size())
+    }
+
     fn is_empty(&self) -> bool {
         self.counters.is_empty()
     }
@@ -1,14 +1,19 @@
 // Copyright (c) 2026 Adrian Robinson. All rights reserved.
 // Licensed under the MIT License. See LICENSE file in the project root for full license information.
 
+use crate::bloom::{self, BloomFilter};
 use crate::gset_capnp;
-use crate::traits::{Crdt, CrdtError, CrdtReader};
+use crate::text_element::{from_text, read_text};
+use crate::traits::{merge_is_noop, Crdt, CrdtError, CrdtReader};
+use crate::type_tag::{check_type_tag, type_tag};
 use crate::vector_clock::VectorClock;
 use capnp::message::{Builder, HeapAllocator, ReaderOptions};
 use capnp::serialize;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::cmp::Ordering;
+use std::collections::HashSet;
 use std::hash::Hash;
+use std::io::{BufRead, Write};
 
 /// G-Set: A Grow-only Set CRDT.
 ///
@@ -42,11 +47,18 @@ use std::hash::Hash;
 /// assert!(set1.contains(&"apple".to_string()));
 /// assert!(set1.contains(&"banana".to_string()));
 /// ```
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+///
+/// For large sets queried mostly for absent elements, [`GSet::with_bloom`] attaches a local
+/// bloom filter, as for [`ORSet`](crate::ORSet). It does not change any result.
+///
+/// The filter is kept in a private field, which makes `GSet` `#[non_exhaustive]`: create
+/// sets with [`GSet::new`] or `Default` rather than a struct literal.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(bound(
     serialize = "T: Serialize",
     deserialize = "T: DeserializeOwned + Eq + Hash + Ord"
 ))]
+#[non_exhaustive]
 pub struct GSet<T: Eq + Hash + Ord> {
     /// Internal storage for set elements.
     #[serde(serialize_with = "serialize_elements", deserialize_with = "deserialize_elements")]
@@ -54,8 +66,19 @@ pub struct GSet<T: Eq + Hash + Ord> {
     /// Vector clock for tracking causal history.
     #[serde(default)]
     pub vclock: VectorClock,
+    /// Optional membership filter for fast negative lookups. Local-only.
+    #[serde(skip)]
+    bloom: Option<BloomFilter>,
+}
+
+impl<T: Eq + Hash + Ord> PartialEq for GSet<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.elements == other.elements && self.vclock == other.vclock
+    }
 }
 
+impl<T: Eq + Hash + Ord> Eq for GSet<T> {}
+
 fn serialize_elements<S, T>(elements: &Vec<T>, serializer: S) -> Result<S::Ok, S::Error>
 where
     S: serde::Serializer,
@@ -108,6 +131,7 @@ impl<T: Eq + Hash + Ord> Default for GSet<T> {
         Self {
             elements: Vec::new(),
             vclock: VectorClock::new(),
+            bloom: None,
         }
     }
 }
@@ -117,12 +141,26 @@ impl<T: Eq + Hash + Ord> GSet<T> {
     pub fn new() -> Self {
         Self::default()
     }
+
+    /// Attaches a bloom filter sized for `expected` elements at false-positive rate
+    /// `fp_rate` (clamped to `[1e-9, 0.5]`), seeded with the current elements.
+    pub fn with_bloom(mut self, expected: usize, fp_rate: f64) -> Self {
+        let mut bloom = BloomFilter::new(expected, fp_rate);
+        for element in &self.elements {
+            bloom.insert(element);
+        }
+        self.bloom = Some(bloom);
+        self
+    }
 }
 
 impl<T: Clone + Eq + Hash + Ord + Serialize + DeserializeOwned + Send + Sync + 'static> GSet<T> {
     /// Adds an element to the set.
     pub fn insert(&mut self, node_id: &str, element: T) {
         if let Err(idx) = self.elements.binary_search(&element) {
+            if let Some(bloom) = &mut self.bloom {
+                bloom.insert(&element);
+            }
             self.elements.insert(idx, element);
             self.vclock.increment(node_id);
         }
@@ -130,16 +168,47 @@ impl<T: Clone + Eq + Hash + Ord + Serialize + DeserializeOwned + Send + Sync + '
 
     /// Returns true if the set contains the element.
     pub fn contains(&self, element: &T) -> bool {
+        if self.bloom.as_ref().is_some_and(|bloom| !bloom.might_contain(element)) {
+            return false;
+        }
         self.elements.binary_search(element).is_ok()
     }
 
-    /// Iterator over the elements in the set.
+    /// Iterator over the elements in the set, in unspecified order.
     pub fn iter(&self) -> impl Iterator<Item = &T> {
         self.elements.iter()
     }
 
+    /// Iterator over the elements in ascending order.
+    ///
+    /// The order is guaranteed, independent of insertion or merge order.
+    pub fn sorted_iter(&self) -> impl Iterator<Item = &T> {
+        // Elements are kept sorted for binary search, so no extra work is needed
+        self.elements.iter()
+    }
+
+    /// Elements present in both sets. A read-only query; neither set is modified.
+    pub fn intersection(&self, other: &Self) -> HashSet<T> {
+        self.iter().filter(|e| other.contains(e)).cloned().collect()
+    }
+
+    /// Elements in this set but not in `other`, e.g. what a peer is missing.
+    pub fn difference(&self, other: &Self) -> HashSet<T> {
+        self.iter().filter(|e| !other.contains(e)).cloned().collect()
+    }
+
+    /// Elements in exactly one of the two sets.
+    pub fn symmetric_difference(&self, other: &Self) -> HashSet<T> {
+        let mut result = self.difference(other);
+        result.extend(other.difference(self));
+        result
+    }
+
     /// Merges another G-Set into this one.
     pub fn merge(&mut self, other: &Self) {
+        if other.is_identity() {
+            return;
+        }
         let mut result = Vec::with_capacity(self.elements.len() + other.elements.len());
         let mut i = 0;
         let mut j = 0;
@@ -167,6 +236,90 @@ impl<T: Clone + Eq + Hash + Ord + Serialize + DeserializeOwned + Send + Sync + '
 
         self.elements = result;
         self.vclock.merge(&other.vclock);
+        bloom::refresh_after_merge(&mut self.bloom, other.bloom.as_ref(), self.elements.iter());
+    }
+
+    /// Compares the observable elements and the logical vclock counters, ignoring wall-clock timestamps.
+    pub fn semantically_eq(&self, other: &Self) -> bool {
+        self.elements == other.elements && self.vclock.logically_eq(&other.vclock)
+    }
+}
+
+impl GSet<String> {
+    /// Builds a set from newline-delimited input, streaming one line at a time.
+    ///
+    /// Trailing `\r` is stripped and empty lines are skipped. The vector clock is
+    /// incremented once for the whole batch rather than once per element.
+    pub fn from_lines<R: BufRead>(node_id: &str, reader: R) -> Result<Self, CrdtError> {
+        let mut set = Self::new();
+        for line in reader.lines() {
+            let mut line = line.map_err(|e| CrdtError::Deserialization(e.to_string()))?;
+            if line.ends_with('\r') {
+                line.pop();
+            }
+            if line.is_empty() {
+                continue;
+            }
+            set.elements.push(line);
+        }
+        // Sort once at the end instead of keeping the vector ordered per insert
+        set.elements.sort_unstable();
+        set.elements.dedup();
+        if !set.elements.is_empty() {
+            set.vclock.increment(node_id);
+        }
+        Ok(set)
+    }
+}
+
+// ============================================================================
+// Std Collection Conversions
+// ============================================================================
+
+/// Node identifier recorded in the vector clock for elements imported from std collections.
+const IMPORT_NODE_ID: &str = "import";
+
+impl<T: Clone + Eq + Hash + Ord + Serialize + DeserializeOwned + Send + Sync + 'static> From<HashSet<T>>
+    for GSet<T>
+{
+    fn from(set: HashSet<T>) -> Self {
+        set.into_iter().collect()
+    }
+}
+
+impl<T: Clone + Eq + Hash + Ord + Serialize + DeserializeOwned + Send + Sync + 'static> FromIterator<T>
+    for GSet<T>
+{
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut set = Self::new();
+        for element in iter {
+            set.insert(IMPORT_NODE_ID, element);
+        }
+        set
+    }
+}
+
+impl<T: Eq + Hash + Ord> IntoIterator for GSet<T> {
+    type Item = T;
+    type IntoIter = std::vec::IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.elements.into_iter()
+    }
+}
+
+impl<'a, T: Eq + Hash + Ord> IntoIterator for &'a GSet<T> {
+    type Item = &'a T;
+    type IntoIter = std::slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.elements.iter()
+    }
+}
+
+impl<T: Eq + Hash + Ord> From<GSet<T>> for HashSet<T> {
+    fn from(set: GSet<T>) -> Self {
+        set.elements.into_iter().collect()
     }
 }
 
@@ -189,23 +342,75 @@ impl<'a, T: Clone + Eq + Hash + Ord + Serialize + DeserializeOwned + Send + Sync
         }
     }
 
-    fn to_set(&self) -> Result<GSet<T>, CrdtError> {
+    /// Returns true if `element` is in the serialized set, decoding elements one at a
+    /// time until a match rather than building the set.
+    pub fn contains(&self, element: &T) -> Result<bool, CrdtError> {
         let reader = serialize::read_message(self.bytes, ReaderOptions::new())
             .map_err(|e: capnp::Error| CrdtError::Deserialization(e.to_string()))?;
         let gset = reader
             .get_root::<gset_capnp::g_set::Reader>()
             .map_err(|e: capnp::Error| CrdtError::Deserialization(e.to_string()))?;
+        check_type_tag::<T>(gset.get_element_type())?;
 
-        let mut elements = Vec::new();
-        let elements_list = gset
-            .get_elements()
+        match gset
+            .which()
+            .map_err(|e| CrdtError::Deserialization(e.to_string()))?
+        {
+            gset_capnp::g_set::Elements(elements_list) => {
+                let elements_list = elements_list
+                    .map_err(|e: capnp::Error| CrdtError::Deserialization(e.to_string()))?;
+                for entry in elements_list {
+                    let bytes = entry.map_err(|e| CrdtError::Deserialization(e.to_string()))?;
+                    let candidate: T = bincode::deserialize(bytes)
+                        .map_err(|e: bincode::Error| CrdtError::Deserialization(e.to_string()))?;
+                    if candidate == *element {
+                        return Ok(true);
+                    }
+                }
+            }
+            gset_capnp::g_set::TextElements(text_list) => {
+                let text_list = text_list
+                    .map_err(|e: capnp::Error| CrdtError::Deserialization(e.to_string()))?;
+                for entry in text_list {
+                    if from_text::<T>(read_text(entry)?)? == *element {
+                        return Ok(true);
+                    }
+                }
+            }
+        }
+        Ok(false)
+    }
+
+    fn to_set(&self) -> Result<GSet<T>, CrdtError> {
+        let reader = serialize::read_message(self.bytes, ReaderOptions::new())
             .map_err(|e: capnp::Error| CrdtError::Deserialization(e.to_string()))?;
+        let gset = reader
+            .get_root::<gset_capnp::g_set::Reader>()
+            .map_err(|e: capnp::Error| CrdtError::Deserialization(e.to_string()))?;
+        check_type_tag::<T>(gset.get_element_type())?;
 
-        for entry in elements_list {
-            let bytes = entry.map_err(|e| CrdtError::Deserialization(e.to_string()))?;
-            let element: T = bincode::deserialize(bytes)
-                .map_err(|e: bincode::Error| CrdtError::Deserialization(e.to_string()))?;
-            elements.push(element);
+        let mut elements = Vec::new();
+        match gset
+            .which()
+            .map_err(|e| CrdtError::Deserialization(e.to_string()))?
+        {
+            gset_capnp::g_set::Elements(elements_list) => {
+                let elements_list = elements_list
+                    .map_err(|e: capnp::Error| CrdtError::Deserialization(e.to_string()))?;
+                for entry in elements_list {
+                    let bytes = entry.map_err(|e| CrdtError::Deserialization(e.to_string()))?;
+                    let element: T = bincode::deserialize(bytes)
+                        .map_err(|e: bincode::Error| CrdtError::Deserialization(e.to_string()))?;
+                    elements.push(element);
+                }
+            }
+            gset_capnp::g_set::TextElements(text_list) => {
+                let text_list = text_list
+                    .map_err(|e: capnp::Error| CrdtError::Deserialization(e.to_string()))?;
+                for entry in text_list {
+                    elements.push(from_text(read_text(entry)?)?);
+                }
+            }
         }
         elements.sort();
         elements.dedup();
@@ -221,7 +426,11 @@ impl<'a, T: Clone + Eq + Hash + Ord + Serialize + DeserializeOwned + Send + Sync
             VectorClock::new()
         };
 
-        Ok(GSet { elements, vclock })
+        Ok(GSet {
+            elements,
+            vclock,
+            bloom: None,
+        })
     }
 }
 
@@ -229,6 +438,10 @@ impl<'a, T> CrdtReader<'a> for GSetReader<'a, T>
 where
     T: Clone + Eq + Hash + Ord + Serialize + DeserializeOwned + Send + Sync + 'static,
 {
+    fn from_bytes(bytes: &'a [u8]) -> Self {
+        Self::new(bytes)
+    }
+
     fn is_empty(&self) -> Result<bool, CrdtError> {
         Ok(self.to_set()?.elements.is_empty())
     }
@@ -238,6 +451,54 @@ where
 // CRDT Trait Implementation
 // ============================================================================
 
+impl<T: Clone + Eq + Hash + Ord + Serialize + DeserializeOwned + Send + Sync + 'static> GSet<T> {
+    /// Serializes elements as plain UTF-8 `Text` ([plain-text encoding](crate#plain-text-encoding)).
+    pub fn to_capnp_text_bytes(&self) -> Vec<u8>
+    where
+        T: AsRef<str>,
+    {
+        let mut buf = Vec::new();
+        self.write_capnp(Some(<T as AsRef<str>>::as_ref), &mut buf)
+            .expect("GSet serialization fail");
+        buf
+    }
+
+    fn write_capnp<W: Write>(&self, as_text: Option<fn(&T) -> &str>, writer: &mut W) -> capnp::Result<()> {
+        let mut message = Builder::new(HeapAllocator::new());
+        self.build_message(as_text, &mut message)?;
+        serialize::write_message(writer, &message)
+    }
+
+    fn build_message(
+        &self,
+        as_text: Option<fn(&T) -> &str>,
+        message: &mut Builder<HeapAllocator>,
+    ) -> capnp::Result<()> {
+        let mut gset = message.init_root::<gset_capnp::g_set::Builder>();
+        match as_text {
+            Some(as_text) => {
+                let mut elements =
+                    gset.reborrow().init_text_elements(self.elements.len() as u32);
+                for (idx, element) in self.elements.iter().enumerate() {
+                    elements.set(idx as u32, as_text(element).into());
+                }
+            }
+            None => {
+                let mut elements = gset.reborrow().init_elements(self.elements.len() as u32);
+                for (idx, element) in self.elements.iter().enumerate() {
+                    let bytes =
+                        bincode::serialize(element).expect("GSet element serialization fail");
+                    elements.set(idx as u32, &bytes);
+                }
+            }
+        }
+        let vclock_bytes = self.vclock.to_capnp_bytes();
+        gset.set_vclock(&vclock_bytes);
+        gset.set_element_type(type_tag::<T>());
+        Ok(())
+    }
+}
+
 impl<T: Clone + Eq + Hash + Ord + Serialize + DeserializeOwned + Send + Sync + 'static> Crdt
     for GSet<T>
 {
@@ -251,20 +512,37 @@ impl<T: Clone + Eq + Hash + Ord + Serialize + DeserializeOwned + Send + Sync + '
         Ok(result)
     }
 
+    fn is_identity(&self) -> bool {
+        *self == Self::default()
+    }
+
+    fn merge(&mut self, other: &Self) {
+        self.merge(other);
+    }
+
+    fn dominates(&self, other: &Self) -> bool {
+        self.vclock.dominates(&other.vclock) && merge_is_noop(self, other)
+    }
+
+    fn merge_bytes(&mut self, bytes: &[u8]) -> Result<(), CrdtError> {
+        let other = GSetReader::new(bytes).to_set()?;
+        self.merge(&other);
+        Ok(())
+    }
+
+    fn write_to<W: Write>(&self, writer: &mut W) -> Result<(), CrdtError> {
+        self.write_capnp(None, writer)
+            .map_err(|e| CrdtError::Serialization(e.to_string()))
+    }
+
+    fn build_capnp(&self, message: &mut Builder<HeapAllocator>) -> Result<(), CrdtError> {
+        self.build_message(None, message)
+            .map_err(|e| CrdtError::Serialization(e.to_string()))
+    }
+
     fn to_capnp_bytes(&self) -> Vec<u8> {
-        let mut message = Builder::new(HeapAllocator::new());
-        {
-            let mut gset = message.init_root::<gset_capnp::g_set::Builder>();
-            let mut elements = gset.reborrow().init_elements(self.elements.len() as u32);
-            for (idx, element) in self.elements.iter().enumerate() {
-                let bytes = bincode::serialize(element).expect("GSet element serialization fail");
-                elements.set(idx as u32, &bytes);
-            }
-            let vclock_bytes = self.vclock.to_capnp_bytes();
-            gset.set_vclock(&vclock_bytes);
-        }
         let mut buf = Vec::new();
-        serialize::write_message(&mut buf, &message).expect("GSet serialization fail");
+        self.write_capnp(None, &mut buf).expect("GSet serialization fail");
         buf
     }
 
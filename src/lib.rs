@@ -5,51 +5,85 @@
 //!
 //! A high-performance library of Conflict-free Replicated Data Types (CRDTs)
 //! optimized for zero-copy merge operations using Cap'n Proto.
+//!
+//! ## Plain-text encoding
+//!
+//! Sets and maps store each element (or key) as an opaque serialized blob. For
+//! string-like types, `to_capnp_text_bytes` writes the raw UTF-8 into a capnp `Text`
+//! field instead, which is smaller and readable by non-Rust capnp consumers. Readers
+//! accept both encodings, so this is purely a producer-side choice.
 
+pub mod atomic_g_counter;
 pub mod bridge;
+mod bloom;
 pub mod compaction;
+pub mod disable_register;
+pub mod dot;
 pub mod enums;
+pub mod frozen;
 pub mod fww_register;
+pub mod g_counted_set;
 pub mod g_counter;
 pub mod g_set;
 pub mod lww_map;
 pub mod lww_register;
 pub mod lww_set;
+pub mod merge_ext;
 pub mod mv_register;
+pub mod node_id;
 pub mod or_map;
 pub mod or_set;
 pub mod pn_counter;
 #[cfg(feature = "probabilistic")]
 pub mod probabilistic;
+pub mod replay;
+pub mod shared;
+mod node_ids;
+mod size_estimate;
+pub mod sync;
+#[cfg(feature = "testkit")]
+pub mod testkit;
+mod text_element;
+mod trace;
 pub mod traits;
+mod type_tag;
 pub mod vector_clock;
 
 // Re-export core traits
-pub use traits::{Crdt, CrdtError, CrdtReader};
+pub use compaction::merge_all;
+pub use merge_ext::MergeExt;
+pub use traits::{Crdt, CrdtError, CrdtKind, CrdtReader};
 
 // Re-export enums
-pub use enums::CrdtType;
+pub use enums::{ConflictPolicy, CrdtType, TieBreak};
 #[cfg(feature = "probabilistic")]
 pub use enums::ProbabilisticCrdtType;
 
 // Re-export types as they are implemented
+pub use atomic_g_counter::AtomicGCounter;
 pub use bridge::SerdeCapnpBridge;
+pub use disable_register::{DisableRegister, DisableRegisterReader};
+pub use dot::Dot;
+pub use frozen::FrozenView;
 pub use fww_register::{FWWRegister, FWWRegisterReader};
+pub use g_counted_set::GCountedSet;
 pub use g_counter::{GCounter, GCounterReader};
 pub use g_set::{GSet, GSetReader};
 pub use lww_map::{LWWMap, LWWMapReader};
 pub use lww_register::{LWWRegister, LWWRegisterReader};
 pub use lww_set::{LWWSet, LWWSetReader};
 pub use mv_register::{MVRegister, MVRegisterReader};
+pub use node_id::{NodeId, MAX_NODE_ID_LEN};
 pub use or_map::{ORMap, ORMapReader};
 pub use or_set::{ORSet, ORSetReader};
 pub use pn_counter::{PNCounter, PNCounterReader};
+pub use shared::Shared;
 pub use vector_clock::{VectorClock, VectorClockReader};
 
 #[cfg(feature = "probabilistic")]
 pub use probabilistic::{
-    CountMinSketch, CountMinSketchReader, HyperLogLog, HyperLogLogReader, RoaringBitmap,
-    RoaringBitmapReader, TDigest, TDigestReader, TopK, TopKReader,
+    ApproxSet, CountMinSketch, CountMinSketchReader, HyperLogLog, HyperLogLogReader,
+    RoaringBitmap, RoaringBitmapReader, TDigest, TDigestReader, TopK, TopKReader,
 };
 
 // Include generated Cap'n Proto modules
@@ -80,6 +114,9 @@ pub mod tdigest_capnp {
 pub mod topk_capnp {
     include!(concat!(env!("OUT_DIR"), "/proto/topk_capnp.rs"));
 }
+pub mod disable_register_capnp {
+    include!(concat!(env!("OUT_DIR"), "/proto/disable_register_capnp.rs"));
+}
 pub mod fww_register_capnp {
     include!(concat!(env!("OUT_DIR"), "/proto/fww_register_capnp.rs"));
 }
@@ -1,14 +1,23 @@
 // Copyright (c) 2026 Adrian Robinson. All rights reserved.
 // Licensed under the MIT License. See LICENSE file in the project root for full license information.
 
+use crate::enums::{ensure_uniform, TieBreak};
 use crate::lww_map_capnp;
-use crate::traits::{Crdt, CrdtError, CrdtReader};
+use crate::node_ids::{read_node_ids, resolve, NodeIdTable};
+use crate::size_estimate::{
+    check_entry_budget, data_size, struct_size, text_size, MESSAGE_OVERHEAD, WORD,
+};
+use crate::text_element::{from_text, read_text};
+use crate::traits::{merge_is_noop, Crdt, CrdtError, CrdtReader};
+use crate::type_tag::{check_pair_type_tag, pair_type_tag};
 use crate::vector_clock::VectorClock;
 use capnp::message::{Builder, HeapAllocator, ReaderOptions};
 use capnp::serialize;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::cmp::Ordering;
 use std::hash::Hash;
+use std::io::Write;
+use std::time::Duration;
 
 /// LWW-Map: A Last-Write-Wins Map CRDT.
 ///
@@ -42,6 +51,24 @@ use std::hash::Hash;
 /// map1.merge(&map2);
 /// assert_eq!(map1.get(&"key1".to_string()), Some(&"value2".to_string())); // Higher timestamp wins
 /// ```
+///
+/// # Multi-Value Mode
+///
+/// Writes with identical timestamp *and* node_id (possible with client-supplied clocks) are
+/// normally resolved by comparing values, silently discarding the loser. A map created with
+/// [`LWWMap::multi`] instead retains every tied value, retrievable via [`LWWMap::get_all`].
+///
+/// **Storage cost**: each retained tie stores an extra copy of the value (plus its key) until a
+/// strictly newer write for that key supersedes it, so heavily contended keys grow linearly
+/// with the number of distinct tied writes.
+///
+/// # Expiry
+///
+/// A map configured with [`LWWMap::with_ttl`] treats entry timestamps as epoch
+/// milliseconds. [`LWWMap::expire_stale`] drops entries older than the TTL and leaves a
+/// tombstone at the expired write's timestamp, so a replica that has not yet expired the
+/// entry cannot resurrect it on merge, while a newer write to the key still wins. All
+/// replicas must use the same TTL; merging maps with different TTLs keeps the shorter one.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(bound(
     serialize = "K: Serialize, V: Serialize",
@@ -54,6 +81,21 @@ pub struct LWWMap<K: Eq + Hash + Ord, V> {
     /// Vector clock representing the causal history of the map.
     #[serde(default)]
     pub vclock: VectorClock,
+    /// Additional values that tied with the winning entry on timestamp and node_id, sorted by key.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ties: Vec<(K, Vec<V>)>,
+    /// When true, tied writes are retained in `ties` rather than discarded.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub multi_value: bool,
+    /// Which node id wins on a timestamp tie.
+    #[serde(default, skip_serializing_if = "TieBreak::is_default")]
+    pub tie_break: TieBreak,
+    /// Expiry tombstones, sorted by key: writes at or before the timestamp are suppressed.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub expired: Vec<(K, u64)>,
+    /// How long an entry lives after its timestamp; `None` if entries never expire.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ttl: Option<Duration>,
 }
 
 fn serialize_entries<S, K, V>(
@@ -75,6 +117,14 @@ where
 
 type LWWMapEntry<K, V> = (K, (V, u64, String));
 
+/// Length of the bincode encoding used for keys and values in the capnp form.
+fn bincode_len<T: Serialize>(value: &T) -> usize {
+    bincode::serialized_size(value).unwrap_or(0) as usize
+}
+
+/// Per-key conflict resolver: `(key, current, incoming) -> incoming wins`.
+type ValueResolver<'r, K, V> = dyn Fn(&K, &V, &V) -> bool + 'r;
+
 fn deserialize_entries<'de, D, K, V>(deserializer: D) -> Result<Vec<LWWMapEntry<K, V>>, D::Error>
 where
     D: serde::Deserializer<'de>,
@@ -117,6 +167,11 @@ impl<K: Eq + Hash + Ord, V> Default for LWWMap<K, V> {
         Self {
             entries: Vec::new(),
             vclock: VectorClock::new(),
+            ties: Vec::new(),
+            multi_value: false,
+            tie_break: TieBreak::default(),
+            expired: Vec::new(),
+            ttl: None,
         }
     }
 }
@@ -126,6 +181,81 @@ impl<K: Eq + Hash + Ord, V> LWWMap<K, V> {
     pub fn new() -> Self {
         Self::default()
     }
+
+    /// Creates a new, empty LWW-Map that retains values tied on timestamp and node_id.
+    pub fn multi() -> Self {
+        Self {
+            multi_value: true,
+            ..Self::default()
+        }
+    }
+
+    /// Sets the node-id tie-break direction used by `insert` and `merge`.
+    pub fn with_tie_break(mut self, tie_break: TieBreak) -> Self {
+        self.tie_break = tie_break;
+        self
+    }
+
+    /// Sets the time-to-live used by [`LWWMap::expire_stale`].
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Returns the expiry tombstone timestamp for `key`, if it has expired before.
+    fn expired_at(&self, key: &K) -> Option<u64> {
+        self.expired
+            .binary_search_by(|(k, _)| k.cmp(key))
+            .ok()
+            .map(|idx| self.expired[idx].1)
+    }
+
+    /// Returns true if the map holds a value for `key`.
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.entries.binary_search_by(|(k, _)| k.cmp(key)).is_ok()
+    }
+
+    /// Iterator over the keys, in ascending order.
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.entries.iter().map(|(k, _)| k)
+    }
+
+    /// Returns the number of keys in the map.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if the map holds no keys.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Projects every value (including retained ties) through `f`.
+    ///
+    /// Timestamps, node ids, expiry tombstones, the vclock and configuration are kept, so
+    /// the result is a valid LWW-Map of the projected type.
+    pub fn map_values<W, F: Fn(&V) -> W>(&self, f: F) -> LWWMap<K, W>
+    where
+        K: Clone,
+    {
+        LWWMap {
+            entries: self
+                .entries
+                .iter()
+                .map(|(k, (v, ts, node))| (k.clone(), (f(v), *ts, node.clone())))
+                .collect(),
+            vclock: self.vclock.clone(),
+            ties: self
+                .ties
+                .iter()
+                .map(|(k, values)| (k.clone(), values.iter().map(&f).collect()))
+                .collect(),
+            multi_value: self.multi_value,
+            tie_break: self.tie_break,
+            expired: self.expired.clone(),
+            ttl: self.ttl,
+        }
+    }
 }
 
 impl<K, V> LWWMap<K, V>
@@ -136,20 +266,38 @@ where
     /// Inserts or updates a value for a specific key.
     ///
     /// The update is applied only if the new timestamp is higher than the current
-    /// one for that key, or if they are equal and the new node_id is lexicographically greater.
+    /// one for that key, or if they are equal and the new node_id wins under the configured
+    /// [`TieBreak`].
     pub fn insert(&mut self, node_id: &str, key: K, value: V, timestamp: u64) {
+        if self.expired_at(&key).is_some_and(|ts| timestamp <= ts) {
+            return;
+        }
         let node_id_str = node_id.to_string();
         
         match self.entries.binary_search_by(|(k, _)| k.cmp(&key)) {
             Ok(idx) => {
                 let (_, (val, ts, nid)) = &self.entries[idx];
+                if self.multi_value && timestamp == *ts && node_id_str == *nid {
+                    if value != *val && !self.tied_values(&key).contains(&value) {
+                        let loser = if value > *val {
+                            std::mem::replace(&mut self.entries[idx].1 .0, value)
+                        } else {
+                            value
+                        };
+                        self.add_tie(key, loser);
+                        self.vclock.increment(node_id);
+                    }
+                    return;
+                }
+
                 let update = timestamp > *ts
-                    || (timestamp == *ts && node_id_str > *nid)
+                    || (timestamp == *ts && self.tie_break.prefers(&node_id_str, nid))
                     || (timestamp == *ts
                         && node_id_str == *nid
                         && value > *val);
                 
                 if update {
+                    self.remove_ties(&key);
                     self.entries[idx] = (key, (value, timestamp, node_id_str));
                     self.vclock.increment(node_id);
                 }
@@ -170,6 +318,32 @@ where
         if let Ok(idx) = self.entries.binary_search_by(|(k, _)| k.cmp(key)) {
             self.entries.remove(idx);
         }
+        self.remove_ties(key);
+    }
+
+    /// Removes every entry whose timestamp is older than `now_epoch_ms - ttl`.
+    ///
+    /// Each expired key gets a tombstone at its entry's timestamp, so the expiry converges
+    /// on merge. Returns the number of entries removed; does nothing if no TTL is set.
+    pub fn expire_stale(&mut self, now_epoch_ms: u64) -> usize {
+        let Some(ttl) = self.ttl else {
+            return 0;
+        };
+        let ttl_ms = u64::try_from(ttl.as_millis()).unwrap_or(u64::MAX);
+        let cutoff = now_epoch_ms.saturating_sub(ttl_ms);
+
+        let (stale, live): (Vec<_>, Vec<_>) = std::mem::take(&mut self.entries)
+            .into_iter()
+            .partition(|(_, (_, ts, _))| *ts < cutoff);
+        self.entries = live;
+        for (key, (_, timestamp, _)) in &stale {
+            self.remove_ties(key);
+            match self.expired.binary_search_by(|(k, _)| k.cmp(key)) {
+                Ok(idx) => self.expired[idx].1 = self.expired[idx].1.max(*timestamp),
+                Err(idx) => self.expired.insert(idx, (key.clone(), *timestamp)),
+            }
+        }
+        stale.len()
     }
 
     /// Returns the value associated with the key, if any.
@@ -180,9 +354,216 @@ where
             .map(|idx| &self.entries[idx].1.0)
     }
 
+    /// Returns the value associated with the key, or `default` if it is absent.
+    pub fn get_or<'a>(&'a self, key: &K, default: &'a V) -> &'a V {
+        self.get(key).unwrap_or(default)
+    }
+
+    /// Read-modify-write for a single key.
+    ///
+    /// Calls `f` with the current value (if any). `Some(v)` is written via [`LWWMap::insert`]
+    /// with the given `timestamp`, so the usual LWW rules apply; `None` removes the key.
+    pub fn update<F>(&mut self, node_id: &str, key: K, timestamp: u64, f: F)
+    where
+        F: FnOnce(Option<&V>) -> Option<V>,
+    {
+        match f(self.get(&key)) {
+            Some(value) => self.insert(node_id, key, value, timestamp),
+            None => self.remove(&key),
+        }
+    }
+
+    /// Returns the winning value followed by any retained tied values for the key.
+    ///
+    /// Outside multi-value mode this contains at most one value.
+    pub fn get_all(&self, key: &K) -> Vec<&V> {
+        self.get(key)
+            .into_iter()
+            .chain(self.tied_values(key).iter())
+            .collect()
+    }
+
+    fn tied_values(&self, key: &K) -> &[V] {
+        self.ties
+            .binary_search_by(|(k, _)| k.cmp(key))
+            .map(|idx| self.ties[idx].1.as_slice())
+            .unwrap_or(&[])
+    }
+
+    fn add_tie(&mut self, key: K, value: V) {
+        match self.ties.binary_search_by(|(k, _)| k.cmp(&key)) {
+            Ok(idx) => {
+                let values = &mut self.ties[idx].1;
+                if let Err(pos) = values.binary_search(&value) {
+                    values.insert(pos, value);
+                }
+            }
+            Err(idx) => self.ties.insert(idx, (key, vec![value])),
+        }
+    }
+
+    fn remove_ties(&mut self, key: &K) {
+        if let Ok(idx) = self.ties.binary_search_by(|(k, _)| k.cmp(key)) {
+            self.ties.remove(idx);
+        }
+    }
+
     /// Merges another LWW-Map into this one.
     pub fn merge(&mut self, other: &Self) {
+        if other.is_identity() {
+            return;
+        }
+        self.merge_inner(other, None);
+    }
+
+    /// Merges another LWW-Map into this one, letting `resolver` pick the winner for keys
+    /// present on both sides.
+    ///
+    /// `resolver(key, current, incoming)` returns true if the incoming value should win,
+    /// overriding the timestamp comparison (e.g. "max value wins" for high-water marks).
+    /// The winning entry keeps its own timestamp and node_id. For replicas to converge,
+    /// every replica must merge with the same resolver, and the resolver must be a
+    /// deterministic, order-independent choice.
+    pub fn merge_with_resolver<F>(&mut self, other: &Self, resolver: F)
+    where
+        F: Fn(&K, &V, &V) -> bool,
+    {
+        self.merge_inner(other, Some(&resolver));
+    }
+
+    /// Merges the map in `reader` into this one entry by entry, without decoding it into a
+    /// second `LWWMap` first.
+    ///
+    /// Produces the same state as decoding the reader and calling [`merge`](Self::merge).
+    /// Everything is decoded before `self` is touched, so on error `self` is unchanged;
+    /// decoded entries are then moved into place in one sorted pass, without cloning.
+    pub fn merge_reader(&mut self, reader: &LWWMapReader<'_, K, V>) -> Result<(), CrdtError> {
+        let message = serialize::read_message(reader.bytes, ReaderOptions::new())
+            .map_err(|e: capnp::Error| CrdtError::Deserialization(e.to_string()))?;
+        let lww_map = message
+            .get_root::<lww_map_capnp::lww_map::Reader>()
+            .map_err(|e: capnp::Error| CrdtError::Deserialization(e.to_string()))?;
+        check_pair_type_tag::<K, V>(lww_map.get_element_type())?;
+
+        let node_ids = if lww_map.has_node_ids() {
+            Some(read_node_ids(
+                lww_map
+                    .get_node_ids()
+                    .map_err(|e: capnp::Error| CrdtError::Deserialization(e.to_string()))?,
+            )?)
+        } else {
+            None
+        };
+        let other_ties = decode_ties::<K, V>(lww_map)?;
+        let other_expired = decode_expired::<K>(lww_map)?;
+        let mut incoming = lww_map
+            .get_entries()
+            .map_err(|e: capnp::Error| CrdtError::Deserialization(e.to_string()))?
+            .iter()
+            .map(|entry| decode_entry::<K, V>(entry, node_ids.as_deref()))
+            .collect::<Result<Vec<_>, _>>()?;
+        // Writers emit entries sorted by key, which makes this a linear check
+        incoming.sort_by(|(a, _), (b, _)| a.cmp(b));
+        let other_vclock = if lww_map.has_vclock() {
+            let vc_bytes = lww_map
+                .get_vclock()
+                .map_err(|e: capnp::Error| CrdtError::Deserialization(e.to_string()))?;
+            Some(VectorClock::from_capnp_bytes(vc_bytes)?)
+        } else {
+            None
+        };
+
+        self.multi_value |= lww_map.get_multi_value();
+        self.tie_break = self.tie_break.join(decode_tie_break(lww_map));
+        self.ttl = match (self.ttl, decode_ttl(lww_map)) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+
+        let mut current = std::mem::take(&mut self.entries).into_iter().peekable();
+        let mut merged = Vec::with_capacity(current.len() + incoming.len());
+        for (key, write) in incoming {
+            while let Some(entry) = current.next_if(|(k, _)| *k < key) {
+                merged.push(entry);
+            }
+            let ties = other_ties
+                .binary_search_by(|(k, _)| k.cmp(&key))
+                .map(|idx| other_ties[idx].1.as_slice())
+                .unwrap_or(&[]);
+            // A repeated key in the reader joins with the entry just merged for it
+            let existing = match merged.last() {
+                Some((k, _)) if *k == key => merged.pop(),
+                _ => current.next_if(|(k, _)| *k == key),
+            };
+            let winner = match existing {
+                Some((_, existing)) => self.join_write(&key, existing, write, ties),
+                None => {
+                    self.replace_ties(key.clone(), ties.to_vec());
+                    write
+                }
+            };
+            merged.push((key, winner));
+        }
+        merged.extend(current);
+        self.entries = merged;
+
+        if let Some(other_vclock) = other_vclock {
+            self.vclock.merge(&other_vclock);
+        }
+        self.merge_expired(&other_expired);
+        Ok(())
+    }
+
+    /// Joins one incoming write and its tied values into the existing write for `key`,
+    /// with the same rules as `merge_inner`, and returns the winner.
+    fn join_write(
+        &mut self,
+        key: &K,
+        existing: (V, u64, String),
+        (value, timestamp, node_id): (V, u64, String),
+        ties: &[V],
+    ) -> (V, u64, String) {
+        let (v1, ts1, nid1) = &existing;
+        if self.multi_value && timestamp == *ts1 && node_id == *nid1 {
+            let mut values: Vec<V> = self
+                .tied_values(key)
+                .iter()
+                .chain(ties)
+                .chain([v1, &value])
+                .cloned()
+                .collect();
+            values.sort();
+            values.dedup();
+            let winner = values.pop().expect("at least two values");
+            self.replace_ties(key.clone(), values);
+            (winner, timestamp, node_id)
+        } else if timestamp > *ts1
+            || (timestamp == *ts1 && self.tie_break.prefers(&node_id, nid1))
+            || (timestamp == *ts1 && node_id == *nid1 && value > *v1)
+        {
+            self.replace_ties(key.clone(), ties.to_vec());
+            (value, timestamp, node_id)
+        } else {
+            existing
+        }
+    }
+
+    fn replace_ties(&mut self, key: K, values: Vec<V>) {
+        match self.ties.binary_search_by(|(k, _)| k.cmp(&key)) {
+            Ok(idx) if values.is_empty() => {
+                self.ties.remove(idx);
+            }
+            Ok(idx) => self.ties[idx].1 = values,
+            Err(_) if values.is_empty() => {}
+            Err(idx) => self.ties.insert(idx, (key, values)),
+        }
+    }
+
+    fn merge_inner(&mut self, other: &Self, resolver: Option<&ValueResolver<'_, K, V>>) {
+        let multi_value = self.multi_value || other.multi_value;
+        let tie_break = self.tie_break.join(other.tie_break);
         let mut result = Vec::with_capacity(self.entries.len() + other.entries.len());
+        let mut ties = Vec::new();
         let mut i = 0;
         let mut j = 0;
 
@@ -192,24 +573,50 @@ where
 
             match k1.cmp(k2) {
                 Ordering::Less => {
+                    self.carry_ties(k1, &mut ties);
                     result.push(self.entries[i].clone());
                     i += 1;
                 }
                 Ordering::Greater => {
+                    other.carry_ties(k2, &mut ties);
                     result.push(other.entries[j].clone());
                     j += 1;
                 }
+                Ordering::Equal if resolver.is_none() && multi_value && ts1 == ts2 && nid1 == nid2 => {
+                    // Same write stamp: keep the greatest value, retain everything else as ties
+                    let mut values: Vec<V> = self
+                        .tied_values(k1)
+                        .iter()
+                        .chain(other.tied_values(k2))
+                        .chain([v1, v2])
+                        .cloned()
+                        .collect();
+                    values.sort();
+                    values.dedup();
+                    let winner = values.pop().expect("at least two values");
+                    if !values.is_empty() {
+                        ties.push((k1.clone(), values));
+                    }
+                    result.push((k1.clone(), (winner, *ts1, nid1.clone())));
+                    i += 1;
+                    j += 1;
+                }
                 Ordering::Equal => {
                     // Conflict resolution
-                    let update = *ts2 > *ts1
-                        || (*ts2 == *ts1 && nid2 > nid1)
-                        || (*ts2 == *ts1
-                            && nid2 == nid1
-                            && v2 > v1);
+                    let update = match resolver {
+                        Some(resolve) => resolve(k1, v1, v2),
+                        None => {
+                            *ts2 > *ts1
+                                || (*ts2 == *ts1 && tie_break.prefers(nid2, nid1))
+                                || (*ts2 == *ts1 && nid2 == nid1 && v2 > v1)
+                        }
+                    };
                     
                     if update {
+                        other.carry_ties(k2, &mut ties);
                         result.push(other.entries[j].clone());
                     } else {
+                        self.carry_ties(k1, &mut ties);
                         result.push(self.entries[i].clone());
                     }
                     i += 1;
@@ -219,16 +626,91 @@ where
         }
 
         while i < self.entries.len() {
+            self.carry_ties(&self.entries[i].0, &mut ties);
             result.push(self.entries[i].clone());
             i += 1;
         }
         while j < other.entries.len() {
+            other.carry_ties(&other.entries[j].0, &mut ties);
             result.push(other.entries[j].clone());
             j += 1;
         }
 
         self.entries = result;
+        self.ties = ties;
+        self.multi_value = multi_value;
+        self.tie_break = tie_break;
+        self.ttl = match (self.ttl, other.ttl) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
         self.vclock.merge(&other.vclock);
+        self.merge_expired(&other.expired);
+    }
+
+    /// Joins expiry tombstones (latest per key) and drops the entries they cover.
+    fn merge_expired(&mut self, other: &[(K, u64)]) {
+        if other.is_empty() && self.expired.is_empty() {
+            return;
+        }
+        for (key, timestamp) in other {
+            match self.expired.binary_search_by(|(k, _)| k.cmp(key)) {
+                Ok(idx) => self.expired[idx].1 = self.expired[idx].1.max(*timestamp),
+                Err(idx) => self.expired.insert(idx, (key.clone(), *timestamp)),
+            }
+        }
+
+        let mut covered = Vec::new();
+        self.entries.retain(|(key, (_, ts, _))| {
+            let expired = self
+                .expired
+                .binary_search_by(|(k, _)| k.cmp(key))
+                .is_ok_and(|idx| *ts <= self.expired[idx].1);
+            if expired {
+                covered.push(key.clone());
+            }
+            !expired
+        });
+        for key in &covered {
+            self.remove_ties(key);
+        }
+    }
+
+    fn carry_ties(&self, key: &K, ties: &mut Vec<(K, Vec<V>)>) {
+        let values = self.tied_values(key);
+        if !values.is_empty() {
+            ties.push((key.clone(), values.to_vec()));
+        }
+    }
+
+    /// Compares the visible key/value pairs (including retained ties) and the logical
+    /// vclock counters, ignoring write metadata and wall-clock timestamps.
+    pub fn semantically_eq(&self, other: &Self) -> bool {
+        self.entries.len() == other.entries.len()
+            && self
+                .entries
+                .iter()
+                .zip(&other.entries)
+                .all(|((k1, (v1, _, _)), (k2, (v2, _, _)))| k1 == k2 && v1 == v2)
+            && self.ties == other.ties
+            && self.vclock.logically_eq(&other.vclock)
+    }
+}
+
+/// `map[&key]` reads, like `HashMap`.
+///
+/// # Panics
+///
+/// Panics if the key is absent (or has been removed). Use [`LWWMap::get`] or
+/// [`LWWMap::get_or`] when the key may be missing.
+impl<K: Eq + Hash + Ord, V> std::ops::Index<&K> for LWWMap<K, V> {
+    type Output = V;
+
+    fn index(&self, key: &K) -> &V {
+        match self.entries.binary_search_by(|(k, _)| k.cmp(key)) {
+            Ok(idx) => &self.entries[idx].1.0,
+            Err(_) => panic!("key not present in LWWMap"),
+        }
     }
 }
 
@@ -253,39 +735,87 @@ where
         }
     }
 
-    fn to_map(&self) -> Result<LWWMap<K, V>, CrdtError> {
+    /// Number of entry, tie and expiry records, read from the list headers only.
+    pub fn entry_count(&self) -> Result<usize, CrdtError> {
+        let reader = serialize::read_message(self.bytes, ReaderOptions::new())
+            .map_err(|e: capnp::Error| CrdtError::Deserialization(e.to_string()))?;
+        let lww_map = reader
+            .get_root::<lww_map_capnp::lww_map::Reader>()
+            .map_err(|e: capnp::Error| CrdtError::Deserialization(e.to_string()))?;
+        let entries = lww_map
+            .get_entries()
+            .map_err(|e: capnp::Error| CrdtError::Deserialization(e.to_string()))?
+            .len();
+        let ties = lww_map
+            .get_ties()
+            .map_err(|e: capnp::Error| CrdtError::Deserialization(e.to_string()))?
+            .len();
+        let expired = lww_map
+            .get_expired()
+            .map_err(|e: capnp::Error| CrdtError::Deserialization(e.to_string()))?
+            .len();
+        Ok(entries as usize + ties as usize + expired as usize)
+    }
+
+    /// Returns the value stored under `key`, decoding keys one at a time until a match
+    /// and only the matching value.
+    pub fn get(&self, key: &K) -> Result<Option<V>, CrdtError> {
         let reader = serialize::read_message(self.bytes, ReaderOptions::new())
             .map_err(|e: capnp::Error| CrdtError::Deserialization(e.to_string()))?;
         let lww_map = reader
             .get_root::<lww_map_capnp::lww_map::Reader>()
             .map_err(|e: capnp::Error| CrdtError::Deserialization(e.to_string()))?;
+        check_pair_type_tag::<K, V>(lww_map.get_element_type())?;
 
-        let mut entries = Vec::new();
         let entries_list = lww_map
             .get_entries()
             .map_err(|e: capnp::Error| CrdtError::Deserialization(e.to_string()))?;
-
         for entry in entries_list {
-            let key_bytes = entry
-                .get_key()
-                .map_err(|e: capnp::Error| CrdtError::Deserialization(e.to_string()))?;
-            let key: K = bincode::deserialize(key_bytes)
-                .map_err(|e: bincode::Error| CrdtError::Deserialization(e.to_string()))?;
-
+            let candidate: K = match entry
+                .which()
+                .map_err(|e| CrdtError::Deserialization(e.to_string()))?
+            {
+                lww_map_capnp::lww_map::entry::Key(key_bytes) => decode_key(key_bytes)?,
+                lww_map_capnp::lww_map::entry::KeyText(text) => from_text(read_text(text)?)?,
+            };
+            if candidate != *key {
+                continue;
+            }
             let value_bytes = entry
                 .get_value()
                 .map_err(|e: capnp::Error| CrdtError::Deserialization(e.to_string()))?;
-            let value: V = bincode::deserialize(value_bytes)
+            let value = bincode::deserialize(value_bytes)
                 .map_err(|e: bincode::Error| CrdtError::Deserialization(e.to_string()))?;
+            return Ok(Some(value));
+        }
+        Ok(None)
+    }
 
-            let timestamp = entry.get_timestamp();
-            let node_id = entry
-                .get_node_id()
-                .map_err(|e: capnp::Error| CrdtError::Deserialization(e.to_string()))?
-                .to_string()
-                .map_err(|e: std::str::Utf8Error| CrdtError::Deserialization(e.to_string()))?;
+    fn to_map(&self) -> Result<LWWMap<K, V>, CrdtError> {
+        let reader = serialize::read_message(self.bytes, ReaderOptions::new())
+            .map_err(|e: capnp::Error| CrdtError::Deserialization(e.to_string()))?;
+        let lww_map = reader
+            .get_root::<lww_map_capnp::lww_map::Reader>()
+            .map_err(|e: capnp::Error| CrdtError::Deserialization(e.to_string()))?;
+        check_pair_type_tag::<K, V>(lww_map.get_element_type())?;
 
-            entries.push((key, (value, timestamp, node_id)));
+        let node_ids = if lww_map.has_node_ids() {
+            Some(read_node_ids(
+                lww_map
+                    .get_node_ids()
+                    .map_err(|e: capnp::Error| CrdtError::Deserialization(e.to_string()))?,
+            )?)
+        } else {
+            None
+        };
+
+        let mut entries: Vec<(K, (V, u64, String))> = Vec::new();
+        let entries_list = lww_map
+            .get_entries()
+            .map_err(|e: capnp::Error| CrdtError::Deserialization(e.to_string()))?;
+
+        for entry in entries_list {
+            entries.push(decode_entry(entry, node_ids.as_deref())?);
         }
         // Sort to maintain invariant
         entries.sort_by(|a, b| a.0.cmp(&b.0));
@@ -301,7 +831,127 @@ where
             VectorClock::new()
         };
 
-        Ok(LWWMap { entries, vclock })
+        Ok(LWWMap {
+            entries,
+            vclock,
+            ties: decode_ties(lww_map)?,
+            multi_value: lww_map.get_multi_value(),
+            tie_break: decode_tie_break(lww_map),
+            expired: decode_expired(lww_map)?,
+            ttl: decode_ttl(lww_map),
+        })
+    }
+}
+
+fn decode_key<K: DeserializeOwned>(key_bytes: capnp::Result<&[u8]>) -> Result<K, CrdtError> {
+    let key_bytes = key_bytes.map_err(|e: capnp::Error| CrdtError::Deserialization(e.to_string()))?;
+    bincode::deserialize(key_bytes)
+        .map_err(|e: bincode::Error| CrdtError::Deserialization(e.to_string()))
+}
+
+fn decode_entry<K, V>(
+    entry: lww_map_capnp::lww_map::entry::Reader<'_>,
+    node_ids: Option<&[&str]>,
+) -> Result<(K, (V, u64, String)), CrdtError>
+where
+    K: DeserializeOwned + Eq + Hash + Ord,
+    V: DeserializeOwned,
+{
+    let key: K = match entry
+        .which()
+        .map_err(|e| CrdtError::Deserialization(e.to_string()))?
+    {
+        lww_map_capnp::lww_map::entry::Key(key_bytes) => decode_key(key_bytes)?,
+        lww_map_capnp::lww_map::entry::KeyText(text) => from_text(read_text(text)?)?,
+    };
+
+    let value_bytes = entry
+        .get_value()
+        .map_err(|e: capnp::Error| CrdtError::Deserialization(e.to_string()))?;
+    let value: V = bincode::deserialize(value_bytes)
+        .map_err(|e: bincode::Error| CrdtError::Deserialization(e.to_string()))?;
+
+    let timestamp = entry.get_timestamp();
+    let node_id = match node_ids {
+        Some(node_ids) => resolve(node_ids, entry.get_node_index())?,
+        None => read_text(entry.get_node_id())?,
+    }
+    .to_string();
+
+    Ok((key, (value, timestamp, node_id)))
+}
+
+/// Decodes the tie list, sorted by key.
+fn decode_ties<K, V>(lww_map: lww_map_capnp::lww_map::Reader<'_>) -> Result<Vec<(K, Vec<V>)>, CrdtError>
+where
+    K: DeserializeOwned + Eq + Hash + Ord,
+    V: DeserializeOwned,
+{
+    let mut ties = Vec::new();
+    let ties_list = lww_map
+        .get_ties()
+        .map_err(|e: capnp::Error| CrdtError::Deserialization(e.to_string()))?;
+    for tie in ties_list {
+        let key: K = match tie
+            .which()
+            .map_err(|e| CrdtError::Deserialization(e.to_string()))?
+        {
+            lww_map_capnp::lww_map::tie::Key(key_bytes) => decode_key(key_bytes)?,
+            lww_map_capnp::lww_map::tie::KeyText(text) => from_text(read_text(text)?)?,
+        };
+
+        let mut values = Vec::new();
+        for value_bytes in tie
+            .get_values()
+            .map_err(|e: capnp::Error| CrdtError::Deserialization(e.to_string()))?
+        {
+            let value_bytes = value_bytes
+                .map_err(|e: capnp::Error| CrdtError::Deserialization(e.to_string()))?;
+            let value: V = bincode::deserialize(value_bytes)
+                .map_err(|e: bincode::Error| CrdtError::Deserialization(e.to_string()))?;
+            values.push(value);
+        }
+        ties.push((key, values));
+    }
+    ties.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(ties)
+}
+
+/// Decodes the expiry tombstones, sorted by key.
+fn decode_expired<K>(lww_map: lww_map_capnp::lww_map::Reader<'_>) -> Result<Vec<(K, u64)>, CrdtError>
+where
+    K: DeserializeOwned + Eq + Hash + Ord,
+{
+    let mut expired = Vec::new();
+    for tombstone in lww_map
+        .get_expired()
+        .map_err(|e: capnp::Error| CrdtError::Deserialization(e.to_string()))?
+    {
+        let key: K = match tombstone
+            .which()
+            .map_err(|e| CrdtError::Deserialization(e.to_string()))?
+        {
+            lww_map_capnp::lww_map::tombstone::Key(key_bytes) => decode_key(key_bytes)?,
+            lww_map_capnp::lww_map::tombstone::KeyText(text) => from_text(read_text(text)?)?,
+        };
+        expired.push((key, tombstone.get_timestamp()));
+    }
+    expired.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(expired)
+}
+
+fn decode_tie_break(lww_map: lww_map_capnp::lww_map::Reader<'_>) -> TieBreak {
+    if lww_map.get_lower_node_wins() {
+        TieBreak::LowerNodeWins
+    } else {
+        TieBreak::HigherNodeWins
+    }
+}
+
+fn decode_ttl(lww_map: lww_map_capnp::lww_map::Reader<'_>) -> Option<Duration> {
+    match lww_map.get_ttl_millis() {
+        0 => None,
+        millis => Some(Duration::from_millis(millis)),
     }
 }
 
@@ -310,6 +960,10 @@ where
     K: Clone + Eq + Hash + Ord + Serialize + DeserializeOwned + Send + Sync + 'static,
     V: Clone + Serialize + DeserializeOwned + Send + Sync + 'static,
 {
+    fn from_bytes(bytes: &'a [u8]) -> Self {
+        Self::new(bytes)
+    }
+
     fn is_empty(&self) -> Result<bool, CrdtError> {
         Ok(self.to_map()?.entries.is_empty())
     }
@@ -319,6 +973,134 @@ where
 // CRDT Trait Implementation
 // ============================================================================
 
+impl<K, V> LWWMap<K, V>
+where
+    K: Clone + Eq + Hash + Ord + Serialize + DeserializeOwned + Send + Sync + 'static,
+    V: Clone + Ord + Serialize + DeserializeOwned + Send + Sync + 'static,
+{
+    /// [`merge_from_readers`](Crdt::merge_from_readers) with an entry budget; see
+    /// [bounded merges](Crdt#bounded-merges).
+    ///
+    /// Entry, tie and expiry records all count.
+    pub fn merge_from_readers_bounded(
+        readers: &[LWWMapReader<'_, K, V>],
+        max_entries: usize,
+    ) -> Result<Self, CrdtError> {
+        check_entry_budget(readers.iter().map(|r| r.entry_count()), max_entries)?;
+        Self::merge_from_readers(readers)
+    }
+
+    /// Serializes keys as plain UTF-8 `Text` ([plain-text encoding](crate#plain-text-encoding)).
+    ///
+    /// Values keep their bincode encoding.
+    pub fn to_capnp_text_bytes(&self) -> Vec<u8>
+    where
+        K: AsRef<str>,
+    {
+        let mut buf = Vec::new();
+        self.write_capnp(Some(<K as AsRef<str>>::as_ref), false, &mut buf)
+            .expect("LWWMap serialization fail");
+        buf
+    }
+
+    /// Serializes with each distinct node id stored once, in a `nodeIds` dictionary that
+    /// entries refer to by index.
+    ///
+    /// Readers before the dictionary was introduced decode such a blob with empty entry
+    /// node ids, so only send it once every consumer has upgraded. `to_capnp_bytes`
+    /// keeps a node id per entry.
+    pub fn to_capnp_interned_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        self.write_capnp(None, true, &mut buf)
+            .expect("LWWMap serialization fail");
+        buf
+    }
+
+    fn write_capnp<W: Write>(
+        &self,
+        as_text: Option<fn(&K) -> &str>,
+        intern_node_ids: bool,
+        writer: &mut W,
+    ) -> capnp::Result<()> {
+        let mut message = Builder::new(HeapAllocator::new());
+        self.build_message(as_text, intern_node_ids, &mut message)?;
+        serialize::write_message(writer, &message)
+    }
+
+    fn build_message(
+        &self,
+        as_text: Option<fn(&K) -> &str>,
+        intern_node_ids: bool,
+        message: &mut Builder<HeapAllocator>,
+    ) -> capnp::Result<()> {
+        let mut lww_map = message.init_root::<lww_map_capnp::lww_map::Builder>();
+        let mut node_ids = NodeIdTable::new();
+        let mut entries = lww_map.reborrow().init_entries(self.entries.len() as u32);
+        for (idx, (key, (value, timestamp, node_id))) in self.entries.iter().enumerate() {
+            let mut entry = entries.reborrow().get(idx as u32);
+            match as_text {
+                Some(as_text) => entry.set_key_text(as_text(key).into()),
+                None => {
+                    let key_bytes =
+                        bincode::serialize(key).expect("LWWMap key serialization fail");
+                    entry.set_key(&key_bytes);
+                }
+            }
+            let value_bytes =
+                bincode::serialize(value).expect("LWWMap value serialization fail");
+            entry.set_value(&value_bytes);
+            entry.set_timestamp(*timestamp);
+            if intern_node_ids {
+                entry.set_node_index(node_ids.intern(node_id));
+            } else {
+                entry.set_node_id(node_id.as_str().into());
+            }
+        }
+        if intern_node_ids {
+            node_ids.write(lww_map.reborrow().init_node_ids(node_ids.len() as u32));
+        }
+        let mut ties = lww_map.reborrow().init_ties(self.ties.len() as u32);
+        for (idx, (key, values)) in self.ties.iter().enumerate() {
+            let mut tie = ties.reborrow().get(idx as u32);
+            match as_text {
+                Some(as_text) => tie.set_key_text(as_text(key).into()),
+                None => {
+                    let key_bytes =
+                        bincode::serialize(key).expect("LWWMap key serialization fail");
+                    tie.set_key(&key_bytes);
+                }
+            }
+            let mut values_builder = tie.init_values(values.len() as u32);
+            for (v_idx, value) in values.iter().enumerate() {
+                let value_bytes =
+                    bincode::serialize(value).expect("LWWMap value serialization fail");
+                values_builder.set(v_idx as u32, &value_bytes);
+            }
+        }
+        let mut expired = lww_map.reborrow().init_expired(self.expired.len() as u32);
+        for (idx, (key, timestamp)) in self.expired.iter().enumerate() {
+            let mut tombstone = expired.reborrow().get(idx as u32);
+            match as_text {
+                Some(as_text) => tombstone.set_key_text(as_text(key).into()),
+                None => {
+                    let key_bytes =
+                        bincode::serialize(key).expect("LWWMap key serialization fail");
+                    tombstone.set_key(&key_bytes);
+                }
+            }
+            tombstone.set_timestamp(*timestamp);
+        }
+        let ttl_millis = self.ttl.map_or(0, |ttl| u64::try_from(ttl.as_millis()).unwrap_or(u64::MAX));
+        lww_map.set_ttl_millis(ttl_millis);
+        lww_map.set_multi_value(self.multi_value);
+        lww_map.set_lower_node_wins(self.tie_break == TieBreak::LowerNodeWins);
+        lww_map.set_element_type(pair_type_tag::<K, V>());
+        let vclock_bytes = self.vclock.to_capnp_bytes();
+        lww_map.set_vclock(&vclock_bytes);
+        Ok(())
+    }
+}
+
 impl<K, V> Crdt for LWWMap<K, V>
 where
     K: Clone + Eq + Hash + Ord + Serialize + DeserializeOwned + Send + Sync + 'static,
@@ -327,36 +1109,100 @@ where
     type Reader<'a> = LWWMapReader<'a, K, V>;
 
     fn merge_from_readers(readers: &[Self::Reader<'_>]) -> Result<Self, CrdtError> {
+        let maps = readers
+            .iter()
+            .map(|r| r.to_map())
+            .collect::<Result<Vec<_>, _>>()?;
+        ensure_uniform("tie-break directions", maps.iter().map(|m| m.tie_break))?;
+
         let mut result = LWWMap::new();
-        for reader in readers {
-            result.merge(&reader.to_map()?);
+        for map in &maps {
+            result.merge(map);
         }
         Ok(result)
     }
 
+    fn is_identity(&self) -> bool {
+        *self == Self::default()
+    }
+
+    fn merge(&mut self, other: &Self) {
+        self.merge(other);
+    }
+
+    fn dominates(&self, other: &Self) -> bool {
+        self.vclock.dominates(&other.vclock) && merge_is_noop(self, other)
+    }
+
+    fn merge_bytes(&mut self, bytes: &[u8]) -> Result<(), CrdtError> {
+        let other = LWWMapReader::new(bytes).to_map()?;
+        self.merge(&other);
+        Ok(())
+    }
+
+    fn ensure_mergeable(&self, other: &Self) -> Result<(), CrdtError> {
+        ensure_uniform("tie-break directions", [self.tie_break, other.tie_break])
+    }
+
+    fn write_to<W: Write>(&self, writer: &mut W) -> Result<(), CrdtError> {
+        self.write_capnp(None, false, writer)
+            .map_err(|e| CrdtError::Serialization(e.to_string()))
+    }
+
+    fn build_capnp(&self, message: &mut Builder<HeapAllocator>) -> Result<(), CrdtError> {
+        self.build_message(None, false, message)
+            .map_err(|e| CrdtError::Serialization(e.to_string()))
+    }
+
     fn to_capnp_bytes(&self) -> Vec<u8> {
-        let mut message = Builder::new(HeapAllocator::new());
-        {
-            let mut lww_map = message.init_root::<lww_map_capnp::lww_map::Builder>();
-            let mut entries = lww_map.reborrow().init_entries(self.entries.len() as u32);
-            for (idx, (key, (value, timestamp, node_id))) in self.entries.iter().enumerate() {
-                let mut entry = entries.reborrow().get(idx as u32);
-                let key_bytes = bincode::serialize(key).expect("LWWMap key serialization fail");
-                let value_bytes =
-                    bincode::serialize(value).expect("LWWMap value serialization fail");
-                entry.set_key(&key_bytes);
-                entry.set_value(&value_bytes);
-                entry.set_timestamp(*timestamp);
-                entry.set_node_id(node_id.as_str().into());
-            }
-            let vclock_bytes = self.vclock.to_capnp_bytes();
-            lww_map.set_vclock(&vclock_bytes);
-        }
         let mut buf = Vec::new();
-        serialize::write_message(&mut buf, &message).expect("LWWMap serialization fail");
+        self.write_capnp(None, false, &mut buf).expect("LWWMap serialization fail");
         buf
     }
 
+    fn estimated_capnp_size(&self) -> usize {
+        let entries: usize = self
+            .entries
+            .iter()
+            .map(|(key, (value, _, node))| {
+                // The second data word holds the key/keyText union discriminant
+                struct_size(2, 3)
+                    + data_size(bincode_len(key))
+                    + data_size(bincode_len(value))
+                    + text_size(node.len())
+            })
+            .sum();
+        let ties: usize = self
+            .ties
+            .iter()
+            .map(|(key, values)| {
+                struct_size(1, 2)
+                    + data_size(bincode_len(key))
+                    + WORD
+                    + values
+                        .iter()
+                        .map(|value| WORD + data_size(bincode_len(value)))
+                        .sum::<usize>()
+            })
+            .sum();
+
+        let expired: usize = self
+            .expired
+            .iter()
+            .map(|(key, _)| struct_size(2, 1) + data_size(bincode_len(key)))
+            .sum();
+
+        MESSAGE_OVERHEAD
+            + struct_size(3, 5)
+            + WORD
+            + entries
+            + WORD
+            + ties
+            + WORD
+            + expired
+            + data_size(self.vclock.estimated_capnp_size())
+    }
+
     fn is_empty(&self) -> bool {
         self.entries.is_empty()
     }
@@ -1,18 +1,22 @@
 // Copyright (c) 2026 Adrian Robinson. All rights reserved.
 // Licensed under the MIT License. See LICENSE file in the project root for full license information.
 
+use crate::enums::{ensure_uniform, TieBreak};
 use crate::lww_register_capnp;
-use crate::traits::{Crdt, CrdtError, CrdtReader};
+use crate::node_id::NodeId;
+use crate::traits::{merge_is_noop, Crdt, CrdtError, CrdtReader};
 use crate::vector_clock::VectorClock;
 use capnp::message::{Builder, HeapAllocator, ReaderOptions};
 use capnp::serialize;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::io::Write;
 
 /// LWW-Register: A Last-Write-Wins Register CRDT.
 ///
 /// An LWW-Register stores a single value and resolves conflicts by choosing
 /// the value with the highest timestamp. On timestamp ties, a lexicographic
-/// comparison of the node identifiers is used as a deterministic tie-breaker.
+/// comparison of the node identifiers is used as a deterministic tie-breaker
+/// (greater node id wins by default; see [`LWWRegister::with_tie_break`]).
 ///
 /// # Key Properties
 ///
@@ -37,8 +41,19 @@ use serde::{de::DeserializeOwned, Deserialize, Serialize};
 /// reg1.merge(&reg2);
 /// assert_eq!(reg1.value, "value2"); // Higher timestamp wins
 /// ```
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+///
+/// # History
+///
+/// A register built with [`LWWRegister::with_history`] keeps a bounded buffer of the writes
+/// it superseded locally. History is best-effort and local-only: it is not serialized,
+/// not merged, and not considered by equality.
+///
+/// The history buffer is a private field, so `LWWRegister` is `#[non_exhaustive]`: outside
+/// this crate, start from [`LWWRegister::new`] or `Default` and opt into history with
+/// [`LWWRegister::with_history`]. `value` and `timestamp` remain public for reading.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(bound(serialize = "T: Serialize", deserialize = "T: DeserializeOwned"))]
+#[non_exhaustive]
 pub struct LWWRegister<T> {
     /// The current value stored in the register.
     pub value: T,
@@ -49,8 +64,29 @@ pub struct LWWRegister<T> {
     /// Vector clock for tracking causal history.
     #[serde(default)]
     pub vclock: VectorClock,
+    /// Which node id wins on a timestamp tie.
+    #[serde(default, skip_serializing_if = "TieBreak::is_default")]
+    pub tie_break: TieBreak,
+    /// Superseded writes as `(value, timestamp, node_id)`, oldest first. Local-only.
+    #[serde(skip)]
+    history: Vec<(T, u64, String)>,
+    /// Maximum number of superseded writes retained in `history`.
+    #[serde(skip)]
+    history_depth: usize,
+}
+
+impl<T: PartialEq> PartialEq for LWWRegister<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+            && self.timestamp == other.timestamp
+            && self.node_id == other.node_id
+            && self.vclock == other.vclock
+            && self.tie_break == other.tie_break
+    }
 }
 
+impl<T: Eq> Eq for LWWRegister<T> {}
+
 impl<T: Clone + Default + Serialize + DeserializeOwned + Send + Sync + 'static> Default
     for LWWRegister<T>
 {
@@ -60,6 +96,9 @@ impl<T: Clone + Default + Serialize + DeserializeOwned + Send + Sync + 'static>
             timestamp: 0,
             node_id: String::new(),
             vclock: VectorClock::new(),
+            tie_break: TieBreak::default(),
+            history: Vec::new(),
+            history_depth: 0,
         }
     }
 }
@@ -75,22 +114,66 @@ impl<T: Clone + Serialize + DeserializeOwned + Ord + Send + Sync + 'static> LWWR
             timestamp,
             node_id,
             vclock,
+            tie_break: TieBreak::default(),
+            history: Vec::new(),
+            history_depth: 0,
+        }
+    }
+
+    /// Like [`new`](Self::new), but rejects an empty or oversized `node_id` with
+    /// [`CrdtError::InvalidInput`]; see [`NodeId`].
+    pub fn try_new(value: T, timestamp: u64, node_id: &str) -> Result<Self, CrdtError> {
+        Ok(Self::new(value, timestamp, NodeId::new(node_id)?))
+    }
+
+    /// Sets the node-id tie-break direction used by `set` and `merge`.
+    pub fn with_tie_break(mut self, tie_break: TieBreak) -> Self {
+        self.tie_break = tie_break;
+        self
+    }
+
+    /// Retains up to `depth` superseded writes, accessible via [`LWWRegister::history`].
+    pub fn with_history(mut self, depth: usize) -> Self {
+        self.history_depth = depth;
+        self.truncate_history();
+        self
+    }
+
+    /// Returns the superseded writes as `(value, timestamp, node_id)`, oldest first.
+    pub fn history(&self) -> &[(T, u64, String)] {
+        &self.history
+    }
+
+    fn record_history(&mut self) {
+        if self.history_depth == 0 {
+            return;
+        }
+        self.history
+            .push((self.value.clone(), self.timestamp, self.node_id.clone()));
+        self.truncate_history();
+    }
+
+    fn truncate_history(&mut self) {
+        if self.history.len() > self.history_depth {
+            let excess = self.history.len() - self.history_depth;
+            self.history.drain(..excess);
         }
     }
 
     /// Updates the register with a new value and timestamp.
     ///
     /// The update is only applied if the new timestamp is higher than the current
-    /// one, or if they are equal and the new node_id is lexicographically greater.
+    /// one, or if they are equal and the new node_id wins under the configured [`TieBreak`].
     pub fn set(&mut self, value: T, timestamp: u64, node_id: impl Into<String>) {
         let node_id = node_id.into();
         let update = timestamp > self.timestamp
-            || (timestamp == self.timestamp && node_id > self.node_id)
+            || (timestamp == self.timestamp && self.tie_break.prefers(&node_id, &self.node_id))
             || (timestamp == self.timestamp
                 && node_id == self.node_id
                 && value > self.value);
 
         if update {
+            self.record_history();
             self.value = value;
             self.timestamp = timestamp;
             self.node_id = node_id.clone();
@@ -99,20 +182,30 @@ impl<T: Clone + Serialize + DeserializeOwned + Ord + Send + Sync + 'static> LWWR
     }
 
     /// Merges another LWW-Register into this one.
+    ///
+    /// Both replicas should share a tie-break direction; see [`TieBreak::join`] otherwise.
     pub fn merge(&mut self, other: &Self) {
+        self.tie_break = self.tie_break.join(other.tie_break);
         let update = other.timestamp > self.timestamp
-            || (other.timestamp == self.timestamp && other.node_id > self.node_id)
+            || (other.timestamp == self.timestamp
+                && self.tie_break.prefers(&other.node_id, &self.node_id))
             || (other.timestamp == self.timestamp
                 && other.node_id == self.node_id
                 && other.value > self.value);
 
         if update {
+            self.record_history();
             self.value = other.value.clone();
             self.timestamp = other.timestamp;
             self.node_id = other.node_id.clone();
         }
         self.vclock.merge(&other.vclock);
     }
+
+    /// Compares the observable value and the logical vclock counters, ignoring wall-clock timestamps.
+    pub fn semantically_eq(&self, other: &Self) -> bool {
+        self.value == other.value && self.vclock.logically_eq(&other.vclock)
+    }
 }
 
 // ============================================================================
@@ -167,6 +260,13 @@ impl<'a, T: Clone + Serialize + DeserializeOwned + Send + Sync + 'static> LWWReg
             timestamp: reg.get_timestamp(),
             node_id,
             vclock,
+            tie_break: if reg.get_lower_node_wins() {
+                TieBreak::LowerNodeWins
+            } else {
+                TieBreak::HigherNodeWins
+            },
+            history: Vec::new(),
+            history_depth: 0,
         })
     }
 }
@@ -174,6 +274,10 @@ impl<'a, T: Clone + Serialize + DeserializeOwned + Send + Sync + 'static> LWWReg
 impl<'a, T: Clone + Serialize + DeserializeOwned + Send + Sync + 'static> CrdtReader<'a>
     for LWWRegisterReader<'a, T>
 {
+    fn from_bytes(bytes: &'a [u8]) -> Self {
+        Self::new(bytes)
+    }
+
     fn is_empty(&self) -> Result<bool, CrdtError> {
         // A register with timestamp 0 is considered "empty" in our context.
         Ok(self.to_register()?.timestamp == 0)
@@ -193,27 +297,61 @@ impl<T: Clone + Default + Serialize + DeserializeOwned + Ord + Send + Sync + 'st
         if readers.is_empty() {
             return Ok(LWWRegister::default());
         }
-        let mut result = readers[0].to_register()?;
-        for reader in &readers[1..] {
-            result.merge(&reader.to_register()?);
+        let registers = readers
+            .iter()
+            .map(|r| r.to_register())
+            .collect::<Result<Vec<_>, _>>()?;
+        ensure_uniform("tie-break directions", registers.iter().map(|r| r.tie_break))?;
+
+        let mut iter = registers.into_iter();
+        let mut result = iter.next().expect("readers is non-empty");
+        for register in iter {
+            result.merge(&register);
         }
         Ok(result)
     }
 
-    fn to_capnp_bytes(&self) -> Vec<u8> {
+    fn merge(&mut self, other: &Self) {
+        self.merge(other);
+    }
+
+    fn dominates(&self, other: &Self) -> bool {
+        self.vclock.dominates(&other.vclock) && merge_is_noop(self, other)
+    }
+
+    fn merge_bytes(&mut self, bytes: &[u8]) -> Result<(), CrdtError> {
+        let other = LWWRegisterReader::new(bytes).to_register()?;
+        self.merge(&other);
+        Ok(())
+    }
+
+    fn ensure_mergeable(&self, other: &Self) -> Result<(), CrdtError> {
+        ensure_uniform("tie-break directions", [self.tie_break, other.tie_break])
+    }
+
+    fn write_to<W: Write>(&self, writer: &mut W) -> Result<(), CrdtError> {
         let mut message = Builder::new(HeapAllocator::new());
-        {
-            let mut reg = message.init_root::<lww_register_capnp::lww_register::Builder>();
-            let bytes =
-                bincode::serialize(&self.value).expect("LWWRegister value serialization fail");
-            reg.set_value(&bytes);
-            reg.set_timestamp(self.timestamp);
-            reg.set_node_id(self.node_id.as_str().into());
-            let vclock_bytes = self.vclock.to_capnp_bytes();
-            reg.set_vclock(&vclock_bytes);
-        }
+        self.build_capnp(&mut message)?;
+        serialize::write_message(writer, &message)
+            .map_err(|e| CrdtError::Serialization(e.to_string()))
+    }
+
+    fn build_capnp(&self, message: &mut Builder<HeapAllocator>) -> Result<(), CrdtError> {
+        let mut reg = message.init_root::<lww_register_capnp::lww_register::Builder>();
+        let bytes =
+            bincode::serialize(&self.value).expect("LWWRegister value serialization fail");
+        reg.set_value(&bytes);
+        reg.set_timestamp(self.timestamp);
+        reg.set_node_id(self.node_id.as_str().into());
+        let vclock_bytes = self.vclock.to_capnp_bytes();
+        reg.set_vclock(&vclock_bytes);
+        reg.set_lower_node_wins(self.tie_break == TieBreak::LowerNodeWins);
+        Ok(())
+    }
+
+    fn to_capnp_bytes(&self) -> Vec<u8> {
         let mut buf = Vec::new();
-        serialize::write_message(&mut buf, &message).expect("LWWRegister serialization fail");
+        self.write_to(&mut buf).expect("LWWRegister serialization fail");
         buf
     }
 
@@ -1,14 +1,19 @@
 // Copyright (c) 2026 Adrian Robinson. All rights reserved.
 // Licensed under the MIT License. See LICENSE file in the project root for full license information.
 
+use crate::enums::{ensure_uniform, ConflictPolicy, TieBreak};
 use crate::lww_set_capnp;
-use crate::traits::{Crdt, CrdtError, CrdtReader};
+use crate::text_element::{from_text, read_text};
+use crate::traits::{merge_is_noop, Crdt, CrdtError, CrdtReader};
+use crate::type_tag::{check_type_tag, type_tag};
 use crate::vector_clock::VectorClock;
 use capnp::message::{Builder, HeapAllocator, ReaderOptions};
 use capnp::serialize;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::cmp::Ordering;
+use std::collections::BTreeSet;
 use std::hash::Hash;
+use std::io::Write;
 
 /// LWW-Set: A Last-Write-Wins Set CRDT.
 ///
@@ -21,7 +26,8 @@ use std::hash::Hash;
 ///
 /// - **Add/Remove Sets**: Maintains separate sets for additions and removals, each with timestamps.
 /// - **LWW Resolution**: An element is present if its addition timestamp is greater than its removal timestamp.
-/// - **Bias**: Typically biased towards addition in case of timestamp ties (configurable, but usually Add-Wins).
+/// - **Bias**: Add/remove timestamp ties are resolved by node id by default; set a
+///   [`ConflictPolicy`] bias to make them always add-wins or remove-wins.
 ///
 /// # Algebraic Properties
 ///
@@ -40,11 +46,24 @@ use std::hash::Hash;
 ///
 /// assert!(set.contains(&"apple".to_string())); // Addition wins (100 > 50)
 /// ```
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+///
+/// # Capacity
+///
+/// A set built with [`LWWSet::with_capacity`] keeps at most that many live elements:
+/// `insert` evicts the elements with the oldest add timestamps. The bound is local-only.
+/// Eviction drops the add entry without a tombstone, so an evicted element comes back when
+/// merged from a replica that still holds it, and the set may exceed its capacity after a
+/// merge until the next `insert`. The capacity is not serialized, merged or compared.
+///
+/// Since the bound is stored privately, `LWWSet` is `#[non_exhaustive]` and struct
+/// literals do not compile outside this crate. Create sets with [`LWWSet::new`] and chain
+/// [`LWWSet::with_capacity`], [`LWWSet::with_tie_break`] or [`LWWSet::with_tie_bias`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(bound(
     serialize = "T: Serialize",
     deserialize = "T: DeserializeOwned + Eq + Hash + Ord"
 ))]
+#[non_exhaustive]
 pub struct LWWSet<T: Eq + Hash + Ord> {
     /// Tracks addition timestamps: element -> (timestamp, node_id).
     #[serde(serialize_with = "serialize_lww_map", deserialize_with = "deserialize_lww_map")]
@@ -55,8 +74,34 @@ pub struct LWWSet<T: Eq + Hash + Ord> {
     /// Vector clock representing the causal history of the set.
     #[serde(default)]
     pub vclock: VectorClock,
+    /// Which node id wins on a timestamp tie, both within each set and between add and remove.
+    #[serde(default, skip_serializing_if = "TieBreak::is_default")]
+    pub tie_break: TieBreak,
+    /// Whether an add or a remove wins when both carry the same timestamp; `None` defers
+    /// to `tie_break` on their node ids.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tie_bias: Option<ConflictPolicy>,
+    /// Maximum number of live elements kept by `insert`; `None` if unbounded. Local-only.
+    #[serde(skip)]
+    capacity: Option<usize>,
+    /// Live elements by add timestamp, oldest first; only kept while a capacity is set.
+    /// Local-only.
+    #[serde(skip)]
+    by_age: BTreeSet<(u64, T)>,
+}
+
+impl<T: Eq + Hash + Ord> PartialEq for LWWSet<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.add_set == other.add_set
+            && self.remove_set == other.remove_set
+            && self.vclock == other.vclock
+            && self.tie_break == other.tie_break
+            && self.tie_bias == other.tie_bias
+    }
 }
 
+impl<T: Eq + Hash + Ord> Eq for LWWSet<T> {}
+
 fn serialize_lww_map<S, T>(
     elements: &Vec<(T, (u64, String))>,
     serializer: S,
@@ -116,6 +161,10 @@ impl<T: Eq + Hash + Ord> Default for LWWSet<T> {
             add_set: Vec::new(),
             remove_set: Vec::new(),
             vclock: VectorClock::new(),
+            tie_break: TieBreak::default(),
+            tie_bias: None,
+            capacity: None,
+            by_age: BTreeSet::new(),
         }
     }
 }
@@ -125,23 +174,92 @@ impl<T: Eq + Hash + Ord> LWWSet<T> {
     pub fn new() -> Self {
         Self::default()
     }
+
+    /// Sets the node-id tie-break direction used by `insert`, `remove`, `contains` and `merge`.
+    pub fn with_tie_break(mut self, tie_break: TieBreak) -> Self {
+        self.tie_break = tie_break;
+        self
+    }
+
+    /// Sets whether an add or a remove wins at an exact timestamp tie, instead of the
+    /// node-id tie-break.
+    pub fn with_tie_bias(mut self, tie_bias: ConflictPolicy) -> Self {
+        self.tie_bias = Some(tie_bias);
+        self
+    }
+
+    /// Returns the local capacity, if one is set.
+    pub fn capacity(&self) -> Option<usize> {
+        self.capacity
+    }
 }
 
 impl<T: Clone + Eq + Hash + Ord + Serialize + DeserializeOwned + Send + Sync + 'static> LWWSet<T> {
+    /// Caps the set at `max` live elements, evicting the oldest adds on `insert`.
+    ///
+    /// Elements already live beyond `max` stay until the next `insert`. The bound is local;
+    /// see the type-level docs.
+    pub fn with_capacity(mut self, max: usize) -> Self {
+        self.capacity = Some(max);
+        self.rebuild_age_index();
+        self
+    }
+
     /// Adds an element to the set with a specific timestamp.
     pub fn insert(&mut self, node_id: &str, element: T, timestamp: u64) {
         let node_id_str = node_id.to_string();
         match self.add_set.binary_search_by(|(e, _)| e.cmp(&element)) {
             Ok(idx) => {
                 let (_, (ts, nid)) = &self.add_set[idx];
-                if timestamp > *ts || (timestamp == *ts && node_id_str > *nid) {
-                    self.add_set[idx] = (element, (timestamp, node_id_str));
+                if timestamp > *ts || (timestamp == *ts && self.tie_break.prefers(&node_id_str, nid)) {
+                    let old_ts = *ts;
+                    if self.capacity.is_some() {
+                        self.by_age.remove(&(old_ts, element.clone()));
+                    }
+                    self.add_set[idx] = (element.clone(), (timestamp, node_id_str));
                     self.vclock.increment(node_id);
+                    self.index_age(element, timestamp);
                 }
             }
             Err(idx) => {
-                self.add_set.insert(idx, (element, (timestamp, node_id_str)));
+                self.add_set.insert(idx, (element.clone(), (timestamp, node_id_str)));
                 self.vclock.increment(node_id);
+                self.index_age(element, timestamp);
+            }
+        }
+        self.evict_oldest();
+    }
+
+    /// Records a newly written add in the age index if it left the element live.
+    fn index_age(&mut self, element: T, timestamp: u64) {
+        if self.capacity.is_some() && self.contains(&element) {
+            self.by_age.insert((timestamp, element));
+        }
+    }
+
+    fn rebuild_age_index(&mut self) {
+        self.by_age = match self.capacity {
+            Some(_) => self
+                .add_set
+                .iter()
+                .filter(|(e, _)| self.contains(e))
+                .map(|(e, (ts, _))| (*ts, e.clone()))
+                .collect(),
+            None => BTreeSet::new(),
+        };
+    }
+
+    /// Drops the add entries of the oldest live elements until the set fits its capacity.
+    fn evict_oldest(&mut self) {
+        let Some(max) = self.capacity else {
+            return;
+        };
+        while self.by_age.len() > max {
+            let Some((_, element)) = self.by_age.pop_first() else {
+                break;
+            };
+            if let Ok(idx) = self.add_set.binary_search_by(|(e, _)| e.cmp(&element)) {
+                self.add_set.remove(idx);
             }
         }
     }
@@ -152,22 +270,29 @@ impl<T: Clone + Eq + Hash + Ord + Serialize + DeserializeOwned + Send + Sync + '
         match self.remove_set.binary_search_by(|(e, _)| e.cmp(&element)) {
             Ok(idx) => {
                 let (_, (ts, nid)) = &self.remove_set[idx];
-                if timestamp > *ts || (timestamp == *ts && node_id_str > *nid) {
-                    self.remove_set[idx] = (element, (timestamp, node_id_str));
+                if timestamp > *ts || (timestamp == *ts && self.tie_break.prefers(&node_id_str, nid)) {
+                    self.remove_set[idx] = (element.clone(), (timestamp, node_id_str));
                     self.vclock.increment(node_id);
                 }
             }
             Err(idx) => {
-                self.remove_set.insert(idx, (element, (timestamp, node_id_str)));
+                self.remove_set.insert(idx, (element.clone(), (timestamp, node_id_str)));
                 self.vclock.increment(node_id);
             }
         }
+        if self.capacity.is_some() && !self.contains(&element) {
+            if let Ok(idx) = self.add_set.binary_search_by(|(e, _)| e.cmp(&element)) {
+                let add_ts = self.add_set[idx].1 .0;
+                self.by_age.remove(&(add_ts, element));
+            }
+        }
     }
 
     /// Returns true if the set contains the specified element.
     ///
     /// An element is present if its latest add timestamp is strictly greater
-    /// than its latest remove timestamp (or if no removal exists).
+    /// than its latest remove timestamp (or if no removal exists). Equal timestamps are
+    /// resolved by the set's tie bias, or by node id if it has none.
     pub fn contains(&self, element: &T) -> bool {
         let add_entry = self
             .add_set
@@ -183,13 +308,22 @@ impl<T: Clone + Eq + Hash + Ord + Serialize + DeserializeOwned + Send + Sync + '
 
         match (add_entry, remove_entry) {
             (Some((a_ts, a_id)), Some((r_ts, r_id))) => {
-                *a_ts > *r_ts || (*a_ts == *r_ts && a_id > r_id)
+                *a_ts > *r_ts || (*a_ts == *r_ts && self.add_wins_tie(a_id, r_id))
             }
             (Some(_), None) => true,
             _ => false,
         }
     }
 
+    /// Returns true if an add by `add_id` beats a remove by `remove_id` at the same timestamp.
+    fn add_wins_tie(&self, add_id: &str, remove_id: &str) -> bool {
+        match self.tie_bias {
+            None => self.tie_break.prefers(add_id, remove_id),
+            Some(ConflictPolicy::AddWins) => true,
+            Some(ConflictPolicy::RemoveWins) => false,
+        }
+    }
+
     /// Iterator over the elements currently in the set.
     pub fn iter(&self) -> impl Iterator<Item = &T> {
         self.add_set.iter().filter_map(move |(e, _)| {
@@ -202,15 +336,28 @@ impl<T: Clone + Eq + Hash + Ord + Serialize + DeserializeOwned + Send + Sync + '
     }
 
     /// Merges another LWW-Set into this one.
+    ///
+    /// Both replicas should share a tie-break direction and tie bias; see [`TieBreak::join`]
+    /// and [`ConflictPolicy::join`] otherwise (a set bias takes precedence over none).
     pub fn merge(&mut self, other: &Self) {
-        self.add_set = Self::merge_vecs(&self.add_set, &other.add_set);
-        self.remove_set = Self::merge_vecs(&self.remove_set, &other.remove_set);
+        if other.is_identity() {
+            return;
+        }
+        self.tie_break = self.tie_break.join(other.tie_break);
+        self.tie_bias = match (self.tie_bias, other.tie_bias) {
+            (Some(a), Some(b)) => Some(a.join(b)),
+            (bias, None) | (None, bias) => bias,
+        };
+        self.add_set = Self::merge_vecs(&self.add_set, &other.add_set, self.tie_break);
+        self.remove_set = Self::merge_vecs(&self.remove_set, &other.remove_set, self.tie_break);
         self.vclock.merge(&other.vclock);
+        self.rebuild_age_index();
     }
 
     fn merge_vecs(
         left: &[(T, (u64, String))],
         right: &[(T, (u64, String))],
+        tie_break: TieBreak,
     ) -> Vec<(T, (u64, String))> {
         let mut result = Vec::with_capacity(left.len() + right.len());
         let mut i = 0;
@@ -231,7 +378,7 @@ impl<T: Clone + Eq + Hash + Ord + Serialize + DeserializeOwned + Send + Sync + '
                 }
                 Ordering::Equal => {
                     // Both have the element, keep the one with higher timestamp/id
-                    if *ts1 > *ts2 || (*ts1 == *ts2 && id1 > id2) {
+                    if *ts1 > *ts2 || (*ts1 == *ts2 && !tie_break.prefers(id2, id1)) {
                         result.push((k1.clone(), (*ts1, id1.clone())));
                     } else {
                         result.push((k2.clone(), (*ts2, id2.clone())));
@@ -253,6 +400,11 @@ impl<T: Clone + Eq + Hash + Ord + Serialize + DeserializeOwned + Send + Sync + '
 
         result
     }
+
+    /// Compares the live elements and the logical vclock counters, ignoring wall-clock timestamps.
+    pub fn semantically_eq(&self, other: &Self) -> bool {
+        self.iter().eq(other.iter()) && self.vclock.logically_eq(&other.vclock)
+    }
 }
 
 // ============================================================================
@@ -280,6 +432,7 @@ impl<'a, T: Clone + Eq + Hash + Ord + Serialize + DeserializeOwned + Send + Sync
         let lww_set = reader
             .get_root::<lww_set_capnp::lww_set::Reader>()
             .map_err(|e: capnp::Error| CrdtError::Deserialization(e.to_string()))?;
+        check_type_tag::<T>(lww_set.get_element_type())?;
 
         let mut add_set = Vec::new();
         let adds = lww_set
@@ -287,12 +440,7 @@ impl<'a, T: Clone + Eq + Hash + Ord + Serialize + DeserializeOwned + Send + Sync
             .map_err(|e: capnp::Error| CrdtError::Deserialization(e.to_string()))?;
         for entry in adds {
             let entry: lww_set_capnp::lww_set::entry::Reader = entry;
-            let element: T = bincode::deserialize(
-                entry
-                    .get_element()
-                    .map_err(|e: capnp::Error| CrdtError::Deserialization(e.to_string()))?,
-            )
-            .map_err(|e: bincode::Error| CrdtError::Deserialization(e.to_string()))?;
+            let element: T = decode_element(entry)?;
             add_set.push((
                 element,
                 (
@@ -316,12 +464,7 @@ impl<'a, T: Clone + Eq + Hash + Ord + Serialize + DeserializeOwned + Send + Sync
             .map_err(|e: capnp::Error| CrdtError::Deserialization(e.to_string()))?;
         for entry in removes {
             let entry: lww_set_capnp::lww_set::entry::Reader = entry;
-            let element: T = bincode::deserialize(
-                entry
-                    .get_element()
-                    .map_err(|e: capnp::Error| CrdtError::Deserialization(e.to_string()))?,
-            )
-            .map_err(|e: bincode::Error| CrdtError::Deserialization(e.to_string()))?;
+            let element: T = decode_element(entry)?;
             remove_set.push((
                 element,
                 (
@@ -354,14 +497,52 @@ impl<'a, T: Clone + Eq + Hash + Ord + Serialize + DeserializeOwned + Send + Sync
             add_set,
             remove_set,
             vclock,
+            tie_break: if lww_set.get_lower_node_wins() {
+                TieBreak::LowerNodeWins
+            } else {
+                TieBreak::HigherNodeWins
+            },
+            tie_bias: match lww_set.get_tie_bias() {
+                0 => None,
+                1 => Some(ConflictPolicy::AddWins),
+                2 => Some(ConflictPolicy::RemoveWins),
+                other => {
+                    return Err(CrdtError::Deserialization(format!(
+                        "Unknown LWWSet tie bias {}",
+                        other
+                    )))
+                }
+            },
+            capacity: None,
+            by_age: BTreeSet::new(),
         })
     }
 }
 
+/// Decodes an entry's element from either its bincode `Data` or plain `Text` form.
+fn decode_element<T: DeserializeOwned>(
+    entry: lww_set_capnp::lww_set::entry::Reader<'_>,
+) -> Result<T, CrdtError> {
+    match entry
+        .which()
+        .map_err(|e| CrdtError::Deserialization(e.to_string()))?
+    {
+        lww_set_capnp::lww_set::entry::Element(bytes) => bincode::deserialize(
+            bytes.map_err(|e: capnp::Error| CrdtError::Deserialization(e.to_string()))?,
+        )
+        .map_err(|e: bincode::Error| CrdtError::Deserialization(e.to_string())),
+        lww_set_capnp::lww_set::entry::Text(text) => from_text(read_text(text)?),
+    }
+}
+
 impl<'a, T> CrdtReader<'a> for LWWSetReader<'a, T>
 where
     T: Clone + Eq + Hash + Ord + Serialize + DeserializeOwned + Send + Sync + 'static,
 {
+    fn from_bytes(bytes: &'a [u8]) -> Self {
+        Self::new(bytes)
+    }
+
     fn is_empty(&self) -> Result<bool, CrdtError> {
         Ok(self.to_set()?.add_set.is_empty())
     }
@@ -371,49 +552,125 @@ where
 // CRDT Trait Implementation
 // ============================================================================
 
+impl<T: Clone + Eq + Hash + Ord + Serialize + DeserializeOwned + Send + Sync + 'static> LWWSet<T> {
+    /// Serializes elements as plain UTF-8 `Text` ([plain-text encoding](crate#plain-text-encoding)).
+    pub fn to_capnp_text_bytes(&self) -> Vec<u8>
+    where
+        T: AsRef<str>,
+    {
+        let mut buf = Vec::new();
+        self.write_capnp(Some(<T as AsRef<str>>::as_ref), &mut buf)
+            .expect("LWWSet serialization fail");
+        buf
+    }
+
+    fn write_capnp<W: Write>(&self, as_text: Option<fn(&T) -> &str>, writer: &mut W) -> capnp::Result<()> {
+        let mut message = Builder::new(HeapAllocator::new());
+        self.build_message(as_text, &mut message)?;
+        serialize::write_message(writer, &message)
+    }
+
+    fn build_message(
+        &self,
+        as_text: Option<fn(&T) -> &str>,
+        message: &mut Builder<HeapAllocator>,
+    ) -> capnp::Result<()> {
+        let write_entries = |mut list: capnp::struct_list::Builder<'_, lww_set_capnp::lww_set::entry::Owned>,
+                             entries: &[LWWSetEntry<T>]| {
+            for (idx, (element, (timestamp, node_id))) in entries.iter().enumerate() {
+                let mut entry = list.reborrow().get(idx as u32);
+                match as_text {
+                    Some(as_text) => entry.set_text(as_text(element).into()),
+                    None => {
+                        let bytes =
+                            bincode::serialize(element).expect("LWWSet element serialization fail");
+                        entry.set_element(&bytes);
+                    }
+                }
+                entry.set_timestamp(*timestamp);
+                entry.set_node_id(node_id.as_str().into());
+            }
+        };
+
+        let mut lww_set = message.init_root::<lww_set_capnp::lww_set::Builder>();
+        write_entries(
+            lww_set.reborrow().init_add_set(self.add_set.len() as u32),
+            &self.add_set,
+        );
+        write_entries(
+            lww_set.reborrow().init_remove_set(self.remove_set.len() as u32),
+            &self.remove_set,
+        );
+
+        let vclock_bytes = self.vclock.to_capnp_bytes();
+        lww_set.set_vclock(&vclock_bytes);
+        lww_set.set_lower_node_wins(self.tie_break == TieBreak::LowerNodeWins);
+        lww_set.set_element_type(type_tag::<T>());
+        lww_set.set_tie_bias(match self.tie_bias {
+            None => 0,
+            Some(ConflictPolicy::AddWins) => 1,
+            Some(ConflictPolicy::RemoveWins) => 2,
+        });
+        Ok(())
+    }
+}
+
 impl<T: Clone + Eq + Hash + Ord + Serialize + DeserializeOwned + Send + Sync + 'static> Crdt
     for LWWSet<T>
 {
     type Reader<'a> = LWWSetReader<'a, T>;
 
     fn merge_from_readers(readers: &[Self::Reader<'_>]) -> Result<Self, CrdtError> {
+        let sets = readers
+            .iter()
+            .map(|r| r.to_set())
+            .collect::<Result<Vec<_>, _>>()?;
+        ensure_uniform("tie-break directions", sets.iter().map(|s| s.tie_break))?;
+        ensure_uniform("tie biases", sets.iter().map(|s| s.tie_bias))?;
+
         let mut result = LWWSet::new();
-        for reader in readers {
-            result.merge(&reader.to_set()?);
+        for set in &sets {
+            result.merge(set);
         }
         Ok(result)
     }
 
-    fn to_capnp_bytes(&self) -> Vec<u8> {
-        let mut message = Builder::new(HeapAllocator::new());
-        {
-            let mut lww_set = message.init_root::<lww_set_capnp::lww_set::Builder>();
+    fn is_identity(&self) -> bool {
+        *self == Self::default()
+    }
 
-            let mut adds = lww_set.reborrow().init_add_set(self.add_set.len() as u32);
-            for (idx, (element, (timestamp, node_id))) in self.add_set.iter().enumerate() {
-                let mut entry = adds.reborrow().get(idx as u32);
-                let bytes = bincode::serialize(element).expect("LWWSet element serialization fail");
-                entry.set_element(&bytes);
-                entry.set_timestamp(*timestamp);
-                entry.set_node_id(node_id.as_str().into());
-            }
+    fn merge(&mut self, other: &Self) {
+        self.merge(other);
+    }
 
-            let mut removes = lww_set
-                .reborrow()
-                .init_remove_set(self.remove_set.len() as u32);
-            for (idx, (element, (timestamp, node_id))) in self.remove_set.iter().enumerate() {
-                let mut entry = removes.reborrow().get(idx as u32);
-                let bytes = bincode::serialize(element).expect("LWWSet element serialization fail");
-                entry.set_element(&bytes);
-                entry.set_timestamp(*timestamp);
-                entry.set_node_id(node_id.as_str().into());
-            }
+    fn dominates(&self, other: &Self) -> bool {
+        self.vclock.dominates(&other.vclock) && merge_is_noop(self, other)
+    }
 
-            let vclock_bytes = self.vclock.to_capnp_bytes();
-            lww_set.set_vclock(&vclock_bytes);
-        }
+    fn merge_bytes(&mut self, bytes: &[u8]) -> Result<(), CrdtError> {
+        let other = LWWSetReader::new(bytes).to_set()?;
+        self.merge(&other);
+        Ok(())
+    }
+
+    fn ensure_mergeable(&self, other: &Self) -> Result<(), CrdtError> {
+        ensure_uniform("tie-break directions", [self.tie_break, other.tie_break])?;
+        ensure_uniform("tie biases", [self.tie_bias, other.tie_bias])
+    }
+
+    fn write_to<W: Write>(&self, writer: &mut W) -> Result<(), CrdtError> {
+        self.write_capnp(None, writer)
+            .map_err(|e| CrdtError::Serialization(e.to_string()))
+    }
+
+    fn build_capnp(&self, message: &mut Builder<HeapAllocator>) -> Result<(), CrdtError> {
+        self.build_message(None, message)
+            .map_err(|e| CrdtError::Serialization(e.to_string()))
+    }
+
+    fn to_capnp_bytes(&self) -> Vec<u8> {
         let mut buf = Vec::new();
-        serialize::write_message(&mut buf, &message).expect("LWWSet serialization fail");
+        self.write_capnp(None, &mut buf).expect("LWWSet serialization fail");
         buf
     }
 
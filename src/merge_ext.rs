@@ -0,0 +1,169 @@
+// Copyright (c) 2026 Adrian Robinson. All rights reserved.
+// Licensed under the MIT License. See LICENSE file in the project root for full license information.
+
+//! Value-style merge ergonomics.
+//!
+//! Every CRDT exposes an in-place `merge(&mut self, &other)`. [`MergeExt`] wraps it as a
+//! consuming `merged`, and the set-like types whose merge is plain union also implement `|`.
+
+use crate::{
+    DisableRegister, FWWRegister, GCounter, GSet, LWWMap, LWWRegister, LWWSet, MVRegister, ORMap,
+    ORSet, PNCounter, VectorClock,
+};
+#[cfg(feature = "probabilistic")]
+use crate::{ApproxSet, CountMinSketch, HyperLogLog, RoaringBitmap, TDigest, TopK};
+use serde::{de::DeserializeOwned, Serialize};
+use std::hash::Hash;
+use std::ops::BitOr;
+
+/// Pure (consuming) merge built on each type's in-place `merge`.
+///
+/// # Example
+///
+/// ```
+/// use crdt_data_types::{GCounter, MergeExt};
+///
+/// let mut a = GCounter::new();
+/// a.increment("node_a", 1);
+/// let mut b = GCounter::new();
+/// b.increment("node_b", 2);
+///
+/// assert_eq!(a.merged(&b).value(), 3);
+/// ```
+pub trait MergeExt: Sized {
+    /// Merges `other` into `self` and returns the result.
+    fn merged(self, other: &Self) -> Self;
+}
+
+macro_rules! impl_merge_ext {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl MergeExt for $ty {
+                fn merged(mut self, other: &Self) -> Self {
+                    self.merge(other);
+                    self
+                }
+            }
+        )*
+    };
+}
+
+impl_merge_ext!(GCounter, PNCounter, VectorClock);
+
+#[cfg(feature = "probabilistic")]
+impl_merge_ext!(CountMinSketch, HyperLogLog, RoaringBitmap, TDigest, TopK);
+
+impl<T: Clone + Serialize + DeserializeOwned + Send + Sync + 'static> MergeExt for DisableRegister<T> {
+    fn merged(mut self, other: &Self) -> Self {
+        self.merge(other);
+        self
+    }
+}
+
+impl<T: Clone + Serialize + DeserializeOwned + Send + Sync + 'static> MergeExt for FWWRegister<T> {
+    fn merged(mut self, other: &Self) -> Self {
+        self.merge(other);
+        self
+    }
+}
+
+impl<T: Clone + Serialize + DeserializeOwned + Ord + Send + Sync + 'static> MergeExt for LWWRegister<T> {
+    fn merged(mut self, other: &Self) -> Self {
+        self.merge(other);
+        self
+    }
+}
+
+impl<T: Clone + Eq + Hash + Ord + Serialize + DeserializeOwned + Send + Sync + 'static> MergeExt for GSet<T> {
+    fn merged(mut self, other: &Self) -> Self {
+        self.merge(other);
+        self
+    }
+}
+
+impl<T: Clone + Eq + Hash + Ord + Serialize + DeserializeOwned + Send + Sync + 'static> MergeExt for LWWSet<T> {
+    fn merged(mut self, other: &Self) -> Self {
+        self.merge(other);
+        self
+    }
+}
+
+impl<T: Clone + Eq + Hash + Serialize + DeserializeOwned + Send + Sync + 'static> MergeExt for MVRegister<T> {
+    fn merged(mut self, other: &Self) -> Self {
+        self.merge(other);
+        self
+    }
+}
+
+impl<T> MergeExt for ORSet<T>
+where
+    T: Clone + Eq + Hash + Ord + Serialize + DeserializeOwned + Default + Send + Sync + 'static,
+{
+    fn merged(mut self, other: &Self) -> Self {
+        self.merge(other);
+        self
+    }
+}
+
+impl<K, V> MergeExt for LWWMap<K, V>
+where
+    K: Clone + Eq + Hash + Ord + Serialize + DeserializeOwned + Send + Sync + 'static,
+    V: Clone + Ord + Serialize + DeserializeOwned + Send + Sync + 'static,
+{
+    fn merged(mut self, other: &Self) -> Self {
+        self.merge(other);
+        self
+    }
+}
+
+impl<K, V> MergeExt for ORMap<K, V>
+where
+    K: Clone + Eq + Hash + Serialize + DeserializeOwned + Default + Send + Sync + 'static + Ord,
+    V: Clone + Eq + Hash + Serialize + DeserializeOwned + Default + Send + Sync + 'static + Ord,
+{
+    fn merged(mut self, other: &Self) -> Self {
+        self.merge(other);
+        self
+    }
+}
+
+#[cfg(feature = "probabilistic")]
+impl<T> MergeExt for ApproxSet<T>
+where
+    T: Clone + Eq + Hash + Ord + Serialize + DeserializeOwned + Default + Send + Sync + 'static,
+{
+    fn merged(mut self, other: &Self) -> Self {
+        self.merge(other);
+        self
+    }
+}
+
+// ============================================================================
+// Union Operators
+// ============================================================================
+
+impl<T: Clone + Eq + Hash + Ord + Serialize + DeserializeOwned + Send + Sync + 'static> BitOr for GSet<T> {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        self.merged(&rhs)
+    }
+}
+
+#[cfg(feature = "probabilistic")]
+impl BitOr for RoaringBitmap {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        self.merged(&rhs)
+    }
+}
+
+#[cfg(feature = "probabilistic")]
+impl BitOr for HyperLogLog {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        self.merged(&rhs)
+    }
+}
@@ -1,14 +1,18 @@
 // Copyright (c) 2026 Adrian Robinson. All rights reserved.
 // Licensed under the MIT License. See LICENSE file in the project root for full license information.
 
+use crate::dot::Dot;
 use crate::mv_register_capnp;
-use crate::traits::{Crdt, CrdtError, CrdtReader};
+use crate::node_ids::{read_node_ids, resolve, NodeIdTable};
+use crate::size_estimate::check_entry_budget;
+use crate::traits::{merge_is_noop, Crdt, CrdtError, CrdtReader};
 use crate::vector_clock::VectorClock;
 use capnp::message::{Builder, HeapAllocator, ReaderOptions};
 use capnp::serialize;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 use std::hash::Hash;
+use std::io::Write;
 
 /// MV-Register: A Multi-Value Register CRDT.
 ///
@@ -52,7 +56,7 @@ use std::hash::Hash;
 ))]
 pub struct MVRegister<T: Eq + Hash> {
     /// Each value is associated with one or more observation IDs (node_id, counter).
-    pub entries: HashMap<T, HashSet<(String, u64)>>,
+    pub entries: HashMap<T, HashSet<Dot>>,
     /// Vector clock representing the cumulative causal history.
     #[serde(default)]
     pub vclock: VectorClock,
@@ -86,10 +90,70 @@ impl<T: Clone + Eq + Hash + Serialize + DeserializeOwned + Send + Sync + 'static
 
         // Add the new version with its unique observation ID (dot)
         let mut ids = HashSet::new();
-        ids.insert((node_id.to_string(), counter));
+        ids.insert(Dot::new(node_id, counter));
         self.entries.insert(value, ids);
     }
 
+    /// Inserts a version carrying foreign dots and causal context, e.g. when importing
+    /// state from another system.
+    ///
+    /// Versions whose dots are covered by `context` are treated as overwritten and dropped;
+    /// versions concurrent with it are kept alongside `value`. `context` (extended to cover
+    /// `dots`) is merged into the vclock. The local clock is not advanced.
+    ///
+    /// # Errors
+    /// Returns `CrdtError::InvalidInput` if `dots` is empty, since the version would be
+    /// invisible.
+    pub fn put_with_context(
+        &mut self,
+        value: T,
+        dots: HashSet<Dot>,
+        mut context: VectorClock,
+    ) -> Result<(), CrdtError> {
+        if dots.is_empty() {
+            return Err(CrdtError::InvalidInput(
+                "put_with_context requires at least one dot".into(),
+            ));
+        }
+        for dot in &dots {
+            let entry = context.clocks.entry(dot.node_id.clone()).or_insert((0, 0));
+            entry.0 = entry.0.max(dot.counter);
+        }
+
+        self.entries.retain(|_, ids| {
+            ids.retain(|id| dots.contains(id) || !id.is_dominated_by(&context));
+            !ids.is_empty()
+        });
+        self.entries.entry(value).or_default().extend(dots);
+        self.vclock.merge(&context);
+        Ok(())
+    }
+
+    /// Clears the register, deleting every version this replica has observed.
+    ///
+    /// The vclock is advanced so the deletion survives merge: replicas holding the cleared
+    /// versions have them dropped as causally dominated, while writes made concurrently
+    /// on other nodes are not covered and survive.
+    pub fn clear(&mut self, node_id: &str) {
+        self.vclock.increment(node_id);
+        self.entries.clear();
+    }
+
+    /// Returns the total number of observation dots across all values.
+    pub fn dot_count(&self) -> usize {
+        self.entries.values().map(|ids| ids.len()).sum()
+    }
+
+    /// Returns the number of concurrent versions held in the register.
+    pub fn conflict_count(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if the register holds more than one concurrent version.
+    pub fn is_conflicted(&self) -> bool {
+        self.entries.len() > 1
+    }
+
     /// Returns the current versions held in the register.
     pub fn versions(&self) -> HashSet<T> {
         self.entries.keys().cloned().collect()
@@ -97,6 +161,9 @@ impl<T: Clone + Eq + Hash + Serialize + DeserializeOwned + Send + Sync + 'static
 
     /// Merges another MV-Register into this one.
     pub fn merge(&mut self, other: &Self) {
+        if other.is_identity() {
+            return;
+        }
         let mut new_entries = HashMap::new();
 
         let all_values: HashSet<_> = self
@@ -111,15 +178,13 @@ impl<T: Clone + Eq + Hash + Serialize + DeserializeOwned + Send + Sync + 'static
 
             if let Some(ids) = self.entries.get(&val) {
                 for id in ids {
-                    let other_version =
-                        other.vclock.clocks.get(&id.0).map(|(c, _)| *c).unwrap_or(0);
                     let in_other = other
                         .entries
                         .get(&val)
                         .map(|other_ids| other_ids.contains(id))
                         .unwrap_or(false);
 
-                    if id.1 > other_version || in_other {
+                    if !id.is_dominated_by(&other.vclock) || in_other {
                         merged_ids.insert(id.clone());
                     }
                 }
@@ -127,14 +192,13 @@ impl<T: Clone + Eq + Hash + Serialize + DeserializeOwned + Send + Sync + 'static
 
             if let Some(ids) = other.entries.get(&val) {
                 for id in ids {
-                    let self_version = self.vclock.clocks.get(&id.0).map(|(c, _)| *c).unwrap_or(0);
                     let in_self = self
                         .entries
                         .get(&val)
                         .map(|self_ids| self_ids.contains(id))
                         .unwrap_or(false);
 
-                    if id.1 > self_version || in_self {
+                    if !id.is_dominated_by(&self.vclock) || in_self {
                         merged_ids.insert(id.clone());
                     }
                 }
@@ -148,6 +212,13 @@ impl<T: Clone + Eq + Hash + Serialize + DeserializeOwned + Send + Sync + 'static
         self.entries = new_entries;
         self.vclock.merge(&other.vclock);
     }
+
+    /// Compares the observable values and the logical vclock counters, ignoring wall-clock timestamps.
+    pub fn semantically_eq(&self, other: &Self) -> bool {
+        self.entries.len() == other.entries.len()
+            && self.entries.keys().all(|v| other.entries.contains_key(v))
+            && self.vclock.logically_eq(&other.vclock)
+    }
 }
 
 // ============================================================================
@@ -170,6 +241,19 @@ where
         }
     }
 
+    /// Number of `(value, dot)` entries, read from the list header only.
+    pub fn entry_count(&self) -> Result<usize, CrdtError> {
+        let reader = serialize::read_message(self.bytes, ReaderOptions::new())
+            .map_err(|e: capnp::Error| CrdtError::Deserialization(e.to_string()))?;
+        let reg_reader = reader
+            .get_root::<mv_register_capnp::mv_register::Reader>()
+            .map_err(|e: capnp::Error| CrdtError::Deserialization(e.to_string()))?;
+        let entries = reg_reader
+            .get_entries()
+            .map_err(|e: capnp::Error| CrdtError::Deserialization(e.to_string()))?;
+        Ok(entries.len() as usize)
+    }
+
     fn to_register(&self) -> Result<MVRegister<T>, CrdtError> {
         let reader = serialize::read_message(self.bytes, ReaderOptions::new())
             .map_err(|e: capnp::Error| CrdtError::Deserialization(e.to_string()))?;
@@ -177,6 +261,16 @@ where
             .get_root::<mv_register_capnp::mv_register::Reader>()
             .map_err(|e: capnp::Error| CrdtError::Deserialization(e.to_string()))?;
 
+        let node_ids = if reg_reader.has_node_ids() {
+            Some(read_node_ids(
+                reg_reader
+                    .get_node_ids()
+                    .map_err(|e: capnp::Error| CrdtError::Deserialization(e.to_string()))?,
+            )?)
+        } else {
+            None
+        };
+
         let mut entries = HashMap::new();
         let entry_list = reg_reader
             .get_entries()
@@ -189,17 +283,20 @@ where
             let value: T = bincode::deserialize(val_bytes)
                 .map_err(|e: bincode::Error| CrdtError::Deserialization(e.to_string()))?;
 
-            let node_id = entry
-                .get_node_id()
-                .map_err(|e: capnp::Error| CrdtError::Deserialization(e.to_string()))?
-                .to_str()
-                .map_err(|e: std::str::Utf8Error| CrdtError::Deserialization(e.to_string()))?;
+            let node_id = match &node_ids {
+                Some(node_ids) => resolve(node_ids, entry.get_node_index())?,
+                None => entry
+                    .get_node_id()
+                    .map_err(|e: capnp::Error| CrdtError::Deserialization(e.to_string()))?
+                    .to_str()
+                    .map_err(|e: std::str::Utf8Error| CrdtError::Deserialization(e.to_string()))?,
+            };
             let counter = entry.get_counter();
 
             entries
                 .entry(value)
                 .or_insert_with(HashSet::new)
-                .insert((node_id.to_string(), counter));
+                .insert(Dot::new(node_id, counter));
         }
 
         let vclock = if reg_reader.has_vclock() {
@@ -221,6 +318,10 @@ impl<'a, T> CrdtReader<'a> for MVRegisterReader<'a, T>
 where
     T: Clone + Eq + Hash + Serialize + DeserializeOwned + Send + Sync + 'static,
 {
+    fn from_bytes(bytes: &'a [u8]) -> Self {
+        Self::new(bytes)
+    }
+
     fn is_empty(&self) -> Result<bool, CrdtError> {
         Ok(self.to_register()?.entries.is_empty())
     }
@@ -230,6 +331,76 @@ where
 // CRDT Trait Implementation
 // ============================================================================
 
+impl<T: Clone + Eq + Hash + Serialize + DeserializeOwned + Send + Sync + 'static> MVRegister<T> {
+    /// [`merge_from_readers`](Crdt::merge_from_readers) with an entry budget; see
+    /// [bounded merges](Crdt#bounded-merges).
+    ///
+    /// Each concurrent value counts as an entry.
+    pub fn merge_from_readers_bounded(
+        readers: &[MVRegisterReader<'_, T>],
+        max_entries: usize,
+    ) -> Result<Self, CrdtError> {
+        check_entry_budget(readers.iter().map(|r| r.entry_count()), max_entries)?;
+        Self::merge_from_readers(readers)
+    }
+
+    /// Serializes with each distinct node id stored once, in a `nodeIds` dictionary that
+    /// entries refer to by index.
+    ///
+    /// Readers before the dictionary was introduced decode such a blob with empty entry
+    /// node ids, so only send it once every consumer has upgraded. `to_capnp_bytes`
+    /// keeps a node id per entry.
+    pub fn to_capnp_interned_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        self.write_capnp(true, &mut buf)
+            .expect("MVRegister serialization fail");
+        buf
+    }
+
+    fn write_capnp<W: Write>(&self, intern_node_ids: bool, writer: &mut W) -> Result<(), CrdtError> {
+        let mut message = Builder::new(HeapAllocator::new());
+        self.build_message(intern_node_ids, &mut message)?;
+        serialize::write_message(writer, &message)
+            .map_err(|e| CrdtError::Serialization(e.to_string()))
+    }
+
+    fn build_message(
+        &self,
+        intern_node_ids: bool,
+        message: &mut Builder<HeapAllocator>,
+    ) -> Result<(), CrdtError> {
+        let mut reg = message.init_root::<mv_register_capnp::mv_register::Builder>();
+
+        let num_dots: usize = self.entries.values().map(|ids| ids.len()).sum();
+        let mut entries = reg.reborrow().init_entries(num_dots as u32);
+        let mut node_ids = NodeIdTable::new();
+
+        let mut idx = 0;
+        for (val, dots) in &self.entries {
+            let val_bytes =
+                bincode::serialize(val).expect("MVRegister value serialization fail");
+            for dot in dots {
+                let mut entry = entries.reborrow().get(idx);
+                entry.set_value(&val_bytes);
+                if intern_node_ids {
+                    entry.set_node_index(node_ids.intern(&dot.node_id));
+                } else {
+                    entry.set_node_id(dot.node_id.as_str().into());
+                }
+                entry.set_counter(dot.counter);
+                idx += 1;
+            }
+        }
+        if intern_node_ids {
+            node_ids.write(reg.reborrow().init_node_ids(node_ids.len() as u32));
+        }
+
+        let vclock_bytes = self.vclock.to_capnp_bytes();
+        reg.set_vclock(&vclock_bytes);
+        Ok(())
+    }
+}
+
 impl<T: Clone + Eq + Hash + Serialize + DeserializeOwned + Send + Sync + 'static> Crdt
     for MVRegister<T>
 {
@@ -243,40 +414,50 @@ impl<T: Clone + Eq + Hash + Serialize + DeserializeOwned + Send + Sync + 'static
         Ok(result)
     }
 
-    fn to_capnp_bytes(&self) -> Vec<u8> {
-        let mut message = Builder::new(HeapAllocator::new());
-        {
-            let mut reg = message.init_root::<mv_register_capnp::mv_register::Builder>();
-
-            let num_dots: usize = self.entries.values().map(|ids| ids.len()).sum();
-            let mut entries = reg.reborrow().init_entries(num_dots as u32);
-
-            let mut idx = 0;
-            for (val, dots) in &self.entries {
-                let val_bytes =
-                    bincode::serialize(val).expect("MVRegister value serialization fail");
-                for (node_id, counter) in dots {
-                    let mut entry = entries.reborrow().get(idx);
-                    entry.set_value(&val_bytes);
-                    entry.set_node_id(node_id.as_str().into());
-                    entry.set_counter(*counter);
-                    idx += 1;
-                }
-            }
+    fn is_identity(&self) -> bool {
+        *self == Self::default()
+    }
 
-            let vclock_bytes = self.vclock.to_capnp_bytes();
-            reg.set_vclock(&vclock_bytes);
-        }
+    fn merge(&mut self, other: &Self) {
+        self.merge(other);
+    }
+
+    fn dominates(&self, other: &Self) -> bool {
+        self.vclock.dominates(&other.vclock) && merge_is_noop(self, other)
+    }
+
+    fn merge_bytes(&mut self, bytes: &[u8]) -> Result<(), CrdtError> {
+        let other = MVRegisterReader::new(bytes).to_register()?;
+        self.merge(&other);
+        Ok(())
+    }
+
+    fn write_to<W: Write>(&self, writer: &mut W) -> Result<(), CrdtError> {
+        self.write_capnp(false, writer)
+    }
+
+    fn build_capnp(&self, message: &mut Builder<HeapAllocator>) -> Result<(), CrdtError> {
+        self.build_message(false, message)
+    }
+
+    fn to_capnp_bytes(&self) -> Vec<u8> {
         let mut buf = Vec::new();
-        serialize::write_message(&mut buf, &message).expect("MVRegister serialization fail");
+        self.write_to(&mut buf).expect("MVRegister serialization fail");
         buf
     }
 
+
     fn is_empty(&self) -> bool {
         self.entries.is_empty()
     }
 
     fn validate(&self) -> Result<(), CrdtError> {
+        // Every dot must be summarized by the vclock; a dot beyond it indicates corruption
+        for dot in self.entries.values().flatten() {
+            if !dot.is_dominated_by(&self.vclock) {
+                return Err(CrdtError::Validation(format!("Dot {} exceeds vclock", dot)));
+            }
+        }
         Ok(())
     }
 }
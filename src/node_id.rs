@@ -0,0 +1,104 @@
+// Copyright (c) 2026 Adrian Robinson. All rights reserved.
+// Licensed under the MIT License. See LICENSE file in the project root for full license information.
+
+use crate::traits::CrdtError;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::ops::Deref;
+
+/// Longest node id, in bytes, that [`NodeId`] accepts.
+pub const MAX_NODE_ID_LEN: usize = 256;
+
+/// NodeId: A node identifier checked once, at the boundary.
+///
+/// The CRDTs take node ids as plain strings and accept any of them, including the empty
+/// string, which sorts before every other id and so silently decides LWW tie-breaks.
+/// Parsing ids into a `NodeId` where they enter the system (and passing it wherever an
+/// `impl Into<String>` or `&str` is expected) rules that out.
+///
+/// # Scope
+///
+/// Validation is opt-in. Only `NodeId::new` (and its `TryFrom`/serde impls) and the
+/// `try_new` register constructors check ids; the CRDTs' own methods (`new`, `insert`,
+/// `set`, `increment`, ...) keep accepting any string so existing callers are unaffected.
+/// `Default` registers (e.g. `LWWRegister::default()`) hold an empty node id at timestamp
+/// 0: the "never written" state, which any write with a later timestamp replaces.
+///
+/// # Example
+///
+/// ```
+/// use crdt_data_types::{LWWRegister, NodeId};
+///
+/// let node = NodeId::new("node_a").unwrap();
+/// let reg = LWWRegister::new("v".to_string(), 1, node);
+/// assert_eq!(reg.node_id, "node_a");
+///
+/// assert!(NodeId::new("").is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct NodeId(String);
+
+impl NodeId {
+    /// Validates `node_id`: it must be non-empty and at most [`MAX_NODE_ID_LEN`] bytes.
+    pub fn new(node_id: impl Into<String>) -> Result<Self, CrdtError> {
+        let node_id = node_id.into();
+        if node_id.is_empty() {
+            return Err(CrdtError::InvalidInput("Node id must not be empty".to_string()));
+        }
+        if node_id.len() > MAX_NODE_ID_LEN {
+            return Err(CrdtError::InvalidInput(format!(
+                "Node id is {} bytes, over the {}-byte limit",
+                node_id.len(),
+                MAX_NODE_ID_LEN
+            )));
+        }
+        Ok(Self(node_id))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Deref for NodeId {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for NodeId {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for NodeId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl TryFrom<String> for NodeId {
+    type Error = CrdtError;
+
+    fn try_from(node_id: String) -> Result<Self, CrdtError> {
+        Self::new(node_id)
+    }
+}
+
+impl TryFrom<&str> for NodeId {
+    type Error = CrdtError;
+
+    fn try_from(node_id: &str) -> Result<Self, CrdtError> {
+        Self::new(node_id)
+    }
+}
+
+impl From<NodeId> for String {
+    fn from(node_id: NodeId) -> Self {
+        node_id.0
+    }
+}
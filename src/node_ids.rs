@@ -0,0 +1,62 @@
+// Copyright (c) 2026 Adrian Robinson. All rights reserved.
+// Licensed under the MIT License. See LICENSE file in the project root for full license information.
+
+//! Node-id dictionaries for serialized CRDTs.
+//!
+//! A set or map with thousands of entries usually has only a handful of writers, yet every
+//! dot or entry repeats its node id as `Text`. The opt-in `to_capnp_interned_bytes`
+//! encodings instead carry each distinct node id once, in a `nodeIds` list, and entries
+//! refer to it by index. Readers accept both layouts; readers that predate the dictionary
+//! do not, which is why the default encodings keep a node id per entry.
+
+use crate::text_element::read_text;
+use crate::traits::CrdtError;
+use std::collections::HashMap;
+
+/// Assigns indices to node ids in order of first use.
+#[derive(Default)]
+pub(crate) struct NodeIdTable<'a> {
+    ids: Vec<&'a str>,
+    index: HashMap<&'a str, u32>,
+}
+
+impl<'a> NodeIdTable<'a> {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the index of `node_id`, adding it to the dictionary if new.
+    pub(crate) fn intern(&mut self, node_id: &'a str) -> u32 {
+        *self.index.entry(node_id).or_insert_with(|| {
+            self.ids.push(node_id);
+            (self.ids.len() - 1) as u32
+        })
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    /// Writes the dictionary into a list initialised with `len()` entries.
+    pub(crate) fn write(&self, mut list: capnp::text_list::Builder<'_>) {
+        for (idx, node_id) in self.ids.iter().enumerate() {
+            list.set(idx as u32, (*node_id).into());
+        }
+    }
+}
+
+/// Decodes a serialized dictionary.
+pub(crate) fn read_node_ids(list: capnp::text_list::Reader<'_>) -> Result<Vec<&str>, CrdtError> {
+    list.iter().map(read_text).collect()
+}
+
+/// Looks up a dictionary index read from a message.
+pub(crate) fn resolve<'a>(node_ids: &[&'a str], idx: u32) -> Result<&'a str, CrdtError> {
+    node_ids.get(idx as usize).copied().ok_or_else(|| {
+        CrdtError::Deserialization(format!(
+            "Node index {} out of range for {} node ids",
+            idx,
+            node_ids.len()
+        ))
+    })
+}
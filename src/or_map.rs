@@ -1,15 +1,17 @@
 // Copyright (c) 2026 Adrian Robinson. All rights reserved.
 // Licensed under the MIT License. See LICENSE file in the project root for full license information.
 
+use crate::dot::Dot;
 use crate::or_map_capnp;
 use crate::or_set::ORSet;
-use crate::traits::{Crdt, CrdtError, CrdtReader};
+use crate::traits::{merge_is_noop, Crdt, CrdtError, CrdtReader};
 use crate::vector_clock::VectorClock;
 use capnp::message::{Builder, HeapAllocator, ReaderOptions};
 use capnp::serialize;
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 use std::hash::Hash;
+use std::io::Write;
 
 use serde::de::DeserializeOwned;
 
@@ -25,6 +27,9 @@ use serde::de::DeserializeOwned;
 /// - **Add-Wins**: Concurrent add and remove of the same key-value pair results in the pair being present.
 /// - **Map Semantics**: Stores key-value pairs.
 /// - **Composition**: Built on top of an OR-Set of (Key, Value) tuples.
+/// - **Per-Key Causality** (opt-in): With [`ORMap::with_key_clocks`], each key keeps a
+///   clock covering every dot written for it, so merge judges a key's pairs only against
+///   that key's history and [`ORMap::delta_for_key`] sends a key only to peers missing it.
 ///
 /// # Algebraic Properties
 ///
@@ -44,28 +49,155 @@ use serde::de::DeserializeOwned;
 ///
 /// assert!(map.get_concurrent(&"key1".to_string()).contains(&"value1".to_string()));
 /// ```
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+///
+/// # Key Clocks
+///
+/// Key clocks are replicated state, but they are only exposed through
+/// [`ORMap::key_clock`] so that they stay sorted and pruned. Together with the local
+/// opt-in flag this makes `ORMap` `#[non_exhaustive]`: build it with [`ORMap::new`] or
+/// `Default`, then [`ORMap::with_key_clocks`] if you sync keys individually.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(bound(
     serialize = "K: Serialize + Eq + Hash + Ord, V: Serialize + Eq + Hash + Ord",
     deserialize = "K: DeserializeOwned + Eq + Hash + Ord, V: DeserializeOwned + Eq + Hash + Ord"
 ))]
+#[non_exhaustive]
 pub struct ORMap<K: Eq + Hash + Ord, V: Eq + Hash + Ord> {
     /// Internal storage using an OR-Set of (K, V) tuples.
     pub elements: ORSet<(K, V)>,
     /// Vector clock representing the causal history of the map.
     #[serde(default)]
     pub vclock: VectorClock,
+    /// Causal context per key, sorted by key: covers every dot ever written for the key,
+    /// including those of removed pairs.
+    ///
+    /// A key without an entry uses the map-wide `elements.vclock`, which only advances on
+    /// local writes and full-state merges, so maps decoded from data without key clocks
+    /// need no migration. Removing a key drops its entry once that clock covers it.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    key_clocks: Vec<(K, VectorClock)>,
+    /// Whether local writes start clocks for keys that have none. Local-only.
+    #[serde(skip)]
+    track_key_clocks: bool,
+}
+
+impl<K: Eq + Hash + Ord, V: Eq + Hash + Ord> PartialEq for ORMap<K, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.elements == other.elements
+            && self.vclock == other.vclock
+            && self.key_clocks == other.key_clocks
+    }
 }
 
+impl<K: Eq + Hash + Ord, V: Eq + Hash + Ord> Eq for ORMap<K, V> {}
+
 impl<K: Eq + Hash + Ord, V: Eq + Hash + Ord> Default for ORMap<K, V> {
     fn default() -> Self {
         Self {
             elements: ORSet::new(),
             vclock: VectorClock::new(),
+            key_clocks: Vec::new(),
+            track_key_clocks: false,
         }
     }
 }
 
+impl<K: Eq + Hash + Ord, V: Eq + Hash + Ord> ORMap<K, V> {
+    /// Returns true if the map holds at least one value for `key`.
+    pub fn contains_key(&self, key: &K) -> bool {
+        // Pairs are sorted by key first, so the first pair not below `key` decides
+        let entries = &self.elements.elements;
+        let idx = entries.partition_point(|((k, _), _)| k < key);
+        entries.get(idx).is_some_and(|((k, _), _)| k == key)
+    }
+
+    /// Iterator over the distinct keys, in ascending order.
+    ///
+    /// A key with several concurrent values is yielded once.
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        let mut last: Option<&K> = None;
+        self.elements.elements.iter().filter_map(move |((k, _), _)| {
+            if last == Some(k) {
+                None
+            } else {
+                last = Some(k);
+                Some(k)
+            }
+        })
+    }
+
+    /// Returns the number of distinct keys in the map.
+    pub fn len(&self) -> usize {
+        self.keys().count()
+    }
+
+    /// Returns true if the map holds no keys.
+    pub fn is_empty(&self) -> bool {
+        self.elements.elements.is_empty()
+    }
+
+    /// Projects every value through `f`, keeping each pair's dots, the key clocks and
+    /// the vclock.
+    ///
+    /// Values of a key that project to the same result become one pair holding the
+    /// union of their dots.
+    pub fn map_values<W: Eq + Hash + Ord, F: Fn(&V) -> W>(&self, f: F) -> ORMap<K, W>
+    where
+        K: Clone,
+    {
+        let project = |entries: &[((K, V), HashSet<Dot>)]| {
+            let mut projected: Vec<((K, W), HashSet<Dot>)> = entries
+                .iter()
+                .map(|((k, v), dots)| ((k.clone(), f(v)), dots.clone()))
+                .collect();
+            projected.sort_by(|(a, _), (b, _)| a.cmp(b));
+            projected.dedup_by(|(pair, dots), (kept, kept_dots)| {
+                let duplicate = pair == kept;
+                if duplicate {
+                    kept_dots.extend(dots.drain());
+                }
+                duplicate
+            });
+            projected
+        };
+
+        let mut elements = ORSet::default();
+        elements.elements = project(&self.elements.elements);
+        elements.removals = project(&self.elements.removals);
+        elements.vclock = self.elements.vclock.clone();
+        elements.policy = self.elements.policy;
+        ORMap {
+            elements,
+            vclock: self.vclock.clone(),
+            key_clocks: self.key_clocks.clone(),
+            track_key_clocks: self.track_key_clocks,
+        }
+    }
+
+    /// Returns the causal context recorded for `key`, if it has one of its own.
+    ///
+    /// Removing a key drops its clock once the map-wide clock covers it.
+    pub fn key_clock(&self, key: &K) -> Option<&VectorClock> {
+        self.key_clocks
+            .binary_search_by(|(k, _)| k.cmp(key))
+            .ok()
+            .map(|idx| &self.key_clocks[idx].1)
+    }
+}
+
+/// The clock a map's pairs for `key` are judged against on merge: the key's own clock,
+/// or the map-wide `fallback` if it has none.
+fn scoped_clock<'c, K: Ord>(
+    key_clocks: &'c [(K, VectorClock)],
+    fallback: &'c VectorClock,
+    key: &K,
+) -> &'c VectorClock {
+    match key_clocks.binary_search_by(|(k, _)| k.cmp(key)) {
+        Ok(idx) => &key_clocks[idx].1,
+        Err(_) => fallback,
+    }
+}
+
 impl<K, V> ORMap<K, V>
 where
     K: Clone + Eq + Hash + Serialize + DeserializeOwned + Default + Send + Sync + 'static + Ord,
@@ -76,6 +208,20 @@ where
         Self::default()
     }
 
+    /// Starts a clock for each key on its first local write, for use with
+    /// [`delta_for_key`](Self::delta_for_key).
+    ///
+    /// Without it, only keys that already have a clock (e.g. received from a peer) keep
+    /// one, and every other key is described by the map-wide clock, so `delta_for_key`
+    /// sends it whenever the peer lacks any event the map has seen. A new clock starts
+    /// from the map-wide clock, since that is all the history the key was judged against
+    /// until then. The setting is local configuration and is neither serialized nor
+    /// merged.
+    pub fn with_key_clocks(mut self) -> Self {
+        self.track_key_clocks = true;
+        self
+    }
+
     /// Inserts or updates a value for a specific key.
     ///
     /// # Arguments
@@ -84,9 +230,47 @@ where
     /// * `value` - The value to associate with the key.
     pub fn insert(&mut self, node_id: &str, key: K, value: V) {
         // Remove existing versions of this key before adding new one
-        self.remove(&key);
-        self.elements.insert(node_id, (key, value));
+        self.remove_pairs(&key);
+        self.elements.insert(node_id, (key.clone(), value));
+        self.vclock.increment(node_id);
+        self.record_event(node_id, key);
+    }
+
+    /// Removes a key on behalf of `node_id`, recording the removal in the key's clock.
+    ///
+    /// Unlike [`remove`](Self::remove), this lets [`delta_for_key`](Self::delta_for_key)
+    /// tell a peer that has seen every earlier write to the key about the removal.
+    pub fn remove_by(&mut self, node_id: &str, key: &K) {
+        self.remove_pairs(key);
+        self.elements.vclock.increment(node_id);
         self.vclock.increment(node_id);
+        self.record_event(node_id, key.clone());
+        self.prune_key_clock(key);
+    }
+
+    /// Copies `node_id`'s latest event into `key`'s clock, leaving other keys untouched.
+    ///
+    /// A key without a clock only gets one if key clocks are enabled.
+    fn record_event(&mut self, node_id: &str, key: K) {
+        let event = self.elements.vclock.clocks[node_id];
+        let idx = match self.key_clocks.binary_search_by(|(k, _)| k.cmp(&key)) {
+            Ok(idx) => idx,
+            Err(_) if !self.track_key_clocks => return,
+            Err(idx) => {
+                self.key_clocks.insert(idx, (key, self.elements.vclock.clone()));
+                idx
+            }
+        };
+        self.key_clocks[idx].1.clocks.insert(node_id.to_string(), event);
+    }
+
+    /// Drops `key`'s clock if the key is gone and the map-wide clock already covers it.
+    fn prune_key_clock(&mut self, key: &K) {
+        if let Ok(idx) = self.key_clocks.binary_search_by(|(k, _)| k.cmp(key)) {
+            if !self.contains_key(key) && self.elements.vclock.dominates(&self.key_clocks[idx].1) {
+                self.key_clocks.remove(idx);
+            }
+        }
     }
 
     /// Removes a key (and its value) from the map.
@@ -94,6 +278,11 @@ where
     /// # Arguments
     /// * `key` - The key to remove.
     pub fn remove(&mut self, key: &K) {
+        self.remove_pairs(key);
+        self.prune_key_clock(key);
+    }
+
+    fn remove_pairs(&mut self, key: &K) {
         let to_remove: Vec<_> = self
             .elements
             .iter()
@@ -118,9 +307,86 @@ where
     }
 
     /// Merges another OR-Map into this one.
+    ///
+    /// Each key's pairs are joined against the two key clocks, so a replica that has
+    /// never seen a key (e.g. the sender of a [`delta_for_key`](Self::delta_for_key) for
+    /// another key) cannot remove its pairs.
     pub fn merge(&mut self, other: &Self) {
-        self.elements.merge(&other.elements);
+        if other.is_identity() {
+            return;
+        }
+        let self_fallback = self.elements.vclock.clone();
+        let self_clocks = std::mem::take(&mut self.key_clocks);
+        let self_clock = |key: &K| scoped_clock(&self_clocks, &self_fallback, key);
+        let other_clock = |key: &K| scoped_clock(&other.key_clocks, &other.elements.vclock, key);
+
+        self.elements
+            .join_scoped(&other.elements, |(k, _)| self_clock(k), |(k, _)| other_clock(k));
+        self.elements.vclock.merge(&other.elements.vclock);
         self.vclock.merge(&other.vclock);
+
+        // Keys with a clock on either side, with both contexts joined. Keys with none
+        // keep using the joined map-wide clock.
+        let mut keys: Vec<&K> = self_clocks
+            .iter()
+            .chain(&other.key_clocks)
+            .map(|(k, _)| k)
+            .collect();
+        keys.sort_unstable();
+        keys.dedup();
+        self.key_clocks = keys
+            .into_iter()
+            .map(|key| {
+                let mut clock = self_clock(key).clone();
+                clock.merge(other_clock(key));
+                (key.clone(), clock)
+            })
+            .collect();
+    }
+
+    /// Returns the state of `key` that a peer whose clock for the key is `peer_clock`
+    /// is missing, or `None` if the peer has already seen every write to it.
+    ///
+    /// The delta holds the key's live pairs and its clock, and nothing about other keys:
+    /// its map-wide clocks are empty, so merging it advances only the receiver's clock
+    /// for `key`. It brings the receiver's copy of the key up to date, including removals
+    /// made with [`remove_by`](Self::remove_by). `peer_clock` is typically the peer's
+    /// [`key_clock`](Self::key_clock), or an empty clock if it has none. A key whose
+    /// clock was pruned is described by the map-wide clock.
+    pub fn delta_for_key(&self, key: &K, peer_clock: &VectorClock) -> Option<Self> {
+        let clock = scoped_clock(&self.key_clocks, &self.elements.vclock, key);
+        if clock
+            .clocks
+            .iter()
+            .all(|(node_id, (counter, _))| peer_clock.covers(node_id, *counter))
+        {
+            return None;
+        }
+
+        let mut delta = Self::new();
+        delta.elements.policy = self.elements.policy;
+        delta.elements.elements = self
+            .elements
+            .elements
+            .iter()
+            .filter(|((k, _), _)| k == key)
+            .cloned()
+            .collect();
+        delta.elements.removals = self
+            .elements
+            .removals
+            .iter()
+            .filter(|((k, _), _)| k == key)
+            .cloned()
+            .collect();
+        delta.key_clocks = vec![(key.clone(), clock.clone())];
+        Some(delta)
+    }
+
+    /// Compares the visible key/value pairs and the logical vclock counters, ignoring
+    /// observation dots and wall-clock timestamps.
+    pub fn semantically_eq(&self, other: &Self) -> bool {
+        self.elements.semantically_eq(&other.elements) && self.vclock.logically_eq(&other.vclock)
     }
 }
 
@@ -168,7 +434,37 @@ where
             VectorClock::new()
         };
 
-        Ok(ORMap { elements, vclock })
+        let key_clocks = if or_map.has_key_clocks() {
+            let list = or_map
+                .get_key_clocks()
+                .map_err(|e: capnp::Error| CrdtError::Deserialization(e.to_string()))?;
+            let mut key_clocks = Vec::with_capacity(list.len() as usize);
+            for entry in list.iter() {
+                let key_bytes = entry
+                    .get_key()
+                    .map_err(|e: capnp::Error| CrdtError::Deserialization(e.to_string()))?;
+                let key: K = serde_json::from_slice(key_bytes)
+                    .map_err(|e| CrdtError::Deserialization(e.to_string()))?;
+                let vc_bytes = entry
+                    .get_vclock()
+                    .map_err(|e: capnp::Error| CrdtError::Deserialization(e.to_string()))?;
+                let clock = VectorClock::merge_from_readers(&[
+                    crate::vector_clock::VectorClockReader::new(vc_bytes),
+                ])?;
+                key_clocks.push((key, clock));
+            }
+            key_clocks.sort_by(|(a, _), (b, _)| a.cmp(b));
+            key_clocks
+        } else {
+            Vec::new()
+        };
+
+        Ok(ORMap {
+            elements,
+            vclock,
+            key_clocks,
+            track_key_clocks: false,
+        })
     }
 }
 
@@ -177,6 +473,10 @@ where
     K: Clone + Eq + Hash + Serialize + DeserializeOwned + Default + Send + Sync + 'static + Ord,
     V: Clone + Eq + Hash + Serialize + DeserializeOwned + Default + Send + Sync + 'static + Ord,
 {
+    fn from_bytes(bytes: &'a [u8]) -> Self {
+        Self::new(bytes)
+    }
+
     fn is_empty(&self) -> Result<bool, CrdtError> {
         Ok(self.to_map()?.elements.is_empty())
     }
@@ -201,15 +501,49 @@ where
         Ok(result)
     }
 
-    fn to_capnp_bytes(&self) -> Vec<u8> {
+    fn is_identity(&self) -> bool {
+        *self == Self::default()
+    }
+
+    fn merge(&mut self, other: &Self) {
+        self.merge(other);
+    }
+
+    fn dominates(&self, other: &Self) -> bool {
+        self.vclock.dominates(&other.vclock) && merge_is_noop(self, other)
+    }
+
+    fn merge_bytes(&mut self, bytes: &[u8]) -> Result<(), CrdtError> {
+        let other = ORMapReader::new(bytes).to_map()?;
+        self.merge(&other);
+        Ok(())
+    }
+
+    fn write_to<W: Write>(&self, writer: &mut W) -> Result<(), CrdtError> {
         let mut message = Builder::new(HeapAllocator::new());
-        {
-            let mut or_map = message.init_root::<or_map_capnp::or_map::Builder>();
-            or_map.set_elements(&self.elements.to_capnp_bytes());
-            or_map.set_vclock(&self.vclock.to_capnp_bytes());
+        self.build_capnp(&mut message)?;
+        serialize::write_message(writer, &message)
+            .map_err(|e| CrdtError::Serialization(e.to_string()))
+    }
+
+    fn build_capnp(&self, message: &mut Builder<HeapAllocator>) -> Result<(), CrdtError> {
+        let mut or_map = message.init_root::<or_map_capnp::or_map::Builder>();
+        or_map.set_elements(&self.elements.to_capnp_bytes());
+        or_map.set_vclock(&self.vclock.to_capnp_bytes());
+        if !self.key_clocks.is_empty() {
+            let mut list = or_map.init_key_clocks(self.key_clocks.len() as u32);
+            for (idx, (key, clock)) in self.key_clocks.iter().enumerate() {
+                let mut entry = list.reborrow().get(idx as u32);
+                entry.set_key(&serde_json::to_vec(key).expect("Failed to serialize key"));
+                entry.set_vclock(&clock.to_capnp_bytes());
+            }
         }
+        Ok(())
+    }
+
+    fn to_capnp_bytes(&self) -> Vec<u8> {
         let mut buf = Vec::new();
-        serialize::write_message(&mut buf, &message).expect("ORMap serialization fail");
+        self.write_to(&mut buf).expect("ORMap serialization fail");
         buf
     }
 
@@ -1,26 +1,39 @@
 // Copyright (c) 2026 Adrian Robinson. All rights reserved.
 // Licensed under the MIT License. See LICENSE file in the project root for full license information.
 
+use crate::bloom::{self, BloomFilter};
+use crate::dot::Dot;
+use crate::enums::{ensure_uniform, ConflictPolicy};
+use crate::node_ids::{read_node_ids, resolve, NodeIdTable};
 use crate::orset_capnp;
-use crate::traits::{Crdt, CrdtError, CrdtReader};
+use crate::size_estimate::{
+    check_entry_budget, data_size, json_len, struct_size, text_size, MESSAGE_OVERHEAD, WORD,
+};
+use crate::text_element::{from_text, read_text};
+use crate::traits::{merge_is_noop, Crdt, CrdtError, CrdtReader};
+use crate::type_tag::{check_type_tag, type_tag};
 use crate::vector_clock::VectorClock;
 use capnp::message::{Builder, HeapAllocator, ReaderOptions};
 use capnp::serialize;
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 use std::hash::Hash;
+use std::io::Write;
 
 use serde::de::DeserializeOwned;
 
 /// OR-Set: An Observed-Remove Set CRDT.
 ///
 /// An OR-Set (Observed-Remove Set) allows both addition and removal of elements.
-/// It resolves conflicts by preferring additions over removals in the case of
+/// By default it resolves conflicts by preferring additions over removals in the case of
 /// concurrent operations (Add-Wins semantics).
 ///
 /// # Key Properties
 ///
 /// - **Add-Wins**: If an element is concurrently added and removed, the addition wins.
+/// - **Remove-Wins** (opt-in): With [`ConflictPolicy::RemoveWins`], removals made through
+///   [`ORSet::remove_by`] are recorded as remove dots, and a concurrent removal suppresses
+///   the element even if it was re-added at the same time.
 /// - **Unique Tags**: Each addition is tagged with a unique identifier (from the vector clock).
 /// - **Removal**: Removing an element removes all currently observed tags for that element.
 ///
@@ -42,22 +55,73 @@ use serde::de::DeserializeOwned;
 ///
 /// assert!(set.contains(&"apple".to_string()));
 /// ```
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+///
+/// # Dot Growth
+///
+/// Every `insert` adds a dot, so a hot element that is re-added constantly accumulates dots.
+/// [`ORSet::with_max_dots_per_element`] bounds this: once an element exceeds the limit, its
+/// older dots from each node are folded into the vector clock, keeping only the newest dot
+/// per node. The limit is local configuration and is neither serialized nor merged.
+///
+/// Local configuration like this lives in private fields, so `ORSet` is
+/// `#[non_exhaustive]` and cannot be built with a struct literal outside this crate. Start
+/// from [`ORSet::new`], `Default` or [`ORSet::with_max_dots_per_element`] and chain the
+/// `with_*` methods; the replicated fields stay public.
+///
+/// # Bloom Filter
+///
+/// For large sets queried mostly for absent elements, [`ORSet::with_bloom`] attaches a
+/// local bloom filter that lets `contains` return false without searching the elements.
+/// It is purely an optimization: positives are always confirmed against the elements, so
+/// results are identical with or without it. The filter is not serialized; it is kept up
+/// to date by `insert` and `merge` (but not by direct edits to `elements`).
+///
+/// [`ConflictPolicy::RemoveWins`]: crate::ConflictPolicy::RemoveWins
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(bound(
     serialize = "T: Serialize",
     deserialize = "T: DeserializeOwned + Eq + Hash + Ord"
 ))]
+#[non_exhaustive]
 pub struct ORSet<T: Eq + Hash + Ord> {
     /// List of (element, set of observations) pairs, sorted by element.
     #[serde(serialize_with = "serialize_elements", deserialize_with = "deserialize_elements")]
-    pub elements: Vec<(T, HashSet<(String, u64)>)>,
+    pub elements: Vec<(T, HashSet<Dot>)>,
     /// Vector clock representing the causal history of the set.
     #[serde(default)]
     pub vclock: VectorClock,
+    /// Resolution of concurrent add and remove.
+    #[serde(default, skip_serializing_if = "ConflictPolicy::is_default")]
+    pub policy: ConflictPolicy,
+    /// Remove dots per element, sorted by element. Always empty under add-wins.
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        serialize_with = "serialize_elements",
+        deserialize_with = "deserialize_elements"
+    )]
+    pub removals: Vec<(T, HashSet<Dot>)>,
+    /// Optional per-element dot limit that triggers compaction on `insert`. Local-only.
+    #[serde(skip)]
+    max_dots_per_element: Option<usize>,
+    /// Optional membership filter for fast negative lookups. Local-only.
+    #[serde(skip)]
+    bloom: Option<BloomFilter>,
+}
+
+impl<T: Eq + Hash + Ord> PartialEq for ORSet<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.elements == other.elements
+            && self.vclock == other.vclock
+            && self.policy == other.policy
+            && self.removals == other.removals
+    }
 }
 
+impl<T: Eq + Hash + Ord> Eq for ORSet<T> {}
+
 fn serialize_elements<S, T>(
-    elements: &Vec<(T, HashSet<(String, u64)>)>,
+    elements: &Vec<(T, HashSet<Dot>)>,
     serializer: S,
 ) -> Result<S::Ok, S::Error>
 where
@@ -69,7 +133,7 @@ where
     #[derive(Serialize)]
     struct EntryRef<'a, T> {
         element: &'a T,
-        observations: &'a HashSet<(String, u64)>,
+        observations: &'a HashSet<Dot>,
     }
 
     let mut seq = serializer.serialize_seq(Some(elements.len()))?;
@@ -82,7 +146,7 @@ where
     seq.end()
 }
 
-type ORSetEntry<T> = (T, HashSet<(String, u64)>);
+type ORSetEntry<T> = (T, HashSet<Dot>);
 
 fn deserialize_elements<'de, D, T>(deserializer: D) -> Result<Vec<ORSetEntry<T>>, D::Error>
 where
@@ -108,7 +172,7 @@ where
             #[derive(Deserialize)]
             struct Entry<T> {
                 element: T,
-                observations: HashSet<(String, u64)>,
+                observations: HashSet<Dot>,
             }
 
             let mut elements = Vec::with_capacity(seq.size_hint().unwrap_or(0));
@@ -129,6 +193,10 @@ impl<T: Eq + Hash + Ord> Default for ORSet<T> {
         Self {
             elements: Vec::new(),
             vclock: VectorClock::new(),
+            policy: ConflictPolicy::default(),
+            removals: Vec::new(),
+            max_dots_per_element: None,
+            bloom: None,
         }
     }
 }
@@ -138,6 +206,39 @@ impl<T: Eq + Hash + Ord> ORSet<T> {
     pub fn new() -> Self {
         Self::default()
     }
+
+    /// Creates a new, empty OR-Set that compacts an element's dots once it holds more than `max`.
+    ///
+    /// Compaction keeps only the newest dot per node, so an element observed by more than
+    /// `max` distinct nodes can still exceed the limit.
+    pub fn with_max_dots_per_element(max: usize) -> Self {
+        Self {
+            max_dots_per_element: Some(max),
+            ..Self::default()
+        }
+    }
+
+    /// Attaches a bloom filter sized for `expected` elements at false-positive rate
+    /// `fp_rate` (clamped to `[1e-9, 0.5]`), seeded with the current elements.
+    pub fn with_bloom(mut self, expected: usize, fp_rate: f64) -> Self {
+        let mut bloom = BloomFilter::new(expected, fp_rate);
+        for (element, _) in &self.elements {
+            bloom.insert(element);
+        }
+        self.bloom = Some(bloom);
+        self
+    }
+
+    /// Sets how concurrent add and remove are resolved.
+    pub fn with_policy(mut self, policy: ConflictPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Returns the total number of observation dots across all elements.
+    pub fn dot_count(&self) -> usize {
+        self.elements.iter().map(|(_, obs)| obs.len()).sum()
+    }
 }
 
 impl<T> ORSet<T>
@@ -150,16 +251,28 @@ where
     /// * `node_id` - The identifier of the node performing the addition.
     /// * `element` - The element to add.
     pub fn insert(&mut self, node_id: &str, element: T) {
-        self.vclock.increment(node_id);
-        let id = self.vclock.clocks.get(node_id).copied().unwrap_or((0, 0));
-        
+        // The dot is the fresh counter returned by the clock, as in `MVRegister::set`
+        let (counter, _) = self.vclock.increment(node_id);
+
+        // The add observes any remove dots for the element, so it wins over them
+        if let Ok(idx) = self.removals.binary_search_by(|(e, _)| e.cmp(&element)) {
+            self.removals.remove(idx);
+        }
+
         match self.elements.binary_search_by(|(e, _)| e.cmp(&element)) {
             Ok(idx) => {
-                self.elements[idx].1.insert((node_id.to_string(), id.0));
+                let obs = &mut self.elements[idx].1;
+                obs.insert(Dot::new(node_id, counter));
+                if self.max_dots_per_element.is_some_and(|max| obs.len() > max) {
+                    compact_dots(obs);
+                }
             }
             Err(idx) => {
+                if let Some(bloom) = &mut self.bloom {
+                    bloom.insert(&element);
+                }
                 let mut obs = HashSet::new();
-                obs.insert((node_id.to_string(), id.0));
+                obs.insert(Dot::new(node_id, counter));
                 self.elements.insert(idx, (element, obs));
             }
         }
@@ -175,11 +288,52 @@ where
         }
     }
 
+    /// Removes an element on behalf of `node_id`.
+    ///
+    /// Under [`ConflictPolicy::RemoveWins`] an observed element is replaced by a remove
+    /// dot, so the removal beats adds that are concurrent with it. Under add-wins this is
+    /// the same as [`ORSet::remove`].
+    ///
+    /// [`ConflictPolicy::RemoveWins`]: crate::ConflictPolicy::RemoveWins
+    pub fn remove_by(&mut self, node_id: &str, element: &T) {
+        if self.policy != ConflictPolicy::RemoveWins {
+            self.remove(element);
+            return;
+        }
+        let Ok(idx) = self.elements.binary_search_by(|(e, _)| e.cmp(element)) else {
+            return;
+        };
+        let (element, _) = self.elements.remove(idx);
+        let (counter, _) = self.vclock.increment(node_id);
+        let dots = HashSet::from([Dot::new(node_id, counter)]);
+        match self.removals.binary_search_by(|(e, _)| e.cmp(&element)) {
+            Ok(idx) => self.removals[idx].1 = dots,
+            Err(idx) => self.removals.insert(idx, (element, dots)),
+        }
+    }
+
     /// Returns true if the set contains the specified element.
     pub fn contains(&self, element: &T) -> bool {
+        if self.bloom.as_ref().is_some_and(|bloom| !bloom.might_contain(element)) {
+            return false;
+        }
         self.elements.binary_search_by(|(e, _)| e.cmp(element)).is_ok()
     }
 
+    /// Returns the distinct node ids among the element's live dots, sorted.
+    ///
+    /// These are the nodes whose adds keep the element in the set; empty if the element
+    /// is absent.
+    pub fn provenance(&self, element: &T) -> Vec<&str> {
+        let Ok(idx) = self.elements.binary_search_by(|(e, _)| e.cmp(element)) else {
+            return Vec::new();
+        };
+        let mut nodes: Vec<&str> = self.elements[idx].1.iter().map(|dot| dot.node_id.as_str()).collect();
+        nodes.sort_unstable();
+        nodes.dedup();
+        nodes
+    }
+
     /// Returns the number of elements in the set.
     pub fn len(&self) -> usize {
         self.elements.len()
@@ -195,114 +349,275 @@ where
         self.elements.iter().map(|(e, _)| e)
     }
 
+    /// Live elements present in both sets. A read-only query; neither set is modified.
+    pub fn intersection(&self, other: &Self) -> HashSet<T> {
+        self.iter().filter(|e| other.contains(e)).cloned().collect()
+    }
+
+    /// Elements in this set but not in `other`, e.g. what a peer is missing.
+    pub fn difference(&self, other: &Self) -> HashSet<T> {
+        self.iter().filter(|e| !other.contains(e)).cloned().collect()
+    }
+
+    /// Elements in exactly one of the two sets.
+    pub fn symmetric_difference(&self, other: &Self) -> HashSet<T> {
+        let mut result = self.difference(other);
+        result.extend(other.difference(self));
+        result
+    }
+
     /// Merges another OR-Set into this one.
     ///
     /// For each element, the merged set contains the union of the observed IDs,
     /// but only those that are not causally overshadowed by a removal.
     pub fn merge(&mut self, other: &Self) {
-        let mut new_elements = Vec::with_capacity(self.elements.len() + other.elements.len());
-        let mut i = 0;
-        let mut j = 0;
-
-        while i < self.elements.len() && j < other.elements.len() {
-            let (k1, v1) = &self.elements[i];
-            let (k2, v2) = &other.elements[j];
-
-            match k1.cmp(k2) {
-                std::cmp::Ordering::Less => {
-                    // Element only in self. Check if it was removed in other.
-                    let mut kept_ids = HashSet::new();
-                    for id in v1 {
-                        let other_version = other.vclock.clocks.get(&id.0).map(|(c, _)| *c).unwrap_or(0);
-                        // Keep the ID if the other replica hasn't observed this addition yet.
-                        // If the other replica *has* observed this addition (id.1 <= other_version)
-                        // but the element is missing from `other`, it implies `other` has removed it.
-                        if id.1 > other_version {
-                            kept_ids.insert(id.clone());
-                        }
-                    }
-                    if !kept_ids.is_empty() {
-                        new_elements.push((k1.clone(), kept_ids));
+        if other.is_identity() {
+            return;
+        }
+        let vclock = std::mem::take(&mut self.vclock);
+        self.join_scoped(other, |_| &vclock, |_| &other.vclock);
+        self.vclock = vclock;
+        self.vclock.merge(&other.vclock);
+    }
+
+    /// Joins the elements and removals of `other`, judging each element's dots against
+    /// the clock returned for it instead of the set-wide vclocks. The vclock itself is
+    /// left to the caller.
+    ///
+    /// `ORMap` uses this to give every key its own causal context.
+    pub(crate) fn join_scoped<'c>(
+        &mut self,
+        other: &Self,
+        self_clock: impl Fn(&T) -> &'c VectorClock,
+        other_clock: impl Fn(&T) -> &'c VectorClock,
+    ) {
+        self.elements = join_dots(&self.elements, &self_clock, &other.elements, &other_clock);
+        self.removals = join_dots(&self.removals, &self_clock, &other.removals, &other_clock);
+        self.policy = self.policy.join(other.policy);
+
+        if self.policy == ConflictPolicy::RemoveWins && !self.removals.is_empty() {
+            // Surviving remove dots were not observed by any add, so they suppress the element
+            let removals = &self.removals;
+            self.elements
+                .retain(|(e, _)| removals.binary_search_by(|(r, _)| r.cmp(e)).is_err());
+        }
+        bloom::refresh_after_merge(
+            &mut self.bloom,
+            other.bloom.as_ref(),
+            self.elements.iter().map(|(e, _)| e),
+        );
+    }
+
+    /// Keeps only the newest dot per node for every element (and remove entry).
+    ///
+    /// A node's older dots are redundant once its newer dot is held: any replica that has
+    /// observed the newer dot has observed the older ones, so merge treats them as seen
+    /// either way. Membership and merge results are unchanged; the serialized form shrinks
+    /// for elements that are re-added repeatedly.
+    pub fn compact(&mut self) {
+        for (_, obs) in self.elements.iter_mut().chain(self.removals.iter_mut()) {
+            compact_dots(obs);
+        }
+    }
+
+    /// Drops every dot added by `node_id`, and any element left without dots.
+    ///
+    /// This is a privileged, local administrative operation for purging a compromised or
+    /// decommissioned node. The vclock is kept, so replicas that have seen the same dots
+    /// treat the eviction like a removal; dots this replica had not yet observed are
+    /// unaffected and will still arrive on merge. Coordinate the eviction across all
+    /// replicas (and stop accepting the node's writes) for it to converge.
+    pub fn evict_node(&mut self, node_id: &str) {
+        for (_, obs) in self.elements.iter_mut() {
+            obs.retain(|dot| dot.node_id != node_id);
+        }
+        self.elements.retain(|(_, obs)| !obs.is_empty());
+        for (_, obs) in self.removals.iter_mut() {
+            obs.retain(|dot| dot.node_id != node_id);
+        }
+        self.removals.retain(|(_, obs)| !obs.is_empty());
+    }
+
+    /// Compares the live elements and the logical vclock counters, ignoring observation
+    /// dots and wall-clock timestamps.
+    pub fn semantically_eq(&self, other: &Self) -> bool {
+        self.iter().eq(other.iter()) && self.vclock.logically_eq(&other.vclock)
+    }
+}
+
+/// Joins two observed-remove dot maps.
+///
+/// A dot survives if both sides hold it, or if the side lacking it has not observed it
+/// yet; a dot the other side has observed but no longer holds was removed there.
+fn join_dots<'c, T: Clone + Ord>(
+    a: &[ORSetEntry<T>],
+    a_clock: impl Fn(&T) -> &'c VectorClock,
+    b: &[ORSetEntry<T>],
+    b_clock: impl Fn(&T) -> &'c VectorClock,
+) -> Vec<ORSetEntry<T>> {
+    let mut new_elements = Vec::with_capacity(a.len() + b.len());
+    let mut i = 0;
+    let mut j = 0;
+
+    while i < a.len() && j < b.len() {
+        let (k1, v1) = &a[i];
+        let (k2, v2) = &b[j];
+
+        match k1.cmp(k2) {
+            std::cmp::Ordering::Less => {
+                // Element only in `a`. Check if it was removed in `b`.
+                let b_vc = b_clock(k1);
+                let mut kept_ids = HashSet::new();
+                for id in v1 {
+                    // Keep the ID if the other replica hasn't observed this addition yet.
+                    // If the other replica *has* observed this addition but the element
+                    // is missing from `b`, it implies `b` has removed it.
+                    if !id.is_dominated_by(b_vc) {
+                        kept_ids.insert(id.clone());
                     }
-                    i += 1;
                 }
-                std::cmp::Ordering::Greater => {
-                    // Element only in other. Check if it was removed in self.
-                    let mut kept_ids = HashSet::new();
-                    for id in v2 {
-                        let self_version = self.vclock.clocks.get(&id.0).map(|(c, _)| *c).unwrap_or(0);
-                        if id.1 > self_version {
-                            kept_ids.insert(id.clone());
-                        }
-                    }
-                    if !kept_ids.is_empty() {
-                        new_elements.push((k2.clone(), kept_ids));
-                    }
-                    j += 1;
+                if !kept_ids.is_empty() {
+                    new_elements.push((k1.clone(), kept_ids));
                 }
-                std::cmp::Ordering::Equal => {
-                    // Element in both. Merge observations.
-                    let mut merged_ids = HashSet::new();
-                    
-                    // Process IDs from self
-                    for id in v1 {
-                        let other_version = other.vclock.clocks.get(&id.0).map(|(c, _)| *c).unwrap_or(0);
-                        if id.1 > other_version || v2.contains(id) {
-                            merged_ids.insert(id.clone());
-                        }
+                i += 1;
+            }
+            std::cmp::Ordering::Greater => {
+                // Element only in `b`. Check if it was removed in `a`.
+                let a_vc = a_clock(k2);
+                let mut kept_ids = HashSet::new();
+                for id in v2 {
+                    if !id.is_dominated_by(a_vc) {
+                        kept_ids.insert(id.clone());
                     }
-                    
-                    // Process IDs from other
-                    for id in v2 {
-                        let self_version = self.vclock.clocks.get(&id.0).map(|(c, _)| *c).unwrap_or(0);
-                        if id.1 > self_version || v1.contains(id) {
-                            merged_ids.insert(id.clone());
-                        }
+                }
+                if !kept_ids.is_empty() {
+                    new_elements.push((k2.clone(), kept_ids));
+                }
+                j += 1;
+            }
+            std::cmp::Ordering::Equal => {
+                // Element in both. Merge observations.
+                let (a_vc, b_vc) = (a_clock(k1), b_clock(k1));
+                let mut merged_ids = HashSet::new();
+                
+                // Process IDs from `a`
+                for id in v1 {
+                    if !id.is_dominated_by(b_vc) || v2.contains(id) {
+                        merged_ids.insert(id.clone());
                     }
-                    
-                    if !merged_ids.is_empty() {
-                        new_elements.push((k1.clone(), merged_ids));
+                }
+                
+                // Process IDs from `b`
+                for id in v2 {
+                    if !id.is_dominated_by(a_vc) || v1.contains(id) {
+                        merged_ids.insert(id.clone());
                     }
-                    i += 1;
-                    j += 1;
                 }
+                
+                if !merged_ids.is_empty() {
+                    new_elements.push((k1.clone(), merged_ids));
+                }
+                i += 1;
+                j += 1;
             }
         }
+    }
 
-        // Process remaining elements in self
-        while i < self.elements.len() {
-            let (k1, v1) = &self.elements[i];
-            let mut kept_ids = HashSet::new();
-            for id in v1 {
-                let other_version = other.vclock.clocks.get(&id.0).map(|(c, _)| *c).unwrap_or(0);
-                if id.1 > other_version {
-                    kept_ids.insert(id.clone());
-                }
+    // Process remaining elements in `a`
+    while i < a.len() {
+        let (k1, v1) = &a[i];
+        let b_vc = b_clock(k1);
+        let mut kept_ids = HashSet::new();
+        for id in v1 {
+            if !id.is_dominated_by(b_vc) {
+                kept_ids.insert(id.clone());
             }
-            if !kept_ids.is_empty() {
-                new_elements.push((k1.clone(), kept_ids));
-            }
-            i += 1;
         }
+        if !kept_ids.is_empty() {
+            new_elements.push((k1.clone(), kept_ids));
+        }
+        i += 1;
+    }
 
-        // Process remaining elements in other
-        while j < other.elements.len() {
-            let (k2, v2) = &other.elements[j];
-            let mut kept_ids = HashSet::new();
-            for id in v2 {
-                let self_version = self.vclock.clocks.get(&id.0).map(|(c, _)| *c).unwrap_or(0);
-                if id.1 > self_version {
-                    kept_ids.insert(id.clone());
-                }
+    // Process remaining elements in `b`
+    while j < b.len() {
+        let (k2, v2) = &b[j];
+        let a_vc = a_clock(k2);
+        let mut kept_ids = HashSet::new();
+        for id in v2 {
+            if !id.is_dominated_by(a_vc) {
+                kept_ids.insert(id.clone());
             }
-            if !kept_ids.is_empty() {
-                new_elements.push((k2.clone(), kept_ids));
-            }
-            j += 1;
         }
+        if !kept_ids.is_empty() {
+            new_elements.push((k2.clone(), kept_ids));
+        }
+        j += 1;
+    }
 
-        self.elements = new_elements;
-        self.vclock.merge(&other.vclock);
+    new_elements
+}
+
+/// Keeps only the newest dot per node.
+///
+/// Older dots from the same node are already covered by the vector clock, and merge
+/// drops any dot the other replica has seen but no longer holds, so removing them does
+/// not change the visible state or the merge outcome.
+fn compact_dots(obs: &mut HashSet<Dot>) {
+    let mut newest: std::collections::HashMap<&str, u64> = std::collections::HashMap::new();
+    for dot in obs.iter() {
+        let entry = newest.entry(dot.node_id.as_str()).or_insert(dot.counter);
+        *entry = (*entry).max(dot.counter);
+    }
+    let keep: HashSet<Dot> = newest
+        .into_iter()
+        .map(|(node, counter)| Dot::new(node, counter))
+        .collect();
+    *obs = keep;
+}
+
+// ============================================================================
+// Std Collection Conversions
+// ============================================================================
+
+/// Node identifier recorded in the vector clock for elements imported from std collections.
+const IMPORT_NODE_ID: &str = "import";
+
+impl<T> From<HashSet<T>> for ORSet<T>
+where
+    T: Clone + Eq + Hash + Ord + Serialize + DeserializeOwned + Default + Send + Sync + 'static,
+{
+    fn from(set: HashSet<T>) -> Self {
+        set.into_iter().collect()
+    }
+}
+
+impl<T> FromIterator<T> for ORSet<T>
+where
+    T: Clone + Eq + Hash + Ord + Serialize + DeserializeOwned + Default + Send + Sync + 'static,
+{
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut set = Self::new();
+        for element in iter {
+            set.insert(IMPORT_NODE_ID, element);
+        }
+        set
+    }
+}
+
+/// Consumes the set, yielding the live elements only (observations are dropped).
+impl<T: Eq + Hash + Ord> IntoIterator for ORSet<T> {
+    type Item = T;
+    type IntoIter = std::iter::Map<std::vec::IntoIter<ORSetEntry<T>>, fn(ORSetEntry<T>) -> T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.elements.into_iter().map(|(e, _)| e)
+    }
+}
+
+impl<T: Eq + Hash + Ord> From<ORSet<T>> for HashSet<T> {
+    fn from(set: ORSet<T>) -> Self {
+        set.into_iter().collect()
     }
 }
 
@@ -322,12 +637,102 @@ impl<'a, T> ORSetReader<'a, T> {
             _phantom: std::marker::PhantomData,
         }
     }
+
+    /// Number of element and removal entries plus the dots they carry, read from list
+    /// headers only.
+    ///
+    /// Dots count whether stored per element or in the `dotNodes`/`dotCounters` columns,
+    /// so a blob with few elements but a huge dot list is still caught.
+    pub fn entry_count(&self) -> Result<usize, CrdtError> {
+        let reader = serialize::read_message(self.bytes, ReaderOptions::new())
+            .map_err(|e| CrdtError::Deserialization(e.to_string()))?;
+        let orset = reader
+            .get_root::<orset_capnp::or_set::Reader>()
+            .map_err(|e| CrdtError::Deserialization(e.to_string()))?;
+        let mut total = Self::count_with_dots(
+            orset
+                .get_elements()
+                .map_err(|e| CrdtError::Deserialization(e.to_string()))?,
+        )?;
+        if orset.has_removals() {
+            total = total.saturating_add(Self::count_with_dots(
+                orset
+                    .get_removals()
+                    .map_err(|e| CrdtError::Deserialization(e.to_string()))?,
+            )?);
+        }
+        if orset.has_dot_nodes() || orset.has_dot_counters() {
+            let nodes = orset
+                .get_dot_nodes()
+                .map_err(|e| CrdtError::Deserialization(e.to_string()))?
+                .len();
+            let counters = orset
+                .get_dot_counters()
+                .map_err(|e| CrdtError::Deserialization(e.to_string()))?
+                .len();
+            total = total.saturating_add(nodes.max(counters) as usize);
+        }
+        Ok(total)
+    }
+
+    fn count_with_dots(
+        list: capnp::struct_list::Reader<'_, orset_capnp::or_set::element::Owned>,
+    ) -> Result<usize, CrdtError> {
+        let mut total = list.len() as usize;
+        for entry in list {
+            if entry.has_ids() {
+                let ids = entry
+                    .get_ids()
+                    .map_err(|e| CrdtError::Deserialization(e.to_string()))?;
+                total = total.saturating_add(ids.len() as usize);
+            }
+        }
+        Ok(total)
+    }
+}
+
+impl<'a, T: DeserializeOwned + Eq + 'static> ORSetReader<'a, T> {
+    /// Returns true if `element` is in the serialized set.
+    ///
+    /// Decodes element values one at a time until a match, without building the set or
+    /// reading any dots.
+    pub fn contains(&self, element: &T) -> Result<bool, CrdtError> {
+        let reader = serialize::read_message(self.bytes, ReaderOptions::new())
+            .map_err(|e| CrdtError::Deserialization(e.to_string()))?;
+        let orset = reader
+            .get_root::<orset_capnp::or_set::Reader>()
+            .map_err(|e| CrdtError::Deserialization(e.to_string()))?;
+        check_type_tag::<T>(orset.get_element_type())?;
+        let elements = orset
+            .get_elements()
+            .map_err(|e| CrdtError::Deserialization(e.to_string()))?;
+        for entry in elements {
+            let candidate: T = match entry
+                .which()
+                .map_err(|e| CrdtError::Deserialization(e.to_string()))?
+            {
+                orset_capnp::or_set::element::Element(bytes) => serde_json::from_slice(
+                    bytes.map_err(|e| CrdtError::Deserialization(e.to_string()))?,
+                )
+                .map_err(|e| CrdtError::Deserialization(e.to_string()))?,
+                orset_capnp::or_set::element::Text(text) => from_text(read_text(text)?)?,
+            };
+            if candidate == *element {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
 }
 
 impl<'a, T> CrdtReader<'a> for ORSetReader<'a, T>
 where
     T: DeserializeOwned + Eq + Hash + Ord + Send + Sync,
 {
+    fn from_bytes(bytes: &'a [u8]) -> Self {
+        Self::new(bytes)
+    }
+
     fn is_empty(&self) -> Result<bool, CrdtError> {
         let reader = serialize::read_message(self.bytes, ReaderOptions::new())
             .map_err(|e| CrdtError::Deserialization(e.to_string()))?;
@@ -345,6 +750,231 @@ where
 // CRDT Trait Implementation
 // ============================================================================
 
+impl<T> ORSet<T>
+where
+    T: Clone + Eq + Hash + Ord + Serialize + DeserializeOwned + Default + Send + Sync + 'static,
+{
+    /// [`merge_from_readers`](Crdt::merge_from_readers) with an entry budget; see
+    /// [bounded merges](Crdt#bounded-merges).
+    ///
+    /// Each element and each of its dots counts as an entry.
+    pub fn merge_from_readers_bounded(
+        readers: &[ORSetReader<'_, T>],
+        max_entries: usize,
+    ) -> Result<Self, CrdtError> {
+        check_entry_budget(readers.iter().map(|r| r.entry_count()), max_entries)?;
+        Self::merge_from_readers(readers)
+    }
+}
+
+impl<T> ORSet<T>
+where
+    T: Clone + Eq + Hash + Ord + Serialize + DeserializeOwned + Default + Send + Sync + 'static,
+{
+    /// Serializes elements as plain UTF-8 `Text` ([plain-text encoding](crate#plain-text-encoding)).
+    pub fn to_capnp_text_bytes(&self) -> Vec<u8>
+    where
+        T: AsRef<str>,
+    {
+        let mut buf = Vec::new();
+        self.write_capnp(Some(<T as AsRef<str>>::as_ref), false, &mut buf)
+            .expect("ORSet serialization fail");
+        buf
+    }
+
+    /// Serializes with each distinct node id stored once, in a `nodeIds` dictionary, and
+    /// the dots stored column-wise as dictionary indices and counters.
+    ///
+    /// Much smaller when many dots share a few writers, but readers before the dictionary
+    /// was introduced decode such a blob as elements without dots, so only send it once
+    /// every consumer has upgraded. `to_capnp_bytes` keeps the per-dot layout.
+    pub fn to_capnp_interned_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        self.write_capnp(None, true, &mut buf)
+            .expect("ORSet serialization fail");
+        buf
+    }
+
+    fn write_capnp<W: Write>(
+        &self,
+        as_text: Option<fn(&T) -> &str>,
+        intern_node_ids: bool,
+        writer: &mut W,
+    ) -> capnp::Result<()> {
+        let mut message = Builder::new(HeapAllocator::new());
+        self.build_message(as_text, intern_node_ids, &mut message)?;
+        serialize::write_message(writer, &message)
+    }
+
+    fn build_message(
+        &self,
+        as_text: Option<fn(&T) -> &str>,
+        intern_node_ids: bool,
+        message: &mut Builder<HeapAllocator>,
+    ) -> capnp::Result<()> {
+        let mut orset = message.init_root::<orset_capnp::or_set::Builder>();
+        let elements = orset.reborrow().init_elements(self.elements.len() as u32);
+        write_entries(elements, &self.elements, as_text, intern_node_ids);
+        if intern_node_ids {
+            self.write_dot_columns(orset.reborrow());
+        }
+        
+        let vclock_bytes = self.vclock.to_capnp_bytes();
+        orset.set_vclock(&vclock_bytes);
+        orset.set_element_type(type_tag::<T>());
+        orset.set_remove_wins(self.policy == ConflictPolicy::RemoveWins);
+        if !self.removals.is_empty() {
+            let removals = orset.reborrow().init_removals(self.removals.len() as u32);
+            write_entries(removals, &self.removals, as_text, intern_node_ids);
+        }
+        Ok(())
+    }
+
+    fn write_dot_columns(&self, mut orset: orset_capnp::or_set::Builder<'_>) {
+        // Dots go in the columns in the same order `write_entries` counts them
+        let mut table = NodeIdTable::new();
+        let (dot_nodes, dot_counters): (Vec<u32>, Vec<u64>) = self
+            .elements
+            .iter()
+            .chain(&self.removals)
+            .flat_map(|(_, obs)| obs)
+            .map(|dot| (table.intern(&dot.node_id), dot.counter))
+            .unzip();
+        table.write(orset.reborrow().init_node_ids(table.len() as u32));
+        let mut nodes = orset.reborrow().init_dot_nodes(dot_nodes.len() as u32);
+        for (idx, node) in dot_nodes.into_iter().enumerate() {
+            nodes.set(idx as u32, node);
+        }
+        let mut counters = orset.reborrow().init_dot_counters(dot_counters.len() as u32);
+        for (idx, counter) in dot_counters.into_iter().enumerate() {
+            counters.set(idx as u32, counter);
+        }
+    }
+}
+
+fn write_entries<T: Serialize>(
+    mut list: capnp::struct_list::Builder<'_, orset_capnp::or_set::element::Owned>,
+    entries: &[ORSetEntry<T>],
+    as_text: Option<fn(&T) -> &str>,
+    intern_node_ids: bool,
+) {
+    for (i, (element, obs)) in entries.iter().enumerate() {
+        let mut element_entry = list.reborrow().get(i as u32);
+        match as_text {
+            Some(as_text) => element_entry.set_text(as_text(element).into()),
+            None => {
+                let element_bytes =
+                    serde_json::to_vec(element).expect("Failed to serialize element");
+                element_entry.set_element(&element_bytes);
+            }
+        }
+
+        if intern_node_ids {
+            element_entry.set_dot_count(obs.len() as u32);
+            continue;
+        }
+        let mut ids = element_entry.init_ids(obs.len() as u32);
+        for (j, dot) in obs.iter().enumerate() {
+            let mut id_entry = ids.reborrow().get(j as u32);
+            id_entry.set_node_id(dot.node_id.as_str().into());
+            id_entry.set_counter(dot.counter);
+        }
+    }
+}
+
+/// Cursor over the column-wise dots of a message with a node-id dictionary.
+struct DotColumns<'a> {
+    node_ids: Vec<&'a str>,
+    nodes: capnp::primitive_list::Reader<'a, u32>,
+    counters: capnp::primitive_list::Reader<'a, u64>,
+    next: u32,
+}
+
+impl<'a> DotColumns<'a> {
+    fn new(orset: orset_capnp::or_set::Reader<'a>) -> Result<Option<Self>, CrdtError> {
+        if !orset.has_node_ids() {
+            return Ok(None);
+        }
+        Ok(Some(Self {
+            node_ids: read_node_ids(
+                orset
+                    .get_node_ids()
+                    .map_err(|e| CrdtError::Deserialization(e.to_string()))?,
+            )?,
+            nodes: orset
+                .get_dot_nodes()
+                .map_err(|e| CrdtError::Deserialization(e.to_string()))?,
+            counters: orset
+                .get_dot_counters()
+                .map_err(|e| CrdtError::Deserialization(e.to_string()))?,
+            next: 0,
+        }))
+    }
+
+    /// Takes the next `count` dots.
+    fn take(&mut self, count: u32) -> Result<HashSet<Dot>, CrdtError> {
+        let end = self.next.saturating_add(count);
+        if end > self.nodes.len() || end > self.counters.len() {
+            return Err(CrdtError::Deserialization(
+                "ORSet dot columns are shorter than the element dot counts".to_string(),
+            ));
+        }
+        let dots = (self.next..end)
+            .map(|idx| {
+                let node_id = resolve(&self.node_ids, self.nodes.get(idx))?;
+                Ok(Dot::new(node_id, self.counters.get(idx)))
+            })
+            .collect();
+        self.next = end;
+        dots
+    }
+}
+
+fn read_entries<T: DeserializeOwned + Ord>(
+    list: capnp::struct_list::Reader<'_, orset_capnp::or_set::element::Owned>,
+    mut columns: Option<&mut DotColumns<'_>>,
+) -> Result<Vec<ORSetEntry<T>>, CrdtError> {
+    let mut entries = Vec::with_capacity(list.len() as usize);
+    for element_entry in list {
+        let element: T = match element_entry
+            .which()
+            .map_err(|e| CrdtError::Deserialization(e.to_string()))?
+        {
+            orset_capnp::or_set::element::Element(element_bytes) => {
+                let element_bytes = element_bytes
+                    .map_err(|e| CrdtError::Deserialization(e.to_string()))?;
+                serde_json::from_slice(element_bytes)
+                    .map_err(|e| CrdtError::Deserialization(e.to_string()))?
+            }
+            orset_capnp::or_set::element::Text(text) => from_text(read_text(text)?)?,
+        };
+
+        if let Some(columns) = columns.as_deref_mut() {
+            entries.push((element, columns.take(element_entry.get_dot_count())?));
+            continue;
+        }
+
+        let mut obs = HashSet::new();
+        let ids = element_entry
+            .get_ids()
+            .map_err(|e| CrdtError::Deserialization(e.to_string()))?;
+
+        for id_entry in ids {
+            let node_id = id_entry
+                .get_node_id()
+                .map_err(|e| CrdtError::Deserialization(e.to_string()))?
+                .to_string()
+                .map_err(|e| CrdtError::Deserialization(e.to_string()))?;
+            let counter = id_entry.get_counter();
+            obs.insert(Dot::new(node_id, counter));
+        }
+        entries.push((element, obs));
+    }
+    // Ensure sorted invariant
+    entries.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(entries)
+}
+
 impl<T> Crdt for ORSet<T>
 where
     T: Clone + Eq + Hash + Ord + Serialize + DeserializeOwned + Default + Send + Sync + 'static,
@@ -353,12 +983,14 @@ where
 
     fn merge_from_readers(readers: &[Self::Reader<'_>]) -> Result<Self, CrdtError> {
         let mut result = ORSet::new();
+        let mut policy = None;
         for reader in readers {
             let msg_reader = serialize::read_message(reader.bytes, ReaderOptions::new())
                 .map_err(|e| CrdtError::Deserialization(e.to_string()))?;
             let orset_reader = msg_reader
                 .get_root::<orset_capnp::or_set::Reader>()
                 .map_err(|e| CrdtError::Deserialization(e.to_string()))?;
+            check_type_tag::<T>(orset_reader.get_element_type())?;
 
             // Deserialize to temp ORSet
             let mut temp_set = ORSet::new();
@@ -375,70 +1007,106 @@ where
             let elements_reader = orset_reader
                 .get_elements()
                 .map_err(|e| CrdtError::Deserialization(e.to_string()))?;
+            let mut columns = DotColumns::new(orset_reader)?;
+            temp_set.elements = read_entries(elements_reader, columns.as_mut())?;
 
-            for element_entry in elements_reader {
-                let element_bytes = element_entry
-                    .get_element()
-                    .map_err(|e| CrdtError::Deserialization(e.to_string()))?;
-                let element: T = serde_json::from_slice(element_bytes)
-                    .map_err(|e| CrdtError::Deserialization(e.to_string()))?;
-                
-                let mut obs = HashSet::new();
-                let ids = element_entry
-                    .get_ids()
+            if orset_reader.get_remove_wins() {
+                temp_set.policy = ConflictPolicy::RemoveWins;
+            }
+            if orset_reader.has_removals() {
+                let removals_reader = orset_reader
+                    .get_removals()
                     .map_err(|e| CrdtError::Deserialization(e.to_string()))?;
-                
-                for id_entry in ids {
-                    let node_id = id_entry
-                        .get_node_id()
-                        .map_err(|e| CrdtError::Deserialization(e.to_string()))?
-                        .to_string()
-                        .map_err(|e| CrdtError::Deserialization(e.to_string()))?;
-                    let counter = id_entry.get_counter();
-                    obs.insert((node_id, counter));
-                }
-                temp_set.elements.push((element, obs));
+                temp_set.removals = read_entries(removals_reader, columns.as_mut())?;
             }
-            // Ensure sorted invariant
-            temp_set.elements.sort_by(|a, b| a.0.cmp(&b.0));
+
+            let first = *policy.get_or_insert(temp_set.policy);
+            ensure_uniform("ORSet conflict policies", [first, temp_set.policy])?;
 
             result.merge(&temp_set);
         }
         Ok(result)
     }
 
+    fn is_identity(&self) -> bool {
+        *self == Self::default()
+    }
+
+    fn merge(&mut self, other: &Self) {
+        self.merge(other);
+    }
+
+    fn dominates(&self, other: &Self) -> bool {
+        self.vclock.dominates(&other.vclock) && merge_is_noop(self, other)
+    }
+
+    fn merge_bytes(&mut self, bytes: &[u8]) -> Result<(), CrdtError> {
+        let other = Self::from_capnp_bytes(bytes)?;
+        self.merge(&other);
+        Ok(())
+    }
+
+    fn ensure_mergeable(&self, other: &Self) -> Result<(), CrdtError> {
+        ensure_uniform("ORSet conflict policies", [self.policy, other.policy])
+    }
+
+    fn write_to<W: Write>(&self, writer: &mut W) -> Result<(), CrdtError> {
+        self.write_capnp(None, false, writer)
+            .map_err(|e| CrdtError::Serialization(e.to_string()))
+    }
+
+    fn build_capnp(&self, message: &mut Builder<HeapAllocator>) -> Result<(), CrdtError> {
+        self.build_message(None, false, message)
+            .map_err(|e| CrdtError::Serialization(e.to_string()))
+    }
+
     fn to_capnp_bytes(&self) -> Vec<u8> {
-        let mut message = Builder::new(HeapAllocator::new());
-        {
-            let mut orset = message.init_root::<orset_capnp::or_set::Builder>();
-            let mut elements = orset.reborrow().init_elements(self.elements.len() as u32);
-            
-            for (i, (element, obs)) in self.elements.iter().enumerate() {
-                let mut element_entry = elements.reborrow().get(i as u32);
-                let element_bytes = serde_json::to_vec(element).expect("Failed to serialize element");
-                element_entry.set_element(&element_bytes);
-                
-                let mut ids = element_entry.init_ids(obs.len() as u32);
-                for (j, (node_id, counter)) in obs.iter().enumerate() {
-                    let mut id_entry = ids.reborrow().get(j as u32);
-                    id_entry.set_node_id(node_id.as_str().into());
-                    id_entry.set_counter(*counter);
-                }
-            }
-            
-            let vclock_bytes = self.vclock.to_capnp_bytes();
-            orset.set_vclock(&vclock_bytes);
-        }
         let mut buf = Vec::new();
-        serialize::write_message(&mut buf, &message).expect("ORSet serialization fail");
+        self.write_capnp(None, false, &mut buf).expect("ORSet serialization fail");
         buf
     }
 
+    fn estimated_capnp_size(&self) -> usize {
+        let entries_size = |entries: &[ORSetEntry<T>]| {
+            WORD + entries
+                .iter()
+                .map(|(element, obs)| {
+                    // One data word holds the element/text union discriminant and dot count
+                    struct_size(1, 2)
+                        + data_size(json_len(element))
+                        + WORD
+                        + obs
+                            .iter()
+                            .map(|dot| struct_size(1, 1) + text_size(dot.node_id.len()))
+                            .sum::<usize>()
+                })
+                .sum::<usize>()
+        };
+        let removals_size = if self.removals.is_empty() {
+            0
+        } else {
+            entries_size(&self.removals)
+        };
+
+        MESSAGE_OVERHEAD
+            + struct_size(2, 6)
+            + entries_size(&self.elements)
+            + removals_size
+            + data_size(self.vclock.estimated_capnp_size())
+    }
+
     fn is_empty(&self) -> bool {
         self.elements.is_empty()
     }
 
     fn validate(&self) -> Result<(), CrdtError> {
+        // Every dot must be summarized by the vclock; a dot beyond it indicates corruption
+        let dots = self.elements.iter().chain(&self.removals).flat_map(|(_, obs)| obs);
+        for dot in dots {
+            if !dot.is_dominated_by(&self.vclock) {
+                return Err(CrdtError::Validation(format!("Dot {} exceeds vclock", dot)));
+            }
+        }
         Ok(())
     }
 }
@@ -1,13 +1,15 @@
 // Copyright (c) 2026 Adrian Robinson. All rights reserved.
 // Licensed under the MIT License. See LICENSE file in the project root for full license information.
 
-use crate::g_counter::GCounter;
+use crate::g_counter::{saturate_i64, GCounter};
 use crate::pncounter_capnp;
-use crate::traits::{Crdt, CrdtError, CrdtReader};
+use crate::traits::{merge_is_noop, Crdt, CrdtError, CrdtReader};
 use crate::vector_clock::VectorClock;
 use capnp::message::{Builder, HeapAllocator, ReaderOptions};
 use capnp::serialize;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Write;
 
 /// PN-Counter: A Positive-Negative Counter CRDT.
 ///
@@ -40,9 +42,11 @@ use serde::{Deserialize, Serialize};
 /// ```
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub struct PNCounter {
-    /// Internal G-Counter for positive increments.
+    /// Internal G-Counter for positive increments. Prefer [`PNCounter::positive_value`]
+    /// for reads.
     pub positive: GCounter,
-    /// Internal G-Counter for negative decrements.
+    /// Internal G-Counter for negative decrements, stored as positive magnitudes. Prefer
+    /// [`PNCounter::negative_value`] for reads.
     pub negative: GCounter,
     /// Vector clock for tracking causal history.
     #[serde(default)]
@@ -55,10 +59,33 @@ impl PNCounter {
         Self::default()
     }
 
+    /// Builds a counter from existing per-node increment and decrement totals.
+    ///
+    /// Both maps hold non-negative magnitudes, as in [`GCounter::from_node_counts`]. Each
+    /// node's vclock entry is advanced once.
+    ///
+    /// # Errors
+    /// Returns `CrdtError::InvalidInput` if any count is negative.
+    pub fn from_node_counts(
+        positive: HashMap<String, i64>,
+        negative: HashMap<String, i64>,
+    ) -> Result<Self, CrdtError> {
+        let mut vclock = VectorClock::new();
+        for node_id in positive.keys().chain(negative.keys().filter(|k| !positive.contains_key(*k))) {
+            vclock.increment(node_id);
+        }
+        Ok(Self {
+            positive: GCounter::from_node_counts(positive)?,
+            negative: GCounter::from_node_counts(negative)?,
+            vclock,
+        })
+    }
+
     /// Increments the counter for a specific node.
     pub fn increment(&mut self, node_id: &str, delta: i64) {
         if delta < 0 {
-            self.decrement(node_id, -delta);
+            // Saturate so that i64::MIN cannot overflow on negation
+            self.decrement(node_id, delta.saturating_neg());
             return;
         }
         self.positive.increment(node_id, delta);
@@ -68,20 +95,49 @@ impl PNCounter {
     /// Decrements the counter for a specific node.
     pub fn decrement(&mut self, node_id: &str, delta: i64) {
         if delta < 0 {
-            self.increment(node_id, -delta);
+            self.increment(node_id, delta.saturating_neg());
             return;
         }
         self.negative.increment(node_id, delta);
         self.vclock.increment(node_id);
     }
 
+    /// Applies a batched net change for a node as a single update.
+    ///
+    /// A positive `net` is recorded as an increment and a negative one as a decrement, so
+    /// a batch of `+10` and `-3` costs one vclock tick as `apply_delta(node, 7)` instead of
+    /// two. A zero `net` is a no-op and does not advance the vclock.
+    pub fn apply_delta(&mut self, node_id: &str, net: i64) {
+        if net != 0 {
+            self.increment(node_id, net);
+        }
+    }
+
     /// Returns the current aggregated value (positive sum - negative sum).
+    ///
+    /// Both sides are accumulated in `i128` and the result saturates at the `i64` bounds.
     pub fn value(&self) -> i64 {
-        self.positive.value() - self.negative.value()
+        saturate_i64(self.positive.value_i128() - self.negative.value_i128())
+    }
+
+    /// Returns the total of all increments (the `positive` G-Counter's value).
+    pub fn positive_value(&self) -> i64 {
+        self.positive.value()
+    }
+
+    /// Returns the total of all decrements as a non-negative magnitude (the `negative`
+    /// G-Counter's value).
+    pub fn negative_value(&self) -> i64 {
+        self.negative.value()
     }
 
     /// Merges another PN-Counter into this one.
+    ///
+    /// Both halves take the per-node max, independent of the vclocks.
     pub fn merge(&mut self, other: &Self) {
+        if other.is_identity() {
+            return;
+        }
         self.positive.merge(&other.positive);
         self.negative.merge(&other.negative);
         self.vclock.merge(&other.vclock);
@@ -96,6 +152,15 @@ impl PNCounter {
             self.decrement(node_id, *count);
         }
     }
+
+    /// Zeroes a node's increments and decrements.
+    ///
+    /// Privileged and local-only, as with [`GCounter::evict_node`]: every replica must
+    /// evict the node, otherwise its contribution returns on merge.
+    pub fn evict_node(&mut self, node_id: &str) {
+        self.positive.evict_node(node_id);
+        self.negative.evict_node(node_id);
+    }
 }
 
 // ============================================================================
@@ -150,6 +215,10 @@ impl<'a> PNCounterReader<'a> {
 }
 
 impl<'a> CrdtReader<'a> for PNCounterReader<'a> {
+    fn from_bytes(bytes: &'a [u8]) -> Self {
+        Self::new(bytes)
+    }
+
     fn is_empty(&self) -> Result<bool, CrdtError> {
         let pn = self.to_pncounter()?;
         Ok(pn.positive.is_empty() && pn.negative.is_empty())
@@ -171,16 +240,42 @@ impl Crdt for PNCounter {
         Ok(result)
     }
 
-    fn to_capnp_bytes(&self) -> Vec<u8> {
+    fn is_identity(&self) -> bool {
+        *self == Self::default()
+    }
+
+    fn merge(&mut self, other: &Self) {
+        self.merge(other);
+    }
+
+    fn dominates(&self, other: &Self) -> bool {
+        self.vclock.dominates(&other.vclock) && merge_is_noop(self, other)
+    }
+
+    fn merge_bytes(&mut self, bytes: &[u8]) -> Result<(), CrdtError> {
+        let other = PNCounterReader::new(bytes).to_pncounter()?;
+        self.merge(&other);
+        Ok(())
+    }
+
+    fn write_to<W: Write>(&self, writer: &mut W) -> Result<(), CrdtError> {
         let mut message = Builder::new(HeapAllocator::new());
-        {
-            let mut pn_counter = message.init_root::<pncounter_capnp::pn_counter::Builder>();
-            pn_counter.set_positive(&self.positive.to_capnp_bytes());
-            pn_counter.set_negative(&self.negative.to_capnp_bytes());
-            pn_counter.set_vclock(&self.vclock.to_capnp_bytes());
-        }
+        self.build_capnp(&mut message)?;
+        serialize::write_message(writer, &message)
+            .map_err(|e| CrdtError::Serialization(e.to_string()))
+    }
+
+    fn build_capnp(&self, message: &mut Builder<HeapAllocator>) -> Result<(), CrdtError> {
+        let mut pn_counter = message.init_root::<pncounter_capnp::pn_counter::Builder>();
+        pn_counter.set_positive(&self.positive.to_capnp_bytes());
+        pn_counter.set_negative(&self.negative.to_capnp_bytes());
+        pn_counter.set_vclock(&self.vclock.to_capnp_bytes());
+        Ok(())
+    }
+
+    fn to_capnp_bytes(&self) -> Vec<u8> {
         let mut buf = Vec::new();
-        serialize::write_message(&mut buf, &message).expect("PNCounter serialization fail");
+        self.write_to(&mut buf).expect("PNCounter serialization fail");
         buf
     }
 
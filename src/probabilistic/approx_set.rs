@@ -0,0 +1,129 @@
+// Copyright (c) 2026 Adrian Robinson. All rights reserved.
+// Licensed under the MIT License. See LICENSE file in the project root for full license information.
+
+use crate::or_set::ORSet;
+use crate::probabilistic::HyperLogLog;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::hash::Hash;
+
+/// ApproxSet - Approximate Set for Very Large Cardinalities
+///
+/// Fuses a [`HyperLogLog`] that counts every element ever added with a small [`ORSet`]
+/// holding only the most recently added elements. Memory stays bounded regardless of how
+/// many elements are added.
+///
+/// # Key Properties
+///
+/// - **Cardinality**: `cardinality()` is the HyperLogLog estimate over all elements (~0.81% error).
+/// - **Membership**: `contains()` is exact only for the recent window. Older elements are
+///   reported as absent even though they are still counted.
+/// - **Window**: At least the `window` most recently added elements are retained. The exact
+///   set is trimmed back to `window` once it grows past twice that size; elements are ranked
+///   by their newest dot counter, with ties broken by element order.
+/// - **Mergeable**: Merging unions both halves. Evicted elements propagate like removals.
+///
+/// # Example
+///
+/// ```
+/// use crdt_data_types::probabilistic::ApproxSet;
+///
+/// let mut set = ApproxSet::new(2);
+/// set.insert("node_a", "apple".to_string());
+/// set.insert("node_a", "banana".to_string());
+///
+/// assert!(set.contains(&"banana".to_string()));
+/// assert_eq!(set.cardinality(), 2);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "T: Serialize",
+    deserialize = "T: DeserializeOwned + Eq + Hash + Ord"
+))]
+pub struct ApproxSet<T: Eq + Hash + Ord> {
+    /// Cardinality estimate over every element ever added.
+    hll: HyperLogLog,
+    /// Exact membership for the recent window.
+    recent: ORSet<T>,
+    /// Minimum number of recent elements kept in `recent`.
+    window: usize,
+}
+
+impl<T> ApproxSet<T>
+where
+    T: Clone + Eq + Hash + Ord + Serialize + DeserializeOwned + Default + Send + Sync + 'static,
+{
+    /// Creates an empty set that tracks exact membership for the `window` most recent elements.
+    pub fn new(window: usize) -> Self {
+        Self {
+            hll: HyperLogLog::new(),
+            recent: ORSet::new(),
+            window,
+        }
+    }
+
+    /// Adds an element, counting it in the estimate and recording it in the recent window.
+    pub fn insert(&mut self, node_id: &str, element: T) {
+        self.hll.add(&element);
+        self.recent.insert(node_id, element);
+        self.trim();
+    }
+
+    /// Estimated number of distinct elements ever added.
+    pub fn cardinality(&self) -> u64 {
+        self.hll.cardinality()
+    }
+
+    /// Returns true if the element is in the recent window.
+    ///
+    /// Elements that have been evicted from the window return `false`.
+    pub fn contains(&self, element: &T) -> bool {
+        self.recent.contains(element)
+    }
+
+    /// Iterator over the elements currently held in the recent window.
+    pub fn recent(&self) -> impl Iterator<Item = &T> {
+        self.recent.iter()
+    }
+
+    /// Number of recent elements retained.
+    pub fn window(&self) -> usize {
+        self.window
+    }
+
+    /// Merges another ApproxSet into this one.
+    ///
+    /// The larger of the two windows is kept.
+    pub fn merge(&mut self, other: &Self) {
+        self.hll.merge(&other.hll);
+        self.recent.merge(&other.recent);
+        self.window = self.window.max(other.window);
+        self.trim();
+    }
+
+    /// Evicts the oldest recent elements once the window has doubled.
+    ///
+    /// Trimming in bulk keeps inserts amortized rather than re-ranking on every call.
+    fn trim(&mut self) {
+        if self.recent.len() <= self.window.saturating_mul(2) {
+            return;
+        }
+
+        let mut ranked: Vec<(u64, usize)> = self
+            .recent
+            .elements
+            .iter()
+            .enumerate()
+            .map(|(idx, (_, obs))| (obs.iter().map(|dot| dot.counter).max().unwrap_or(0), idx))
+            .collect();
+        // Newest first; the stable sort keeps element order among equal counters
+        ranked.sort_by_key(|&(counter, _)| std::cmp::Reverse(counter));
+
+        let mut keep = vec![false; ranked.len()];
+        for (_, idx) in ranked.into_iter().take(self.window) {
+            keep[idx] = true;
+        }
+        let mut flags = keep.into_iter();
+        self.recent.elements.retain(|_| flags.next().unwrap_or(false));
+    }
+}
@@ -3,11 +3,78 @@
 
 use crate::count_min_sketch_capnp;
 use crate::traits::{Crdt, CrdtError, CrdtReader};
-use capnp::message::{Builder, ReaderOptions};
+use capnp::message::{Builder, HeapAllocator, ReaderOptions};
 use capnp::serialize;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use std::collections::hash_map::DefaultHasher;
+use std::fmt::Debug;
 use std::hash::{Hash, Hasher};
+use std::io::BufRead;
+
+/// Integer type of a [`CountMinSketch`]'s counters.
+///
+/// Implemented for `u16`, `u32` and `u64`. Narrower counters cut the sketch's memory at
+/// the cost of saturating sooner; all arithmetic saturates at `MAX`.
+pub trait Counter:
+    Copy + Default + Ord + Debug + Hash + Serialize + DeserializeOwned + Send + Sync + 'static + sealed::Sealed
+{
+    /// Width in bits, recorded in the serialized form.
+    const BITS: u8;
+    const MAX: Self;
+
+    fn saturating_add(self, other: Self) -> Self;
+    fn to_u64(self) -> u64;
+    /// Converts `value`, clamping it to `MAX`.
+    fn from_u64_saturating(value: u64) -> Self;
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+macro_rules! counter {
+    ($($ty:ty),*) => {$(
+        impl sealed::Sealed for $ty {}
+        impl Counter for $ty {
+            const BITS: u8 = <$ty>::BITS as u8;
+            const MAX: Self = <$ty>::MAX;
+
+            fn saturating_add(self, other: Self) -> Self {
+                <$ty>::saturating_add(self, other)
+            }
+
+            fn to_u64(self) -> u64 {
+                self as u64
+            }
+
+            fn from_u64_saturating(value: u64) -> Self {
+                <$ty>::try_from(value).unwrap_or(<$ty>::MAX)
+            }
+        }
+    )*};
+}
+
+counter!(u16, u32, u64);
+
+/// Reads the counter width tag; messages written before it was added hold `u64` counters.
+fn counter_bits(root: count_min_sketch_capnp::count_min_sketch::Reader<'_>) -> u8 {
+    match root.get_counter_bits() {
+        0 => 64,
+        bits => bits,
+    }
+}
+
+fn check_counter_bits<C: Counter>(found: u8) -> Result<(), CrdtError> {
+    if found != C::BITS {
+        return Err(CrdtError::SchemaMismatch(format!(
+            "CountMinSketch has {}-bit counters, expected {}-bit",
+            found,
+            C::BITS
+        )));
+    }
+    Ok(())
+}
 
 /// Count-Min Sketch - Frequency Estimation CRDT
 ///
@@ -16,7 +83,9 @@ use std::hash::{Hash, Hasher};
 ///
 /// # Key Properties
 ///
-/// - **Fixed Memory**: Uses a fixed size matrix (`width` × `depth` × 8 bytes), regardless of the number of unique items.
+/// - **Fixed Memory**: Uses a fixed size matrix (`width` × `depth` counters), regardless of the number of unique items.
+/// - **Counter Width**: Counters are `u64` by default; `CountMinSketch<u32>` or
+///   `CountMinSketch<u16>` halve or quarter the memory for low-frequency workloads.
 /// - **Conservative**: Frequencies are never underestimated, but may be overestimated due to collisions.
 /// - **Mergeable**: Can be merged from multiple replicas by summing the corresponding counters.
 ///
@@ -39,27 +108,73 @@ use std::hash::{Hash, Hasher};
 /// assert!(cms.estimate("apple") >= 2);
 /// assert!(cms.estimate("banana") >= 1);
 /// assert_eq!(cms.estimate("cherry"), 0);
+///
+/// let mut small = CountMinSketch::<u16>::with_dimensions(100, 5);
+/// small.increment("apple", 100_000);
+/// assert_eq!(small.estimate("apple"), u16::MAX as u64);
 /// ```
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
-pub struct CountMinSketch {
+#[serde(bound(serialize = "C: Counter", deserialize = "C: Counter"))]
+pub struct CountMinSketch<C: Counter = u64> {
     /// Number of counters per row
     pub width: usize,
     /// Number of hash functions (rows)
     pub depth: usize,
     /// The matrix of counters (flattened or row-major)
-    pub matrix: Vec<Vec<u64>>,
+    pub matrix: Vec<Vec<C>>,
 }
 
 impl CountMinSketch {
     pub fn new(width: usize, depth: usize) -> Self {
+        Self::with_dimensions(width, depth)
+    }
+
+    /// Creates a sketch sized from the desired error bounds.
+    ///
+    /// With `width = ceil(e / epsilon)` and `depth = ceil(ln(1 / delta))`, an estimate
+    /// exceeds the true count by at most `epsilon * N` (where `N` is the total of all
+    /// increments) with probability at least `1 - delta`. For example, "2% error with 99%
+    /// confidence" is `with_error_bounds(0.02, 0.01)`.
+    ///
+    /// Both `epsilon` and `delta` must lie in the open interval `(0, 1)`.
+    pub fn with_error_bounds(epsilon: f64, delta: f64) -> Result<Self, CrdtError> {
+        let (width, depth) = Self::dimensions_for(epsilon, delta)?;
+        Ok(Self::new(width, depth))
+    }
+
+    /// Computes `(width, depth)` for the given error bounds. See [`CountMinSketch::with_error_bounds`].
+    pub(crate) fn dimensions_for(epsilon: f64, delta: f64) -> Result<(usize, usize), CrdtError> {
+        if !(epsilon > 0.0 && epsilon < 1.0) {
+            return Err(CrdtError::InvalidInput(format!(
+                "epsilon must be in (0, 1), got {}",
+                epsilon
+            )));
+        }
+        if !(delta > 0.0 && delta < 1.0) {
+            return Err(CrdtError::InvalidInput(format!(
+                "delta must be in (0, 1), got {}",
+                delta
+            )));
+        }
+        let width = (std::f64::consts::E / epsilon).ceil() as usize;
+        let depth = (1.0 / delta).ln().ceil() as usize;
+        Ok((width, depth.max(1)))
+    }
+}
+
+impl<C: Counter> CountMinSketch<C> {
+    /// Creates an empty sketch; unlike [`CountMinSketch::new`], for any counter type.
+    pub fn with_dimensions(width: usize, depth: usize) -> Self {
         Self {
             width,
             depth,
-            matrix: vec![vec![0; width]; depth],
+            matrix: vec![vec![C::default(); width]; depth],
         }
     }
 
+    /// Adds `count` to the item's counters, saturating at the counter type's maximum.
     pub fn increment<T: Hash>(&mut self, item: T, count: u64) {
+        let count = C::from_u64_saturating(count);
         let mut hasher = DefaultHasher::new();
         item.hash(&mut hasher);
         let hash = hasher.finish();
@@ -77,13 +192,29 @@ impl CountMinSketch {
         }
     }
 
+    /// Errors if `other` was built with a different width or depth.
+    pub(crate) fn check_dimensions(&self, other: &Self) -> Result<(), CrdtError> {
+        if self.width != other.width || self.depth != other.depth {
+            return Err(CrdtError::Merge("Dimension mismatch in CountMinSketch merge".into()));
+        }
+        Ok(())
+    }
+
     /// Merges another CountMinSketch into this one.
     ///
     /// # Arguments
     /// * `other` - The other CountMinSketch to merge.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `other` has a different width or depth; `merge_bytes` reports that as an
+    /// error instead.
     pub fn merge(&mut self, other: &Self) {
-        if self.width != other.width || self.depth != other.depth {
-            panic!("Dimension mismatch in CountMinSketch merge");
+        if other.is_identity() {
+            return;
+        }
+        if let Err(e) = self.check_dimensions(other) {
+            panic!("{}", e);
         }
 
         for r in 0..self.depth {
@@ -97,7 +228,7 @@ impl CountMinSketch {
         let mut hasher = DefaultHasher::new();
         item.hash(&mut hasher);
         let hash = hasher.finish();
-        let mut min_count = u64::MAX;
+        let mut min_count = C::MAX;
 
         for row in 0..self.depth {
             let mut row_hasher = DefaultHasher::new();
@@ -109,16 +240,38 @@ impl CountMinSketch {
             min_count = std::cmp::min(min_count, self.matrix[row][col]);
         }
 
-        if min_count == u64::MAX { 0 } else { min_count }
+        if self.depth == 0 { 0 } else { min_count.to_u64() }
+    }
+
+    /// Streams newline-delimited items from `reader`, incrementing each by one.
+    ///
+    /// Lines are read one at a time, so arbitrarily large inputs never need to fit in memory.
+    /// Trailing `\r` is stripped and empty lines are skipped. Returns the number of items ingested.
+    pub fn ingest_lines<R: BufRead>(&mut self, reader: R) -> Result<u64, CrdtError> {
+        let mut ingested = 0;
+        for line in reader.lines() {
+            let line = line.map_err(|e| CrdtError::Deserialization(e.to_string()))?;
+            let item = line.trim_end_matches('\r');
+            if item.is_empty() {
+                continue;
+            }
+            self.increment(item, 1);
+            ingested += 1;
+        }
+        Ok(ingested)
     }
 }
 
-impl Crdt for CountMinSketch {
+impl<C: Counter> Crdt for CountMinSketch<C> {
     type Reader<'a> = CountMinSketchReader<'a>;
 
+    fn from_capnp_bytes(bytes: &[u8]) -> Result<Self, CrdtError> {
+        Self::decode(bytes)
+    }
+
     fn merge_from_readers(readers: &[Self::Reader<'_>]) -> Result<Self, CrdtError> {
         if readers.is_empty() {
-            return Ok(Self::new(0, 0)); // Or error?
+            return Ok(Self::with_dimensions(0, 0)); // Or error?
         }
 
         // 1. Open all readers
@@ -147,19 +300,22 @@ impl Crdt for CountMinSketch {
                 return Err(CrdtError::Merge("Dimension mismatch in CountMinSketch merge".into()));
             }
         }
+        for root in &capnp_roots {
+            check_counter_bits::<C>(counter_bits(*root))?;
+        }
 
-        let mut merged = Self::new(width, depth);
+        let mut merged = Self::with_dimensions(width, depth);
 
         // Naive merge: iterate and sum
         // Optimization: This could be SIMD if we had flat arrays
         for r in 0..depth {
             for c in 0..width {
-                let mut sum: u64 = 0;
+                let mut sum = C::default();
                 for root in &capnp_roots {
                     // Access via reader (zero-copyish)
                     let counters = root.get_counters().map_err(|e| CrdtError::Deserialization(e.to_string()))?;
                     let idx = r * width + c;
-                    let val = counters.get(idx as u32);
+                    let val = C::from_u64_saturating(counters.get(idx as u32));
                     sum = sum.saturating_add(val);
                 }
                 merged.matrix[r][c] = sum;
@@ -182,15 +338,42 @@ impl Crdt for CountMinSketch {
     }
 
     fn is_empty(&self) -> bool {
-        self.matrix.iter().all(|row| row.iter().all(|&x| x == 0))
+        self.matrix.iter().all(|row| row.iter().all(|&x| x == C::default()))
+    }
+
+    fn is_identity(&self) -> bool {
+        self.matrix.iter().flatten().all(|c| *c == C::default())
+    }
+
+    fn merge(&mut self, other: &Self) {
+        self.merge(other);
+    }
+
+    fn merge_bytes(&mut self, bytes: &[u8]) -> Result<(), CrdtError> {
+        let other = Self::decode(bytes)?;
+        self.ensure_mergeable(&other)?;
+        self.merge(&other);
+        Ok(())
+    }
+
+    fn ensure_mergeable(&self, other: &Self) -> Result<(), CrdtError> {
+        self.check_dimensions(other)
     }
 
     fn to_capnp_bytes(&self) -> Vec<u8> {
         let mut message = Builder::new_default();
+        self.build_capnp(&mut message).unwrap();
+        let mut data = Vec::new();
+        serialize::write_message(&mut data, &message).unwrap();
+        data
+    }
+
+    fn build_capnp(&self, message: &mut Builder<HeapAllocator>) -> Result<(), CrdtError> {
         let mut root = message.init_root::<count_min_sketch_capnp::count_min_sketch::Builder>();
         
         root.set_width(self.width as u32);
         root.set_depth(self.depth as u32);
+        root.set_counter_bits(C::BITS);
         
         // Flatten matrix for storage
         let total_size = self.width * self.depth;
@@ -199,18 +382,35 @@ impl Crdt for CountMinSketch {
         for (r, row) in self.matrix.iter().enumerate() {
             for (c, &val) in row.iter().enumerate() {
                 let idx = r * self.width + c;
-                counters_builder.set(idx as u32, val);
+                counters_builder.set(idx as u32, val.to_u64());
             }
         }
-
-        let mut data = Vec::new();
-        serialize::write_message(&mut data, &message).unwrap();
-        data
+        Ok(())
     }
 }
 
 impl CountMinSketch {
+    /// Decodes a `u64`-counter sketch; use `Crdt::from_capnp_bytes` for other widths.
     pub fn from_capnp_bytes(data: &[u8]) -> Result<Self, CrdtError> {
+        Self::decode(data)
+    }
+}
+
+impl<C: Counter> CountMinSketch<C> {
+    /// Serializes in Cap'n Proto canonical form, for byte-level dedup of snapshots.
+    ///
+    /// Counters are written row-major, so `to_capnp_bytes` is already deterministic:
+    /// equal sketches produce identical bytes however they were built. This additionally
+    /// fixes the message layout, making the bytes stable across allocators and capnp
+    /// versions.
+    ///
+    /// # Errors
+    /// Returns `CrdtError::Serialization` if capnp cannot canonicalize the message.
+    pub fn canonical_bytes(&self) -> Result<Vec<u8>, CrdtError> {
+        super::canonicalize(&self.to_capnp_bytes())
+    }
+
+    fn decode(data: &[u8]) -> Result<Self, CrdtError> {
         let message_reader = serialize::read_message(
             data,
             ReaderOptions {
@@ -224,17 +424,19 @@ impl CountMinSketch {
             .get_root::<count_min_sketch_capnp::count_min_sketch::Reader>()
             .map_err(|e| CrdtError::Deserialization(e.to_string()))?;
 
+        check_counter_bits::<C>(counter_bits(root))?;
+
         // Convert reader back to struct
         let width = root.get_width() as usize;
         let depth = root.get_depth() as usize;
-        let mut matrix = vec![vec![0; width]; depth];
+        let mut matrix = vec![vec![C::default(); width]; depth];
         
         let counters = root.get_counters().map_err(|e| CrdtError::Deserialization(e.to_string()))?;
 
-        for r in 0..depth {
-            for c in 0..width {
+        for (r, row) in matrix.iter_mut().enumerate() {
+            for (c, cell) in row.iter_mut().enumerate() {
                 let idx = r * width + c;
-                matrix[r][c] = counters.get(idx as u32);
+                *cell = C::from_u64_saturating(counters.get(idx as u32));
             }
         }
         
@@ -253,6 +455,10 @@ impl<'a> CountMinSketchReader<'a> {
 }
 
 impl<'a> CrdtReader<'a> for CountMinSketchReader<'a> {
+    fn from_bytes(bytes: &'a [u8]) -> Self {
+        Self::new(bytes)
+    }
+
     fn is_empty(&self) -> Result<bool, CrdtError> {
         // We have to parse to check if empty, but we can stop early
         let message_reader = serialize::read_message(
@@ -2,12 +2,14 @@
 // Licensed under the MIT License. See LICENSE file in the project root for full license information.
 
 use crate::hyperloglog_capnp;
+use crate::size_estimate::{data_size, struct_size, MESSAGE_OVERHEAD};
 use crate::traits::{Crdt, CrdtError, CrdtReader};
-use capnp::message::{Builder, ReaderOptions};
+use capnp::message::{Builder, HeapAllocator, ReaderOptions};
 use capnp::serialize;
 use serde::{Deserialize, Serialize};
 use siphasher::sip::SipHasher13;
 use std::hash::{Hash, Hasher};
+use std::sync::OnceLock;
 
 /// Precision (number of bits for register index)
 const PRECISION: usize = 14;
@@ -15,6 +17,32 @@ const PRECISION: usize = 14;
 /// Number of registers (2^14 = 16,384)
 const NUM_REGISTERS: usize = 1 << PRECISION;
 
+/// Register encoding written by this version of the crate.
+///
+/// Version 0 (data written before versions existed) counted leading zeros over the full
+/// 64-bit word instead of the bits left after the index, so each non-zero register is
+/// `PRECISION` too high. It is migrated on decode so registers from either version
+/// merge correctly.
+const FORMAT_VERSION: u8 = 1;
+
+/// Converts a register written under `version` to the current encoding.
+fn migrate_rank(rank: u8, version: u8) -> u8 {
+    match version {
+        0 if rank > PRECISION as u8 => rank - PRECISION as u8,
+        _ => rank,
+    }
+}
+
+fn check_format_version(version: u8) -> Result<(), CrdtError> {
+    if version > FORMAT_VERSION {
+        return Err(CrdtError::Deserialization(format!(
+            "Unsupported HyperLogLog format version: {} (expected at most {})",
+            version, FORMAT_VERSION
+        )));
+    }
+    Ok(())
+}
+
 /// Alpha constant for bias correction
 const ALPHA: f64 = 0.7213 / (1.0 + 1.079 / NUM_REGISTERS as f64);
 
@@ -44,12 +72,48 @@ const ALPHA: f64 = 0.7213 / (1.0 + 1.079 / NUM_REGISTERS as f64);
 /// let count = hll.cardinality();
 /// assert!(count >= 3 && count <= 4); // Approximate count
 /// ```
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "HyperLogLogRepr")]
 pub struct HyperLogLog {
     /// 16,384 registers (each stores max leading zeros + 1)
     registers: Vec<u8>,
+    /// Register encoding; always `FORMAT_VERSION` in memory, older input is migrated.
+    format_version: u8,
+    /// Last `cardinality()` result, cleared whenever a register changes. Local-only.
+    #[serde(skip)]
+    cardinality_cache: OnceLock<u64>,
 }
 
+/// Serde input shape: JSON written before `format_version` existed is version 0.
+#[derive(Deserialize)]
+struct HyperLogLogRepr {
+    registers: Vec<u8>,
+    #[serde(default)]
+    format_version: u8,
+}
+
+impl From<HyperLogLogRepr> for HyperLogLog {
+    fn from(repr: HyperLogLogRepr) -> Self {
+        Self {
+            registers: repr
+                .registers
+                .into_iter()
+                .map(|rank| migrate_rank(rank, repr.format_version))
+                .collect(),
+            format_version: FORMAT_VERSION,
+            cardinality_cache: OnceLock::new(),
+        }
+    }
+}
+
+impl PartialEq for HyperLogLog {
+    fn eq(&self, other: &Self) -> bool {
+        self.registers == other.registers
+    }
+}
+
+impl Eq for HyperLogLog {}
+
 impl Default for HyperLogLog {
     fn default() -> Self {
         Self::new()
@@ -61,6 +125,8 @@ impl HyperLogLog {
     pub fn new() -> Self {
         Self {
             registers: vec![0u8; NUM_REGISTERS],
+            format_version: FORMAT_VERSION,
+            cardinality_cache: OnceLock::new(),
         }
     }
 
@@ -76,17 +142,19 @@ impl HyperLogLog {
         // Extract remaining bits for leading zero count
         let remaining_bits = hash >> PRECISION;
 
-        // Count leading zeros + 1 (HLL algorithm convention)
+        // Count leading zeros + 1 (HLL algorithm convention) within the (64 - PRECISION)
+        // bits left after the shift, not the full word
         let leading_zeros = if remaining_bits == 0 {
             (64 - PRECISION) as u8 + 1
         } else {
-            remaining_bits.leading_zeros() as u8 + 1
+            (remaining_bits.leading_zeros() as usize - PRECISION) as u8 + 1
         };
 
         // Update register if new value is larger (CRDT merge rule)
         let old_value = self.registers[register_idx];
         if leading_zeros > old_value {
             self.registers[register_idx] = leading_zeros;
+            self.cardinality_cache.take();
             true // State changed
         } else {
             false // No change
@@ -94,7 +162,19 @@ impl HyperLogLog {
     }
 
     /// Estimate the cardinality (number of unique elements)
+    ///
+    /// The estimate is cached until a register changes, so repeated calls between adds
+    /// are O(1).
     pub fn cardinality(&self) -> u64 {
+        *self.cardinality_cache.get_or_init(|| self.estimate_cardinality())
+    }
+
+    /// Returns the cached estimate, if `cardinality()` has run since the last change.
+    pub fn cached_cardinality(&self) -> Option<u64> {
+        self.cardinality_cache.get().copied()
+    }
+
+    fn estimate_cardinality(&self) -> u64 {
         // Calculate harmonic mean of registers
         let mut sum = 0.0;
         let mut zeros = 0;
@@ -126,6 +206,9 @@ impl HyperLogLog {
 
     /// Merge another HyperLogLog into this one
     pub fn merge(&mut self, other: &Self) {
+        if other.is_identity() {
+            return;
+        }
         if self.registers.len() != other.registers.len() {
             // Should not happen with fixed size, but good to check
             return;
@@ -133,10 +216,24 @@ impl HyperLogLog {
         for (i, &val) in other.registers.iter().enumerate() {
             if val > self.registers[i] {
                 self.registers[i] = val;
+                self.cardinality_cache.take();
             }
         }
     }
 
+    /// Serializes in Cap'n Proto canonical form, for byte-level dedup of snapshots.
+    ///
+    /// The registers have a fixed order, so `to_capnp_bytes` is already deterministic:
+    /// equal sketches produce identical bytes however they were built. This additionally
+    /// fixes the message layout, making the bytes stable across allocators and capnp
+    /// versions.
+    ///
+    /// # Errors
+    /// Returns `CrdtError::Serialization` if capnp cannot canonicalize the message.
+    pub fn canonical_bytes(&self) -> Result<Vec<u8>, CrdtError> {
+        super::canonicalize(&self.to_capnp_bytes())
+    }
+
     fn hash_element<T: Hash + ?Sized>(&self, element: &T) -> u64 {
         let mut hasher = SipHasher13::new();
         element.hash(&mut hasher);
@@ -169,8 +266,13 @@ impl HyperLogLog {
             )));
         }
 
+        let version = root.get_format_version();
+        check_format_version(version)?;
+
         Ok(Self {
-            registers: registers_data.to_vec(),
+            registers: registers_data.iter().map(|&rank| migrate_rank(rank, version)).collect(),
+            format_version: FORMAT_VERSION,
+            cardinality_cache: OnceLock::new(),
         })
     }
 }
@@ -184,6 +286,7 @@ impl<'a> HyperLogLogReader<'a> {
         Self { bytes }
     }
 
+    /// Returns the registers in the current encoding, migrating older data.
     pub fn get_registers(&self) -> Result<Vec<u8>, CrdtError> {
          let message_reader = serialize::read_message(
             self.bytes,
@@ -194,12 +297,18 @@ impl<'a> HyperLogLogReader<'a> {
             .get_root::<hyperloglog_capnp::hyper_log_log::Reader>()
             .map_err(|e| CrdtError::Deserialization(e.to_string()))?;
             
+        let version = root.get_format_version();
+        check_format_version(version)?;
         let registers = root.get_registers().map_err(|e| CrdtError::Deserialization(e.to_string()))?;
-        Ok(registers.to_vec())
+        Ok(registers.iter().map(|&rank| migrate_rank(rank, version)).collect())
     }
 }
 
 impl<'a> CrdtReader<'a> for HyperLogLogReader<'a> {
+    fn from_bytes(bytes: &'a [u8]) -> Self {
+        Self::new(bytes)
+    }
+
     fn is_empty(&self) -> Result<bool, CrdtError> {
         let message_reader = serialize::read_message(
             self.bytes,
@@ -225,6 +334,10 @@ impl<'a> CrdtReader<'a> for HyperLogLogReader<'a> {
 impl Crdt for HyperLogLog {
     type Reader<'a> = HyperLogLogReader<'a>;
 
+    fn from_capnp_bytes(bytes: &[u8]) -> Result<Self, CrdtError> {
+        HyperLogLog::from_capnp_bytes(bytes)
+    }
+
     fn merge_from_readers(readers: &[Self::Reader<'_>]) -> Result<Self, CrdtError> {
         let mut merged = Self::new();
 
@@ -239,6 +352,8 @@ impl Crdt for HyperLogLog {
                 .get_root::<hyperloglog_capnp::hyper_log_log::Reader>()
                 .map_err(|e| CrdtError::Deserialization(e.to_string()))?;
                 
+            let version = root.get_format_version();
+            check_format_version(version)?;
             let registers = root.get_registers().map_err(|e| CrdtError::Deserialization(e.to_string()))?;
             
             if registers.len() != NUM_REGISTERS {
@@ -251,6 +366,7 @@ impl Crdt for HyperLogLog {
 
             // Zero-copy merge: iterate over the slice directly
             for (i, &val) in registers.iter().enumerate() {
+                let val = migrate_rank(val, version);
                 if val > merged.registers[i] {
                     merged.registers[i] = val;
                 }
@@ -275,14 +391,43 @@ impl Crdt for HyperLogLog {
         self.registers.iter().all(|&x| x == 0)
     }
 
+    fn is_identity(&self) -> bool {
+        self.registers.iter().all(|r| *r == 0)
+    }
+
+    fn merge(&mut self, other: &Self) {
+        self.merge(other);
+    }
+
+    fn dominates(&self, other: &Self) -> bool {
+        self.registers.len() == other.registers.len()
+            && self.registers.iter().zip(&other.registers).all(|(a, b)| a >= b)
+    }
+
+    fn merge_bytes(&mut self, bytes: &[u8]) -> Result<(), CrdtError> {
+        let other = Self::from_capnp_bytes(bytes)?;
+        self.merge(&other);
+        Ok(())
+    }
+
     fn to_capnp_bytes(&self) -> Vec<u8> {
         let mut message = Builder::new_default();
+        self.build_capnp(&mut message).unwrap();
+        let mut data = Vec::new();
+        serialize::write_message(&mut data, &message).unwrap();
+        data
+    }
+
+    fn build_capnp(&self, message: &mut Builder<HeapAllocator>) -> Result<(), CrdtError> {
         let mut root = message.init_root::<hyperloglog_capnp::hyper_log_log::Builder>();
         
         root.set_registers(&self.registers);
+        root.set_format_version(self.format_version);
+        Ok(())
+    }
 
-        let mut data = Vec::new();
-        serialize::write_message(&mut data, &message).unwrap();
-        data
+    fn estimated_capnp_size(&self) -> usize {
+        // Fixed layout: the version word plus a single Data blob of NUM_REGISTERS bytes
+        MESSAGE_OVERHEAD + struct_size(1, 1) + data_size(NUM_REGISTERS)
     }
 }
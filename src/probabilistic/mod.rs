@@ -7,6 +7,7 @@
 //! using significantly less memory than exact structures. They satisfy CRDT properties (commutativity,
 //! associativity, idempotence) and can be merged from multiple replicas.
 
+pub mod approx_set;
 pub mod count_min_sketch;
 pub mod hyperloglog;
 pub mod roaring_bitmap;
@@ -14,9 +15,35 @@ pub mod tdigest;
 pub mod topk;
 
 
-pub use count_min_sketch::{CountMinSketch, CountMinSketchReader};
+pub use approx_set::ApproxSet;
+pub use count_min_sketch::{Counter, CountMinSketch, CountMinSketchReader};
 pub use hyperloglog::{HyperLogLog, HyperLogLogReader};
 pub use roaring_bitmap::{RoaringBitmap, RoaringBitmapReader};
 pub use tdigest::{TDigest, TDigestReader};
 pub use topk::{TopK, TopKReader};
 
+use crate::traits::CrdtError;
+
+/// Rewrites a serialized message in Cap'n Proto canonical form: a single segment with
+/// objects in pre-order and no padding or far pointers.
+///
+/// The sketches' register arrays outgrow the builder's first segment, so `to_capnp_bytes`
+/// produces a multi-segment message whose layout depends on the allocator. The canonical
+/// form depends only on the message content.
+pub(crate) fn canonicalize(bytes: &[u8]) -> Result<Vec<u8>, CrdtError> {
+    let reader = capnp::serialize::read_message(
+        bytes,
+        capnp::message::ReaderOptions {
+            traversal_limit_in_words: None,
+            nesting_limit: 64,
+        },
+    )
+    .map_err(|e| CrdtError::Serialization(e.to_string()))?;
+    let words = reader
+        .canonicalize()
+        .map_err(|e| CrdtError::Serialization(e.to_string()))?;
+    let segments = [capnp::Word::words_to_bytes(&words)];
+    Ok(capnp::serialize::write_message_segments_to_words(
+        &capnp::message::SegmentArray::new(&segments),
+    ))
+}
@@ -3,7 +3,7 @@
 
 use crate::roaring_bitmap_capnp;
 use crate::traits::{Crdt, CrdtError, CrdtReader};
-use capnp::message::{Builder, ReaderOptions};
+use capnp::message::{Builder, HeapAllocator, ReaderOptions};
 use capnp::serialize;
 use roaring::RoaringBitmap as Rb;
 use serde::{Deserialize, Serialize};
@@ -18,7 +18,8 @@ use std::io::Cursor;
 ///
 /// - **Compression**: Uses Roaring Bitmap compression to store sets efficiently (often 10-100x smaller than uncompressed bitmaps).
 /// - **Fast Operations**: Optimized for fast set operations like union (merge), intersection, and difference.
-/// - **Mergeable**: Merging two RoaringBitmaps results in their union (bitwise OR).
+/// - **Mergeable**: Merging two RoaringBitmaps results in their union (bitwise OR). The
+///   merged `max_value` is the larger of the two, so every merged bit stays in range.
 /// - **Use Cases**: User segmentation, activity tracking, inverted indices.
 ///
 /// # Example
@@ -102,8 +103,68 @@ impl RoaringBitmap {
         self.bitmap.len()
     }
 
+    /// Iterates over the set values in ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = u32> + '_ {
+        self.bitmap.iter()
+    }
+
+    /// Returns the set values in ascending order.
+    pub fn to_vec(&self) -> Vec<u32> {
+        self.bitmap.iter().collect()
+    }
+
+    /// Returns the smallest value in the set, if any.
+    pub fn min(&self) -> Option<u32> {
+        self.bitmap.min()
+    }
+
+    /// Returns the largest value in the set, if any.
+    pub fn max(&self) -> Option<u32> {
+        self.bitmap.max()
+    }
+
+    /// Merges another bitmap into this one (bitwise OR).
+    ///
+    /// `max_value` only bounds local inserts; replicas with different bounds merge to the
+    /// larger one and keep all bits, mirroring how `TopK` reconciles `k`.
     pub fn merge(&mut self, other: &Self) {
         self.bitmap |= &other.bitmap;
+        self.max_value = self.max_value.max(other.max_value);
+    }
+
+    /// Run-length encodes the containers where that is smaller, e.g. for contiguous
+    /// ranges. Returns true if any container changed.
+    ///
+    /// A lossless size optimization: the set of values (and equality) is unchanged, and
+    /// `to_capnp_bytes` then writes the compact containers.
+    pub fn optimize(&mut self) -> bool {
+        self.bitmap.optimize()
+    }
+
+    /// Serializes a run-optimized copy, leaving `self` untouched.
+    ///
+    /// Equivalent to [`RoaringBitmap::optimize`] followed by `to_capnp_bytes`.
+    pub fn optimized_bytes(&self) -> Vec<u8> {
+        let mut optimized = self.clone();
+        optimized.optimize();
+        optimized.to_capnp_bytes()
+    }
+
+    /// Returns the bits present in `self` but not in `base` (`self - base`).
+    ///
+    /// The delta is a regular `RoaringBitmap` and serializes with the same capnp schema,
+    /// so only the changed bits need to be shipped to a replica that already holds `base`.
+    pub fn delta_since(&self, base: &Self) -> Self {
+        Self {
+            bitmap: &self.bitmap - &base.bitmap,
+            max_value: self.max_value,
+            description: self.description.clone(),
+        }
+    }
+
+    /// Applies a delta produced by [`RoaringBitmap::delta_since`] (bitwise OR).
+    pub fn apply_delta(&mut self, delta: &Self) {
+        self.merge(delta);
     }
 
     pub fn from_capnp_bytes(data: &[u8]) -> Result<Self, CrdtError> {
@@ -150,9 +211,12 @@ impl<'a> RoaringBitmapReader<'a> {
     }
 }
 
-impl<'a> CrdtReader<'a> for RoaringBitmapReader<'a> {
-    fn is_empty(&self) -> Result<bool, CrdtError> {
-        // We have to parse to check if empty, or at least check the data length
+impl<'a> RoaringBitmapReader<'a> {
+    /// Returns the number of set bits.
+    ///
+    /// This is O(1) for payloads carrying the stored cardinality; older payloads fall back
+    /// to deserializing the bitmap body.
+    pub fn cardinality(&self) -> Result<u64, CrdtError> {
         let message_reader = serialize::read_message(
             self.bytes,
             ReaderOptions::new(),
@@ -161,22 +225,36 @@ impl<'a> CrdtReader<'a> for RoaringBitmapReader<'a> {
         let root = message_reader
             .get_root::<roaring_bitmap_capnp::roaring_bitmap::Reader>()
             .map_err(|e| CrdtError::Deserialization(e.to_string()))?;
-            
+
+        if root.get_has_cardinality() {
+            return Ok(root.get_cardinality());
+        }
+
         let bitmap_data = root.get_bitmap_data().map_err(|e| CrdtError::Deserialization(e.to_string()))?;
-        
-        // The roaring crate doesn't expose a way to check for emptiness directly from bytes
-        // without at least partial deserialization. For safety, we deserialize the bitmap.
-        
         let bitmap = Rb::deserialize_from(&mut Cursor::new(bitmap_data))
             .map_err(|e| CrdtError::Deserialization(e.to_string()))?;
-            
-        Ok(bitmap.is_empty())
+
+        Ok(bitmap.len())
+    }
+}
+
+impl<'a> CrdtReader<'a> for RoaringBitmapReader<'a> {
+    fn from_bytes(bytes: &'a [u8]) -> Self {
+        Self::new(bytes)
+    }
+
+    fn is_empty(&self) -> Result<bool, CrdtError> {
+        Ok(self.cardinality()? == 0)
     }
 }
 
 impl Crdt for RoaringBitmap {
     type Reader<'a> = RoaringBitmapReader<'a>;
 
+    fn from_capnp_bytes(bytes: &[u8]) -> Result<Self, CrdtError> {
+        RoaringBitmap::from_capnp_bytes(bytes)
+    }
+
     fn merge_from_readers(readers: &[Self::Reader<'_>]) -> Result<Self, CrdtError> {
         let mut merged = Rb::new();
         let mut max_value = 0;
@@ -192,8 +270,8 @@ impl Crdt for RoaringBitmap {
                 .get_root::<roaring_bitmap_capnp::roaring_bitmap::Reader>()
                 .map_err(|e| CrdtError::Deserialization(e.to_string()))?;
             
+            max_value = max_value.max(root.get_max_value());
             if i == 0 {
-                max_value = root.get_max_value();
                 if let Ok(desc) = root.get_description() {
                     description = desc.to_string().unwrap_or_default();
                 }
@@ -231,8 +309,29 @@ impl Crdt for RoaringBitmap {
         self.bitmap.is_empty()
     }
 
+    fn merge(&mut self, other: &Self) {
+        self.merge(other);
+    }
+
+    fn dominates(&self, other: &Self) -> bool {
+        self.max_value >= other.max_value && other.bitmap.is_subset(&self.bitmap)
+    }
+
+    fn merge_bytes(&mut self, bytes: &[u8]) -> Result<(), CrdtError> {
+        let other = Self::from_capnp_bytes(bytes)?;
+        self.merge(&other);
+        Ok(())
+    }
+
     fn to_capnp_bytes(&self) -> Vec<u8> {
         let mut message = Builder::new_default();
+        self.build_capnp(&mut message).unwrap();
+        let mut data = Vec::new();
+        serialize::write_message(&mut data, &message).unwrap();
+        data
+    }
+
+    fn build_capnp(&self, message: &mut Builder<HeapAllocator>) -> Result<(), CrdtError> {
         let mut root = message.init_root::<roaring_bitmap_capnp::roaring_bitmap::Builder>();
         
         root.set_max_value(self.max_value);
@@ -241,9 +340,8 @@ impl Crdt for RoaringBitmap {
         let mut bytes = Vec::new();
         self.bitmap.serialize_into(&mut bytes).unwrap();
         root.set_bitmap_data(&bytes);
-
-        let mut data = Vec::new();
-        serialize::write_message(&mut data, &message).unwrap();
-        data
+        root.set_cardinality(self.bitmap.len());
+        root.set_has_cardinality(true);
+        Ok(())
     }
 }
@@ -3,9 +3,10 @@
 
 use crate::tdigest_capnp;
 use crate::traits::{Crdt, CrdtError, CrdtReader};
-use capnp::message::{Builder, ReaderOptions};
+use capnp::message::{Builder, HeapAllocator, ReaderOptions};
 use capnp::serialize;
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
 use tdigest::TDigest as Td;
 
 /// TDigest - Percentile Estimation CRDT
@@ -34,8 +35,8 @@ use tdigest::TDigest as Td;
 /// assert!((td.quantile(0.99) - 99.0).abs() < 1.0);
 /// ```
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(into = "TDigestRepr", from = "TDigestRepr")]
 pub struct TDigest {
-    #[serde(skip)]
     digest: Td,
     // We keep these for serialization/deserialization consistency
     // but the real state is in `digest`
@@ -43,14 +44,62 @@ pub struct TDigest {
     pub max: f64,
     pub sum: f64,
     pub count: u64,
+    /// Digests merged by `merge_preserving` and not yet folded in by `compress`.
+    pending: Vec<Td>,
+}
+
+/// Serde form of [`TDigest`], mirroring the capnp layout.
+///
+/// The `tdigest` crate's own representation cannot round-trip through JSON (an empty
+/// digest holds NaN bounds), so the centroids are carried explicitly and the infinite
+/// bounds of an empty digest become `null`. JSON written before the centroids were carried
+/// has only the bounds and totals; it decodes with the default compression and no
+/// centroids, as it did then.
+#[derive(Serialize, Deserialize)]
+struct TDigestRepr {
+    #[serde(default = "default_compression")]
+    compression: usize,
+    #[serde(default)]
+    centroids: Vec<(f64, f64)>,
+    min: Option<f64>,
+    max: Option<f64>,
+    sum: f64,
+    count: u64,
+}
+
+impl From<TDigest> for TDigestRepr {
+    fn from(td: TDigest) -> Self {
+        Self {
+            compression: td.digest().max_size(),
+            centroids: td.centroids(),
+            min: td.min.is_finite().then_some(td.min),
+            max: td.max.is_finite().then_some(td.max),
+            sum: td.sum,
+            count: td.count,
+        }
+    }
+}
+
+impl From<TDigestRepr> for TDigest {
+    fn from(repr: TDigestRepr) -> Self {
+        let min = repr.min.unwrap_or(f64::INFINITY);
+        let max = repr.max.unwrap_or(f64::NEG_INFINITY);
+        Self::from_parts(repr.compression, &repr.centroids, min, max, repr.sum, repr.count)
+    }
+}
+
+fn default_compression() -> usize {
+    100
 }
 
 impl Default for TDigest {
     fn default() -> Self {
-        Self::new(100)
+        Self::new(default_compression())
     }
 }
 
+/// Strict: `sum`, `min` and `max` must match exactly, which digests built from the same
+/// data by different merge orders rarely do. Use [`TDigest::approx_eq`] for those.
 impl PartialEq for TDigest {
     fn eq(&self, other: &Self) -> bool {
         // TDigest equality is tricky because of internal state (centroids).
@@ -72,6 +121,7 @@ impl TDigest {
             max: f64::NEG_INFINITY,
             sum: 0.0,
             count: 0,
+            pending: Vec::new(),
         }
     }
 
@@ -93,14 +143,232 @@ impl TDigest {
     }
 
     pub fn quantile(&self, q: f64) -> f64 {
-        self.digest.estimate_quantile(q)
+        self.digest().estimate_quantile(q)
+    }
+
+    /// Estimates several quantiles in a single ordered pass over the centroids.
+    ///
+    /// Results are returned in the same order as `qs` and match [`TDigest::quantile`]
+    /// up to floating-point rounding.
+    pub fn quantiles(&self, qs: &[f64]) -> Vec<f64> {
+        let digest = self.digest();
+        let centroids = centroids_of(&digest);
+        let mut results = vec![0.0; qs.len()];
+        if centroids.is_empty() {
+            return results;
+        }
+
+        let count = digest.count();
+        let last = centroids.len() - 1;
+        let mut order: Vec<usize> = (0..qs.len()).collect();
+        order.sort_by(|&a, &b| qs[a].total_cmp(&qs[b]));
+
+        // Cursor over centroids: `t` is the cumulative weight before centroid `k`.
+        let mut k = 0;
+        let mut t = 0.0;
+        for idx in order {
+            let q = qs[idx];
+            if q <= 0.0 {
+                results[idx] = digest.min();
+                continue;
+            }
+            if q >= 1.0 {
+                results[idx] = digest.max();
+                continue;
+            }
+
+            let rank = q * count;
+            while k < last && rank >= t + centroids[k].1 {
+                t += centroids[k].1;
+                k += 1;
+            }
+            // Past the final centroid the lower half of the distribution counts its weight too
+            let t_used = if q <= 0.5 && k == last && rank >= t + centroids[k].1 {
+                t + centroids[k].1
+            } else {
+                t
+            };
+            results[idx] = interpolate(&digest, &centroids, k, rank, t_used);
+        }
+        results
     }
 
+    /// Returns the mean of the values whose ranks fall between quantiles `lo` and `hi`.
+    ///
+    /// Centroids straddling a boundary contribute proportionally to the weight inside
+    /// the range. Returns `0.0` for an empty digest or an empty range.
+    pub fn trimmed_mean(&self, lo: f64, hi: f64) -> f64 {
+        let lo = lo.clamp(0.0, 1.0);
+        let hi = hi.clamp(0.0, 1.0);
+        let digest = self.digest();
+        let centroids = centroids_of(&digest);
+        if centroids.is_empty() || hi <= lo {
+            return 0.0;
+        }
+
+        let count = digest.count();
+        let (lo_rank, hi_rank) = (lo * count, hi * count);
+        let mut t: f64 = 0.0;
+        let mut weighted_sum = 0.0;
+        let mut total_weight = 0.0;
+        for (mean, weight) in centroids {
+            let start = t.max(lo_rank);
+            let end = (t + weight).min(hi_rank);
+            if end > start {
+                weighted_sum += mean * (end - start);
+                total_weight += end - start;
+            }
+            t += weight;
+            if t >= hi_rank {
+                break;
+            }
+        }
+
+        if total_weight == 0.0 {
+            0.0
+        } else {
+            weighted_sum / total_weight
+        }
+    }
+
+    /// Exports the digest as a cumulative fixed-bucket histogram (Prometheus `le` buckets).
+    ///
+    /// Each `(boundary, count)` pair holds the estimated number of values `<= boundary`,
+    /// derived from the digest's CDF. A final `+Inf` bucket equal to `count` is appended
+    /// unless `boundaries` already ends with one. Returns `CrdtError::InvalidInput` if the
+    /// boundaries are not strictly ascending or contain NaN.
+    pub fn to_buckets(&self, boundaries: &[f64]) -> Result<Vec<(f64, u64)>, CrdtError> {
+        if boundaries.iter().any(|b| b.is_nan()) || boundaries.windows(2).any(|w| w[0] >= w[1]) {
+            return Err(CrdtError::InvalidInput(
+                "Histogram boundaries must be strictly ascending".into(),
+            ));
+        }
+
+        let centroids = self.centroids();
+        let mut buckets: Vec<(f64, u64)> = boundaries
+            .iter()
+            .map(|&le| {
+                let rank = if le == f64::INFINITY {
+                    self.count as f64
+                } else {
+                    self.rank(&centroids, le)
+                };
+                (le, (rank.round() as u64).min(self.count))
+            })
+            .collect();
+        if boundaries.last() != Some(&f64::INFINITY) {
+            buckets.push((f64::INFINITY, self.count));
+        }
+        Ok(buckets)
+    }
+
+    /// Estimates the error of `quantile(q)`, as a fraction of `count` (i.e. in quantile units).
+    ///
+    /// A value of `0.01` means the true rank of the returned value may be off by about 1%
+    /// of the data. The estimate is half the weight of the centroid covering rank `q * count`,
+    /// capped by the t-digest scale function, which limits a centroid near `q` to a span of
+    /// `2 * sqrt(2 * min(q, 1 - q)) / compression` in quantile space. Errors therefore shrink
+    /// towards the tails and grow with repeated merging into coarse centroids. Returns `0.0`
+    /// for an empty digest.
+    pub fn estimated_error(&self, q: f64) -> f64 {
+        let digest = self.digest();
+        let centroids = centroids_of(&digest);
+        if centroids.is_empty() || q.is_nan() {
+            return 0.0;
+        }
+        let q = q.clamp(0.0, 1.0);
+        let count = digest.count();
+
+        let rank = q * count;
+        let mut t = 0.0;
+        let mut weight = centroids[centroids.len() - 1].1;
+        for (_, w) in &centroids {
+            if rank < t + w {
+                weight = *w;
+                break;
+            }
+            t += w;
+        }
+        let observed = weight / (2.0 * count);
+        let bound = (2.0 * q.min(1.0 - q)).sqrt() / digest.max_size() as f64;
+        observed.min(bound)
+    }
+
+    /// Estimated number of values `<= x`.
+    ///
+    /// Each centroid's weight is treated as centred on its mean, and the cumulative weight
+    /// is interpolated linearly between neighbouring means (and out to `min`/`max`).
+    fn rank(&self, centroids: &[(f64, f64)], x: f64) -> f64 {
+        let (Some(first), Some(last)) = (centroids.first(), centroids.last()) else {
+            return 0.0;
+        };
+        let count = self.count as f64;
+        if x < self.min {
+            return 0.0;
+        }
+        if x >= self.max {
+            return count;
+        }
+        if x < first.0 {
+            let span = first.0 - self.min;
+            let frac = if span > 0.0 { (x - self.min) / span } else { 1.0 };
+            return frac * first.1 / 2.0;
+        }
+        if x >= last.0 {
+            let span = self.max - last.0;
+            let frac = if span > 0.0 { (x - last.0) / span } else { 0.0 };
+            return count - last.1 / 2.0 + frac * last.1 / 2.0;
+        }
+
+        let mut t = 0.0;
+        for pair in centroids.windows(2) {
+            let ((m0, w0), (m1, w1)) = (pair[0], pair[1]);
+            if x < m1 {
+                let span = m1 - m0;
+                let frac = if span > 0.0 { (x - m0) / span } else { 1.0 };
+                return t + w0 / 2.0 + frac * (w0 + w1) / 2.0;
+            }
+            t += w0;
+        }
+        count
+    }
+
+    /// Returns the centroids as `(mean, weight)` pairs in ascending order of mean.
+    ///
+    /// These are the same centroids `to_capnp_bytes` writes, and are suitable for plotting
+    /// a density estimate. The weights sum to `count`.
+    ///
+    /// The `tdigest` crate (v0.2) does not expose its centroids, but it implements
+    /// `Serialize`, so we read them back out of a `serde_json::Value`.
+    pub fn centroids(&self) -> Vec<(f64, f64)> {
+        centroids_of(&self.digest())
+    }
+
+    /// Compares the summaries with `sum`, `min` and `max` equal within relative
+    /// tolerance `tol` and `count` exactly.
+    ///
+    /// Floating-point sums depend on the order values were added in, so two digests of
+    /// the same data can be `approx_eq` without being `==`.
+    pub fn approx_eq(&self, other: &Self, tol: f64) -> bool {
+        let close = |a: f64, b: f64| a == b || (a - b).abs() <= tol * a.abs().max(b.abs());
+        self.count == other.count
+            && close(self.sum, other.sum)
+            && close(self.min, other.min)
+            && close(self.max, other.max)
+    }
+
+    /// The maximum number of centroids kept; higher is more accurate.
+    pub fn compression(&self) -> usize {
+        self.digest.max_size()
+    }
+
+    /// Merges another digest into this one, keeping the higher of the two compressions.
     pub fn merge(&mut self, other: &Self) {
-        // To merge, we extract centroids from other and merge them into self.
-        // The `tdigest` crate supports merging digests.
-        let digests = vec![self.digest.clone(), other.digest.clone()];
-        self.digest = Td::merge_digests(digests);
+        if other.is_identity() {
+            return;
+        }
+        let digests = vec![self.digest.clone(), other.digest().into_owned()];
+        self.digest = merge_digests(digests);
         
         self.count += other.count;
         self.min = self.min.min(other.min);
@@ -108,6 +376,62 @@ impl TDigest {
         self.sum += other.sum;
     }
 
+    /// Merges another digest without re-compressing.
+    ///
+    /// `merge` re-bins the centroids on every call, so a fan-in of N digests compresses N
+    /// times and the errors compound. This queues `other` instead; the queued digests are
+    /// compressed together in a single pass by [`TDigest::compress`]. Queries and
+    /// serialization before then still see every merged value, but compress on each call.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use crdt_data_types::TDigest;
+    ///
+    /// let mut total = TDigest::new(100);
+    /// for shard in 0..10 {
+    ///     let mut part = TDigest::new(100);
+    ///     for i in 0..100 {
+    ///         part.insert((shard * 100 + i) as f64);
+    ///     }
+    ///     total.merge_preserving(&part);
+    /// }
+    /// total.compress();
+    /// assert!((total.quantile(0.5) - 500.0).abs() < 10.0);
+    /// ```
+    pub fn merge_preserving(&mut self, other: &Self) {
+        if other.is_identity() {
+            return;
+        }
+        self.pending.push(other.digest.clone());
+        self.pending.extend(other.pending.iter().cloned());
+
+        self.count += other.count;
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+        self.sum += other.sum;
+    }
+
+    /// Compresses the digests queued by [`TDigest::merge_preserving`] in one pass.
+    pub fn compress(&mut self) {
+        if self.pending.is_empty() {
+            return;
+        }
+        let mut digests = vec![self.digest.clone()];
+        digests.append(&mut self.pending);
+        self.digest = merge_digests(digests);
+    }
+
+    /// The digest including any queued merges.
+    fn digest(&self) -> Cow<'_, Td> {
+        if self.pending.is_empty() {
+            return Cow::Borrowed(&self.digest);
+        }
+        let mut digests = vec![self.digest.clone()];
+        digests.extend(self.pending.iter().cloned());
+        Cow::Owned(merge_digests(digests))
+    }
+
     pub fn from_capnp_bytes(data: &[u8]) -> Result<Self, CrdtError> {
         let message_reader = serialize::read_message(
             data,
@@ -129,7 +453,7 @@ impl TDigest {
         let sum = root.get_sum();
         
         let centroids_data = root.get_centroids().map_err(|e| CrdtError::Deserialization(e.to_string()))?;
-        let mut centroids = Vec::new();
+        let mut centroids = Vec::with_capacity(centroids_data.len() as usize / 2);
         
         // Centroids are stored as [mean1, weight1, mean2, weight2, ...]
         let len = centroids_data.len();
@@ -138,21 +462,118 @@ impl TDigest {
         }
         
         for i in (0..len).step_by(2) {
-            let mean = centroids_data.get(i);
-            let weight = centroids_data.get(i+1);
-            use tdigest::Centroid;
-            centroids.push(Centroid::new(mean, weight));
+            centroids.push((centroids_data.get(i), centroids_data.get(i + 1)));
         }
 
-        let digest = Td::new(centroids, sum, count as f64, max, min, compression as usize);
+        Ok(Self::from_parts(compression as usize, &centroids, min, max, sum, count))
+    }
 
-        Ok(Self {
+    /// Rebuilds a digest from `(mean, weight)` centroids and its summary fields.
+    fn from_parts(
+        compression: usize,
+        centroids: &[(f64, f64)],
+        min: f64,
+        max: f64,
+        sum: f64,
+        count: u64,
+    ) -> Self {
+        let digest = build_digest(compression, centroids.to_vec(), sum, count as f64, min, max);
+
+        Self {
             digest,
             min,
             max,
             sum,
             count,
+            pending: Vec::new(),
+        }
+    }
+}
+
+/// Merges digests into one with the highest compression among them.
+///
+/// `tdigest::TDigest::merge_digests` takes the first digest's compression and merges
+/// centroids in argument order; this gives the same result for any order.
+fn merge_digests(digests: Vec<Td>) -> Td {
+    let compression = digests.iter().map(Td::max_size).max().unwrap_or(100);
+    let mut sums: Vec<f64> = digests.iter().map(Td::sum).collect();
+    sums.sort_by(f64::total_cmp);
+    let count = digests.iter().map(Td::count).sum();
+    let min = digests.iter().map(Td::min).fold(f64::INFINITY, f64::min);
+    let max = digests.iter().map(Td::max).fold(f64::NEG_INFINITY, f64::max);
+    let centroids = digests.iter().flat_map(centroids_of).collect();
+    build_digest(compression, centroids, sums.iter().sum(), count, min, max)
+}
+
+/// Builds a digest of `compression` from centroids in any order, compressing if needed.
+fn build_digest(
+    compression: usize,
+    mut centroids: Vec<(f64, f64)>,
+    sum: f64,
+    count: f64,
+    min: f64,
+    max: f64,
+) -> Td {
+    centroids.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.total_cmp(&b.1)));
+    let len = centroids.len();
+    let centroids = centroids
+        .into_iter()
+        .map(|(mean, weight)| tdigest::Centroid::new(mean, weight))
+        .collect();
+    if len <= compression {
+        return Td::new(centroids, sum, count, max, min, compression);
+    }
+    // `Td::new` would compress to its default size; the merge takes the first digest's
+    let uncompressed = Td::new(centroids, sum, count, max, min, len);
+    Td::merge_digests(vec![Td::new_with_size(compression), uncompressed])
+}
+
+/// Reads `(mean, weight)` centroids out of a `tdigest` digest (see [`TDigest::centroids`]).
+fn centroids_of(digest: &Td) -> Vec<(f64, f64)> {
+    let val = serde_json::to_value(digest).unwrap_or_default();
+    val.get("centroids")
+        .and_then(|v| v.as_array())
+        .map(|centroids| {
+            centroids
+                .iter()
+                .map(|c| {
+                    let mean = c.get("mean").and_then(|v| v.as_f64()).unwrap_or(0.0);
+                    let weight = c.get("weight").and_then(|v| v.as_f64()).unwrap_or(0.0);
+                    (mean, weight)
+                })
+                .collect()
         })
+        .unwrap_or_default()
+}
+
+/// Mirrors the interpolation step of `tdigest::TDigest::estimate_quantile`.
+fn interpolate(digest: &Td, centroids: &[(f64, f64)], pos: usize, rank: f64, t: f64) -> f64 {
+    let mut delta = 0.0;
+    let mut min = digest.min();
+    let mut max = digest.max();
+    let last = centroids.len() - 1;
+
+    if centroids.len() > 1 {
+        if pos == 0 {
+            delta = centroids[pos + 1].0 - centroids[pos].0;
+            max = centroids[pos + 1].0;
+        } else if pos == last {
+            delta = centroids[pos].0 - centroids[pos - 1].0;
+            min = centroids[pos - 1].0;
+        } else {
+            delta = (centroids[pos + 1].0 - centroids[pos - 1].0) / 2.0;
+            min = centroids[pos - 1].0;
+            max = centroids[pos + 1].0;
+        }
+    }
+
+    let value = centroids[pos].0 + ((rank - t) / centroids[pos].1 - 0.5) * delta;
+    if value > max {
+        max
+    } else if value < min {
+        min
+    } else {
+        value
     }
 }
 
@@ -167,6 +588,10 @@ impl<'a> TDigestReader<'a> {
 }
 
 impl<'a> CrdtReader<'a> for TDigestReader<'a> {
+    fn from_bytes(bytes: &'a [u8]) -> Self {
+        Self::new(bytes)
+    }
+
     fn is_empty(&self) -> Result<bool, CrdtError> {
         let message_reader = serialize::read_message(
             self.bytes,
@@ -184,6 +609,10 @@ impl<'a> CrdtReader<'a> for TDigestReader<'a> {
 impl Crdt for TDigest {
     type Reader<'a> = TDigestReader<'a>;
 
+    fn from_capnp_bytes(bytes: &[u8]) -> Result<Self, CrdtError> {
+        TDigest::from_capnp_bytes(bytes)
+    }
+
     fn merge_from_readers(readers: &[Self::Reader<'_>]) -> Result<Self, CrdtError> {
         if readers.is_empty() {
             return Ok(Self::default());
@@ -194,9 +623,9 @@ impl Crdt for TDigest {
         let mut global_min = f64::INFINITY;
         let mut global_max = f64::NEG_INFINITY;
         let mut total_sum = 0.0;
-        let mut compression = 100; // Default
+        let mut compression = 0;
 
-        for (i, reader) in readers.iter().enumerate() {
+        for reader in readers {
             let message_reader = serialize::read_message(
                 reader.bytes,
                 ReaderOptions::new(),
@@ -206,9 +635,8 @@ impl Crdt for TDigest {
                 .get_root::<tdigest_capnp::t_digest::Reader>()
                 .map_err(|e| CrdtError::Deserialization(e.to_string()))?;
             
-            if i == 0 {
-                compression = root.get_compression();
-            }
+            // Keep the most accurate input's compression, whatever the order
+            compression = compression.max(root.get_compression());
 
             total_count += root.get_sample_count();
             let min = root.get_min();
@@ -225,15 +653,18 @@ impl Crdt for TDigest {
             let len = centroids_data.len();
             
             for j in (0..len).step_by(2) {
-                let mean = centroids_data.get(j);
-                let weight = centroids_data.get(j+1);
-                use tdigest::Centroid;
-                all_centroids.push(Centroid::new(mean, weight));
+                all_centroids.push((centroids_data.get(j), centroids_data.get(j + 1)));
             }
         }
 
-        // Re-compress by creating a new TDigest from all centroids
-        let digest = Td::new(all_centroids, total_sum, total_count as f64, global_max, global_min, compression as usize);
+        let digest = build_digest(
+            compression as usize,
+            all_centroids,
+            total_sum,
+            total_count as f64,
+            global_min,
+            global_max,
+        );
 
         Ok(Self {
             digest,
@@ -241,6 +672,7 @@ impl Crdt for TDigest {
             max: if total_count > 0 { global_max } else { f64::NEG_INFINITY },
             sum: total_sum,
             count: total_count,
+            pending: Vec::new(),
         })
     }
 
@@ -252,45 +684,46 @@ impl Crdt for TDigest {
         self.count == 0
     }
 
+    fn is_identity(&self) -> bool {
+        self.count == 0
+    }
+
+    fn merge(&mut self, other: &Self) {
+        self.merge(other);
+    }
+
+    fn merge_bytes(&mut self, bytes: &[u8]) -> Result<(), CrdtError> {
+        let other = Self::from_capnp_bytes(bytes)?;
+        self.merge(&other);
+        Ok(())
+    }
+
     fn to_capnp_bytes(&self) -> Vec<u8> {
         let mut message = Builder::new_default();
+        self.build_capnp(&mut message).unwrap();
+        let mut data = Vec::new();
+        serialize::write_message(&mut data, &message).unwrap();
+        data
+    }
+
+    fn build_capnp(&self, message: &mut Builder<HeapAllocator>) -> Result<(), CrdtError> {
         let mut root = message.init_root::<tdigest_capnp::t_digest::Builder>();
         
-        root.set_compression(self.digest.max_size() as u32);
+        root.set_compression(self.digest().max_size() as u32);
         root.set_sample_count(self.count);
         root.set_min(self.min);
         root.set_max(self.max);
         root.set_sum(self.sum);
         
-        // WORKAROUND: Extract centroids via serialization.
-        //
-        // The `tdigest` crate (v0.2) encapsulates the `centroids` vector and does not expose
-        // a public accessor method. However, it implements `Serialize`.
-        //
-        // To persist the full state (including the distribution data), we serialize the
-        // internal `digest` to a `serde_json::Value`, extract the "centroids" array,
-        // and populate the Cap'n Proto list.
-        //
-        // This is less efficient than direct access but necessary to ensure data persistence
-        // and correct merging after deserialization.
-        
-        let val = serde_json::to_value(&self.digest).unwrap();
-        if let Some(centroids_array) = val.get("centroids").and_then(|v| v.as_array()) {
-             let mut centroids_list = root.init_centroids((centroids_array.len() * 2) as u32);
-             for (i, c_val) in centroids_array.iter().enumerate() {
-                 let mean = c_val.get("mean").and_then(|v| v.as_f64()).unwrap_or(0.0);
-                 let weight = c_val.get("weight").and_then(|v| v.as_f64()).unwrap_or(0.0);
-                 centroids_list.set((i * 2) as u32, mean);
-                 centroids_list.set((i * 2 + 1) as u32, weight);
-             }
-        } else {
-            // Fallback or empty
-            root.init_centroids(0);
+        // Centroids are stored flattened as [mean1, weight1, mean2, weight2, ...].
+        // See `centroids` for why they are extracted via serde.
+        let centroids = self.centroids();
+        let mut centroids_list = root.init_centroids((centroids.len() * 2) as u32);
+        for (i, (mean, weight)) in centroids.iter().enumerate() {
+            centroids_list.set((i * 2) as u32, *mean);
+            centroids_list.set((i * 2 + 1) as u32, *weight);
         }
-
-        let mut data = Vec::new();
-        serialize::write_message(&mut data, &message).unwrap();
-        data
+        Ok(())
     }
 }
 
@@ -4,7 +4,7 @@
 use crate::topk_capnp;
 use crate::traits::{Crdt, CrdtError, CrdtReader};
 use crate::probabilistic::count_min_sketch::CountMinSketch;
-use capnp::message::{Builder, ReaderOptions};
+use capnp::message::{Builder, HeapAllocator, ReaderOptions};
 use capnp::serialize;
 use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
@@ -78,6 +78,15 @@ impl TopK {
         }
     }
 
+    /// Creates a TopK whose sketch is sized from error bounds.
+    ///
+    /// See [`CountMinSketch::with_error_bounds`] for how `epsilon` and `delta` map to
+    /// the sketch dimensions.
+    pub fn with_error_bounds(k: usize, epsilon: f64, delta: f64) -> Result<Self, CrdtError> {
+        let (width, depth) = CountMinSketch::dimensions_for(epsilon, delta)?;
+        Ok(Self::new(k, width, depth))
+    }
+
     pub fn increment(&mut self, item: &str, count: u64) {
         self.sketch.increment(item, count);
         let freq = self.sketch.estimate(item);
@@ -116,6 +125,16 @@ impl TopK {
         // Sorting is deferred until `top_k()` is called.
     }
 
+    /// Returns the estimated frequency of `item`, even if it is not in the top-K list.
+    pub fn estimate(&self, item: &str) -> u64 {
+        self.sketch.estimate(item)
+    }
+
+    /// Read-only access to the underlying Count-Min Sketch.
+    pub fn sketch(&self) -> &CountMinSketch {
+        &self.sketch
+    }
+
     pub fn top_k(&self) -> Vec<(String, u64)> {
         let mut result: Vec<_> = self
             .heap
@@ -123,11 +142,20 @@ impl TopK {
             .map(|item| (item.key.clone(), item.frequency))
             .collect();
         // Sort by frequency descending
-        result.sort_by(|a, b| b.1.cmp(&a.1));
+        result.sort_by_key(|item| std::cmp::Reverse(item.1));
         result
     }
 
+    /// Merges another TopK into this one.
+    ///
+    /// Replicas configured with different `k` merge to the larger one, so no tracked item
+    /// is dropped merely because the local `k` is smaller. Use [`TopK::merge_strict`] to
+    /// reject a mismatch instead.
+    ///
+    /// # Panics
+    /// Panics if the sketch dimensions differ.
     pub fn merge(&mut self, other: &Self) {
+        self.k = self.k.max(other.k);
         self.sketch.merge(&other.sketch);
 
         // Rebuild heap from union of top-K sets
@@ -145,11 +173,30 @@ impl TopK {
         }
 
         // Sort by frequency descending to pick top K
-        all_items.sort_by(|a, b| b.frequency.cmp(&a.frequency));
+        all_items.sort_by_key(|item| std::cmp::Reverse(item.frequency));
         all_items.truncate(self.k);
         self.heap = all_items;
     }
 
+    /// Merges another TopK, returning `CrdtError::Merge` instead of adapting when `k` or
+    /// the sketch dimensions differ.
+    pub fn merge_strict(&mut self, other: &Self) -> Result<(), CrdtError> {
+        if self.k != other.k {
+            return Err(CrdtError::Merge(format!(
+                "TopK k mismatch: {} vs {}",
+                self.k, other.k
+            )));
+        }
+        self.sketch.check_dimensions(&other.sketch)?;
+        self.merge(other);
+        Ok(())
+    }
+
+    /// The number of heavy hitters tracked.
+    pub fn k(&self) -> usize {
+        self.k
+    }
+
     pub fn is_empty(&self) -> bool {
         self.heap.is_empty() && self.sketch.matrix.iter().all(|row| row.iter().all(|&x| x == 0))
     }
@@ -172,6 +219,10 @@ impl<'a> TopKReader<'a> {
 }
 
 impl<'a> CrdtReader<'a> for TopKReader<'a> {
+    fn from_bytes(bytes: &'a [u8]) -> Self {
+        Self::new(bytes)
+    }
+
     fn is_empty(&self) -> Result<bool, CrdtError> {
         let message_reader = serialize::read_message(
             self.bytes,
@@ -189,6 +240,10 @@ impl<'a> CrdtReader<'a> for TopKReader<'a> {
 impl Crdt for TopK {
     type Reader<'a> = TopKReader<'a>;
 
+    fn from_capnp_bytes(bytes: &[u8]) -> Result<Self, CrdtError> {
+        TopK::from_capnp_bytes(bytes)
+    }
+
     fn validate(&self) -> Result<(), CrdtError> {
         if self.k == 0 {
             return Err(CrdtError::Validation("K must be positive".into()));
@@ -209,52 +264,74 @@ impl Crdt for TopK {
         
         for reader in &readers[1..] {
             let other = Self::from_capnp_bytes(reader.bytes)?;
+            merged.sketch.check_dimensions(&other.sketch)?;
             merged.merge(&other);
         }
 
         Ok(merged)
     }
 
+    fn merge(&mut self, other: &Self) {
+        self.merge(other);
+    }
+
+    fn merge_bytes(&mut self, bytes: &[u8]) -> Result<(), CrdtError> {
+        let other = Self::from_capnp_bytes(bytes)?;
+        self.ensure_mergeable(&other)?;
+        self.merge(&other);
+        Ok(())
+    }
+
+    fn ensure_mergeable(&self, other: &Self) -> Result<(), CrdtError> {
+        self.sketch.check_dimensions(&other.sketch)
+    }
+
     fn to_capnp_bytes(&self) -> Vec<u8> {
         let mut message = Builder::new_default();
-        {
-            let mut topk_builder = message.init_root::<topk_capnp::top_k::Builder>();
+        self.build_capnp(&mut message).unwrap();
+        let mut buf = Vec::new();
+        serialize::write_message(&mut buf, &message)
+            .expect("TopK Cap'n Proto serialization should not fail");
 
-            topk_builder.set_k(self.k as u32);
-            topk_builder.set_width(self.sketch.width as u32);
-            topk_builder.set_depth(self.sketch.depth as u32);
+        buf
+    }
 
-            // Serialize sketch counters
-            let counters_len = self.sketch.width * self.sketch.depth;
-            let mut counters_builder = topk_builder.reborrow().init_counters(counters_len as u32);
-            
-            let mut idx = 0;
-            for row in &self.sketch.matrix {
-                for &val in row {
-                    counters_builder.set(idx, val);
-                    idx += 1;
-                }
-            }
+    fn build_capnp(&self, message: &mut Builder<HeapAllocator>) -> Result<(), CrdtError> {
+        let mut topk_builder = message.init_root::<topk_capnp::top_k::Builder>();
 
-            // Serialize heap
-            let mut keys_builder = topk_builder
-                .reborrow()
-                .init_top_keys(self.heap.len() as u32);
-            for (i, item) in self.heap.iter().enumerate() {
-                keys_builder.set(i as u32, item.key.as_str().into());
-            }
+        topk_builder.set_k(self.k as u32);
+        topk_builder.set_width(self.sketch.width as u32);
+        topk_builder.set_depth(self.sketch.depth as u32);
 
-            let mut freqs_builder = topk_builder.init_top_frequencies(self.heap.len() as u32);
-            for (i, item) in self.heap.iter().enumerate() {
-                freqs_builder.set(i as u32, item.frequency);
+        // Serialize sketch counters
+        let counters_len = self.sketch.width * self.sketch.depth;
+        let mut counters_builder = topk_builder.reborrow().init_counters(counters_len as u32);
+            
+        let mut idx = 0;
+        for row in &self.sketch.matrix {
+            for &val in row {
+                counters_builder.set(idx, val);
+                idx += 1;
             }
         }
 
-        let mut buf = Vec::new();
-        serialize::write_message(&mut buf, &message)
-            .expect("TopK Cap'n Proto serialization should not fail");
+        // Serialize heap in (frequency desc, key asc) order so equal states encode
+        // identically regardless of insertion order
+        let mut heap: Vec<&HeapItem> = self.heap.iter().collect();
+        heap.sort();
 
-        buf
+        let mut keys_builder = topk_builder
+            .reborrow()
+            .init_top_keys(heap.len() as u32);
+        for (i, item) in heap.iter().enumerate() {
+            keys_builder.set(i as u32, item.key.as_str().into());
+        }
+
+        let mut freqs_builder = topk_builder.init_top_frequencies(heap.len() as u32);
+        for (i, item) in heap.iter().enumerate() {
+            freqs_builder.set(i as u32, item.frequency);
+        }
+        Ok(())
     }
 }
 
@@ -279,10 +356,10 @@ impl TopK {
 
         let mut matrix = vec![vec![0; width]; depth];
         let mut idx = 0;
-        for r in 0..depth {
-            for c in 0..width {
+        for row in matrix.iter_mut() {
+            for cell in row.iter_mut() {
                 if idx < counters_reader.len() {
-                    matrix[r][c] = counters_reader.get(idx);
+                    *cell = counters_reader.get(idx);
                     idx += 1;
                 }
             }
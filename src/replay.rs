@@ -0,0 +1,193 @@
+// Copyright (c) 2026 Adrian Robinson. All rights reserved.
+// Licensed under the MIT License. See LICENSE file in the project root for full license information.
+
+//! Operation logging and replay, for root-causing divergence.
+//!
+//! An [`OpLog`] wraps a CRDT and records every mutating call as a `(Dot, Op)` entry. The
+//! dot is the executing node's clock entry after the operation; operations that do not
+//! advance the clock (removals) carry the node's current counter. [`replay`] rebuilds the
+//! state from such a log and checks at every step that the operation lands on the
+//! recorded dot, so the first entry where a replica's history stops making sense is
+//! reported rather than silently absorbed.
+//!
+//! Supported for [`GCounter`], [`ORSet`] and [`LWWMap`].
+
+pub use crate::dot::Dot;
+use crate::sync::Causal;
+use crate::traits::{Crdt, CrdtError};
+use crate::{GCounter, LWWMap, ORSet};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::hash::Hash;
+
+/// CRDTs whose mutations can be described as replayable operations.
+pub trait Replayable: Crdt + Causal + Default {
+    /// A single mutating call.
+    type Op: Clone + Debug;
+
+    /// Applies `op` on behalf of `node_id`.
+    fn apply_op(&mut self, node_id: &str, op: &Self::Op);
+}
+
+/// Operations on a [`GCounter`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GCounterOp {
+    Increment(i64),
+}
+
+impl Replayable for GCounter {
+    type Op = GCounterOp;
+
+    fn apply_op(&mut self, node_id: &str, op: &Self::Op) {
+        match op {
+            GCounterOp::Increment(delta) => self.increment(node_id, *delta),
+        }
+    }
+}
+
+/// Operations on an [`ORSet`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ORSetOp<T> {
+    Insert(T),
+    /// Applied with [`ORSet::remove_by`], so the set's conflict policy is honoured.
+    Remove(T),
+}
+
+impl<T> Replayable for ORSet<T>
+where
+    T: Clone + Eq + Hash + Ord + Debug + Serialize + DeserializeOwned + Default + Send + Sync + 'static,
+{
+    type Op = ORSetOp<T>;
+
+    fn apply_op(&mut self, node_id: &str, op: &Self::Op) {
+        match op {
+            ORSetOp::Insert(element) => self.insert(node_id, element.clone()),
+            ORSetOp::Remove(element) => self.remove_by(node_id, element),
+        }
+    }
+}
+
+/// Operations on an [`LWWMap`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LWWMapOp<K, V> {
+    Insert { key: K, value: V, timestamp: u64 },
+    Remove(K),
+}
+
+impl<K, V> Replayable for LWWMap<K, V>
+where
+    K: Clone + Eq + Hash + Ord + Debug + Serialize + DeserializeOwned + Send + Sync + 'static,
+    V: Clone + Ord + Debug + Serialize + DeserializeOwned + Send + Sync + 'static,
+{
+    type Op = LWWMapOp<K, V>;
+
+    fn apply_op(&mut self, node_id: &str, op: &Self::Op) {
+        match op {
+            LWWMapOp::Insert {
+                key,
+                value,
+                timestamp,
+            } => self.insert(node_id, key.clone(), value.clone(), *timestamp),
+            LWWMapOp::Remove(key) => self.remove(key),
+        }
+    }
+}
+
+/// A CRDT with an attached log of the operations applied to it.
+///
+/// # Example
+///
+/// ```
+/// use crdt_data_types::replay::{replay, GCounterOp, OpLog};
+/// use crdt_data_types::GCounter;
+///
+/// let mut log = OpLog::attach(GCounter::new());
+/// log.apply("node_a", GCounterOp::Increment(2));
+/// log.apply("node_b", GCounterOp::Increment(3));
+///
+/// let rebuilt: GCounter = replay(log.entries()).unwrap();
+/// assert_eq!(rebuilt.counters, log.state().counters);
+/// ```
+#[derive(Debug, Clone)]
+pub struct OpLog<C: Replayable> {
+    state: C,
+    entries: Vec<(Dot, C::Op)>,
+}
+
+impl<C: Replayable> OpLog<C> {
+    /// Starts logging operations applied to `state`.
+    pub fn attach(state: C) -> Self {
+        Self {
+            state,
+            entries: Vec::new(),
+        }
+    }
+
+    /// Applies `op` on behalf of `node_id`, records it, and returns its dot.
+    pub fn apply(&mut self, node_id: &str, op: C::Op) -> Dot {
+        self.state.apply_op(node_id, &op);
+        let dot = Dot::new(node_id, counter_of(&self.state, node_id));
+        self.entries.push((dot.clone(), op));
+        dot
+    }
+
+    /// The current state.
+    pub fn state(&self) -> &C {
+        &self.state
+    }
+
+    /// The recorded operations, in the order they were applied.
+    pub fn entries(&self) -> &[(Dot, C::Op)] {
+        &self.entries
+    }
+
+    /// Detaches the log, returning the state and the recorded operations.
+    pub fn into_parts(self) -> (C, Vec<(Dot, C::Op)>) {
+        (self.state, self.entries)
+    }
+}
+
+/// Rebuilds a CRDT by replaying `ops` onto an empty state.
+///
+/// See [`replay_onto`].
+pub fn replay<C: Replayable>(ops: &[(Dot, C::Op)]) -> Result<C, CrdtError> {
+    replay_onto(C::default(), ops)
+}
+
+/// Rebuilds a CRDT by replaying `ops` onto `base`, the state the log was attached to.
+///
+/// Operations are applied in the given order, which must be causal: each node's dots
+/// must be non-decreasing. After every operation the executing node's clock must equal
+/// the recorded dot.
+///
+/// # Errors
+/// Returns `CrdtError::Validation` naming the first entry that is out of causal order or
+/// whose replay does not reproduce its dot.
+pub fn replay_onto<C: Replayable>(mut base: C, ops: &[(Dot, C::Op)]) -> Result<C, CrdtError> {
+    let mut last_seen: HashMap<&str, u64> = HashMap::new();
+    for (idx, (Dot { node_id, counter }, op)) in ops.iter().enumerate() {
+        let last = last_seen.entry(node_id.as_str()).or_insert(0);
+        if *counter < *last {
+            return Err(CrdtError::Validation(format!(
+                "Entry {} ({}, {}) follows dot ({}, {}) out of causal order",
+                idx, node_id, counter, node_id, last
+            )));
+        }
+        *last = *counter;
+
+        base.apply_op(node_id, op);
+        let actual = counter_of(&base, node_id);
+        if actual != *counter {
+            return Err(CrdtError::Validation(format!(
+                "Entry {} {:?} replayed to dot ({}, {}), recorded as ({}, {})",
+                idx, op, node_id, actual, node_id, counter
+            )));
+        }
+    }
+    Ok(base)
+}
+
+fn counter_of<C: Causal>(state: &C, node_id: &str) -> u64 {
+    state.vclock().clocks.get(node_id).map_or(0, |(counter, _)| *counter)
+}
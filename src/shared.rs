@@ -0,0 +1,95 @@
+// Copyright (c) 2026 Adrian Robinson. All rights reserved.
+// Licensed under the MIT License. See LICENSE file in the project root for full license information.
+
+use crate::frozen::FrozenView;
+use crate::traits::{Crdt, CrdtError};
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+/// Shared: A thread-safe handle around a live CRDT value.
+///
+/// Wraps the value in an `Arc<RwLock<_>>` so applications don't need to re-implement
+/// the locking around merge-then-broadcast. Cloning a `Shared` is cheap and yields
+/// another handle to the same value.
+///
+/// Lock poisoning is ignored, so later callers keep working after a panic in another
+/// thread. That panic may have interrupted a mutation halfway, leaving a partially
+/// applied update behind; call `Crdt::validate` on the value if that matters to you.
+///
+/// # Example
+///
+/// ```
+/// use crdt_data_types::{Crdt, GCounter, Shared};
+///
+/// let shared = Shared::new(GCounter::new());
+///
+/// let mut remote = GCounter::new();
+/// remote.increment("node_b", 5);
+/// shared.merge_bytes(&remote.to_capnp_bytes()).unwrap();
+///
+/// assert_eq!(shared.read(|gc| gc.value()), 5);
+/// ```
+#[derive(Debug, Default)]
+pub struct Shared<C: Crdt> {
+    inner: Arc<RwLock<C>>,
+}
+
+impl<C: Crdt> Clone for Shared<C> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+impl<C: Crdt> Shared<C> {
+    /// Wraps an existing CRDT value.
+    pub fn new(value: C) -> Self {
+        Self {
+            inner: Arc::new(RwLock::new(value)),
+        }
+    }
+
+    /// Merges a serialized Cap'n Proto blob into the shared value.
+    ///
+    /// The blob is decoded before the write lock is taken; the lock is held only for the
+    /// in-memory merge. On a decode error the value is left unchanged.
+    pub fn merge_bytes(&self, bytes: &[u8]) -> Result<(), CrdtError> {
+        let incoming = C::from_capnp_bytes(bytes)?;
+        self.write_guard().merge(&incoming);
+        Ok(())
+    }
+
+    /// Serializes the current value to Cap'n Proto bytes, e.g. for broadcasting.
+    pub fn snapshot(&self) -> Vec<u8> {
+        self.read_guard().to_capnp_bytes()
+    }
+
+    /// Takes an immutable snapshot, holding the read lock only while serializing.
+    pub fn freeze(&self) -> FrozenView<C> {
+        self.read_guard().freeze()
+    }
+
+    /// Runs `f` against the current value while holding the read lock.
+    pub fn read<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(&C) -> R,
+    {
+        f(&self.read_guard())
+    }
+
+    /// Runs `f` against the current value while holding the write lock (e.g. for local updates).
+    pub fn update<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(&mut C) -> R,
+    {
+        f(&mut self.write_guard())
+    }
+
+    fn read_guard(&self) -> RwLockReadGuard<'_, C> {
+        self.inner.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn write_guard(&self) -> RwLockWriteGuard<'_, C> {
+        self.inner.write().unwrap_or_else(|e| e.into_inner())
+    }
+}
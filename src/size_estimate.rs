@@ -0,0 +1,70 @@
+// Copyright (c) 2026 Adrian Robinson. All rights reserved.
+// Licensed under the MIT License. See LICENSE file in the project root for full license information.
+
+//! Helpers for approximating Cap'n Proto message sizes from in-memory structures.
+//!
+//! Cap'n Proto lays everything out in 8-byte words: structs occupy their data and pointer
+//! sections, lists carry a tag word, and blobs are padded to a word boundary.
+
+/// Bytes for a single-segment message header (segment table) plus the root pointer.
+pub(crate) const MESSAGE_OVERHEAD: usize = 16;
+
+/// Bytes for a pointer or list tag word.
+pub(crate) const WORD: usize = 8;
+
+/// Bytes for a struct with `data_words` data words and `pointers` pointers.
+pub(crate) const fn struct_size(data_words: usize, pointers: usize) -> usize {
+    (data_words + pointers) * WORD
+}
+
+/// Bytes for a `Data` blob of `len` bytes, padded to a word boundary.
+pub(crate) const fn data_size(len: usize) -> usize {
+    len.div_ceil(WORD) * WORD
+}
+
+/// Bytes for a `Text` value of `len` bytes (includes the NUL terminator).
+pub(crate) const fn text_size(len: usize) -> usize {
+    data_size(len + 1)
+}
+
+/// Length of `value` serialized as JSON, counted without buffering the output.
+pub(crate) fn json_len<T: serde::Serialize + ?Sized>(value: &T) -> usize {
+    struct Counter(usize);
+
+    impl std::io::Write for Counter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0 += buf.len();
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let mut counter = Counter(0);
+    match serde_json::to_writer(&mut counter, value) {
+        Ok(()) => counter.0,
+        Err(_) => 0,
+    }
+}
+
+/// Sums per-reader entry counts, failing as soon as the total exceeds `max_entries`.
+///
+/// Backs the `merge_from_readers_bounded` methods.
+pub(crate) fn check_entry_budget(
+    counts: impl Iterator<Item = Result<usize, crate::traits::CrdtError>>,
+    max_entries: usize,
+) -> Result<(), crate::traits::CrdtError> {
+    let mut total = 0usize;
+    for count in counts {
+        total = total.saturating_add(count?);
+        if total > max_entries {
+            return Err(crate::traits::CrdtError::SizeLimitExceeded(format!(
+                "Readers hold more than {} entries",
+                max_entries
+            )));
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,291 @@
+// Copyright (c) 2026 Adrian Robinson. All rights reserved.
+// Licensed under the MIT License. See LICENSE file in the project root for full license information.
+
+//! Helpers for the replication layer.
+//!
+//! [`Coalescer`] rate-limits outgoing broadcasts: incoming states are joined, and at most
+//! once per interval the part not yet broadcast is released as a delta (see
+//! [`DeltaSince`]). A burst of small updates therefore costs one message instead of one
+//! per update.
+
+use crate::merge_ext::MergeExt;
+use crate::traits::Crdt;
+use crate::vector_clock::VectorClock;
+use crate::{
+    DisableRegister, FWWRegister, GCounter, GSet, LWWMap, LWWRegister, LWWSet, MVRegister, ORMap,
+    ORSet, PNCounter,
+};
+use std::hash::Hash;
+use std::time::{Duration, Instant};
+
+/// CRDTs that carry a vector clock summarizing their causal history.
+pub trait Causal {
+    /// The clock summarizing every update reflected in this state.
+    fn vclock(&self) -> &VectorClock;
+}
+
+/// Write access to the clock, for bridge code that has to record an event the state's own
+/// operations did not tick.
+pub(crate) trait CausalMut: Causal {
+    fn vclock_mut(&mut self) -> &mut VectorClock;
+}
+
+macro_rules! impl_causal {
+    ($($ty:ty => [$($generics:tt)*]),* $(,)?) => {
+        $(
+            impl<$($generics)*> Causal for $ty {
+                fn vclock(&self) -> &VectorClock {
+                    &self.vclock
+                }
+            }
+
+            impl<$($generics)*> CausalMut for $ty {
+                fn vclock_mut(&mut self) -> &mut VectorClock {
+                    &mut self.vclock
+                }
+            }
+        )*
+    };
+}
+
+impl_causal!(
+    GCounter => [],
+    PNCounter => [],
+    DisableRegister<T> => [T],
+    FWWRegister<T> => [T],
+    LWWRegister<T> => [T],
+    GSet<T> => [T: Eq + Hash + Ord],
+    ORSet<T> => [T: Eq + Hash + Ord],
+    LWWSet<T> => [T: Eq + Hash + Ord],
+    MVRegister<T> => [T: Eq + Hash],
+    LWWMap<K, V> => [K: Eq + Hash + Ord, V],
+    ORMap<K, V> => [K: Eq + Hash + Ord, V: Eq + Hash + Ord],
+);
+
+/// States that can extract the part of themselves a replica holding `base` is missing.
+pub trait DeltaSince: Sized {
+    /// Returns a delta-state that, merged into `base` (or anything that has seen `base`),
+    /// gives the same result as merging `self`.
+    fn delta_since(&self, base: &Self) -> Self;
+}
+
+/// The entries of `clock` that advanced past `base`: the writers a delta has to answer for.
+fn advanced_clock(clock: &VectorClock, base: &VectorClock) -> VectorClock {
+    let mut delta = VectorClock::new();
+    for (node, entry) in &clock.clocks {
+        if !base.covers(node, entry.0) {
+            delta.clocks.insert(node.clone(), *entry);
+        }
+    }
+    delta
+}
+
+/// The entries of a sorted `(key, value)` list that are absent from or differ in `base`.
+fn changed_entries<K: Ord + Clone, V: PartialEq + Clone>(
+    entries: &[(K, V)],
+    base: &[(K, V)],
+) -> Vec<(K, V)> {
+    entries
+        .iter()
+        .filter(|(key, value)| {
+            base.binary_search_by(|(k, _)| k.cmp(key))
+                .map_or(true, |idx| base[idx].1 != *value)
+        })
+        .cloned()
+        .collect()
+}
+
+impl DeltaSince for GCounter {
+    /// The counters that grew, with their totals (the join takes the per-node maximum).
+    fn delta_since(&self, base: &Self) -> Self {
+        let mut delta = GCounter::new();
+        delta.counters = changed_entries(&self.counters, &base.counters);
+        delta.vclock = advanced_clock(&self.vclock, &base.vclock);
+        delta
+    }
+}
+
+impl DeltaSince for PNCounter {
+    fn delta_since(&self, base: &Self) -> Self {
+        let mut delta = PNCounter::new();
+        delta.positive = self.positive.delta_since(&base.positive);
+        delta.negative = self.negative.delta_since(&base.negative);
+        delta.vclock = advanced_clock(&self.vclock, &base.vclock);
+        delta
+    }
+}
+
+impl<T: Clone + Eq + Hash + Ord> DeltaSince for GSet<T> {
+    /// The elements `base` lacks.
+    fn delta_since(&self, base: &Self) -> Self {
+        let mut delta = GSet::new();
+        delta.elements = self
+            .elements
+            .iter()
+            .filter(|element| base.elements.binary_search(element).is_err())
+            .cloned()
+            .collect();
+        delta.vclock = advanced_clock(&self.vclock, &base.vclock);
+        delta
+    }
+}
+
+impl<T: Clone + Eq + Hash + Ord> DeltaSince for LWWSet<T> {
+    /// The add and remove entries that are new or newer than in `base`.
+    fn delta_since(&self, base: &Self) -> Self {
+        let mut delta = LWWSet::new();
+        delta.add_set = changed_entries(&self.add_set, &base.add_set);
+        delta.remove_set = changed_entries(&self.remove_set, &base.remove_set);
+        delta.vclock = advanced_clock(&self.vclock, &base.vclock);
+        delta.tie_break = self.tie_break;
+        delta.tie_bias = self.tie_bias;
+        delta
+    }
+}
+
+/// Types whose merge infers removals or overwrites from the whole state (the vclock, or a
+/// single winning value): the delta since any `base` is the full state.
+macro_rules! impl_full_state_delta {
+    ($($ty:ty => [$($generics:tt)*]),* $(,)?) => {
+        $(
+            impl<$($generics)*> DeltaSince for $ty {
+                fn delta_since(&self, _base: &Self) -> Self {
+                    self.clone()
+                }
+            }
+        )*
+    };
+}
+
+impl_full_state_delta!(
+    DisableRegister<T> => [T: Clone],
+    FWWRegister<T> => [T: Clone],
+    LWWRegister<T> => [T: Clone],
+    ORSet<T> => [T: Clone + Eq + Hash + Ord],
+    MVRegister<T> => [T: Clone + Eq + Hash],
+    LWWMap<K, V> => [K: Clone + Eq + Hash + Ord, V: Clone],
+    ORMap<K, V> => [K: Clone + Eq + Hash + Ord, V: Clone + Eq + Hash + Ord],
+);
+
+/// Buffers incoming states and emits one coalesced delta at most every `interval`.
+///
+/// Offered states are joined into the coalescer's view of the replica. Each emission is
+/// the [`DeltaSince`] of that view against the previous emission, so only what changed
+/// since the last broadcast goes out. The coalescer tracks a watermark clock of
+/// everything it has emitted and drops offers the watermark already covers, so
+/// re-delivered or stale states do not trigger a broadcast.
+///
+/// # Example
+///
+/// ```
+/// use crdt_data_types::sync::Coalescer;
+/// use crdt_data_types::GCounter;
+/// use std::time::{Duration, Instant};
+///
+/// let mut coalescer = Coalescer::new(Duration::from_millis(50));
+/// let mut counter = GCounter::new();
+/// for _ in 0..10 {
+///     counter.increment("node_a", 1);
+///     coalescer.offer(&counter);
+/// }
+///
+/// let delta = coalescer.poll_at(Instant::now()).unwrap();
+/// assert_eq!(delta.value(), 10);
+/// assert!(coalescer.poll_at(Instant::now()).is_none());
+/// ```
+#[derive(Debug, Clone)]
+pub struct Coalescer<C> {
+    interval: Duration,
+    /// Join of every accepted offer.
+    state: Option<C>,
+    /// `state` as of the last emission.
+    emitted: Option<C>,
+    pending: bool,
+    last_emit: Option<Instant>,
+    watermark: VectorClock,
+}
+
+impl<C: Crdt + MergeExt + Causal + DeltaSince> Coalescer<C> {
+    /// Creates a coalescer that emits at most once per `interval`.
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            state: None,
+            emitted: None,
+            pending: false,
+            last_emit: None,
+            watermark: VectorClock::new(),
+        }
+    }
+
+    /// Buffers a state for the next emission.
+    ///
+    /// Returns false if the state was already covered by what has been emitted.
+    pub fn offer(&mut self, update: &C) -> bool {
+        if self.covered(update.vclock()) {
+            return false;
+        }
+        self.state = Some(match self.state.take() {
+            Some(state) => state.merged(update),
+            None => update.clone(),
+        });
+        self.pending = true;
+        true
+    }
+
+    /// Emits the coalesced delta if one is pending and `interval` has elapsed since the
+    /// previous emission (the first emission is never delayed).
+    pub fn poll_at(&mut self, now: Instant) -> Option<C> {
+        let due = self
+            .last_emit
+            .is_none_or(|last| now.saturating_duration_since(last) >= self.interval);
+        if due {
+            self.emit(now)
+        } else {
+            None
+        }
+    }
+
+    /// [`Coalescer::poll_at`] with the current time.
+    pub fn poll(&mut self) -> Option<C> {
+        self.poll_at(Instant::now())
+    }
+
+    /// Emits any pending delta immediately, ignoring the interval.
+    pub fn flush(&mut self) -> Option<C> {
+        self.emit(Instant::now())
+    }
+
+    /// Returns true if a delta is waiting to be emitted.
+    pub fn has_pending(&self) -> bool {
+        self.pending
+    }
+
+    /// Clock covering every delta emitted so far.
+    pub fn watermark(&self) -> &VectorClock {
+        &self.watermark
+    }
+
+    fn emit(&mut self, now: Instant) -> Option<C> {
+        if !self.pending {
+            return None;
+        }
+        let state = self.state.as_ref()?;
+        let delta = match &self.emitted {
+            Some(emitted) => state.delta_since(emitted),
+            None => state.clone(),
+        };
+        self.watermark.merge(state.vclock());
+        self.emitted = Some(state.clone());
+        self.pending = false;
+        self.last_emit = Some(now);
+        Some(delta)
+    }
+
+    fn covered(&self, vclock: &VectorClock) -> bool {
+        vclock
+            .clocks
+            .iter()
+            .all(|(node, (counter, _))| self.watermark.covers(node, *counter))
+    }
+}
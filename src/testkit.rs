@@ -0,0 +1,265 @@
+// Copyright (c) 2026 Adrian Robinson. All rights reserved.
+// Licensed under the MIT License. See LICENSE file in the project root for full license information.
+
+//! Convergence testing for CRDTs and compositions built on them.
+//!
+//! [`assert_converges`] scatters a list of operations across N replicas in randomized
+//! interleavings, gossips between random pairs along the way, and asserts every replica
+//! ends up identical once all states have been exchanged. Operations are described by
+//! implementing [`Op`] for the CRDT under test.
+//!
+//! [`assert_serialize_merge_equivalent`] checks that the zero-copy merge of two serialized
+//! states agrees with the in-memory merge, for use in property tests of any `Crdt`.
+//!
+//! [`assert_would_converge`] merges two states both ways and, if the results differ,
+//! reports which fields, keys or elements disagree.
+//!
+//! Enabled with the `testkit` feature.
+
+use crate::merge_ext::MergeExt;
+use crate::traits::{Crdt, CrdtReader};
+use serde_json::Value;
+use std::fmt::Debug;
+
+/// Number of randomized interleavings tried by [`assert_converges`].
+pub const DEFAULT_ROUNDS: u64 = 32;
+
+/// An operation that can be applied to a replica of `C`.
+pub trait Op<C> {
+    /// Applies the operation on behalf of `node_id`, the replica executing it.
+    fn apply(&self, replica: &mut C, node_id: &str);
+}
+
+/// Asserts that `replicas` converge under random interleavings of `ops`.
+///
+/// Runs [`DEFAULT_ROUNDS`] seeded rounds; see [`assert_converges_seeded`].
+///
+/// # Example
+///
+/// ```
+/// use crdt_data_types::testkit::{assert_converges, Op};
+/// use crdt_data_types::GCounter;
+///
+/// struct Inc(i64);
+///
+/// impl Op<GCounter> for Inc {
+///     fn apply(&self, replica: &mut GCounter, node_id: &str) {
+///         replica.increment(node_id, self.0);
+///     }
+/// }
+///
+/// assert_converges(vec![GCounter::new(); 3], vec![Inc(1), Inc(2), Inc(3)]);
+/// ```
+pub fn assert_converges<C, O>(replicas: Vec<C>, ops: Vec<O>)
+where
+    C: Crdt + MergeExt + PartialEq + Debug,
+    O: Op<C>,
+{
+    for seed in 0..DEFAULT_ROUNDS {
+        assert_converges_seeded(replicas.clone(), &ops, seed);
+    }
+}
+
+/// Runs a single interleaving chosen by `seed` and returns the converged state.
+///
+/// Each operation is applied at a randomly chosen replica, as node `replica_<index>`.
+/// Between operations, a random replica occasionally merges in the state of another.
+/// After the last operation every replica is merged with the join of all replicas, and
+/// the results must be equal and pass `validate()`.
+///
+/// # Panics
+///
+/// Panics if `replicas` is empty, if a replica fails validation, or if the replicas do
+/// not converge. The panic message includes the seed so a failure can be replayed.
+pub fn assert_converges_seeded<C, O>(mut replicas: Vec<C>, ops: &[O], seed: u64) -> C
+where
+    C: Crdt + MergeExt + PartialEq + Debug,
+    O: Op<C>,
+{
+    assert!(!replicas.is_empty(), "assert_converges needs at least one replica");
+    let n = replicas.len();
+    let node_ids: Vec<String> = (0..n).map(|i| format!("replica_{i}")).collect();
+    let mut rng = SplitMix64(seed);
+
+    for op in ops {
+        let target = rng.below(n);
+        op.apply(&mut replicas[target], &node_ids[target]);
+
+        // Partial gossip: roughly every other op, one replica pulls from another
+        if n > 1 && rng.next() & 1 == 0 {
+            let (to, from) = (rng.below(n), rng.below(n));
+            if to != from {
+                let source = replicas[from].clone();
+                replicas[to] = replicas[to].clone().merged(&source);
+            }
+        }
+    }
+
+    // Full gossip: everyone receives the join of every replica
+    let joined = replicas
+        .iter()
+        .skip(1)
+        .fold(replicas[0].clone(), |acc, replica| acc.merged(replica));
+    let converged: Vec<C> = replicas
+        .into_iter()
+        .map(|replica| replica.merged(&joined))
+        .collect();
+
+    for (i, replica) in converged.iter().enumerate() {
+        if let Err(e) = replica.validate() {
+            panic!("seed {seed}: replica {i} failed validation: {e}");
+        }
+        assert_eq!(
+            replica, &converged[0],
+            "seed {seed}: replica {i} diverged from replica 0"
+        );
+    }
+    converged.into_iter().next().expect("non-empty")
+}
+
+/// Asserts that merging the serialized states of `a` and `b` through their readers gives
+/// the same result as merging them in memory, and returns that result.
+///
+/// # Example
+///
+/// ```
+/// use crdt_data_types::testkit::assert_serialize_merge_equivalent;
+/// use crdt_data_types::GCounter;
+///
+/// let mut a = GCounter::new();
+/// a.increment("node_a", 1);
+/// let mut b = GCounter::new();
+/// b.increment("node_b", 2);
+///
+/// assert_eq!(assert_serialize_merge_equivalent(a, b).value(), 3);
+/// ```
+///
+/// # Panics
+///
+/// Panics if `merge_from_readers` fails or disagrees with [`MergeExt::merged`].
+pub fn assert_serialize_merge_equivalent<C>(a: C, b: C) -> C
+where
+    C: Crdt + MergeExt + PartialEq + Debug,
+{
+    let (bytes_a, bytes_b) = (a.to_capnp_bytes(), b.to_capnp_bytes());
+    let readers = [
+        C::Reader::from_bytes(&bytes_a),
+        C::Reader::from_bytes(&bytes_b),
+    ];
+    let from_bytes = match C::merge_from_readers(&readers) {
+        Ok(merged) => merged,
+        Err(e) => panic!("merge_from_readers failed: {e}"),
+    };
+    let in_memory = a.merged(&b);
+    assert_eq!(
+        from_bytes, in_memory,
+        "zero-copy merge disagrees with in-memory merge"
+    );
+    in_memory
+}
+
+/// Checks that `a` merged with `b` equals `b` merged with `a`.
+///
+/// On disagreement, returns a report listing each divergent location as a JSON pointer
+/// into the serialized state, with the value on each side. Set and map types serialize
+/// their elements and keys as object fields, so the report names the divergent keys.
+///
+/// # Example
+///
+/// ```
+/// use crdt_data_types::testkit::assert_would_converge;
+/// use crdt_data_types::ORSet;
+///
+/// let mut a = ORSet::new();
+/// a.insert("node_a", "apple".to_string());
+/// let mut b = ORSet::new();
+/// b.insert("node_b", "pear".to_string());
+///
+/// assert_would_converge(&a, &b).unwrap();
+/// ```
+pub fn assert_would_converge<C: Crdt + PartialEq>(a: &C, b: &C) -> Result<(), String> {
+    let mut ab = a.clone();
+    Crdt::merge(&mut ab, b);
+    let mut ba = b.clone();
+    Crdt::merge(&mut ba, a);
+    if ab == ba {
+        return Ok(());
+    }
+
+    let to_json = |state: &C| {
+        serde_json::to_value(state)
+            .map_err(|e| format!("merged states differ; serialization failed: {e}"))
+    };
+    let (ab, ba) = (to_json(&ab)?, to_json(&ba)?);
+    let mut diffs = Vec::new();
+    diff_json(&mut String::new(), &ab, &ba, &mut diffs);
+    if diffs.is_empty() {
+        return Err("merged states differ in fields that are not serialized".to_string());
+    }
+    Err(format!(
+        "a.merge(b) and b.merge(a) differ at {} location(s):\n{}",
+        diffs.len(),
+        diffs.join("\n")
+    ))
+}
+
+/// Appends one line per divergent location between `ab` and `ba`.
+fn diff_json(path: &mut String, ab: &Value, ba: &Value, out: &mut Vec<String>) {
+    if ab == ba {
+        return;
+    }
+    let at = |path: &str| if path.is_empty() { "/".to_string() } else { path.to_string() };
+    match (ab, ba) {
+        (Value::Object(left), Value::Object(right)) => {
+            let mut keys: Vec<&String> = left.keys().chain(right.keys()).collect();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                let len = path.len();
+                path.push('/');
+                path.push_str(&key.replace('~', "~0").replace('/', "~1"));
+                match (left.get(key), right.get(key)) {
+                    (Some(l), Some(r)) => diff_json(path, l, r, out),
+                    (Some(l), None) => out.push(format!("  {}: only in a.merge(b) = {l}", at(path))),
+                    (None, Some(r)) => out.push(format!("  {}: only in b.merge(a) = {r}", at(path))),
+                    (None, None) => {}
+                }
+                path.truncate(len);
+            }
+        }
+        (Value::Array(left), Value::Array(right)) if left.len() == right.len() => {
+            for (i, (l, r)) in left.iter().zip(right).enumerate() {
+                let len = path.len();
+                path.push_str(&format!("/{i}"));
+                diff_json(path, l, r, out);
+                path.truncate(len);
+            }
+        }
+        (Value::Array(left), Value::Array(right)) => {
+            for l in left.iter().filter(|l| !right.contains(l)) {
+                out.push(format!("  {}: only in a.merge(b) = {l}", at(path)));
+            }
+            for r in right.iter().filter(|r| !left.contains(r)) {
+                out.push(format!("  {}: only in b.merge(a) = {r}", at(path)));
+            }
+        }
+        _ => out.push(format!("  {}: a.merge(b) = {ab}, b.merge(a) = {ba}", at(path))),
+    }
+}
+
+/// Small deterministic generator so runs are reproducible from the seed alone.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+}
@@ -0,0 +1,21 @@
+// Copyright (c) 2026 Adrian Robinson. All rights reserved.
+// Licensed under the MIT License. See LICENSE file in the project root for full license information.
+
+//! Decoding helpers for the plain-text element encoding described in the crate docs.
+
+use crate::traits::CrdtError;
+use serde::de::value::{Error as ValueError, StrDeserializer};
+use serde::de::DeserializeOwned;
+
+/// Decodes an element stored as plain text.
+pub(crate) fn from_text<T: DeserializeOwned>(text: &str) -> Result<T, CrdtError> {
+    T::deserialize(StrDeserializer::<ValueError>::new(text))
+        .map_err(|e| CrdtError::Deserialization(format!("Text element: {}", e)))
+}
+
+/// Reads a capnp `Text` field as `&str`, mapping errors to `CrdtError`.
+pub(crate) fn read_text(text: capnp::Result<capnp::text::Reader<'_>>) -> Result<&str, CrdtError> {
+    text.map_err(|e| CrdtError::Deserialization(e.to_string()))?
+        .to_str()
+        .map_err(|e| CrdtError::Deserialization(e.to_string()))
+}
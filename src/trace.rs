@@ -0,0 +1,116 @@
+// Copyright (c) 2026 Adrian Robinson. All rights reserved.
+// Licensed under the MIT License. See LICENSE file in the project root for full license information.
+
+//! Optional `tracing` instrumentation for the byte-level entry points.
+//!
+//! With the `tracing` feature enabled, each wrapped operation runs inside a debug-level
+//! `crdt` span and emits one debug event carrying `op`, `crdt_type`, `input_count`,
+//! `output_elements`, `output_bytes` and `elapsed_us`. The ops are named after the public
+//! functions they wrap (`compact` for [`compact_capnp_bytes`], `json_to_capnp_bytes` for
+//! the bridge), and each covers producing the typed value and serializing it. Without the
+//! feature the wrapper is an inlined call, so nothing is timed, counted or allocated.
+//!
+//! [`compact_capnp_bytes`]: crate::compaction::compact_capnp_bytes
+
+use crate::enums::CrdtType;
+#[cfg(feature = "probabilistic")]
+use crate::probabilistic::{CountMinSketch, HyperLogLog, RoaringBitmap, TDigest, TopK};
+use crate::traits::{Crdt, CrdtError};
+use crate::{FWWRegister, GCounter, GSet, LWWMap, LWWRegister, LWWSet, MVRegister, ORMap, ORSet, PNCounter};
+
+/// Size of a state in its own terms, reported as `output_elements`.
+pub(crate) trait ElementCount {
+    #[cfg(feature = "tracing")]
+    fn element_count(&self) -> usize;
+}
+
+macro_rules! element_count {
+    ($($ty:ty => |$value:ident| $count:expr;)*) => {$(
+        impl ElementCount for $ty {
+            #[cfg(feature = "tracing")]
+            fn element_count(&self) -> usize {
+                let $value = self;
+                $count
+            }
+        }
+    )*};
+}
+
+// Nodes for counters, live elements for sets, held values for registers, live keys for
+// maps; the types the bridge and compaction dispatch to.
+element_count! {
+    GCounter => |c| c.counters.len();
+    PNCounter => |c| c.positive.counters.len() + c.negative.counters.len();
+    GSet<String> => |s| s.elements.len();
+    ORSet<String> => |s| s.len();
+    LWWSet<String> => |s| s.iter().count();
+    LWWRegister<String> => |_r| 1;
+    FWWRegister<String> => |_r| 1;
+    MVRegister<String> => |r| r.entries.len();
+    LWWMap<String, String> => |m| m.len();
+    ORMap<String, String> => |m| m.len();
+}
+
+// Estimated distinct items, counters, set bits, centroids and tracked heavy hitters.
+#[cfg(feature = "probabilistic")]
+element_count! {
+    HyperLogLog => |h| h.cardinality() as usize;
+    CountMinSketch => |c| c.width * c.depth;
+    RoaringBitmap => |r| r.cardinality() as usize;
+    TDigest => |t| t.centroids().len();
+    TopK => |t| t.top_k().len();
+}
+
+/// Runs `f` and serializes its result, recording a span and a completion event when the
+/// `tracing` feature is on.
+#[cfg(feature = "tracing")]
+pub(crate) fn observe<C: Crdt + ElementCount>(
+    op: &'static str,
+    crdt_type: CrdtType,
+    input_count: usize,
+    f: impl FnOnce() -> Result<C, CrdtError>,
+) -> Result<Vec<u8>, CrdtError> {
+    let span = tracing::debug_span!("crdt", op, crdt_type = %crdt_type, input_count);
+    let _guard = span.enter();
+    let start = std::time::Instant::now();
+    let result = f().map(|value| (value.to_capnp_bytes(), value.element_count()));
+    let elapsed_us = start.elapsed().as_micros() as u64;
+    match result {
+        Ok((bytes, output_elements)) => {
+            tracing::debug!(
+                op,
+                crdt_type = %crdt_type,
+                input_count,
+                output_elements,
+                output_bytes = bytes.len(),
+                elapsed_us,
+                "crdt operation complete"
+            );
+            Ok(bytes)
+        }
+        Err(e) => {
+            tracing::debug!(
+                op,
+                crdt_type = %crdt_type,
+                input_count,
+                elapsed_us,
+                error = %e,
+                "crdt operation failed"
+            );
+            Err(e)
+        }
+    }
+}
+
+/// Runs `f` and serializes its result; the `tracing` feature is off, so nothing is
+/// recorded.
+#[cfg(not(feature = "tracing"))]
+#[inline(always)]
+pub(crate) fn observe<C: Crdt + ElementCount>(
+    _op: &'static str,
+    _crdt_type: CrdtType,
+    _input_count: usize,
+    f: impl FnOnce() -> Result<C, CrdtError>,
+) -> Result<Vec<u8>, CrdtError> {
+    f().map(|value| value.to_capnp_bytes())
+}
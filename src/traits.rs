@@ -1,11 +1,24 @@
 // Copyright (c) 2026 Adrian Robinson. All rights reserved.
 // Licensed under the MIT License. See LICENSE file in the project root for full license information.
 
+use crate::enums::CrdtType;
+use crate::frozen::FrozenView;
+#[cfg(feature = "probabilistic")]
+use crate::probabilistic::{CountMinSketch, HyperLogLog, RoaringBitmap, TDigest, TopK};
+use crate::{FWWRegister, GCounter, GSet, LWWMap, LWWRegister, LWWSet, MVRegister, ORMap, ORSet, PNCounter};
+use capnp::message::{Builder, HeapAllocator, ReaderOptions};
+use capnp::serialize;
 use serde::{de::DeserializeOwned, Serialize};
+use std::hash::Hash;
+use std::io::{Read, Write};
 use thiserror::Error;
 
 /// Error type for CRDT operations
+///
+/// New failure modes get new variants, so the enum is `#[non_exhaustive]`: match it with a
+/// wildcard arm.
 #[derive(Debug, Error)]
+#[non_exhaustive]
 pub enum CrdtError {
     #[error("Serialization error: {0}")]
     Serialization(String),
@@ -19,6 +32,10 @@ pub enum CrdtError {
     InvalidInput(String),
     #[error("Internal error: {0}")]
     Internal(String),
+    #[error("Schema mismatch: {0}")]
+    SchemaMismatch(String),
+    #[error("Size limit exceeded: {0}")]
+    SizeLimitExceeded(String),
 }
 
 /// CRDT Reader trait - provides zero-copy access to serialized CRDT data.
@@ -27,6 +44,14 @@ pub enum CrdtError {
 /// inspection of CRDT fields without full deserialization. This is critical
 /// for the performance of the zero-copy merge pattern.
 pub trait CrdtReader<'a> {
+    /// Wraps a serialized CRDT buffer. No parsing happens until the reader is queried.
+    ///
+    /// Used by the helpers that build readers from bytes (`Crdt::from_capnp_bytes`,
+    /// `Crdt::merge_bytes`, `Crdt::freeze`).
+    fn from_bytes(bytes: &'a [u8]) -> Self
+    where
+        Self: Sized;
+
     /// Returns true if the CRDT is in its default (empty) state.
     fn is_empty(&self) -> Result<bool, CrdtError>;
 }
@@ -42,6 +67,15 @@ pub trait CrdtReader<'a> {
 /// - **Commutativity**: `merge([A, B]) == merge([B, A])`
 /// - **Associativity**: `merge([merge([A, B]), C]) == merge([A, merge([B, C])])`
 /// - **Idempotence**: `merge([A, A]) == merge([A])`
+///
+/// # Bounded merges
+///
+/// Types whose blobs can declare arbitrarily long lists also offer an inherent
+/// `merge_from_readers_bounded(readers, max_entries)` for untrusted input. It reads only
+/// list headers and fails with `CrdtError::SizeLimitExceeded`, before decoding anything,
+/// if the readers together declare more than `max_entries` entries. Entries are counted
+/// per reader, before duplicates across readers are merged away, so the check is
+/// conservative.
 pub trait Crdt: Clone + Serialize + DeserializeOwned + Send + Sync {
     /// Zero-copy reader type associated with this CRDT.
     type Reader<'a>: CrdtReader<'a>
@@ -56,10 +90,111 @@ pub trait Crdt: Clone + Serialize + DeserializeOwned + Send + Sync {
     ///
     /// # Arguments
     /// * `readers` - A slice of zero-copy readers pointing to serialized CRDT states.
+    ///   An empty slice yields the empty state.
     fn merge_from_readers(readers: &[Self::Reader<'_>]) -> Result<Self, CrdtError>
     where
         Self: Sized;
 
+    /// Merges every readable state in `readers`, reporting the ones that failed.
+    ///
+    /// Unlike `merge_from_readers`, a corrupt reader does not abort the batch: the result
+    /// is the merge of all readers that decoded and merged cleanly, alongside the index
+    /// and error of each one that did not. Intended for compaction over storage that may
+    /// hold damaged blobs. If no reader fails this costs the same as `merge_from_readers`.
+    ///
+    /// Readers that decode on their own are joined with `Crdt::merge`, after
+    /// `Crdt::ensure_mergeable` against the states merged so far: a reader whose settings or
+    /// dimensions disagree with them is reported like a corrupt one. The outer error is
+    /// returned only if every reader failed and the empty state cannot be built either.
+    fn merge_from_readers_lenient(
+        readers: &[Self::Reader<'_>],
+    ) -> Result<(Self, Vec<(usize, CrdtError)>), CrdtError>
+    where
+        Self: Sized,
+    {
+        if let Ok(merged) = Self::merge_from_readers(readers) {
+            return Ok((merged, Vec::new()));
+        }
+
+        let mut merged: Option<Self> = None;
+        let mut errors = Vec::new();
+        for (idx, reader) in readers.iter().enumerate() {
+            match Self::merge_from_readers(std::slice::from_ref(reader)) {
+                Ok(state) => match merged.as_mut() {
+                    Some(acc) => match acc.ensure_mergeable(&state) {
+                        Ok(()) => acc.merge(&state),
+                        Err(e) => errors.push((idx, e)),
+                    },
+                    None => merged = Some(state),
+                },
+                Err(e) => errors.push((idx, e)),
+            }
+        }
+
+        let merged = match merged {
+            Some(merged) => merged,
+            None => Self::merge_from_readers(&[])?,
+        };
+        Ok((merged, errors))
+    }
+
+    /// Reconstructs a CRDT from bytes produced by `to_capnp_bytes()`.
+    ///
+    /// The default decodes through a single reader via `merge_from_readers`; types with
+    /// a dedicated decoder override it.
+    fn from_capnp_bytes(bytes: &[u8]) -> Result<Self, CrdtError>
+    where
+        Self: Sized,
+    {
+        Self::merge_from_readers(&[Self::Reader::from_bytes(bytes)])
+    }
+
+    /// Merges another in-memory state into `self`.
+    ///
+    /// Required, so that generic code over `C: Crdt` merges live values without
+    /// serializing and never hits a failure it cannot report. Every type in this crate
+    /// forwards to its inherent `merge`.
+    fn merge(&mut self, other: &Self)
+    where
+        Self: Sized;
+
+    /// Decodes a blob produced by `to_capnp_bytes()` and merges it into `self` in place.
+    ///
+    /// The default does exactly that; types in this crate override it to decode straight
+    /// into `self`.
+    fn merge_bytes(&mut self, bytes: &[u8]) -> Result<(), CrdtError>
+    where
+        Self: Sized,
+    {
+        let other = Self::from_capnp_bytes(bytes)?;
+        self.merge(&other);
+        Ok(())
+    }
+
+    /// Errors if `other` may not be merged into `self` on the terms `merge_from_readers`
+    /// enforces: both must share their merge settings (tie-break direction, conflict
+    /// policy) and, for sketches, their dimensions.
+    ///
+    /// `merge` itself joins differing settings and panics on differing dimensions, so code
+    /// that folds decoded states one at a time checks each against the running result
+    /// first. The default accepts everything, for types without such settings.
+    fn ensure_mergeable(&self, _other: &Self) -> Result<(), CrdtError>
+    where
+        Self: Sized,
+    {
+        Ok(())
+    }
+
+    /// Takes an immutable snapshot that can be read without holding any lock on `self`.
+    ///
+    /// The snapshot is serialized now and decoded lazily on first read.
+    fn freeze(&self) -> FrozenView<Self>
+    where
+        Self: Sized,
+    {
+        FrozenView::from_bytes(self.to_capnp_bytes())
+    }
+
     /// Validates the internal consistency of the CRDT state.
     ///
     /// This is typically called after a merge operation to ensure that all
@@ -69,8 +204,200 @@ pub trait Crdt: Clone + Serialize + DeserializeOwned + Send + Sync {
     /// Returns true if the CRDT is in its default (empty) state.
     fn is_empty(&self) -> bool;
 
+    /// Returns true if the CRDT is the merge identity: merging it into any state is a no-op.
+    ///
+    /// Stricter than `is_empty`, which only checks for visible content; the identity also
+    /// has an empty vclock and default configuration. Merges skip identity states. The
+    /// default is `false`, which is always safe.
+    fn is_identity(&self) -> bool {
+        false
+    }
+
+    /// Returns true if `self` already reflects everything in `other`, so merging `other`
+    /// into it would change nothing, e.g. to skip sending a peer state it has seen.
+    ///
+    /// Types with a vclock reject on a componentwise clock comparison first, then confirm
+    /// by merging into a copy, since some updates (expiry, eviction, state built from
+    /// per-node totals) change contents without ticking the clock. `HyperLogLog` and
+    /// `RoaringBitmap` compare their contents directly. The default is `false`, which is always safe; it is kept by types whose
+    /// merge is not an idempotent join of contents (`CountMinSketch` adds counters) or
+    /// that have no cheap structural check (`TDigest`, `TopK`).
+    fn dominates(&self, _other: &Self) -> bool {
+        false
+    }
+
     /// Serializes the CRDT to a byte buffer using Cap'n Proto.
     ///
     /// The resulting bytes are optimized for zero-copy reading by `CrdtReader`.
     fn to_capnp_bytes(&self) -> Vec<u8>;
+
+    /// Streams the Cap'n Proto encoding to `writer`, producing the same bytes as
+    /// `to_capnp_bytes()`.
+    ///
+    /// The default writes the result of `to_capnp_bytes()`; the core CRDTs override it
+    /// to hand their message straight to `capnp::serialize::write_message`, avoiding the
+    /// intermediate buffer.
+    fn write_to<W: Write>(&self, writer: &mut W) -> Result<(), CrdtError> {
+        writer
+            .write_all(&self.to_capnp_bytes())
+            .map_err(|e| CrdtError::Serialization(e.to_string()))
+    }
+
+    /// Builds the message `to_capnp_bytes()` serializes into `message`, which must be empty.
+    ///
+    /// Every type in this crate builds in place. The default decodes `to_capnp_bytes()`
+    /// and copies its root across.
+    fn build_capnp(&self, message: &mut Builder<HeapAllocator>) -> Result<(), CrdtError> {
+        let bytes = self.to_capnp_bytes();
+        let reader = serialize::read_message(&bytes[..], ReaderOptions::new())
+            .map_err(|e| CrdtError::Serialization(e.to_string()))?;
+        let root = reader
+            .get_root::<capnp::any_pointer::Reader>()
+            .map_err(|e| CrdtError::Serialization(e.to_string()))?;
+        message
+            .set_root(root)
+            .map_err(|e| CrdtError::Serialization(e.to_string()))
+    }
+
+    /// Serializes into `buf` through the caller's `message` builder, replacing the
+    /// contents of both but keeping `buf`'s capacity.
+    ///
+    /// For loops that serialize the same state repeatedly (e.g. a broadcast every tick):
+    /// once `buf` has grown to the message size, later calls write into it without
+    /// reallocating. Produces the same bytes as `to_capnp_bytes()`, and leaves `message`
+    /// holding the built message, e.g. to write it to a second sink.
+    ///
+    /// capnp 0.18 cannot clear a builder in place, so each call releases the builder's
+    /// previous segments and starts a new message with the same allocator (keeping its
+    /// allocation strategy and segment limit).
+    fn to_capnp_bytes_into(
+        &self,
+        message: &mut Builder<HeapAllocator>,
+        buf: &mut Vec<u8>,
+    ) -> Result<(), CrdtError> {
+        let previous = std::mem::replace(message, Builder::new(HeapAllocator::new()));
+        *message = Builder::new(previous.into_allocator());
+        self.build_capnp(message)?;
+        buf.clear();
+        serialize::write_message(buf, message).map_err(|e| CrdtError::Serialization(e.to_string()))
+    }
+
+    /// Reads one message written by `write_to()` or `to_capnp_bytes()` from `reader`.
+    ///
+    /// Consumes exactly the bytes of that message, so several messages can be read back
+    /// to back from the same stream.
+    fn read_from<R: Read>(reader: &mut R) -> Result<Self, CrdtError>
+    where
+        Self: Sized,
+    {
+        Self::from_capnp_bytes(&read_message_bytes(reader)?)
+    }
+
+    /// Approximates the length of `to_capnp_bytes()` without serializing.
+    ///
+    /// Useful for deciding between shipping full state or a delta. The default falls back
+    /// to serializing; types with a fixed layout override it with a structural estimate.
+    fn estimated_capnp_size(&self) -> usize {
+        self.to_capnp_bytes().len()
+    }
+}
+
+/// Associates a CRDT type with its runtime [`CrdtType`], so generic code can reach the
+/// type-dispatched bridge without naming the type twice.
+///
+/// Sealed: implemented for every type with a [`CrdtType`] variant and nothing else.
+/// [`DisableRegister`](crate::DisableRegister) and [`VectorClock`](crate::VectorClock)
+/// implement [`Crdt`] but are not dispatched by the bridge, so they are not `CrdtKind`.
+pub trait CrdtKind: sealed::Sealed {
+    const TYPE: CrdtType;
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+macro_rules! crdt_kind {
+    ($($(#[$attr:meta])* impl[$($generics:tt)*] $ty:ty => $kind:ident;)*) => {$(
+        $(#[$attr])*
+        impl<$($generics)*> sealed::Sealed for $ty {}
+        $(#[$attr])*
+        impl<$($generics)*> CrdtKind for $ty {
+            const TYPE: CrdtType = CrdtType::$kind;
+        }
+    )*};
+}
+
+crdt_kind! {
+    impl[] GCounter => GCounter;
+    impl[] PNCounter => PNCounter;
+    impl[T: Eq + Hash + Ord] GSet<T> => GSet;
+    impl[T: Eq + Hash + Ord] ORSet<T> => ORSet;
+    impl[T: Eq + Hash + Ord] LWWSet<T> => LWWSet;
+    impl[T] LWWRegister<T> => LWWRegister;
+    impl[T] FWWRegister<T> => FWWRegister;
+    impl[T: Eq + Hash] MVRegister<T> => MVRegister;
+    impl[K: Eq + Hash + Ord, V] LWWMap<K, V> => LWWMap;
+    impl[K: Eq + Hash + Ord, V: Eq + Hash + Ord] ORMap<K, V> => ORMap;
+    #[cfg(feature = "probabilistic")]
+    impl[] HyperLogLog => HyperLogLog;
+    #[cfg(feature = "probabilistic")]
+    impl[] CountMinSketch => CountMinSketch;
+    #[cfg(feature = "probabilistic")]
+    impl[] RoaringBitmap => RoaringBitmap;
+    #[cfg(feature = "probabilistic")]
+    impl[] TDigest => TDigest;
+    #[cfg(feature = "probabilistic")]
+    impl[] TopK => TopK;
+}
+
+/// Returns true if merging `other` into `a` leaves it unchanged.
+pub(crate) fn merge_is_noop<C: Crdt + PartialEq>(a: &C, other: &C) -> bool {
+    let mut merged = a.clone();
+    merged.merge(other);
+    merged == *a
+}
+
+/// Upper bound on the segment count accepted by [`read_message_bytes`], matching capnp's
+/// own limit for stream framing.
+const MAX_SEGMENTS: usize = 512;
+
+/// Reads the bytes of a single framed Cap'n Proto message (segment table plus segments).
+fn read_message_bytes<R: Read>(reader: &mut R) -> Result<Vec<u8>, CrdtError> {
+    let io_err = |e: std::io::Error| CrdtError::Deserialization(e.to_string());
+
+    let mut first = [0u8; 4];
+    reader.read_exact(&mut first).map_err(io_err)?;
+    let segment_count = u32::from_le_bytes(first) as usize + 1;
+    if segment_count > MAX_SEGMENTS {
+        return Err(CrdtError::Deserialization(format!(
+            "Too many segments: {}",
+            segment_count
+        )));
+    }
+
+    // The segment table is padded to a whole number of words
+    let table_len = (4 * (segment_count + 1)).div_ceil(8) * 8 - 4;
+    let mut table = vec![0u8; table_len];
+    reader.read_exact(&mut table).map_err(io_err)?;
+    let body_words: u64 = table
+        .chunks_exact(4)
+        .take(segment_count)
+        .map(|size| u32::from_le_bytes([size[0], size[1], size[2], size[3]]) as u64)
+        .sum();
+
+    let mut bytes = first.to_vec();
+    bytes.extend_from_slice(&table);
+    // `take` bounds the read, so a corrupt size cannot trigger a huge allocation up front
+    let expected = body_words * 8;
+    let read = reader
+        .take(expected)
+        .read_to_end(&mut bytes)
+        .map_err(io_err)?;
+    if read as u64 != expected {
+        return Err(CrdtError::Deserialization(format!(
+            "Truncated message: expected {} body bytes, got {}",
+            expected, read
+        )));
+    }
+    Ok(bytes)
 }
@@ -0,0 +1,94 @@
+// Copyright (c) 2026 Adrian Robinson. All rights reserved.
+// Licensed under the MIT License. See LICENSE file in the project root for full license information.
+
+//! Element type fingerprints for set and map messages.
+//!
+//! Elements are stored as opaque serialized blobs, so decoding a message with the wrong
+//! generic parameter can silently produce garbage. Each set/map message records a tag for
+//! its element type, checked on read. A tag of zero means "untagged" and is accepted.
+//!
+//! Tags are explicit: each supported element type has a fixed name below, hashed into the
+//! tag, so tags never depend on the compiler. Element types without a name (user-defined
+//! types) are written untagged.
+
+use crate::traits::CrdtError;
+use std::any::TypeId;
+
+type NamedType = (fn() -> TypeId, &'static str);
+
+/// Stable names of the element types that get a tag. Never rename an entry: the name is
+/// part of the wire format.
+const NAMES: &[NamedType] = &[
+    (TypeId::of::<bool>, "bool"),
+    (TypeId::of::<char>, "char"),
+    (TypeId::of::<i8>, "i8"),
+    (TypeId::of::<i16>, "i16"),
+    (TypeId::of::<i32>, "i32"),
+    (TypeId::of::<i64>, "i64"),
+    (TypeId::of::<i128>, "i128"),
+    (TypeId::of::<isize>, "isize"),
+    (TypeId::of::<u8>, "u8"),
+    (TypeId::of::<u16>, "u16"),
+    (TypeId::of::<u32>, "u32"),
+    (TypeId::of::<u64>, "u64"),
+    (TypeId::of::<u128>, "u128"),
+    (TypeId::of::<usize>, "usize"),
+    (TypeId::of::<f32>, "f32"),
+    (TypeId::of::<f64>, "f64"),
+    (TypeId::of::<String>, "string"),
+    (TypeId::of::<Vec<u8>>, "bytes"),
+];
+
+fn stable_name<T: ?Sized + 'static>() -> Option<&'static str> {
+    let id = TypeId::of::<T>();
+    NAMES.iter().find(|(type_id, _)| type_id() == id).map(|(_, name)| *name)
+}
+
+/// FNV-1a over `parts`, never zero.
+fn fnv1a<'a>(parts: impl IntoIterator<Item = &'a str>) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in parts.into_iter().flat_map(str::bytes) {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    hash.max(1)
+}
+
+/// Tag for elements of type `T`, or 0 if `T` has no stable name.
+pub(crate) fn type_tag<T: ?Sized + 'static>() -> u64 {
+    stable_name::<T>().map_or(0, |name| fnv1a([name]))
+}
+
+/// Tag for map entries with keys `K` and values `V`, or 0 unless both have stable names.
+pub(crate) fn pair_type_tag<K: 'static, V: 'static>() -> u64 {
+    match (stable_name::<K>(), stable_name::<V>()) {
+        (Some(key), Some(value)) => fnv1a(["(", key, ",", value, ")"]),
+        _ => 0,
+    }
+}
+
+fn check(found: u64, expected: u64, describe: impl FnOnce() -> String) -> Result<(), CrdtError> {
+    if found == 0 || expected == 0 || found == expected {
+        return Ok(());
+    }
+    Err(CrdtError::SchemaMismatch(format!(
+        "element type tag {:#018x} does not match {} ({:#018x})",
+        found,
+        describe(),
+        expected
+    )))
+}
+
+/// Errors with `SchemaMismatch` if `found` is a tag for a type other than `T`.
+pub(crate) fn check_type_tag<T: ?Sized + 'static>(found: u64) -> Result<(), CrdtError> {
+    check(found, type_tag::<T>(), || {
+        std::any::type_name::<T>().to_string()
+    })
+}
+
+/// Errors with `SchemaMismatch` if `found` is a tag for entries other than `(K, V)`.
+pub(crate) fn check_pair_type_tag<K: 'static, V: 'static>(found: u64) -> Result<(), CrdtError> {
+    check(found, pair_type_tag::<K, V>(), || {
+        std::any::type_name::<(K, V)>().to_string()
+    })
+}
@@ -1,13 +1,16 @@
 // Copyright (c) 2026 Adrian Robinson. All rights reserved.
 // Licensed under the MIT License. See LICENSE file in the project root for full license information.
 
-use crate::traits::{Crdt, CrdtError, CrdtReader};
+use crate::size_estimate::{struct_size, text_size, MESSAGE_OVERHEAD, WORD};
+use crate::text_element::read_text;
+use crate::traits::{merge_is_noop, Crdt, CrdtError, CrdtReader};
 use crate::vclock_capnp;
 use capnp::message::{Builder, HeapAllocator, ReaderOptions};
 use capnp::serialize;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::hash::Hash;
+use std::io::Write;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 /// A Hybrid Vector Clock for causal ordering and stable temporal queries.
@@ -39,12 +42,56 @@ use std::time::{Duration, SystemTime, UNIX_EPOCH};
 /// vc1.merge(&vc2);
 /// assert!(vc2.happens_before(&vc1)); // vc2 is now causally before vc1
 /// ```
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+///
+/// # Logical-only mode
+///
+/// Replicas without a synchronized wall clock can use [`VectorClock::logical_only`].
+/// Such a clock records `0` for every timestamp, so its encoding depends only on the
+/// logical counters, and wall-clock queries like `is_stable_for` always return false.
+/// The mode is sticky: merging with a logical-only clock makes the result logical-only
+/// (dropping any timestamps), which keeps merge commutative across mixed replicas.
+///
+/// # Logical rounds
+///
+/// Simulations without real time can drive stability from a round counter instead:
+/// after [`VectorClock::mark_round`], each `increment` also records the round in which
+/// the node's entry advanced, and [`VectorClock::is_stable_since_round`] plays the role
+/// of `is_stable_for`. Rounds merge by per-node maximum, like timestamps, and coexist
+/// with them.
+///
+/// # Construction
+///
+/// Build clocks with [`VectorClock::new`], [`VectorClock::logical_only`] or `Default`.
+/// Only `clocks` is public: the mode and the per-node rounds are read through
+/// [`VectorClock::is_logical_only`] and [`VectorClock::rounds`], and the struct is
+/// `#[non_exhaustive]` so further bookkeeping can be added without another break to
+/// struct literals.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[non_exhaustive]
 pub struct VectorClock {
     /// Map of node_id -> (logical_counter, epoch_seconds)
     pub clocks: HashMap<String, (u64, u64)>,
+    /// If true, timestamps are not recorded and are always `0`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    logical_only: bool,
+    /// Map of node_id -> logical round in which its entry last advanced.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    rounds: HashMap<String, u64>,
+    /// Round recorded by `increment`, set with `mark_round`. Local-only.
+    #[serde(skip)]
+    current_round: Option<u64>,
+}
+
+impl PartialEq for VectorClock {
+    fn eq(&self, other: &Self) -> bool {
+        self.clocks == other.clocks
+            && self.logical_only == other.logical_only
+            && self.rounds == other.rounds
+    }
 }
 
+impl Eq for VectorClock {}
+
 impl Hash for VectorClock {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
         let mut sorted: Vec<_> = self.clocks.iter().collect();
@@ -63,12 +110,36 @@ impl VectorClock {
         Self::default()
     }
 
+    /// Returns a new, empty clock that records no wall-clock timestamps.
+    pub fn logical_only() -> Self {
+        Self {
+            logical_only: true,
+            ..Self::default()
+        }
+    }
+
+    /// Returns true if this clock records no wall-clock timestamps.
+    pub fn is_logical_only(&self) -> bool {
+        self.logical_only
+    }
+
+    /// Map of node_id -> logical round in which its entry last advanced.
+    pub fn rounds(&self) -> &HashMap<String, u64> {
+        &self.rounds
+    }
+
     /// Increments the clock for a specific node and updates its timestamp.
+    ///
+    /// In logical-only mode the timestamp is always `0`.
     pub fn increment(&mut self, node_id: &str) -> (u64, u64) {
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_secs();
+        let now = if self.logical_only {
+            0
+        } else {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs()
+        };
 
         let entry = self
             .clocks
@@ -78,7 +149,38 @@ impl VectorClock {
                 *timestamp = now;
             })
             .or_insert((1, now));
-        *entry
+        let entry = *entry;
+        if let Some(round) = self.current_round {
+            self.rounds.insert(node_id.to_string(), round);
+        }
+        entry
+    }
+
+    /// Sets the logical round that subsequent increments record for their node.
+    pub fn mark_round(&mut self, round: u64) {
+        self.current_round = Some(round);
+    }
+
+    /// Returns true if every entry last advanced in a round before `round`.
+    ///
+    /// Entries with no recorded round count as round 0. Like `is_stable_for`, an empty
+    /// clock is never stable.
+    pub fn is_stable_since_round(&self, round: u64) -> bool {
+        !self.clocks.is_empty()
+            && self
+                .clocks
+                .keys()
+                .all(|node| self.rounds.get(node).copied().unwrap_or(0) < round)
+    }
+
+    /// Keeps the later round for each node.
+    fn join_rounds<'a>(&mut self, rounds: impl Iterator<Item = (&'a str, u64)>) {
+        for (node_id, round) in rounds {
+            self.rounds
+                .entry(node_id.to_string())
+                .and_modify(|r| *r = (*r).max(round))
+                .or_insert(round);
+        }
     }
 
     /// Merges another vector clock into this one, keeping the maximum values.
@@ -92,6 +194,18 @@ impl VectorClock {
                 })
                 .or_insert((other_counter, other_timestamp));
         }
+        self.join_rounds(other.rounds.iter().map(|(node, round)| (node.as_str(), *round)));
+        self.join_mode(other.logical_only);
+    }
+
+    /// Switches to logical-only mode if `other_logical_only` is set, clearing timestamps.
+    fn join_mode(&mut self, other_logical_only: bool) {
+        self.logical_only |= other_logical_only;
+        if self.logical_only {
+            for (_, timestamp) in self.clocks.values_mut() {
+                *timestamp = 0;
+            }
+        }
     }
 
     /// Returns true if this vector clock causally precedes another.
@@ -118,9 +232,22 @@ impl VectorClock {
         strictly_less
     }
 
+    /// Returns true if this clock has seen every event `other` has: each node's counter is
+    /// at least the one in `other`. Equal clocks dominate each other.
+    pub fn dominates(&self, other: &VectorClock) -> bool {
+        other
+            .clocks
+            .iter()
+            .all(|(node_id, (counter, _))| {
+                self.clocks.get(node_id).map_or(0, |(c, _)| *c) >= *counter
+            })
+    }
+
     /// Checks for temporal stability across all tracked nodes.
+    ///
+    /// Always false for a logical-only clock, which has no timestamps to compare.
     pub fn is_stable_for(&self, duration: Duration) -> bool {
-        if self.clocks.is_empty() {
+        if self.clocks.is_empty() || self.logical_only {
             return false;
         }
         let cutoff = SystemTime::now()
@@ -132,6 +259,72 @@ impl VectorClock {
         self.clocks.values().all(|(_, ts)| *ts < cutoff)
     }
 
+    /// Computes the causally stable frontier across a known membership set.
+    ///
+    /// Given the latest clock received from every peer, returns the componentwise minimum:
+    /// every event at or below it has been observed by all peers, so no concurrent
+    /// operation can still arrive beneath it (the basis for safe tombstone GC). A node
+    /// absent from any peer's clock counts as zero and is omitted. Timestamps take the
+    /// per-node minimum as well; rounds are not carried. An empty peer set yields an empty
+    /// clock; the frontier is logical-only if any peer is.
+    pub fn stable_frontier(peers: &[&VectorClock]) -> VectorClock {
+        let Some((first, rest)) = peers.split_first() else {
+            return VectorClock::new();
+        };
+
+        let mut clocks = first.clocks.clone();
+        for peer in rest {
+            clocks.retain(|node, (counter, timestamp)| match peer.clocks.get(node) {
+                Some((c, ts)) => {
+                    *counter = (*counter).min(*c);
+                    *timestamp = (*timestamp).min(*ts);
+                    true
+                }
+                None => false,
+            });
+        }
+        clocks.retain(|_, (counter, _)| *counter > 0);
+        let mut frontier = VectorClock {
+            clocks,
+            ..VectorClock::default()
+        };
+        frontier.join_mode(peers.iter().any(|peer| peer.logical_only));
+        frontier
+    }
+
+    /// Returns the total number of logical events across all nodes (saturating).
+    pub fn total_events(&self) -> u64 {
+        self.clocks
+            .values()
+            .fold(0u64, |acc, (counter, _)| acc.saturating_add(*counter))
+    }
+
+    /// Returns the node with the highest logical counter.
+    ///
+    /// Ties are broken by the lexicographically smallest node id so the result is deterministic.
+    pub fn busiest_node(&self) -> Option<(&str, u64)> {
+        self.clocks
+            .iter()
+            .map(|(node, (counter, _))| (node.as_str(), *counter))
+            .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(a.0)))
+    }
+
+    /// Returns true if both clocks hold the same logical counters, ignoring wall-clock timestamps.
+    ///
+    /// Nodes absent from one clock are treated as having a counter of zero.
+    pub fn logically_eq(&self, other: &VectorClock) -> bool {
+        let counter = |vc: &VectorClock, node: &str| vc.clocks.get(node).map(|(c, _)| *c).unwrap_or(0);
+        self.clocks
+            .keys()
+            .chain(other.clocks.keys())
+            .all(|node| counter(self, node) == counter(other, node))
+    }
+
+    /// Returns true if the dot `(node_id, counter)` has been observed by this clock.
+    pub fn covers(&self, node_id: &str, counter: u64) -> bool {
+        self.clocks.get(node_id).is_some_and(|(c, _)| counter <= *c)
+    }
+
     pub fn merge_reader(&mut self, reader: &VectorClockReader) -> Result<(), CrdtError> {
         let msg_reader = serialize::read_message(reader.bytes, ReaderOptions::new())
             .map_err(|e| CrdtError::Deserialization(e.to_string()))?;
@@ -160,10 +353,27 @@ impl VectorClock {
                 })
                 .or_insert((other_counter, other_timestamp));
         }
+        let rounds = read_rounds(vclock)?;
+        self.join_rounds(rounds.into_iter());
+        self.join_mode(vclock.get_logical_only());
         Ok(())
     }
 }
 
+fn read_rounds(
+    vclock: vclock_capnp::vector_clock::Reader<'_>,
+) -> Result<Vec<(&str, u64)>, CrdtError> {
+    if !vclock.has_rounds() {
+        return Ok(Vec::new());
+    }
+    vclock
+        .get_rounds()
+        .map_err(|e| CrdtError::Deserialization(e.to_string()))?
+        .iter()
+        .map(|entry| Ok((read_text(entry.get_node_id())?, entry.get_round())))
+        .collect()
+}
+
 // ============================================================================
 // Zero-Copy Reader
 // ============================================================================
@@ -200,11 +410,24 @@ impl<'a> VectorClockReader<'a> {
                 (entry.get_logical_counter(), entry.get_epoch_seconds()),
             );
         }
-        Ok(VectorClock { clocks })
+        let rounds = read_rounds(vclock)?
+            .into_iter()
+            .map(|(node, round)| (node.to_string(), round))
+            .collect();
+        Ok(VectorClock {
+            clocks,
+            logical_only: vclock.get_logical_only(),
+            rounds,
+            current_round: None,
+        })
     }
 }
 
 impl<'a> CrdtReader<'a> for VectorClockReader<'a> {
+    fn from_bytes(bytes: &'a [u8]) -> Self {
+        Self::new(bytes)
+    }
+
     fn is_empty(&self) -> Result<bool, CrdtError> {
         Ok(self.to_vclock()?.clocks.is_empty())
     }
@@ -225,23 +448,84 @@ impl Crdt for VectorClock {
         Ok(result)
     }
 
-    fn to_capnp_bytes(&self) -> Vec<u8> {
+    fn is_identity(&self) -> bool {
+        *self == Self::default()
+    }
+
+    fn merge(&mut self, other: &Self) {
+        self.merge(other);
+    }
+
+    fn dominates(&self, other: &Self) -> bool {
+        VectorClock::dominates(self, other) && merge_is_noop(self, other)
+    }
+
+    fn merge_bytes(&mut self, bytes: &[u8]) -> Result<(), CrdtError> {
+        let other = VectorClockReader::new(bytes).to_vclock()?;
+        self.merge(&other);
+        Ok(())
+    }
+
+    fn write_to<W: Write>(&self, writer: &mut W) -> Result<(), CrdtError> {
         let mut message = Builder::new(HeapAllocator::new());
-        {
-            let mut vclock = message.init_root::<vclock_capnp::vector_clock::Builder>();
-            let mut entries = vclock.reborrow().init_entries(self.clocks.len() as u32);
-            for (idx, (node_id, (counter, ts))) in self.clocks.iter().enumerate() {
-                let mut entry = entries.reborrow().get(idx as u32);
+        self.build_capnp(&mut message)?;
+        serialize::write_message(writer, &message)
+            .map_err(|e| CrdtError::Serialization(e.to_string()))
+    }
+
+    fn build_capnp(&self, message: &mut Builder<HeapAllocator>) -> Result<(), CrdtError> {
+        let mut vclock = message.init_root::<vclock_capnp::vector_clock::Builder>();
+        vclock.set_logical_only(self.logical_only);
+        // Sorted so equal clocks serialize to identical bytes, matching the Hash impl
+        let mut sorted: Vec<_> = self.clocks.iter().collect();
+        sorted.sort_by_key(|(node, _)| node.as_str());
+        let mut entries = vclock.reborrow().init_entries(sorted.len() as u32);
+        for (idx, (node_id, (counter, ts))) in sorted.into_iter().enumerate() {
+            let mut entry = entries.reborrow().get(idx as u32);
+            entry.set_node_id(node_id.as_str().into());
+            entry.set_logical_counter(*counter);
+            entry.set_epoch_seconds(*ts);
+        }
+        if !self.rounds.is_empty() {
+            let mut sorted: Vec<_> = self.rounds.iter().collect();
+            sorted.sort_by_key(|(node, _)| node.as_str());
+            let mut rounds = vclock.reborrow().init_rounds(sorted.len() as u32);
+            for (idx, (node_id, round)) in sorted.into_iter().enumerate() {
+                let mut entry = rounds.reborrow().get(idx as u32);
                 entry.set_node_id(node_id.as_str().into());
-                entry.set_logical_counter(*counter);
-                entry.set_epoch_seconds(*ts);
+                entry.set_round(*round);
             }
         }
+        Ok(())
+    }
+
+    fn to_capnp_bytes(&self) -> Vec<u8> {
         let mut buf = Vec::new();
-        serialize::write_message(&mut buf, &message).expect("VectorClock serialization fail");
+        self.write_to(&mut buf).expect("VectorClock serialization fail");
         buf
     }
 
+    fn estimated_capnp_size(&self) -> usize {
+        let rounds = if self.rounds.is_empty() {
+            0
+        } else {
+            WORD + self
+                .rounds
+                .keys()
+                .map(|node| struct_size(1, 1) + text_size(node.len()))
+                .sum::<usize>()
+        };
+        MESSAGE_OVERHEAD
+            + struct_size(1, 2)
+            + WORD
+            + self
+                .clocks
+                .keys()
+                .map(|node| struct_size(2, 1) + text_size(node.len()))
+                .sum::<usize>()
+            + rounds
+    }
+
     fn is_empty(&self) -> bool {
         self.clocks.is_empty()
     }
@@ -77,6 +77,86 @@ fn test_vector_clock_stability() {
     assert!(vc.is_stable_for(Duration::from_secs(10)));
 }
 
+#[test]
+fn test_vector_clock_logical_only_merge_is_deterministic() {
+    let mut a = VectorClock::logical_only();
+    a.increment("A");
+    a.increment("A");
+    let mut b = VectorClock::logical_only();
+    b.increment("B");
+
+    let mut ab = a.clone();
+    ab.merge(&b);
+    let mut ba = b.clone();
+    ba.merge(&a);
+    assert_eq!(ab, ba);
+    assert_eq!(ab.clocks.get("A"), Some(&(2, 0)));
+    assert_eq!(ab.clocks.get("B"), Some(&(1, 0)));
+    assert!(!ab.is_stable_for(Duration::from_secs(0)));
+
+    // The mode and the zeroed timestamps survive a round trip
+    let decoded = VectorClock::from_capnp_bytes(&ab.to_capnp_bytes()).unwrap();
+    assert_eq!(decoded, ab);
+
+    // Merging a wall-clock replica into a logical-only one drops its timestamps
+    let mut wall = VectorClock::new();
+    wall.increment("C");
+    ab.merge(&wall);
+    wall.merge(&ba);
+    assert_eq!(ab.clocks.get("C"), Some(&(1, 0)));
+    assert_eq!(ab.is_logical_only(), wall.is_logical_only());
+    assert_eq!(ab.clocks, wall.clocks);
+}
+
+#[test]
+fn test_vector_clock_total_events_and_busiest_node() {
+    let mut vc = VectorClock::new();
+    assert_eq!(vc.total_events(), 0);
+    assert_eq!(vc.busiest_node(), None);
+
+    vc.clocks.insert("A".to_string(), (3, 0));
+    vc.clocks.insert("B".to_string(), (7, 0));
+    vc.clocks.insert("C".to_string(), (2, 0));
+
+    assert_eq!(vc.total_events(), 12);
+    assert_eq!(vc.busiest_node(), Some(("B", 7)));
+
+    // Saturates rather than overflowing
+    vc.clocks.insert("D".to_string(), (u64::MAX, 0));
+    assert_eq!(vc.total_events(), u64::MAX);
+    assert_eq!(vc.busiest_node(), Some(("D", u64::MAX)));
+}
+
+#[test]
+fn test_vector_clock_stable_frontier() {
+    let mut p1 = VectorClock::new();
+    p1.clocks.insert("A".to_string(), (5, 50));
+    p1.clocks.insert("B".to_string(), (3, 30));
+    p1.clocks.insert("C".to_string(), (9, 90));
+
+    let mut p2 = VectorClock::new();
+    p2.clocks.insert("A".to_string(), (4, 40));
+    p2.clocks.insert("B".to_string(), (6, 60));
+    p2.clocks.insert("C".to_string(), (7, 70));
+
+    let mut p3 = VectorClock::new();
+    p3.clocks.insert("A".to_string(), (8, 80));
+    p3.clocks.insert("B".to_string(), (2, 20));
+    // p3 has never heard from C, so nothing from C is stable yet
+
+    let frontier = VectorClock::stable_frontier(&[&p1, &p2, &p3]);
+    let mut expected = VectorClock::new();
+    expected.clocks.insert("A".to_string(), (4, 40));
+    expected.clocks.insert("B".to_string(), (2, 20));
+    assert_eq!(frontier, expected);
+
+    // The frontier precedes (or equals) every peer
+    for peer in [&p1, &p2, &p3] {
+        assert!(frontier.happens_before(peer));
+    }
+    assert_eq!(VectorClock::stable_frontier(&[]), VectorClock::new());
+}
+
 #[test]
 fn test_vector_clock_hashing() {
     use std::collections::hash_map::DefaultHasher;
@@ -115,6 +195,56 @@ fn test_vector_clock_hashing() {
     assert_eq!(h1.finish(), h2.finish());
 }
 
+#[test]
+fn test_vector_clock_round_stability() {
+    let mut a = VectorClock::logical_only();
+    let mut b = VectorClock::logical_only();
+    assert!(!a.is_stable_since_round(10));
+
+    a.mark_round(1);
+    a.increment("node_a");
+    b.mark_round(1);
+    b.increment("node_b");
+    a.merge(&b);
+    assert!(!a.is_stable_since_round(1));
+    assert!(a.is_stable_since_round(2));
+
+    // node_b advances in round 3; node_a stays quiet
+    b.mark_round(3);
+    b.increment("node_b");
+    a.merge(&b);
+    assert!(!a.is_stable_since_round(3));
+    assert!(a.is_stable_since_round(4));
+    assert_eq!(a.rounds()["node_a"], 1);
+    assert_eq!(a.rounds()["node_b"], 3);
+
+    // Rounds survive the wire, and merging is order-independent
+    let decoded = VectorClock::from_capnp_bytes(&a.to_capnp_bytes()).unwrap();
+    assert_eq!(decoded, a);
+    let mut reversed = b.clone();
+    reversed.merge(&a);
+    assert_eq!(reversed, a);
+}
+
+#[test]
+fn test_vector_clock_serialization_is_order_independent() {
+    let mut vc1 = VectorClock::new();
+    let mut vc2 = VectorClock::new();
+    for i in 0..32 {
+        vc1.clocks.insert(format!("node_{}", i), (i, 1000));
+        vc2.clocks.insert(format!("node_{}", 31 - i), (31 - i, 1000));
+    }
+    assert_eq!(vc1, vc2);
+    assert_eq!(vc1.to_capnp_bytes(), vc2.to_capnp_bytes());
+
+    // Embedded clocks serialize canonically too
+    let mut gc1 = GCounter::new();
+    let mut gc2 = GCounter::new();
+    gc1.vclock = vc1;
+    gc2.vclock = vc2;
+    assert_eq!(gc1.to_capnp_bytes(), gc2.to_capnp_bytes());
+}
+
 // ============================================================================
 // Compaction Tests
 // ============================================================================
@@ -183,3 +313,40 @@ fn test_compact_capnp_all_types() {
     assert!(!res.is_empty());
 }
 
+
+// ============================================================================
+// Size Estimation Tests
+// ============================================================================
+
+fn assert_estimate_close<C: Crdt>(crdt: &C) {
+    let actual = crdt.to_capnp_bytes().len();
+    let estimate = crdt.estimated_capnp_size();
+    assert!(
+        estimate * 2 >= actual && estimate <= actual * 2,
+        "estimate {} not within 2x of actual {}",
+        estimate,
+        actual
+    );
+}
+
+#[test]
+fn test_estimated_capnp_size() {
+    let mut gc = GCounter::new();
+    for i in 0..50 {
+        gc.increment(&format!("node_{}", i), i);
+    }
+    assert_estimate_close(&gc);
+    assert_estimate_close(&GCounter::new());
+
+    let mut set = ORSet::new();
+    for i in 0..100 {
+        set.insert(&format!("node_{}", i % 5), format!("element_{}", i));
+    }
+    assert_estimate_close(&set);
+
+    let mut map = LWWMap::new();
+    for i in 0..100 {
+        map.insert("node1", format!("key_{}", i), format!("a longer value {}", i), i as u64);
+    }
+    assert_estimate_close(&map);
+}
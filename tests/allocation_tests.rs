@@ -0,0 +1,66 @@
+// Copyright (c) 2026 Adrian Robinson. All rights reserved.
+// Licensed under the MIT License. See LICENSE file in the project root for full license information.
+
+use crdt_data_types::*;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Counts allocations so tests can compare serialization paths.
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+fn allocations_during(f: impl FnOnce()) -> usize {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    f();
+    ALLOCATIONS.load(Ordering::Relaxed) - before
+}
+
+#[test]
+fn test_to_capnp_bytes_into_reuses_buffer() {
+    let mut map = ORMap::new();
+    for i in 0..50 {
+        map.insert(&format!("node{}", i % 5), format!("key{}", i), format!("value{}", i));
+    }
+    let expected = map.to_capnp_bytes();
+
+    let fresh = allocations_during(|| {
+        for _ in 0..1000 {
+            std::hint::black_box(map.to_capnp_bytes());
+        }
+    });
+    let mut message = capnp::message::Builder::new_default();
+    let mut buf = Vec::new();
+    map.to_capnp_bytes_into(&mut message, &mut buf).unwrap();
+    let reused = allocations_during(|| {
+        for _ in 0..1000 {
+            map.to_capnp_bytes_into(&mut message, &mut buf).unwrap();
+        }
+    });
+
+    assert_eq!(buf, expected);
+    // The builder holds exactly the last message, not a thousand stale roots
+    let mut rewritten = Vec::new();
+    capnp::serialize::write_message(&mut rewritten, &message).unwrap();
+    assert_eq!(rewritten, expected);
+    assert!(reused < fresh, "reused buffer: {} allocations, fresh: {}", reused, fresh);
+}
@@ -81,3 +81,1203 @@ fn test_zero_copy_merge() {
     assert!(merged.contains(&"a".to_string()));
     assert!(merged.contains(&"b".to_string()));
 }
+
+#[test]
+fn test_gset_hashset_round_trip() {
+    let original: std::collections::HashSet<String> =
+        ["apple", "banana", "cherry"].iter().map(|s| s.to_string()).collect();
+
+    let gs: GSet<String> = original.clone().into();
+    assert_eq!(gs.elements.len(), 3);
+    assert!(gs.vclock.clocks.contains_key("import"));
+
+    let back: std::collections::HashSet<String> = gs.clone().into();
+    assert_eq!(back, original);
+
+    let collected: GSet<String> = gs.into_iter().collect();
+    assert!(collected.contains(&"banana".to_string()));
+}
+
+#[test]
+fn test_orset_hashset_round_trip() {
+    let original: std::collections::HashSet<String> =
+        ["apple", "banana"].iter().map(|s| s.to_string()).collect();
+
+    let mut set: ORSet<String> = original.clone().into();
+    set.remove(&"apple".to_string());
+
+    let live: Vec<String> = set.into_iter().collect();
+    assert_eq!(live, vec!["banana".to_string()]);
+}
+
+#[test]
+fn test_gset_from_lines() {
+    let input = std::io::Cursor::new("apple\nbanana\r\n\napple\ncherry");
+    let set = GSet::from_lines("loader", input).unwrap();
+
+    assert_eq!(set.elements, vec!["apple", "banana", "cherry"]);
+    // One vclock tick for the whole batch
+    assert_eq!(set.vclock.clocks.get("loader").map(|(c, _)| *c), Some(1));
+}
+
+#[test]
+fn test_lww_register_history() {
+    let mut reg = LWWRegister::new("v1".to_string(), 100, "node1").with_history(2);
+    reg.set("v2".to_string(), 200, "node1");
+    reg.set("v3".to_string(), 300, "node1");
+    reg.set("v4".to_string(), 400, "node1");
+
+    assert_eq!(reg.value, "v4");
+    let history: Vec<&str> = reg.history().iter().map(|(v, _, _)| v.as_str()).collect();
+    assert_eq!(history, vec!["v2", "v3"]);
+    assert_eq!(reg.history()[1].1, 300);
+
+    // Default registers keep no history
+    let mut plain = LWWRegister::new("v1".to_string(), 100, "node1");
+    plain.set("v2".to_string(), 200, "node1");
+    assert!(plain.history().is_empty());
+}
+
+#[test]
+fn test_orset_max_dots_per_element_guard() {
+    let mut guarded = ORSet::with_max_dots_per_element(4);
+    let mut unguarded = ORSet::new();
+    for _ in 0..1000 {
+        guarded.insert("node1", "hot".to_string());
+        unguarded.insert("node1", "hot".to_string());
+    }
+
+    assert!(guarded.dot_count() <= 4);
+    assert_eq!(unguarded.dot_count(), 1000);
+    assert!(guarded.contains(&"hot".to_string()));
+
+    // Compaction does not change the merge outcome
+    let mut a = guarded.clone();
+    a.merge(&unguarded);
+    let mut b = unguarded.clone();
+    b.merge(&guarded);
+    assert_eq!(a.elements, b.elements);
+}
+
+#[test]
+fn test_orset_first_insert_records_counter_one() {
+    let mut set = ORSet::new();
+    set.insert("fresh_node", "apple".to_string());
+    set.insert("fresh_node", "banana".to_string());
+
+    let dots = |elem: &str| set.elements.iter().find(|(e, _)| e == elem).unwrap().1.clone();
+    assert!(dots("apple").contains(&Dot::new("fresh_node", 1)));
+    assert!(dots("banana").contains(&Dot::new("fresh_node", 2)));
+}
+
+#[test]
+fn test_orset_validate_rejects_dot_beyond_vclock() {
+    let mut set = ORSet::new();
+    set.insert("node1", "apple".to_string());
+    assert!(set.validate().is_ok());
+
+    // Hand-craft a dot the vclock has never seen
+    set.elements[0].1.insert(Dot::new("node1", 5));
+    let err = set.validate().unwrap_err();
+    assert!(matches!(err, CrdtError::Validation(ref msg) if msg.contains("node1") && msg.contains('5')));
+
+    let mut reg = MVRegister::new();
+    reg.set("node1", 1);
+    assert!(reg.validate().is_ok());
+    reg.entries.get_mut(&1).unwrap().insert(Dot::new("ghost", 1));
+    assert!(matches!(reg.validate(), Err(CrdtError::Validation(_))));
+}
+
+#[test]
+fn test_gset_union_operator_matches_merged() {
+    let mut a = GSet::new();
+    a.insert("node1", "apple".to_string());
+    let mut b = GSet::new();
+    b.insert("node2", "banana".to_string());
+
+    let union = a.clone() | b.clone();
+    assert_eq!(union, a.clone().merged(&b));
+    assert_eq!(union.elements.len(), 2);
+}
+
+#[test]
+fn test_lww_register_lower_node_wins_tie_break() {
+    let mut reg_a = LWWRegister::new("from_a".to_string(), 100, "node_a").with_tie_break(TieBreak::LowerNodeWins);
+    let mut reg_b = LWWRegister::new("from_b".to_string(), 100, "node_b").with_tie_break(TieBreak::LowerNodeWins);
+
+    let snapshot_a = reg_a.clone();
+    reg_a.merge(&reg_b);
+    reg_b.merge(&snapshot_a);
+
+    assert_eq!(reg_a.value, "from_a");
+    assert_eq!(reg_b.value, "from_a");
+    assert_eq!(reg_a, reg_b);
+
+    // The direction survives Cap'n Proto round trips
+    let bytes = reg_b.to_capnp_bytes();
+    let decoded = LWWRegister::<String>::merge_from_readers(&[LWWRegisterReader::new(&bytes)]).unwrap();
+    assert_eq!(decoded.tie_break, TieBreak::LowerNodeWins);
+
+    // Mixing directions is rejected rather than silently diverging
+    let default_bytes = LWWRegister::new("x".to_string(), 1, "node_c").to_capnp_bytes();
+    let result = LWWRegister::<String>::merge_from_readers(&[
+        LWWRegisterReader::new(&bytes),
+        LWWRegisterReader::new(&default_bytes),
+    ]);
+    assert!(matches!(result, Err(CrdtError::Merge(_))));
+}
+
+#[test]
+fn test_gset_text_encoding_is_plain_utf8() {
+    let mut set = GSet::new();
+    set.insert("node1", "apple".to_string());
+    set.insert("node1", "banana".to_string());
+
+    let text_bytes = set.to_capnp_text_bytes();
+    assert!(text_bytes.len() < set.to_capnp_bytes().len());
+
+    // Readable as plain text without bincode
+    let message = capnp::serialize::read_message(&text_bytes[..], capnp::message::ReaderOptions::new()).unwrap();
+    let root = message.get_root::<crdt_data_types::gset_capnp::g_set::Reader>().unwrap();
+    match root.which().unwrap() {
+        crdt_data_types::gset_capnp::g_set::TextElements(list) => {
+            let texts: Vec<&str> = list.unwrap().iter().map(|t| t.unwrap().to_str().unwrap()).collect();
+            assert_eq!(texts, vec!["apple", "banana"]);
+        }
+        _ => panic!("expected text elements"),
+    }
+
+    // Round trips through the regular reader
+    let decoded = GSet::<String>::merge_from_readers(&[GSetReader::new(&text_bytes)]).unwrap();
+    assert_eq!(decoded, set);
+}
+
+#[test]
+fn test_text_encoding_round_trips_for_sets_and_maps() {
+    let mut orset = ORSet::new();
+    orset.insert("node1", "x".to_string());
+    let decoded = ORSet::<String>::from_capnp_bytes(&orset.to_capnp_text_bytes()).unwrap();
+    assert_eq!(decoded, orset);
+
+    let mut lww_set = LWWSet::new();
+    lww_set.insert("node1", "x".to_string(), 10);
+    lww_set.remove("node1", "y".to_string(), 5);
+    let decoded = LWWSet::<String>::from_capnp_bytes(&lww_set.to_capnp_text_bytes()).unwrap();
+    assert_eq!(decoded, lww_set);
+
+    let mut map = LWWMap::multi();
+    map.insert("node1", "k".to_string(), 1i64, 10);
+    map.insert("node1", "k".to_string(), 2i64, 10);
+    let decoded = LWWMap::<String, i64>::from_capnp_bytes(&map.to_capnp_text_bytes()).unwrap();
+    assert_eq!(decoded, map);
+}
+
+#[test]
+fn test_reading_with_wrong_element_type_is_schema_mismatch() {
+    let mut set = ORSet::new();
+    set.insert("node1", "apple".to_string());
+    let bytes = set.to_capnp_bytes();
+
+    let result = ORSet::<i64>::merge_from_readers(&[ORSetReader::new(&bytes)]);
+    assert!(matches!(result, Err(CrdtError::SchemaMismatch(_))));
+
+    let mut gset = GSet::new();
+    gset.insert("node1", "apple".to_string());
+    let result = GSet::<i64>::from_capnp_bytes(&gset.to_capnp_bytes());
+    assert!(matches!(result, Err(CrdtError::SchemaMismatch(_))));
+
+    let mut map = LWWMap::new();
+    map.insert("node1", "k".to_string(), "v".to_string(), 1);
+    let result = LWWMap::<String, i64>::from_capnp_bytes(&map.to_capnp_bytes());
+    assert!(matches!(result, Err(CrdtError::SchemaMismatch(_))));
+
+    // The matching type still decodes
+    assert!(ORSet::<String>::merge_from_readers(&[ORSetReader::new(&bytes)]).is_ok());
+}
+
+#[test]
+fn test_evict_node_removes_contributions_locally() {
+    let mut set = ORSet::new();
+    set.insert("good", "shared".to_string());
+    set.insert("bad", "shared".to_string());
+    set.insert("bad", "only_bad".to_string());
+    set.evict_node("bad");
+    assert!(set.contains(&"shared".to_string()));
+    assert!(!set.contains(&"only_bad".to_string()));
+    assert!(set.elements.iter().all(|(_, obs)| obs.iter().all(|dot| dot.node_id != "bad")));
+
+    let mut gc = GCounter::new();
+    gc.increment("good", 3);
+    gc.increment("bad", 100);
+    gc.evict_node("bad");
+    assert_eq!(gc.value(), 3);
+
+    let mut pn = PNCounter::new();
+    pn.increment("good", 3);
+    pn.decrement("bad", 50);
+    pn.evict_node("bad");
+    assert_eq!(pn.value(), 3);
+}
+
+#[test]
+fn test_orset_conflict_policies_on_concurrent_add_remove() {
+    let run = |policy: ConflictPolicy| {
+        let mut a = ORSet::new().with_policy(policy);
+        a.insert("node_a", "x".to_string());
+        let mut b = a.clone();
+
+        a.insert("node_a", "x".to_string());
+        b.remove_by("node_b", &"x".to_string());
+
+        let mut ab = a.clone();
+        ab.merge(&b);
+        let mut ba = b.clone();
+        ba.merge(&a);
+        assert_eq!(ab, ba);
+        ab
+    };
+
+    assert!(run(ConflictPolicy::AddWins).contains(&"x".to_string()));
+
+    let merged = run(ConflictPolicy::RemoveWins);
+    assert!(!merged.contains(&"x".to_string()));
+
+    // An add that observed the removal wins over it
+    let mut readded = merged.clone();
+    readded.insert("node_c", "x".to_string());
+    let mut synced = merged.clone();
+    synced.merge(&readded);
+    assert!(synced.contains(&"x".to_string()));
+
+    // The policy and remove dots survive Cap'n Proto
+    let decoded = ORSet::<String>::from_capnp_bytes(&merged.to_capnp_bytes()).unwrap();
+    assert_eq!(decoded, merged);
+    let mut stale = run(ConflictPolicy::RemoveWins);
+    stale.merge(&decoded);
+    assert!(!stale.contains(&"x".to_string()));
+}
+
+#[test]
+fn test_orset_rejects_mixed_conflict_policies() {
+    let mut add_wins = ORSet::new();
+    add_wins.insert("node_a", "x".to_string());
+    let remove_wins = ORSet::<String>::new().with_policy(ConflictPolicy::RemoveWins);
+
+    let (b1, b2) = (add_wins.to_capnp_bytes(), remove_wins.to_capnp_bytes());
+    let result = ORSet::<String>::merge_from_readers(&[ORSetReader::new(&b1), ORSetReader::new(&b2)]);
+    assert!(matches!(result, Err(CrdtError::Merge(_))));
+}
+
+#[test]
+fn test_counters_from_node_counts() {
+    use std::collections::HashMap;
+
+    let counts: HashMap<String, i64> =
+        [("node_a".to_string(), 10), ("node_b".to_string(), 32)].into_iter().collect();
+    let gc = GCounter::from_node_counts(counts.clone()).unwrap();
+    assert_eq!(gc.value(), counts.values().sum::<i64>());
+    assert_eq!(gc.vclock.clocks.get("node_a").map(|(c, _)| *c), Some(1));
+    gc.validate().unwrap();
+
+    let negative: HashMap<String, i64> =
+        [("node_b".to_string(), 2), ("node_c".to_string(), 5)].into_iter().collect();
+    let pn = PNCounter::from_node_counts(counts, negative).unwrap();
+    assert_eq!(pn.value(), 42 - 7);
+    assert_eq!(pn.vclock.clocks.len(), 3);
+
+    let bad: HashMap<String, i64> = [("node_a".to_string(), -1)].into_iter().collect();
+    assert!(matches!(GCounter::from_node_counts(bad), Err(CrdtError::InvalidInput(_))));
+}
+
+#[test]
+fn test_mvregister_clear_survives_merge() {
+    let mut a = MVRegister::new();
+    a.set("node_a", "v1".to_string());
+    let mut b = a.clone();
+
+    // node_b observed v1 and clears it, while node_c writes concurrently
+    b.clear("node_b");
+    assert!(b.entries.is_empty());
+    let mut c = a.clone();
+    c.set("node_c", "v2".to_string());
+
+    let mut merged = b.clone();
+    merged.merge(&a);
+    assert!(merged.entries.is_empty(), "cleared value must not come back");
+
+    merged.merge(&c);
+    assert_eq!(merged.versions(), ["v2".to_string()].into_iter().collect());
+
+    let bytes = b.to_capnp_bytes();
+    let decoded = MVRegister::<String>::from_capnp_bytes(&bytes).unwrap();
+    assert_eq!(decoded, b);
+}
+
+#[test]
+fn test_gset_sorted_iter_ignores_insertion_order() {
+    let mut forward = GSet::new();
+    let mut shuffled = GSet::new();
+    for n in [1, 2, 3, 4, 5] {
+        forward.insert("node_a", n);
+    }
+    for n in [4, 1, 5, 3, 2] {
+        shuffled.insert("node_b", n);
+    }
+    let mut merged = GSet::new();
+    merged.insert("node_c", 9);
+    merged.merge(&shuffled);
+
+    assert_eq!(shuffled.sorted_iter().copied().collect::<Vec<_>>(), vec![1, 2, 3, 4, 5]);
+    assert!(forward.sorted_iter().eq(shuffled.sorted_iter()));
+    assert_eq!(merged.sorted_iter().copied().collect::<Vec<_>>(), vec![1, 2, 3, 4, 5, 9]);
+}
+
+#[test]
+fn test_lww_set_tie_bias_at_equal_timestamps() {
+    let apple = "apple".to_string();
+    for (bias, expected) in [(ConflictPolicy::AddWins, true), (ConflictPolicy::RemoveWins, false)] {
+        // Node ids chosen so the default node-order rule would pick the opposite outcome
+        let (add_node, remove_node) = if expected { ("node_a", "node_b") } else { ("node_b", "node_a") };
+        let mut adds = LWWSet::new().with_tie_bias(bias);
+        adds.insert(add_node, apple.clone(), 100);
+        let mut removes = LWWSet::new().with_tie_bias(bias);
+        removes.remove(remove_node, apple.clone(), 100);
+
+        let merged = adds.clone().merged(&removes);
+        assert_eq!(merged.contains(&apple), expected);
+        assert_eq!(removes.merged(&adds).contains(&apple), expected);
+
+        let decoded = LWWSet::<String>::from_capnp_bytes(&merged.to_capnp_bytes()).unwrap();
+        assert_eq!(decoded.tie_bias, Some(bias));
+        assert_eq!(decoded.contains(&apple), expected);
+    }
+
+    let add_wins = LWWSet::<String>::new().with_tie_bias(ConflictPolicy::AddWins).to_capnp_bytes();
+    let remove_wins = LWWSet::<String>::new().with_tie_bias(ConflictPolicy::RemoveWins).to_capnp_bytes();
+    let readers = [LWWSetReader::new(&add_wins), LWWSetReader::new(&remove_wins)];
+    assert!(matches!(LWWSet::<String>::merge_from_readers(&readers), Err(CrdtError::Merge(_))));
+}
+
+#[test]
+fn test_gcounter_merge_bytes_in_place() {
+    let mut local = GCounter::new();
+    local.increment("node_a", 5);
+    local.increment("node_b", 1);
+
+    let mut remote = GCounter::new();
+    remote.increment("node_b", 3);
+    remote.increment("node_c", 2);
+
+    local.merge_bytes(&remote.to_capnp_bytes()).unwrap();
+    assert_eq!(local.value(), 10);
+    assert_eq!(local, remote.merged(&local.clone()));
+
+    assert!(local.merge_bytes(b"not capnp").is_err());
+}
+
+#[test]
+fn test_atomic_gcounter_concurrent_increments_are_exact() {
+    use std::sync::Arc;
+
+    let counter = Arc::new(AtomicGCounter::new());
+    let handles: Vec<_> = (0..8)
+        .map(|t| {
+            let counter = Arc::clone(&counter);
+            std::thread::spawn(move || {
+                let node = format!("node_{}", t % 4);
+                for _ in 0..10_000 {
+                    counter.increment(&node, 1);
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    assert_eq!(counter.value(), 80_000);
+    let snapshot = counter.snapshot();
+    assert_eq!(snapshot.value(), 80_000);
+    assert_eq!(snapshot.counters.len(), 4);
+    assert_eq!(snapshot.vclock.clocks["node_0"].0, 20_000);
+
+    let decoded = GCounter::from_capnp_bytes(&snapshot.to_capnp_bytes()).unwrap();
+    assert_eq!(decoded.value(), 80_000);
+    assert_eq!(AtomicGCounter::from_gcounter(&decoded).snapshot().counters, decoded.counters);
+}
+
+#[test]
+fn test_identity_merge_is_noop() {
+    let identity = GCounter::new();
+    assert!(identity.is_identity());
+
+    let mut gc = GCounter::new();
+    gc.increment("node_a", 5);
+    assert!(!gc.is_identity());
+    let before = gc.clone();
+    gc.merge(&identity);
+    assert_eq!(gc, before);
+
+    // Empty of visible content is not the same as the identity
+    let mut zeroed = GCounter::new();
+    zeroed.increment("node_a", 0);
+    assert_eq!(zeroed.value(), 0);
+    assert!(!zeroed.is_identity());
+
+    let bytes = [identity.to_capnp_bytes(), before.to_capnp_bytes(), identity.to_capnp_bytes()];
+    let readers: Vec<_> = bytes.iter().map(|b| GCounterReader::new(b)).collect();
+    assert_eq!(GCounter::merge_from_readers(&readers).unwrap().counters, before.counters);
+
+    assert!(ORSet::<String>::new().is_identity());
+    assert!(!ORSet::<String>::new().with_policy(ConflictPolicy::RemoveWins).is_identity());
+}
+
+#[test]
+fn test_write_to_and_read_from_stream() {
+    let mut map = LWWMap::new();
+    for i in 0..100 {
+        map.insert("node_a", format!("key_{}", i), i, i as u64);
+    }
+    let mut counter = GCounter::new();
+    counter.increment("node_a", 7);
+
+    let mut stream: Vec<u8> = Vec::new();
+    map.write_to(&mut stream).unwrap();
+    assert_eq!(stream, map.to_capnp_bytes());
+    counter.write_to(&mut stream).unwrap();
+
+    // Messages are read back one at a time from the same stream
+    let mut cursor = std::io::Cursor::new(stream);
+    let decoded: LWWMap<String, i32> = LWWMap::read_from(&mut cursor).unwrap();
+    assert_eq!(decoded.entries, map.entries);
+    assert_eq!(GCounter::read_from(&mut cursor).unwrap().value(), 7);
+    assert!(GCounter::read_from(&mut cursor).is_err());
+
+    // Types without an override fall back to writing `to_capnp_bytes`
+    let mut pn = PNCounter::new();
+    pn.decrement("node_a", 3);
+    let mut buf = Vec::new();
+    pn.write_to(&mut buf).unwrap();
+    assert_eq!(buf, pn.to_capnp_bytes());
+    assert_eq!(PNCounter::read_from(&mut buf.as_slice()).unwrap().value(), -3);
+
+    let truncated = &counter.to_capnp_bytes()[..12];
+    assert!(GCounter::read_from(&mut &truncated[..]).is_err());
+}
+
+#[test]
+fn test_mv_register_put_with_context_imports_concurrent_values() {
+    use std::collections::HashSet;
+
+    let dot = |node: &str, counter: u64| Dot::new(node, counter);
+    let clock = |entries: &[(&str, u64)]| {
+        let mut vc = VectorClock::new();
+        for (node, counter) in entries {
+            for _ in 0..*counter {
+                vc.increment(node);
+            }
+        }
+        vc
+    };
+
+    let mut reg = MVRegister::new();
+    reg.put_with_context("left".to_string(), HashSet::from([dot("ext_a", 3)]), clock(&[("ext_a", 3)]))
+        .unwrap();
+    reg.put_with_context("right".to_string(), HashSet::from([dot("ext_b", 5)]), clock(&[("ext_b", 5)]))
+        .unwrap();
+
+    assert_eq!(reg.versions(), HashSet::from(["left".to_string(), "right".to_string()]));
+    assert_eq!(reg.vclock.clocks["ext_a"].0, 3);
+    assert_eq!(reg.vclock.clocks["ext_b"].0, 5);
+    reg.validate().unwrap();
+
+    // Both survive a round trip and a merge with a replica that never saw them
+    let decoded = MVRegister::<String>::from_capnp_bytes(&reg.to_capnp_bytes()).unwrap();
+    assert_eq!(decoded.versions(), reg.versions());
+    let mut other = MVRegister::new();
+    other.set("node_c", "local".to_string());
+    other.merge(&decoded);
+    assert_eq!(other.versions().len(), 3);
+
+    // A version whose context covers both supersedes them
+    reg.put_with_context(
+        "resolved".to_string(),
+        HashSet::from([dot("ext_a", 4)]),
+        clock(&[("ext_a", 4), ("ext_b", 5)]),
+    )
+    .unwrap();
+    assert_eq!(reg.versions(), HashSet::from(["resolved".to_string()]));
+
+    assert!(reg.put_with_context("x".to_string(), HashSet::new(), VectorClock::new()).is_err());
+}
+
+#[test]
+fn test_orset_compact_shrinks_without_changing_semantics() {
+    let mut set = ORSet::new();
+    for _ in 0..50 {
+        set.insert("node_a", "apple".to_string());
+        set.insert("node_b", "apple".to_string());
+    }
+    set.insert("node_a", "banana".to_string());
+    let original = set.clone();
+
+    let mut other = original.clone();
+    other.remove(&"banana".to_string());
+    other.insert("node_c", "cherry".to_string());
+
+    set.compact();
+    assert_eq!(set.elements[0].1.len(), 2);
+    assert!(set.to_capnp_bytes().len() < original.to_capnp_bytes().len());
+    assert!(set.iter().eq(original.iter()));
+
+    // Merging either way gives the same membership as with the uncompacted state
+    let expected = original.clone().merged(&other);
+    assert!(set.clone().merged(&other).iter().eq(expected.iter()));
+    assert!(other.clone().merged(&set).iter().eq(expected.iter()));
+    assert!(!expected.contains(&"banana".to_string()));
+}
+
+#[test]
+fn test_mv_register_conflict_count() {
+    let mut reg1 = MVRegister::new();
+    reg1.set("node_a", "value1".to_string());
+    assert_eq!(reg1.conflict_count(), 1);
+    assert!(!reg1.is_conflicted());
+
+    let mut reg2 = MVRegister::new();
+    reg2.set("node_b", "value2".to_string());
+    reg1.merge(&reg2);
+    assert_eq!(reg1.conflict_count(), 2);
+    assert!(reg1.is_conflicted());
+
+    reg1.set("node_a", "resolved".to_string());
+    assert_eq!(reg1.conflict_count(), 1);
+    assert_eq!(MVRegister::<String>::new().conflict_count(), 0);
+}
+
+#[test]
+fn test_merge_from_readers_lenient_skips_corrupt_reader() {
+    let blobs: Vec<Vec<u8>> = ["node_a", "node_b", "node_c"]
+        .iter()
+        .map(|node| {
+            let mut set = ORSet::new();
+            set.insert(node, node.to_string());
+            set.to_capnp_bytes()
+        })
+        .collect();
+    let corrupt = vec![0xFFu8; 24];
+
+    let readers = [
+        ORSetReader::new(&blobs[0]),
+        ORSetReader::new(&corrupt),
+        ORSetReader::new(&blobs[1]),
+        ORSetReader::new(&blobs[2]),
+    ];
+    assert!(ORSet::<String>::merge_from_readers(&readers).is_err());
+
+    let (merged, errors) = ORSet::<String>::merge_from_readers_lenient(&readers).unwrap();
+    assert_eq!(merged.len(), 3);
+    assert!(merged.contains(&"node_c".to_string()));
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].0, 1);
+
+    // A clean batch reports nothing; an all-corrupt batch yields the empty state
+    let (_, errors) = ORSet::<String>::merge_from_readers_lenient(&[ORSetReader::new(&blobs[0])]).unwrap();
+    assert!(errors.is_empty());
+    let (empty, errors) = GCounter::merge_from_readers_lenient(&[GCounterReader::new(&corrupt)]).unwrap();
+    assert_eq!(empty, GCounter::new());
+    assert_eq!(errors.len(), 1);
+}
+
+fn merged_generic<C: Crdt>(a: &C, b: &C) -> C {
+    let mut result = a.clone();
+    Crdt::merge(&mut result, b);
+    result
+}
+
+#[test]
+fn test_crdt_trait_merge_generic() {
+    let mut a = GCounter::new();
+    a.increment("node_a", 2);
+    let mut b = GCounter::new();
+    b.increment("node_b", 3);
+    assert_eq!(merged_generic(&a, &b).value(), 5);
+
+    let mut s1 = ORSet::new();
+    s1.insert("node_a", "apple".to_string());
+    let mut s2 = ORSet::new();
+    s2.insert("node_b", "banana".to_string());
+    let merged = merged_generic(&s1, &s2);
+    assert_eq!(merged, s1.clone().merged(&s2));
+    assert_eq!(merged.len(), 2);
+
+    let r1 = LWWRegister::new("old".to_string(), 1, "node_a");
+    let r2 = LWWRegister::new("new".to_string(), 2, "node_b");
+    assert_eq!(merged_generic(&r1, &r2).value, "new");
+}
+
+#[test]
+fn test_dot_is_dominated_by_clock() {
+    let mut vclock = VectorClock::new();
+    vclock.increment("node_a");
+    vclock.increment("node_a");
+    vclock.increment("node_b");
+
+    assert!(Dot::new("node_a", 1).is_dominated_by(&vclock));
+    assert!(Dot::new("node_a", 2).is_dominated_by(&vclock));
+    assert!(!Dot::new("node_a", 3).is_dominated_by(&vclock));
+    assert!(Dot::new("node_b", 1).is_dominated_by(&vclock));
+    assert!(!Dot::new("node_c", 1).is_dominated_by(&VectorClock::new()));
+
+    // Tuple conversions, ordering, and the pair-shaped serde format
+    let dot: Dot = ("node_a".to_string(), 2).into();
+    assert_eq!(dot, Dot::new("node_a", 2));
+    assert!(Dot::new("node_a", 9) < Dot::new("node_b", 1));
+    assert!(Dot::new("node_a", 1) < Dot::new("node_a", 2));
+    assert_eq!(serde_json::to_value(&dot).unwrap(), serde_json::json!(["node_a", 2]));
+    let (node_id, counter): (String, u64) = dot.into();
+    assert_eq!((node_id.as_str(), counter), ("node_a", 2));
+}
+
+#[test]
+fn test_bloom_filter_does_not_change_contains() {
+    let mut plain = ORSet::new();
+    let mut filtered = ORSet::new().with_bloom(100, 0.01);
+    for i in 0..60 {
+        plain.insert("node_a", format!("item_{}", i));
+        filtered.insert("node_a", format!("item_{}", i));
+    }
+    for i in (0..60).step_by(3) {
+        plain.remove(&format!("item_{}", i));
+        filtered.remove(&format!("item_{}", i));
+    }
+
+    // One peer with an identically sized filter (unioned), one without (rebuilt)
+    let mut peer = ORSet::new().with_bloom(100, 0.01);
+    let mut bare = ORSet::new();
+    for i in 50..80 {
+        peer.insert("node_b", format!("item_{}", i));
+        bare.insert("node_c", format!("item_{}", i + 30));
+    }
+    for other in [&peer, &bare] {
+        plain.merge(other);
+        filtered.merge(other);
+    }
+    assert_eq!(plain, filtered);
+
+    let mut gset_plain = GSet::new();
+    let mut gset_filtered = GSet::new().with_bloom(10, 0.05);
+    let mut gset_peer = GSet::new();
+    for i in 0..40 {
+        gset_plain.insert("node_a", format!("item_{}", i));
+        gset_filtered.insert("node_a", format!("item_{}", i));
+        gset_peer.insert("node_b", format!("item_{}", i + 100));
+    }
+    gset_plain.merge(&gset_peer);
+    gset_filtered.merge(&gset_peer);
+
+    for i in 0..500 {
+        let element = format!("item_{}", i);
+        assert_eq!(plain.contains(&element), filtered.contains(&element), "{}", element);
+        assert_eq!(gset_plain.contains(&element), gset_filtered.contains(&element), "{}", element);
+    }
+}
+
+#[test]
+fn test_merge_from_readers_bounded() {
+    let mut orset = ORSet::new();
+    for i in 0..100 {
+        orset.insert("node_a", format!("item_{}", i));
+    }
+    let bytes = orset.to_capnp_bytes();
+    let readers = [ORSetReader::new(&bytes), ORSetReader::new(&bytes)];
+
+    // Entries are counted per reader, and each element carries one dot, so two copies of
+    // 100 elements need a budget of 400
+    assert!(matches!(
+        ORSet::<String>::merge_from_readers_bounded(&readers, 399),
+        Err(CrdtError::SizeLimitExceeded(_))
+    ));
+    assert_eq!(ORSet::merge_from_readers_bounded(&readers, 400).unwrap(), orset);
+
+    let mut map = LWWMap::new();
+    for i in 0..10 {
+        map.insert("node_a", format!("key_{}", i), format!("value_{}", i), i as u64);
+    }
+    let map_bytes = map.to_capnp_bytes();
+    let map_readers = [LWWMapReader::new(&map_bytes)];
+    assert!(matches!(
+        LWWMap::<String, String>::merge_from_readers_bounded(&map_readers, 9),
+        Err(CrdtError::SizeLimitExceeded(_))
+    ));
+    assert!(LWWMap::<String, String>::merge_from_readers_bounded(&map_readers, 10).is_ok());
+
+    let mut register = MVRegister::new();
+    register.set("node_a", "a".to_string());
+    let mut other = MVRegister::new();
+    other.set("node_b", "b".to_string());
+    register.merge(&other);
+    let reg_bytes = register.to_capnp_bytes();
+    let reg_readers = [MVRegisterReader::new(&reg_bytes)];
+    assert!(matches!(
+        MVRegister::<String>::merge_from_readers_bounded(&reg_readers, 1),
+        Err(CrdtError::SizeLimitExceeded(_))
+    ));
+    assert!(MVRegister::<String>::merge_from_readers_bounded(&reg_readers, 2).is_ok());
+}
+
+#[test]
+fn test_node_id_dictionary_shrinks_serialization() {
+    use capnp::message::ReaderOptions;
+    use crdt_data_types::orset_capnp::or_set;
+
+    let mut set = ORSet::new();
+    for i in 0..10_000 {
+        set.insert(&format!("node_{}", i % 3), format!("item_{}", i));
+    }
+    let bytes = set.to_capnp_interned_bytes();
+    assert_eq!(ORSet::<String>::from_capnp_bytes(&bytes).unwrap(), set);
+
+    // The default layout repeats the node id on every dot, which older readers expect
+    let legacy = set.to_capnp_bytes();
+    let message = capnp::serialize::read_message(legacy.as_slice(), ReaderOptions::new()).unwrap();
+    let root = message.get_root::<or_set::Reader>().unwrap();
+    assert!(!root.has_node_ids());
+    assert_eq!(root.get_elements().unwrap().get(0).get_ids().unwrap().len(), 1);
+    assert_eq!(ORSet::<String>::from_capnp_bytes(&legacy).unwrap(), set);
+    assert!(bytes.len() * 10 < legacy.len() * 8, "{} vs {}", bytes.len(), legacy.len());
+
+    let mut map = LWWMap::new();
+    for i in 0..1_000 {
+        map.insert(&format!("node_{}", i % 3), i, i, i as u64);
+    }
+    for map_bytes in [map.to_capnp_bytes(), map.to_capnp_interned_bytes()] {
+        assert_eq!(LWWMap::<i32, i32>::from_capnp_bytes(&map_bytes).unwrap(), map);
+    }
+    assert!(map.to_capnp_interned_bytes().len() < map.to_capnp_bytes().len());
+
+    let mut reg = MVRegister::new();
+    reg.set("node_a", "x".to_string());
+    let mut other = MVRegister::new();
+    other.set("node_b", "y".to_string());
+    reg.merge(&other);
+    for reg_bytes in [reg.to_capnp_bytes(), reg.to_capnp_interned_bytes()] {
+        assert_eq!(MVRegister::<String>::from_capnp_bytes(&reg_bytes).unwrap(), reg);
+    }
+}
+
+#[test]
+fn test_set_intersection_and_difference_match_hashset() {
+    use std::collections::HashSet;
+
+    let a: Vec<String> = (0..30).map(|i| format!("item_{}", i)).collect();
+    let b: Vec<String> = (20..50).map(|i| format!("item_{}", i)).collect();
+    let oracle_a: HashSet<String> = a.iter().cloned().collect();
+    let oracle_b: HashSet<String> = b.iter().cloned().collect();
+
+    let mut orset_a = ORSet::new();
+    let mut gset_a = GSet::new();
+    for element in &a {
+        orset_a.insert("node_a", element.clone());
+        gset_a.insert("node_a", element.clone());
+    }
+    let mut orset_b = ORSet::new();
+    let mut gset_b = GSet::new();
+    for element in &b {
+        orset_b.insert("node_b", element.clone());
+        gset_b.insert("node_b", element.clone());
+    }
+
+    let intersection: HashSet<String> = oracle_a.intersection(&oracle_b).cloned().collect();
+    let difference: HashSet<String> = oracle_a.difference(&oracle_b).cloned().collect();
+    let symmetric: HashSet<String> = oracle_a.symmetric_difference(&oracle_b).cloned().collect();
+    assert_eq!(orset_a.intersection(&orset_b), intersection);
+    assert_eq!(orset_a.difference(&orset_b), difference);
+    assert_eq!(orset_a.symmetric_difference(&orset_b), symmetric);
+    assert_eq!(gset_a.intersection(&gset_b), intersection);
+    assert_eq!(gset_a.difference(&gset_b), difference);
+    assert_eq!(gset_a.symmetric_difference(&gset_b), symmetric);
+
+    // Only live elements count for the OR-Set
+    orset_b.remove(&"item_25".to_string());
+    assert!(!orset_a.intersection(&orset_b).contains("item_25"));
+    assert!(orset_a.difference(&orset_b).contains("item_25"));
+}
+
+#[test]
+fn test_orset_provenance() {
+    let mut set1 = ORSet::new();
+    set1.insert("node1", "shared".to_string());
+    set1.insert("node1", "shared".to_string());
+    set1.insert("node1", "only_node1".to_string());
+    let mut set2 = ORSet::new();
+    set2.insert("node2", "shared".to_string());
+    set1.merge(&set2);
+
+    assert_eq!(set1.provenance(&"shared".to_string()), vec!["node1", "node2"]);
+    assert_eq!(set1.provenance(&"only_node1".to_string()), vec!["node1"]);
+    assert!(set1.provenance(&"missing".to_string()).is_empty());
+
+    set1.remove(&"shared".to_string());
+    assert!(set1.provenance(&"shared".to_string()).is_empty());
+}
+
+#[test]
+fn test_ormap_per_key_clocks() {
+    let a = "a".to_string();
+    let b = "b".to_string();
+    let mut map: ORMap<String, String> = ORMap::new().with_key_clocks();
+    map.insert("node1", a.clone(), "1".to_string());
+    map.insert("node1", b.clone(), "1".to_string());
+    let b_clock = map.key_clock(&b).cloned().unwrap();
+
+    // Writes to `a` leave `b`'s causal context untouched
+    map.insert("node1", a.clone(), "2".to_string());
+    map.insert("node2", a.clone(), "3".to_string());
+    assert_eq!(map.key_clock(&b), Some(&b_clock));
+    assert_eq!(map.key_clock(&a).unwrap().clocks.len(), 2);
+
+    // A peer that has seen `b` is sent nothing for it, and only `a`'s state otherwise
+    let mut peer: ORMap<String, String> = ORMap::new();
+    peer.merge(&map.delta_for_key(&b, &VectorClock::new()).unwrap());
+    assert!(map.delta_for_key(&b, peer.key_clock(&b).unwrap()).is_none());
+    assert!(!peer.contains_key(&a));
+    let delta = map.delta_for_key(&a, &VectorClock::new()).unwrap();
+    assert_eq!(delta.len(), 1);
+    peer.merge(&delta);
+    assert!(peer.get_concurrent(&a).contains("3"));
+
+    // A delta for `a` cannot remove what the peer wrote to `b` in the meantime
+    peer.insert("node3", b.clone(), "peer".to_string());
+    map.remove_by("node1", &a);
+    peer.merge(&map.delta_for_key(&a, peer.key_clock(&a).unwrap()).unwrap());
+    assert!(!peer.contains_key(&a));
+    assert!(peer.get_concurrent(&b).contains("peer"));
+
+    // Key clocks survive both encodings
+    let bytes = peer.to_capnp_bytes();
+    assert_eq!(ORMap::<String, String>::merge_from_readers(&[ORMapReader::new(&bytes)]).unwrap(), peer);
+    let json = serde_json::to_value(&peer).unwrap();
+    assert_eq!(serde_json::from_value::<ORMap<String, String>>(json).unwrap(), peer);
+
+    // Without the opt-in, a new key is described by the map-wide clock
+    let mut plain: ORMap<String, String> = ORMap::new();
+    plain.insert("node1", a.clone(), "1".to_string());
+    assert!(plain.key_clock(&a).is_none());
+    assert!(plain.delta_for_key(&a, &VectorClock::new()).is_some());
+}
+
+#[test]
+fn test_ormap_key_delta_leaves_map_clock_alone() {
+    let a = "a".to_string();
+    let b = "b".to_string();
+    let mut map: ORMap<String, String> = ORMap::new().with_key_clocks();
+    map.insert("node1", a.clone(), "1".to_string());
+    map.insert("node1", b.clone(), "1".to_string());
+
+    // Only `b`'s clock travels: the receiver's map-wide clock claims nothing about `a`
+    let delta = map.delta_for_key(&b, &VectorClock::new()).unwrap();
+    assert!(delta.vclock.clocks.is_empty());
+    let mut peer: ORMap<String, String> = ORMap::new();
+    peer.merge(&delta);
+    assert!(peer.vclock.clocks.is_empty());
+    assert_eq!(peer.key_clock(&b), map.key_clock(&b));
+    assert!(!peer.dominates(&map));
+
+    // The full state still reaches the peer afterwards
+    peer.merge(&map);
+    assert!(peer.contains_key(&a));
+
+    // Removed keys drop their clock; the map-wide clock stands in for it
+    map.remove_by("node1", &a);
+    assert!(map.key_clock(&a).is_none());
+    peer.merge(&map.delta_for_key(&a, peer.key_clock(&a).unwrap()).unwrap());
+    assert!(!peer.contains_key(&a));
+    peer.merge(&map);
+    peer.remove(&a);
+    assert!(peer.key_clock(&a).is_none());
+}
+
+#[test]
+fn test_counter_merge_ignores_vclock_state() {
+    // Built by hand: counters ahead of (or missing from) their vclocks
+    let mut a = GCounter::new();
+    a.counters = vec![("node1".to_string(), 7), ("node2".to_string(), 2)];
+    let mut b = GCounter::new();
+    b.counters = vec![("node2".to_string(), 5), ("node3".to_string(), 1)];
+    b.vclock.increment("node1");
+    b.vclock.increment("node1");
+
+    let expected = vec![
+        ("node1".to_string(), 7),
+        ("node2".to_string(), 5),
+        ("node3".to_string(), 1),
+    ];
+    assert_eq!(a.clone().merged(&b).counters, expected);
+    assert_eq!(b.clone().merged(&a).counters, expected);
+    let twice = a.clone().merged(&b).merged(&b).merged(&a);
+    assert_eq!(twice.counters, expected);
+    let (a_bytes, b_bytes) = (a.to_capnp_bytes(), b.to_capnp_bytes());
+    let from_bytes =
+        GCounter::merge_from_readers(&[GCounterReader::new(&a_bytes), GCounterReader::new(&b_bytes)])
+            .unwrap();
+    assert_eq!(from_bytes.counters, expected);
+
+    let mut pn_a = PNCounter::new();
+    pn_a.positive = a.clone();
+    pn_a.negative.counters = vec![("node1".to_string(), 3)];
+    let mut pn_b = PNCounter::new();
+    pn_b.positive = b.clone();
+    pn_b.negative.counters = vec![("node1".to_string(), 1), ("node3".to_string(), 4)];
+    let merged = pn_a.clone().merged(&pn_b);
+    assert_eq!(merged, pn_b.clone().merged(&pn_a));
+    assert_eq!(merged.clone().merged(&pn_a).merged(&pn_b), merged);
+    assert_eq!(merged.value(), 13 - 7);
+}
+
+#[test]
+fn test_disable_register_disable_beats_concurrent_set() {
+    let base = DisableRegister::new("draft".to_string(), 100, "node_a");
+    let mut a = base.clone();
+    let mut b = base.clone();
+
+    a.disable("node_a");
+    b.set("final".to_string(), 200, "node_b");
+    assert_eq!(b.get().map(String::as_str), Some("final"));
+
+    let ab = a.clone().merged(&b);
+    let ba = b.clone().merged(&a);
+    assert_eq!(ab, ba);
+    assert!(ab.is_disabled());
+    assert_eq!(ab.get(), None);
+    assert!(ab.validate().is_ok());
+
+    // The tombstone is permanent: later sets are ignored.
+    let mut after = ab.clone();
+    after.set("revived".to_string(), 300, "node_b");
+    assert_eq!(after, ab);
+
+    let (a_bytes, b_bytes) = (a.to_capnp_bytes(), b.to_capnp_bytes());
+    assert!(DisableRegisterReader::<String>::new(&a_bytes).is_disabled().unwrap());
+    let from_bytes = DisableRegister::merge_from_readers(&[
+        DisableRegisterReader::new(&b_bytes),
+        DisableRegisterReader::new(&a_bytes),
+    ])
+    .unwrap();
+    assert_eq!(from_bytes, ab);
+}
+
+#[test]
+fn test_orset_dominates_by_vclock() {
+    let mut a = ORSet::new();
+    a.insert("node_a", "apple".to_string());
+    let mut b = a.clone();
+    b.insert("node_b", "pear".to_string());
+
+    assert!(b.dominates(&a));
+    assert!(!a.dominates(&b));
+    assert!(a.dominates(&a.clone()));
+
+    // Concurrent updates: neither side has seen the other.
+    a.insert("node_a", "cherry".to_string());
+    assert!(!a.dominates(&b));
+    assert!(!b.dominates(&a));
+
+    let merged = a.clone().merged(&b);
+    assert!(merged.dominates(&a) && merged.dominates(&b));
+    assert_eq!(merged.clone().merged(&a), merged);
+}
+
+#[test]
+fn test_dominates_rejects_updates_without_clock_ticks() {
+    fn merge_is_noop<C: Crdt + PartialEq>(a: &C, b: &C) -> bool {
+        let mut merged = a.clone();
+        merged.merge(b);
+        merged == *a
+    }
+
+    // Per-node totals carry more than the clock that counted the increments
+    let mut ticked = GCounter::new();
+    for _ in 0..5 {
+        ticked.increment("node_a", 1);
+    }
+    let totals = GCounter::from_node_counts(std::collections::HashMap::from([("node_a".to_string(), 10)])).unwrap();
+    assert!(ticked.vclock.dominates(&totals.vclock));
+    assert!(!merge_is_noop(&ticked, &totals));
+    assert!(!ticked.dominates(&totals));
+
+    // Expiry records tombstones without ticking
+    let mut live: LWWMap<String, i64> = LWWMap::new().with_ttl(std::time::Duration::from_millis(10));
+    live.insert("node_a", "k".to_string(), 1, 1_000);
+    let mut expired = live.clone();
+    assert_eq!(expired.expire_stale(2_000), 1);
+    assert!(!merge_is_noop(&live, &expired));
+    assert!(!live.dominates(&expired));
+    assert!(expired.dominates(&live));
+
+    // Evicting to capacity must not leave a state that claims the evicted add
+    let mut before = LWWSet::new().with_capacity(1);
+    before.insert("node_a", "old".to_string(), 1);
+    let mut after = before.clone();
+    after.insert("node_a", "new".to_string(), 2);
+    assert!(!after.contains(&"old".to_string()));
+    for (a, b) in [(&after, &before), (&before, &after)] {
+        assert!(!a.dominates(b) || merge_is_noop(a, b));
+    }
+}
+
+#[test]
+fn test_element_type_tags_are_explicit() {
+    use capnp::message::ReaderOptions;
+    use crdt_data_types::orset_capnp::or_set;
+
+    let element_type = |bytes: &[u8]| {
+        let message = capnp::serialize::read_message(bytes, ReaderOptions::new()).unwrap();
+        message.get_root::<or_set::Reader>().unwrap().get_element_type()
+    };
+
+    // FNV-1a of the fixed name "string": the same in every build
+    let mut set = ORSet::new();
+    set.insert("node1", "apple".to_string());
+    assert_eq!(element_type(&set.to_capnp_bytes()), 0x704b_e0d8_faaf_fc58);
+
+    // Types without a fixed name are written untagged and read back without a check
+    #[derive(Debug, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord, serde::Serialize, serde::Deserialize)]
+    struct Custom(String);
+    let mut custom = ORSet::new();
+    custom.insert("node1", Custom("apple".to_string()));
+    let bytes = custom.to_capnp_bytes();
+    assert_eq!(element_type(&bytes), 0);
+    assert_eq!(ORSet::<Custom>::from_capnp_bytes(&bytes).unwrap(), custom);
+}
+
+#[test]
+fn test_external_crdt_uses_default_byte_helpers() {
+    // A downstream type that implements only the required methods
+    #[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+    struct Tally(GCounter);
+
+    impl Crdt for Tally {
+        type Reader<'a> = GCounterReader<'a>;
+
+        fn merge_from_readers(readers: &[GCounterReader<'_>]) -> Result<Self, CrdtError> {
+            GCounter::merge_from_readers(readers).map(Tally)
+        }
+
+        fn merge(&mut self, other: &Self) {
+            self.0.merge(&other.0);
+        }
+
+        fn validate(&self) -> Result<(), CrdtError> {
+            Ok(())
+        }
+
+        fn is_empty(&self) -> bool {
+            self.0.is_empty()
+        }
+
+        fn to_capnp_bytes(&self) -> Vec<u8> {
+            self.0.to_capnp_bytes()
+        }
+    }
+
+    let mut ours = GCounter::new();
+    ours.increment("node1", 2);
+    let mut theirs = GCounter::new();
+    theirs.increment("node2", 3);
+
+    let mut tally = Tally(ours);
+    Crdt::merge_bytes(&mut tally, &theirs.to_capnp_bytes()).unwrap();
+    assert_eq!(tally.0.value(), 5);
+    assert!(Crdt::merge_bytes(&mut tally, &[1, 2, 3]).is_err());
+    assert_eq!(tally.0.value(), 5);
+
+    // The default builder path copies the type's own encoding
+    let mut message = capnp::message::Builder::new_default();
+    let mut buf = Vec::new();
+    tally.to_capnp_bytes_into(&mut message, &mut buf).unwrap();
+    assert_eq!(buf, tally.to_capnp_bytes());
+
+    let shared = Shared::new(tally);
+    shared.merge_bytes(&theirs.to_capnp_bytes()).unwrap();
+    assert_eq!(shared.read(|t| t.0.value()), 5);
+}
+
+#[test]
+fn test_orset_bounded_merge_counts_dots() {
+    use crdt_data_types::orset_capnp::or_set;
+
+    // One element claiming 10,000 dots, in the per-element layout
+    let mut message = capnp::message::Builder::new_default();
+    {
+        let root = message.init_root::<or_set::Builder>();
+        let mut elements = root.init_elements(1);
+        let mut element = elements.reborrow().get(0);
+        element.set_element(&serde_json::to_vec("apple").unwrap());
+        let mut ids = element.init_ids(10_000);
+        for i in 0..10_000 {
+            let mut id = ids.reborrow().get(i);
+            id.set_node_id("node_a".into());
+            id.set_counter(u64::from(i) + 1);
+        }
+    }
+    let mut per_element = Vec::new();
+    capnp::serialize::write_message(&mut per_element, &message).unwrap();
+
+    // The same in the dot-column layout
+    let mut message = capnp::message::Builder::new_default();
+    {
+        let mut root = message.init_root::<or_set::Builder>();
+        root.reborrow().init_node_ids(1).set(0, "node_a".into());
+        let mut elements = root.reborrow().init_elements(1);
+        let mut element = elements.reborrow().get(0);
+        element.set_element(&serde_json::to_vec("apple").unwrap());
+        element.set_dot_count(10_000);
+        root.reborrow().init_dot_nodes(10_000);
+        let mut counters = root.init_dot_counters(10_000);
+        for i in 0..10_000 {
+            counters.set(i, u64::from(i) + 1);
+        }
+    }
+    let mut columns = Vec::new();
+    capnp::serialize::write_message(&mut columns, &message).unwrap();
+
+    for bytes in [&per_element, &columns] {
+        let reader = ORSetReader::<String>::new(bytes);
+        assert_eq!(reader.entry_count().unwrap(), 10_001);
+        assert!(matches!(
+            ORSet::<String>::merge_from_readers_bounded(&[reader], 100),
+            Err(CrdtError::SizeLimitExceeded(_))
+        ));
+    }
+}
+
+#[test]
+fn test_atomic_gcounter_saturates_instead_of_wrapping() {
+    let counter = AtomicGCounter::new();
+    counter.increment("node_a", i64::MAX);
+    counter.increment("node_a", 5);
+    assert_eq!(counter.value(), i64::MAX);
+    assert_eq!(counter.snapshot().value(), i64::MAX);
+}
+
+#[test]
+fn test_merge_from_readers_lenient_reports_mismatched_settings() {
+    let mut add_wins = ORSet::new();
+    add_wins.insert("node_a", "a".to_string());
+    let mut remove_wins = ORSet::new().with_policy(ConflictPolicy::RemoveWins);
+    remove_wins.insert("node_b", "b".to_string());
+    let (add_wins, remove_wins) = (add_wins.to_capnp_bytes(), remove_wins.to_capnp_bytes());
+    let corrupt = vec![0xFFu8; 24];
+
+    let readers = [
+        ORSetReader::new(&add_wins),
+        ORSetReader::new(&corrupt),
+        ORSetReader::new(&remove_wins),
+    ];
+    let (merged, errors) = ORSet::<String>::merge_from_readers_lenient(&readers).unwrap();
+    assert_eq!(merged.policy, ConflictPolicy::AddWins);
+    assert!(!merged.contains(&"b".to_string()));
+    assert_eq!(errors.iter().map(|(idx, _)| *idx).collect::<Vec<_>>(), vec![1, 2]);
+    assert!(matches!(errors[1].1, CrdtError::Merge(_)));
+}
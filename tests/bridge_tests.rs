@@ -114,3 +114,371 @@ fn test_case_insensitive_parsing() {
     assert!(CrdtType::from_str("invalid_type").is_err());
 }
 
+
+#[test]
+fn test_detect_type_of_framed_gset() {
+    let mut set = GSet::new();
+    set.insert("node_a", "apple".to_string());
+    let payload = set.to_capnp_bytes();
+
+    let framed = SerdeCapnpBridge::frame(CrdtType::GSet, &payload);
+    assert_eq!(SerdeCapnpBridge::detect_type(&framed).unwrap(), CrdtType::GSet);
+
+    let (crdt_type, inner) = SerdeCapnpBridge::unframe(&framed).unwrap();
+    let json = SerdeCapnpBridge::capnp_bytes_to_json(crdt_type, inner).unwrap();
+    assert_eq!(json["elements"][0], "apple");
+
+    // Raw capnp bytes carry no type information
+    assert!(SerdeCapnpBridge::detect_type(&payload).is_err());
+}
+
+#[test]
+fn test_inspect_small_and_large_messages() {
+    let mut gc = GCounter::new();
+    gc.increment("node_a", 1);
+    let small = SerdeCapnpBridge::inspect(&gc.to_capnp_bytes()).unwrap();
+    assert_eq!(small.segment_count, 1);
+    assert!(small.traversed_words > 0);
+    assert!(small.traversed_words <= small.total_words as u64);
+    assert!(small.within_default_traversal_limit);
+
+    let mut set = GSet::new();
+    for i in 0..5000 {
+        set.insert("node_a", format!("element-{:05}", i));
+    }
+    let large = SerdeCapnpBridge::inspect(&set.to_capnp_bytes()).unwrap();
+    assert!(large.segment_count > 1);
+    assert!(large.total_words > 10 * small.total_words);
+    assert!(large.within_default_traversal_limit);
+
+    assert!(SerdeCapnpBridge::inspect(&[1, 2, 3]).is_err());
+}
+
+#[test]
+fn test_bridge_shape_validation_names_field() {
+    let field_error = |crdt_type: CrdtType, value: serde_json::Value| {
+        match SerdeCapnpBridge::json_to_capnp_bytes(crdt_type, value) {
+            Err(CrdtError::Validation(msg)) => msg,
+            other => panic!("expected a validation error, got {:?}", other),
+        }
+    };
+
+    assert_eq!(
+        field_error(CrdtType::GSet, json!({"elements": [1, 2]})),
+        "$.elements[0]: expected a string, found a number"
+    );
+    assert_eq!(
+        field_error(CrdtType::GSet, json!({"elements": "apple"})),
+        "$.elements: expected an array, found a string"
+    );
+    assert_eq!(field_error(CrdtType::GSet, json!([])), "$: expected an object, found an array");
+
+    assert!(field_error(CrdtType::GCounter, json!({"counters": {"a": "10"}})).starts_with("$.counters.a:"));
+    assert!(field_error(CrdtType::GCounter, json!({"counters": [["a", 1]]})).starts_with("$.counters:"));
+    assert!(field_error(CrdtType::PNCounter, json!({"positive": {"counters": {"a": 1.5}}}))
+        .starts_with("$.positive.counters.a:"));
+
+    assert!(field_error(CrdtType::ORSet, json!({"elements": [{"element": true, "observations": []}]}))
+        .starts_with("$.elements[0].element:"));
+    assert!(field_error(CrdtType::ORSet, json!({"elements": ["apple"]})).starts_with("$.elements[0]:"));
+
+    assert!(field_error(CrdtType::LWWRegister, json!({"value": 5, "timestamp": 1, "node_id": "a"}))
+        .starts_with("$.value:"));
+    assert!(field_error(CrdtType::LWWMap, json!({"entries": {"k": [7, 3, "a"]}})).starts_with("$.entries.k[0]:"));
+    assert!(field_error(CrdtType::LWWMap, json!({"entries": {"k": []}})).starts_with("$.entries.k:"));
+    assert!(field_error(
+        CrdtType::ORMap,
+        json!({"elements": {"elements": [{"element": ["k", 1], "observations": []}]}})
+    )
+    .starts_with("$.elements.elements[0].element[1]:"));
+    assert!(field_error(CrdtType::LWWSet, json!({"add_set": ["x"]})).starts_with("$.add_set:"));
+    assert!(field_error(CrdtType::GSet, json!({"elements": [], "vclock": {"clocks": []}}))
+        .starts_with("$.vclock.clocks:"));
+
+    // Well-formed input still converts, and merging checks every value
+    assert!(SerdeCapnpBridge::validate_json(CrdtType::GSet, json!({"elements": ["a"]})).is_ok());
+    assert!(matches!(
+        SerdeCapnpBridge::merge_json_values(CrdtType::GSet, &[json!({"elements": ["a"]}), json!({"elements": [1]})]),
+        Err(CrdtError::Validation(_))
+    ));
+}
+
+/// Keywords `schema_accepts` understands. Annotations are listed so they are not mistaken
+/// for unsupported assertions.
+const SCHEMA_KEYWORDS: &[&str] = &[
+    "$schema", "$id", "$defs", "title", "description", "$ref", "enum", "type", "required",
+    "properties", "additionalProperties", "prefixItems", "items", "minItems", "minimum",
+];
+
+/// Minimal validator for the JSON Schema keywords the bridge's schemas use.
+///
+/// A full validator (e.g. the `jsonschema` crate) is not among the dev-dependencies, so
+/// this one is strict instead: it panics on any keyword or type it does not implement, so
+/// a schema change cannot slip past the tests by using an assertion that is silently
+/// ignored here.
+fn schema_accepts(root: &serde_json::Value, schema: &serde_json::Value, value: &serde_json::Value) -> bool {
+    use serde_json::Value;
+
+    let schema = match schema {
+        Value::Bool(allowed) => return *allowed,
+        Value::Object(schema) => schema,
+        _ => panic!("unexpected schema {}", schema),
+    };
+    assert_keywords_supported(schema);
+    if let Some(Value::String(reference)) = schema.get("$ref") {
+        let target = root.pointer(reference.trim_start_matches('#')).expect("dangling $ref");
+        return schema_accepts(root, target, value);
+    }
+    if let Some(Value::Array(options)) = schema.get("enum") {
+        return options.contains(value);
+    }
+    match schema.get("type").and_then(Value::as_str) {
+        Some("object") => {
+            let Some(object) = value.as_object() else { return false };
+            let required = schema.get("required").and_then(Value::as_array).cloned().unwrap_or_default();
+            if !required.iter().all(|field| object.contains_key(field.as_str().unwrap())) {
+                return false;
+            }
+            let properties = schema.get("properties").and_then(Value::as_object);
+            object.iter().all(|(key, field)| match properties.and_then(|p| p.get(key)) {
+                Some(property) => schema_accepts(root, property, field),
+                None => schema
+                    .get("additionalProperties")
+                    .is_none_or(|extra| schema_accepts(root, extra, field)),
+            })
+        }
+        Some("array") => {
+            let Some(items) = value.as_array() else { return false };
+            let prefix = schema.get("prefixItems").and_then(Value::as_array).cloned().unwrap_or_default();
+            let min = schema.get("minItems").and_then(Value::as_u64).unwrap_or(0);
+            items.len() as u64 >= min
+                && items.iter().enumerate().all(|(idx, item)| match prefix.get(idx) {
+                    Some(item_schema) => schema_accepts(root, item_schema, item),
+                    None => schema.get("items").is_none_or(|rest| schema_accepts(root, rest, item)),
+                })
+        }
+        Some("string") => value.is_string(),
+        Some("boolean") => value.is_boolean(),
+        Some("integer") => {
+            let minimum = schema.get("minimum").and_then(Value::as_i64).unwrap_or(i64::MIN);
+            value.as_i64().is_some_and(|n| n >= minimum) || (minimum <= 0 && value.is_u64())
+        }
+        Some("number") => value.is_number(),
+        Some("null") => value.is_null(),
+        Some(other) => panic!("schema_accepts does not implement type `{}`", other),
+        None if schema.contains_key("type") => panic!("schema_accepts only implements single types"),
+        None => true,
+    }
+}
+
+fn assert_keywords_supported(schema: &serde_json::Map<String, serde_json::Value>) {
+    if let Some(keyword) = schema.keys().find(|k| !SCHEMA_KEYWORDS.contains(&k.as_str())) {
+        panic!("schema_accepts does not implement the `{}` keyword", keyword);
+    }
+}
+
+/// Checks every subschema, including ones no example reaches.
+fn assert_schema_supported(schema: &serde_json::Value) {
+    use serde_json::Value;
+
+    let Value::Object(object) = schema else { return };
+    assert_keywords_supported(object);
+    for keyword in ["properties", "$defs"] {
+        if let Some(Value::Object(children)) = object.get(keyword) {
+            children.values().for_each(assert_schema_supported);
+        }
+    }
+    if let Some(Value::Array(children)) = object.get("prefixItems") {
+        children.iter().for_each(assert_schema_supported);
+    }
+    for keyword in ["items", "additionalProperties"] {
+        if let Some(child) = object.get(keyword) {
+            assert_schema_supported(child);
+        }
+    }
+}
+
+#[test]
+fn test_json_schema_accepts_serialized_examples() {
+    let mut gcounter = GCounter::new();
+    gcounter.increment("node_a", 3);
+    let mut pncounter = PNCounter::new();
+    pncounter.increment("node_a", 2);
+    pncounter.decrement("node_b", 1);
+    let mut gset = GSet::new();
+    gset.insert("node_a", "x".to_string());
+    let mut orset = ORSet::new().with_policy(ConflictPolicy::RemoveWins);
+    orset.insert("node_a", "x".to_string());
+    orset.insert("node_a", "y".to_string());
+    orset.remove(&"y".to_string());
+    let mut lww_set = LWWSet::new();
+    lww_set.insert("node_a", "x".to_string(), 1);
+    lww_set.remove("node_a", "y".to_string(), 2);
+    let mut mv_register = MVRegister::new();
+    mv_register.set("node_a", "v".to_string());
+    let mut lww_map = LWWMap::new().with_ttl(std::time::Duration::from_secs(60));
+    lww_map.insert("node_a", "k".to_string(), "v".to_string(), 1);
+    let mut or_map = ORMap::new();
+    or_map.insert("node_a", "k".to_string(), "v".to_string());
+
+    let examples = [
+        (CrdtType::GCounter, serde_json::to_value(&gcounter).unwrap()),
+        (CrdtType::PNCounter, serde_json::to_value(&pncounter).unwrap()),
+        (CrdtType::GSet, serde_json::to_value(&gset).unwrap()),
+        (CrdtType::ORSet, serde_json::to_value(&orset).unwrap()),
+        (CrdtType::LWWSet, serde_json::to_value(&lww_set).unwrap()),
+        (
+            CrdtType::LWWRegister,
+            serde_json::to_value(LWWRegister::new("v".to_string(), 1, "node_a")).unwrap(),
+        ),
+        (
+            CrdtType::FWWRegister,
+            serde_json::to_value(FWWRegister::new("v".to_string(), 1, "node_a")).unwrap(),
+        ),
+        (CrdtType::MVRegister, serde_json::to_value(&mv_register).unwrap()),
+        (CrdtType::LWWMap, serde_json::to_value(&lww_map).unwrap()),
+        (CrdtType::ORMap, serde_json::to_value(&or_map).unwrap()),
+    ];
+    for (crdt_type, example) in examples {
+        let schema = SerdeCapnpBridge::json_schema(crdt_type);
+        assert_schema_supported(&schema);
+        assert_eq!(schema["title"], crdt_type.to_string());
+        assert!(schema_accepts(&schema, &schema, &example), "{} rejected {}", crdt_type, example);
+        SerdeCapnpBridge::json_to_capnp_bytes(crdt_type, example).unwrap();
+    }
+
+    let schema = SerdeCapnpBridge::json_schema(CrdtType::GCounter);
+    assert!(!schema_accepts(&schema, &schema, &json!({"counters": {"node_a": "3"}})));
+    assert!(!schema_accepts(&schema, &schema, &json!({"vclock": {"clocks": {}}})));
+}
+
+#[test]
+fn test_typed_to_json_matches_runtime_dispatch() {
+    assert_eq!(<GCounter as CrdtKind>::TYPE, CrdtType::GCounter);
+    assert_eq!(<LWWMap<String, String> as CrdtKind>::TYPE, CrdtType::LWWMap);
+
+    let mut counter = GCounter::new();
+    counter.increment("node_a", 4);
+    let bytes = counter.to_capnp_bytes();
+    let json = crdt_data_types::bridge::to_json::<GCounter>(&bytes).unwrap();
+    assert_eq!(json, SerdeCapnpBridge::capnp_bytes_to_json(CrdtType::GCounter, &bytes).unwrap());
+    assert_eq!(json["counters"]["node_a"], 4);
+
+    let mut map = LWWMap::new();
+    map.insert("node_a", "k".to_string(), "v".to_string(), 1);
+    let bytes = map.to_capnp_bytes();
+    let json = crdt_data_types::bridge::to_json::<LWWMap<String, String>>(&bytes).unwrap();
+    assert_eq!(json, SerdeCapnpBridge::capnp_bytes_to_json(CrdtType::LWWMap, &bytes).unwrap());
+
+    // The element type is checked too, not just the CRDT kind
+    assert!(crdt_data_types::bridge::to_json::<LWWMap<String, u64>>(&bytes).is_err());
+}
+
+#[test]
+fn test_bridge_point_lookups() {
+    let mut orset = ORSet::new();
+    orset.insert("node_a", "apple".to_string());
+    orset.insert("node_a", "pear".to_string());
+    orset.remove(&"pear".to_string());
+    let mut gset = GSet::new();
+    gset.insert("node_a", "apple".to_string());
+    for (crdt_type, bytes) in [
+        (CrdtType::ORSet, orset.to_capnp_bytes()),
+        (CrdtType::ORSet, orset.to_capnp_text_bytes()),
+        (CrdtType::GSet, gset.to_capnp_bytes()),
+        (CrdtType::GSet, gset.to_capnp_text_bytes()),
+    ] {
+        assert!(SerdeCapnpBridge::set_contains(crdt_type, &bytes, &json!("apple")).unwrap());
+        assert!(!SerdeCapnpBridge::set_contains(crdt_type, &bytes, &json!("pear")).unwrap());
+    }
+
+    let mut map = LWWMap::new();
+    map.insert("node_a", "color".to_string(), "red".to_string(), 1);
+    map.insert("node_b", "color".to_string(), "blue".to_string(), 2);
+    let bytes = map.to_capnp_bytes();
+    assert_eq!(
+        SerdeCapnpBridge::map_get(CrdtType::LWWMap, &bytes, &json!("color")).unwrap(),
+        Some(json!("blue"))
+    );
+    assert_eq!(SerdeCapnpBridge::map_get(CrdtType::LWWMap, &bytes, &json!("size")).unwrap(), None);
+
+    assert!(matches!(
+        SerdeCapnpBridge::map_get(CrdtType::ORSet, &bytes, &json!("color")),
+        Err(CrdtError::InvalidInput(_))
+    ));
+    assert!(matches!(
+        SerdeCapnpBridge::set_contains(CrdtType::ORSet, &bytes, &json!(1)),
+        Err(CrdtError::InvalidInput(_))
+    ));
+}
+
+#[test]
+fn test_flat_counter_json_roundtrip() {
+    let mut gc = GCounter::new();
+    gc.increment("node_a", 10);
+    gc.increment("node_b", 5);
+    let canonical = serde_json::to_value(&gc).unwrap();
+
+    let flat = SerdeCapnpBridge::to_flat_json(CrdtType::GCounter, &canonical).unwrap();
+    assert_eq!(flat, json!({"node_a": 10, "node_b": 5}));
+    let rebuilt = SerdeCapnpBridge::from_flat_json(CrdtType::GCounter, &flat).unwrap();
+    let rebuilt: GCounter = serde_json::from_value(rebuilt).unwrap();
+    assert_eq!(rebuilt.counters, gc.counters);
+    assert_eq!(serde_json::to_value(&rebuilt).unwrap()["counters"], canonical["counters"]);
+    assert!(SerdeCapnpBridge::json_to_capnp_bytes(
+        CrdtType::GCounter,
+        serde_json::to_value(&rebuilt).unwrap()
+    )
+    .is_ok());
+
+    let mut pn = PNCounter::new();
+    pn.increment("node_a", 10);
+    pn.decrement("node_a", 3);
+    pn.decrement("node_b", 4);
+    let canonical = serde_json::to_value(&pn).unwrap();
+    let flat = SerdeCapnpBridge::to_flat_json(CrdtType::PNCounter, &canonical).unwrap();
+    assert_eq!(flat, json!({"node_a": 7, "node_b": -4}));
+    let rebuilt = SerdeCapnpBridge::from_flat_json(CrdtType::PNCounter, &flat).unwrap();
+    let rebuilt: PNCounter = serde_json::from_value(rebuilt).unwrap();
+    assert_eq!(rebuilt.value(), pn.value());
+    assert_eq!(
+        SerdeCapnpBridge::to_flat_json(CrdtType::PNCounter, &serde_json::to_value(&rebuilt).unwrap())
+            .unwrap(),
+        flat
+    );
+
+    assert!(matches!(
+        SerdeCapnpBridge::from_flat_json(CrdtType::GCounter, &json!({"node_a": -1})),
+        Err(CrdtError::InvalidInput(_))
+    ));
+    assert!(matches!(
+        SerdeCapnpBridge::from_flat_json(CrdtType::GCounter, &json!({"node_a": "ten"})),
+        Err(CrdtError::InvalidInput(_))
+    ));
+    assert!(matches!(
+        SerdeCapnpBridge::to_flat_json(CrdtType::ORSet, &canonical),
+        Err(CrdtError::InvalidInput(_))
+    ));
+}
+
+#[test]
+fn test_inspect_bounds_traversal_of_aliased_pointers() {
+    // A root struct whose 64 pointers all alias one 64-word byte list: 129 words on the
+    // wire, but a traversal reads the list 64 times
+    let (pointers, list_words) = (64u64, 64u64);
+    let mut words = vec![pointers << 48];
+    for i in 0..pointers {
+        let offset = pointers - 1 - i;
+        words.push(1 | (offset << 2) | (2 << 32) | ((list_words * 8) << 35));
+    }
+    words.extend(std::iter::repeat_n(0u64, list_words as usize));
+
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(&0u32.to_le_bytes());
+    bytes.extend_from_slice(&(words.len() as u32).to_le_bytes());
+    for word in &words {
+        bytes.extend_from_slice(&word.to_le_bytes());
+    }
+
+    assert!(matches!(SerdeCapnpBridge::inspect(&bytes), Err(CrdtError::Deserialization(_))));
+}
@@ -80,6 +80,216 @@ fn test_pn_counter_capnp_roundtrip() {
     assert_eq!(decoded.negative.value(), 50);
 }
 
+#[test]
+fn test_pn_counter_positive_negative_accessors() {
+    let mut pn = PNCounter::new();
+    pn.increment("node_a", 30);
+    pn.increment("node_b", 12);
+    pn.decrement("node_a", 7);
+    pn.decrement("node_c", 5);
+
+    assert_eq!(pn.positive_value(), 42);
+    assert_eq!(pn.negative_value(), 12);
+    assert_eq!(pn.positive_value() - pn.negative_value(), pn.value());
+}
+
+#[test]
+fn test_pn_counter_apply_delta_matches_separate_updates() {
+    let batches: [&[i64]; 4] = [&[10, -3], &[-8, 2, 1], &[5, -5], &[-1]];
+
+    let mut separate = PNCounter::new();
+    let mut batched = PNCounter::new();
+    for batch in batches {
+        for &delta in batch {
+            separate.increment("node_a", delta);
+        }
+        batched.apply_delta("node_a", batch.iter().sum());
+    }
+
+    assert_eq!(batched.value(), separate.value());
+    assert_eq!(batched.value(), 1);
+    // One tick per non-empty batch; the zero-net batch is skipped
+    assert_eq!(batched.vclock.clocks["node_a"].0, 3);
+    assert_eq!(separate.vclock.clocks["node_a"].0, 8);
+}
+
+#[test]
+fn test_counter_value_saturates_instead_of_overflowing() {
+    let half = i64::MAX / 2;
+
+    let mut gc = GCounter::new();
+    for node in ["a", "b", "c", "d"] {
+        gc.increment(node, half);
+    }
+    assert_eq!(gc.value(), i64::MAX);
+
+    let mut pn = PNCounter::new();
+    for node in ["a", "b", "c"] {
+        pn.increment(node, half);
+    }
+    // Negative side large enough that the naive i64 subtraction would also overflow
+    for node in ["x", "y", "z", "w", "v", "u", "t"] {
+        pn.decrement(node, half);
+    }
+    assert_eq!(pn.value(), i64::MIN);
+
+    // Intermediate sums above i64::MAX still produce the exact result when they cancel out
+    let mut balanced = PNCounter::new();
+    for node in ["a", "b", "c"] {
+        balanced.increment(node, half);
+        balanced.decrement(node, half);
+    }
+    assert_eq!(balanced.value(), 0);
+}
+
+// ============================================================================
+// LWWMap Tests
+// ============================================================================
+
+#[test]
+fn test_lww_map_multi_retains_ties() {
+    let mut map = LWWMap::multi();
+    map.insert("node1", "k".to_string(), "a".to_string(), 100);
+    map.insert("node1", "k".to_string(), "b".to_string(), 100);
+
+    let all = map.get_all(&"k".to_string());
+    assert_eq!(all.len(), 2);
+    assert!(all.contains(&&"a".to_string()));
+    assert!(all.contains(&&"b".to_string()));
+
+    // Survives a capnp round trip and an idempotent merge
+    let bytes = map.to_capnp_bytes();
+    let decoded = LWWMap::merge_from_readers(&[LWWMapReader::<String, String>::new(&bytes)]).unwrap();
+    assert_eq!(decoded, map);
+    let mut merged = map.clone();
+    merged.merge(&decoded);
+    assert_eq!(merged, map);
+
+    // A strictly newer write supersedes every tied value
+    map.insert("node1", "k".to_string(), "c".to_string(), 101);
+    assert_eq!(map.get_all(&"k".to_string()), vec![&"c".to_string()]);
+
+    // Default mode still discards the loser
+    let mut plain = LWWMap::new();
+    plain.insert("node1", "k".to_string(), "a".to_string(), 100);
+    plain.insert("node1", "k".to_string(), "b".to_string(), 100);
+    assert_eq!(plain.get_all(&"k".to_string()), vec![&"b".to_string()]);
+}
+
+#[test]
+fn test_lww_map_multi_merge_unions_ties() {
+    let mut a = LWWMap::multi();
+    a.insert("node1", "k".to_string(), "a".to_string(), 100);
+    let mut b = LWWMap::multi();
+    b.insert("node1", "k".to_string(), "b".to_string(), 100);
+
+    let mut ab = a.clone();
+    ab.merge(&b);
+    let mut ba = b.clone();
+    ba.merge(&a);
+    assert_eq!(ab, ba);
+    assert_eq!(ab.get_all(&"k".to_string()).len(), 2);
+}
+
+#[test]
+fn test_lww_map_merge_with_resolver_max_wins() {
+    let mut local: LWWMap<String, i64> = LWWMap::new();
+    local.insert("node1", "high_water".to_string(), 10, 200);
+    local.insert("node1", "plain".to_string(), 1, 200);
+
+    let mut remote: LWWMap<String, i64> = LWWMap::new();
+    remote.insert("node2", "high_water".to_string(), 50, 100); // Older but larger
+    remote.insert("node2", "plain".to_string(), 2, 100);
+
+    let mut resolved = local.clone();
+    resolved.merge_with_resolver(&remote, |key, current, incoming| {
+        if key == "high_water" {
+            incoming > current
+        } else {
+            false
+        }
+    });
+    assert_eq!(resolved.get(&"high_water".to_string()), Some(&50));
+    assert_eq!(resolved.get(&"plain".to_string()), Some(&1));
+
+    // Default merge still uses timestamps
+    let mut plain = local.clone();
+    plain.merge(&remote);
+    assert_eq!(plain.get(&"high_water".to_string()), Some(&10));
+}
+
+#[test]
+fn test_lww_map_update() {
+    let mut map: LWWMap<String, i64> = LWWMap::new();
+
+    // Missing key with a producing closure inserts
+    map.update("node1", "visits".to_string(), 100, |current| {
+        assert!(current.is_none());
+        Some(1)
+    });
+    assert_eq!(map.get(&"visits".to_string()), Some(&1));
+
+    // Existing key is read-modified-written
+    map.update("node1", "visits".to_string(), 101, |current| current.map(|v| v + 1));
+    assert_eq!(map.get(&"visits".to_string()), Some(&2));
+
+    // Returning None removes
+    map.update("node1", "visits".to_string(), 102, |_| None);
+    assert_eq!(map.get(&"visits".to_string()), None);
+}
+
+#[test]
+fn test_lww_map_and_set_lower_node_wins() {
+    let mut map1 = LWWMap::new().with_tie_break(TieBreak::LowerNodeWins);
+    let mut map2 = LWWMap::new().with_tie_break(TieBreak::LowerNodeWins);
+    map1.insert("node_b", "k".to_string(), "b".to_string(), 100);
+    map2.insert("node_a", "k".to_string(), "a".to_string(), 100);
+    map1.merge(&map2);
+    assert_eq!(map1.get(&"k".to_string()), Some(&"a".to_string()));
+
+    let mut set = LWWSet::new().with_tie_break(TieBreak::LowerNodeWins);
+    set.insert("node_a", "x".to_string(), 100);
+    set.remove("node_b", "x".to_string(), 100);
+    assert!(set.contains(&"x".to_string()));
+}
+
+#[test]
+fn test_lww_map_from_capnp_bytes() {
+    let mut map = LWWMap::new();
+    map.insert("node_a", "k1".to_string(), 1i64, 100);
+    map.insert("node_b", "k2".to_string(), 2i64, 200);
+
+    let bytes = map.to_capnp_bytes();
+    let decoded = LWWMap::<String, i64>::from_capnp_bytes(&bytes).unwrap();
+    assert_eq!(decoded, map);
+
+    assert!(LWWMap::<String, i64>::from_capnp_bytes(&[0xFF, 0x00]).is_err());
+}
+
+#[test]
+fn test_map_contains_key_keys_and_len() {
+    let mut lww = LWWMap::new();
+    assert!(lww.is_empty());
+    lww.insert("node_a", "b".to_string(), 1i64, 10);
+    lww.insert("node_a", "a".to_string(), 2i64, 10);
+    assert!(lww.contains_key(&"a".to_string()));
+    assert!(!lww.contains_key(&"z".to_string()));
+    assert_eq!(lww.len(), 2);
+    assert_eq!(lww.keys().collect::<Vec<_>>(), vec!["a", "b"]);
+
+    // Concurrent values for one key count once
+    let mut or_a = ORMap::new();
+    or_a.insert("node_a", "k".to_string(), "v1".to_string());
+    let mut or_b = ORMap::new();
+    or_b.insert("node_b", "k".to_string(), "v2".to_string());
+    or_b.insert("node_b", "other".to_string(), "v3".to_string());
+    or_a.merge(&or_b);
+    assert!(or_a.contains_key(&"k".to_string()));
+    assert!(!or_a.contains_key(&"missing".to_string()));
+    assert_eq!(or_a.len(), 2);
+    assert_eq!(or_a.keys().collect::<Vec<_>>(), vec!["k", "other"]);
+}
+
 // ============================================================================
 // Bridge Coverage Tests
 // ============================================================================
@@ -117,7 +327,7 @@ fn test_bridge_orset() {
             { "element": "elem1", "observations": [["node1", 1], ["node2", 2]] },
             { "element": "elem2", "observations": [["node1", 3]] }
         ],
-        "vclock": { "clocks": {} }
+        "vclock": { "clocks": { "node1": [3, 0], "node2": [2, 0] } }
     });
     
     let bytes = SerdeCapnpBridge::json_to_capnp_bytes(CrdtType::ORSet, json.clone()).unwrap();
@@ -132,7 +342,7 @@ fn test_bridge_orset() {
         "elements": [
             { "element": "elem3", "observations": [["node3", 4]] }
         ],
-        "vclock": { "clocks": {} }
+        "vclock": { "clocks": { "node3": [4, 0] } }
     });
     let merged = SerdeCapnpBridge::merge_json_values(CrdtType::ORSet, &[json, json2]).unwrap();
     let merged_set: ORSet<String> = serde_json::from_value(merged).unwrap();
@@ -268,3 +478,221 @@ fn test_bridge_errors() {
     // Empty merge
     assert!(SerdeCapnpBridge::merge_json_values(CrdtType::GCounter, &[]).unwrap().is_null());
 }
+
+#[test]
+fn test_lww_map_ttl_expiry_converges() {
+    use std::time::Duration;
+
+    let now_ms = 1_700_000_000_000;
+    let ttl = Duration::from_secs(60);
+    let mut map = LWWMap::new().with_ttl(ttl);
+    map.insert("node_a", "old".to_string(), 1, now_ms - 120_000);
+    map.insert("node_a", "fresh".to_string(), 2, now_ms - 1_000);
+    let stale_replica = map.clone();
+
+    assert_eq!(map.expire_stale(now_ms), 1);
+    assert_eq!(map.get(&"old".to_string()), None);
+    assert_eq!(map.get(&"fresh".to_string()), Some(&2));
+
+    // A replica that has not expired the entry yet cannot resurrect it
+    map.merge(&stale_replica);
+    assert_eq!(map.get(&"old".to_string()), None);
+    let mut other = stale_replica.clone();
+    other.merge(&map);
+    assert_eq!(other.get(&"old".to_string()), None);
+
+    // The tombstone survives serialization, and a newer write still wins
+    let bytes = map.to_capnp_bytes();
+    let mut decoded = LWWMap::<String, i32>::merge_from_readers(&[LWWMapReader::new(&bytes)]).unwrap();
+    assert_eq!(decoded.ttl, Some(ttl));
+    decoded.merge(&stale_replica);
+    assert_eq!(decoded.get(&"old".to_string()), None);
+    decoded.insert("node_b", "old".to_string(), 3, now_ms);
+    assert_eq!(decoded.get(&"old".to_string()), Some(&3));
+
+    // Without a TTL nothing expires
+    let mut no_ttl = stale_replica.clone();
+    no_ttl.ttl = None;
+    assert_eq!(no_ttl.expire_stale(now_ms), 0);
+}
+
+#[test]
+fn test_gcounter_advanced_since_peer_clock() {
+    let mut peer = GCounter::new();
+    peer.increment("node_a", 5);
+    peer.increment("node_b", 2);
+
+    let mut local = peer.clone();
+    local.increment("node_b", 3);
+    local.increment("node_c", 7);
+
+    let delta: Vec<_> = local.advanced_since(&peer.vclock).collect();
+    assert_eq!(delta, vec![("node_b", 5), ("node_c", 7)]);
+
+    // Merging only the advanced counts catches the peer up
+    let mut update = GCounter::new();
+    for (node, count) in delta {
+        update.increment(node, count);
+    }
+    peer.merge(&update);
+    assert_eq!(peer.value(), local.value());
+
+    assert_eq!(local.advanced_since(&local.vclock).count(), 0);
+}
+
+#[test]
+fn test_lww_map_index_and_get_or() {
+    let mut map = LWWMap::new();
+    map.insert("node_a", "present".to_string(), 1, 100);
+
+    assert_eq!(map[&"present".to_string()], 1);
+    assert_eq!(*map.get_or(&"present".to_string(), &0), 1);
+    assert_eq!(*map.get_or(&"missing".to_string(), &0), 0);
+}
+
+#[test]
+#[should_panic(expected = "key not present")]
+fn test_lww_map_index_panics_on_missing_key() {
+    let map: LWWMap<String, i32> = LWWMap::new();
+    let _ = map[&"missing".to_string()];
+}
+
+#[test]
+fn test_map_values_preserves_metadata() {
+    let mut map: LWWMap<String, i64> = LWWMap::new().with_tie_break(TieBreak::LowerNodeWins);
+    map.insert("node_a", "hp".to_string(), 42, 100);
+    map.insert("node_b", "mp".to_string(), 7, 200);
+    let view: LWWMap<String, String> = map.map_values(|v| format!("{} pts", v));
+
+    assert_eq!(view.get(&"hp".to_string()).map(String::as_str), Some("42 pts"));
+    assert_eq!(view.entries[1].1, ("7 pts".to_string(), 200, "node_b".to_string()));
+    assert_eq!(view.vclock, map.vclock);
+    assert_eq!(view.tie_break, TieBreak::LowerNodeWins);
+
+    // ORMap values that project to the same result share their dots
+    let mut or_map: ORMap<String, i64> = ORMap::new().with_key_clocks();
+    or_map.insert("node_a", "k".to_string(), 1);
+    let mut other = ORMap::new();
+    other.insert("node_b", "k".to_string(), 3);
+    or_map.merge(&other);
+    let parity: ORMap<String, bool> = or_map.map_values(|v| v % 2 == 1);
+    assert_eq!(parity.get_concurrent(&"k".to_string()).len(), 1);
+    assert_eq!(parity.elements.dot_count(), 2);
+    assert!(parity.key_clock(&"k".to_string()).is_some());
+    assert_eq!(parity.key_clock(&"k".to_string()), or_map.key_clock(&"k".to_string()));
+}
+
+#[test]
+fn test_node_ids_are_validated_at_construction() {
+    let oversized = "n".repeat(MAX_NODE_ID_LEN + 1);
+    for bad in ["", oversized.as_str()] {
+        assert!(matches!(NodeId::new(bad), Err(CrdtError::InvalidInput(_))));
+        assert!(matches!(
+            LWWRegister::try_new("v".to_string(), 1, bad),
+            Err(CrdtError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            FWWRegister::try_new("v".to_string(), 1, bad),
+            Err(CrdtError::InvalidInput(_))
+        ));
+    }
+
+    let node = NodeId::new("n".repeat(MAX_NODE_ID_LEN)).unwrap();
+    let reg = LWWRegister::try_new("v".to_string(), 1, &node).unwrap();
+    assert_eq!(reg.node_id, node.as_str());
+
+    // Deserialization goes through the same check
+    assert_eq!(serde_json::to_value(&node).unwrap(), json!(node.as_str()));
+    assert!(serde_json::from_value::<NodeId>(json!("")).is_err());
+}
+
+#[test]
+fn test_lww_map_merge_reader_matches_decoded_merge() {
+    let mut live = LWWMap::multi().with_ttl(std::time::Duration::from_millis(1_000));
+    live.insert("node_a", "shared".to_string(), "a".to_string(), 10);
+    live.insert("node_a", "tied".to_string(), "x".to_string(), 5);
+    live.insert("node_a", "only_live".to_string(), "l".to_string(), 1);
+    live.insert("node_a", "stale".to_string(), "s".to_string(), 2);
+
+    let mut incoming = LWWMap::multi();
+    incoming.insert("node_b", "shared".to_string(), "b".to_string(), 20);
+    incoming.insert("node_a", "tied".to_string(), "y".to_string(), 5);
+    incoming.insert("node_a", "tied".to_string(), "z".to_string(), 5);
+    incoming.insert("node_b", "only_incoming".to_string(), "i".to_string(), 3);
+    incoming.insert("node_b", "stale".to_string(), "t".to_string(), 4);
+    incoming.ttl = Some(std::time::Duration::from_millis(1_000));
+    incoming.expire_stale(1_005);
+
+    for bytes in [incoming.to_capnp_bytes(), incoming.to_capnp_text_bytes()] {
+        let mut expected = live.clone();
+        expected.merge_bytes(&bytes).unwrap();
+
+        let mut actual = live.clone();
+        actual.merge_reader(&LWWMapReader::new(&bytes)).unwrap();
+        assert_eq!(actual, expected);
+        assert_eq!(actual.get_all(&"tied".to_string()), vec![&"z".to_string(), &"x".to_string(), &"y".to_string()]);
+        assert_eq!(actual.get(&"shared".to_string()), Some(&"b".to_string()));
+        assert_eq!(actual.get(&"stale".to_string()), None);
+    }
+}
+
+#[test]
+fn test_lww_set_capacity_evicts_oldest_add() {
+    let mut set = LWWSet::new().with_capacity(3);
+    set.insert("node_a", "b".to_string(), 20);
+    set.insert("node_a", "a".to_string(), 10);
+    set.insert("node_a", "c".to_string(), 30);
+    set.insert("node_a", "d".to_string(), 40);
+
+    let live: Vec<&String> = set.iter().collect();
+    assert_eq!(live, vec!["b", "c", "d"]);
+    assert!(!set.contains(&"a".to_string()));
+
+    // Removed elements do not count towards the capacity.
+    set.remove("node_a", "c".to_string(), 50);
+    set.insert("node_a", "e".to_string(), 60);
+    assert_eq!(set.iter().count(), 3);
+    assert!(set.contains(&"b".to_string()));
+
+    // The bound is local: eviction writes no tombstone, so a replica that still holds the
+    // element brings it back, and merging the capped set elsewhere loses nothing.
+    let mut other = LWWSet::new();
+    other.insert("node_b", "a".to_string(), 10);
+    assert!(set.remove_set.iter().all(|(e, _)| e != "a"));
+    other.merge(&set);
+    assert_eq!(other.iter().count(), 4);
+    set.merge(&other);
+    assert!(set.contains(&"a".to_string()));
+    assert_eq!(set.capacity(), Some(3));
+    assert_eq!(other.capacity(), None);
+}
+
+#[test]
+fn test_lww_map_merge_reader_leaves_map_unchanged_on_error() {
+    use crdt_data_types::lww_map_capnp::lww_map;
+
+    // A valid first entry followed by one whose key is not JSON
+    let mut message = capnp::message::Builder::new_default();
+    {
+        let root = message.init_root::<lww_map::Builder>();
+        let mut entries = root.init_entries(2);
+        let mut good = entries.reborrow().get(0);
+        good.set_key(&serde_json::to_vec("fresh").unwrap());
+        good.set_value(&serde_json::to_vec("v").unwrap());
+        good.set_timestamp(99);
+        good.set_node_id("node_b".into());
+        let mut bad = entries.get(1);
+        bad.set_key(b"{not json");
+        bad.set_value(&serde_json::to_vec("v").unwrap());
+        bad.set_timestamp(99);
+        bad.set_node_id("node_b".into());
+    }
+    let mut bytes = Vec::new();
+    capnp::serialize::write_message(&mut bytes, &message).unwrap();
+
+    let mut map = LWWMap::new();
+    map.insert("node_a", "kept".to_string(), "k".to_string(), 1);
+    let before = map.clone();
+    assert!(map.merge_reader(&LWWMapReader::<String, String>::new(&bytes)).is_err());
+    assert_eq!(map, before);
+}
@@ -1,4 +1,4 @@
-use crdt_data_types::{SerdeCapnpBridge, CrdtType};
+use crdt_data_types::{CrdtError, SerdeCapnpBridge, CrdtType};
 use crdt_data_types::deltas_capnp::delta;
 use capnp::serialize;
 
@@ -111,3 +111,155 @@ fn test_capnp_batch_deltas_gcounter() {
     let json_val = SerdeCapnpBridge::capnp_bytes_to_json(CrdtType::GCounter, &result_bytes).unwrap();
     assert_eq!(json_val["counters"]["node1"], 35);
 }
+
+#[test]
+fn test_capnp_delta_batch_lenient_quarantines_bad_deltas() {
+    let gcounter_delta = |amount: i64| {
+        let mut message = capnp::message::Builder::new_default();
+        message.init_root::<delta::Builder>().set_g_counter(amount);
+        let mut bytes = Vec::new();
+        serialize::write_message(&mut bytes, &message).unwrap();
+        bytes
+    };
+    let good1 = gcounter_delta(5);
+    let good2 = gcounter_delta(7);
+
+    // Wrong variant for a GCounter
+    let mut message = capnp::message::Builder::new_default();
+    message.init_root::<delta::Builder>().set_pn_counter(100);
+    let mismatched = {
+        let mut bytes = Vec::new();
+        serialize::write_message(&mut bytes, &message).unwrap();
+        bytes
+    };
+    let garbage = vec![0xFFu8; 3];
+
+    let batch: Vec<&[u8]> = vec![&good1, &mismatched, &good2, &garbage];
+
+    // The strict batch aborts entirely
+    assert!(SerdeCapnpBridge::apply_batch_capnp_deltas(CrdtType::GCounter, None, &batch, "node1").is_err());
+
+    let (state, failures) =
+        SerdeCapnpBridge::apply_batch_capnp_deltas_lenient(CrdtType::GCounter, None, &batch, "node1").unwrap();
+    let failed: Vec<usize> = failures.iter().map(|(idx, _)| *idx).collect();
+    assert_eq!(failed, vec![1, 3]);
+
+    let json_val = SerdeCapnpBridge::capnp_bytes_to_json(CrdtType::GCounter, &state).unwrap();
+    assert_eq!(json_val["counters"]["node1"], 12);
+
+    // A fully valid batch reports no failures
+    let (_, failures) = SerdeCapnpBridge::apply_batch_capnp_deltas_lenient(
+        CrdtType::GCounter,
+        Some(&state),
+        &[&good1],
+        "node1",
+    )
+    .unwrap();
+    assert!(failures.is_empty());
+}
+
+#[test]
+fn test_capnp_delta_idempotent_counts_once() {
+    let dotted = |amount: i64, counter: u64| {
+        let mut message = capnp::message::Builder::new_default();
+        let mut root = message.init_root::<delta::Builder>();
+        root.set_g_counter(amount);
+        root.set_dot_node("node1".into());
+        root.set_dot_counter(counter);
+        let mut bytes = Vec::new();
+        serialize::write_message(&mut bytes, &message).unwrap();
+        bytes
+    };
+    let value = |bytes: &[u8]| {
+        SerdeCapnpBridge::capnp_bytes_to_json(CrdtType::GCounter, bytes).unwrap()["counters"]["node1"].clone()
+    };
+
+    let first = dotted(10, 1);
+    let state = SerdeCapnpBridge::apply_capnp_delta_idempotent(CrdtType::GCounter, None, &first, "node1").unwrap();
+    let replayed = SerdeCapnpBridge::apply_capnp_delta_idempotent(CrdtType::GCounter, Some(&state), &first, "node1").unwrap();
+    assert_eq!(value(&replayed), 10);
+
+    let state = SerdeCapnpBridge::apply_capnp_delta_idempotent(CrdtType::GCounter, Some(&replayed), &dotted(5, 2), "node1").unwrap();
+    assert_eq!(value(&state), 15);
+
+    // Counter 4 skips 3: the jump is rejected until 3 arrives, and then counts once
+    assert!(matches!(
+        SerdeCapnpBridge::apply_capnp_delta_idempotent(CrdtType::GCounter, Some(&state), &dotted(1, 4), "node1"),
+        Err(CrdtError::InvalidInput(_))
+    ));
+    let state = SerdeCapnpBridge::apply_capnp_delta_idempotent(CrdtType::GCounter, Some(&state), &dotted(2, 3), "node1").unwrap();
+    let jumped = SerdeCapnpBridge::apply_capnp_delta_idempotent(CrdtType::GCounter, Some(&state), &dotted(1, 4), "node1").unwrap();
+    assert_eq!(value(&jumped), 18);
+    for counter in [4, 3, 1] {
+        let redelivered = SerdeCapnpBridge::apply_capnp_delta_idempotent(CrdtType::GCounter, Some(&jumped), &dotted(1, counter), "node1").unwrap();
+        assert_eq!(value(&redelivered), 18);
+    }
+    // The dot must belong to the applying node
+    assert!(SerdeCapnpBridge::apply_capnp_delta_idempotent(CrdtType::GCounter, Some(&state), &dotted(1, 3), "node2").is_err());
+}
+
+#[test]
+fn test_delta_builder_produces_applicable_deltas() {
+    use crdt_data_types::deltas::DeltaBuilder;
+
+    let cases = [
+        DeltaBuilder::gcounter(10),
+        DeltaBuilder::pncounter(-3),
+        DeltaBuilder::gset(["a", "b"]),
+        DeltaBuilder::orset_add(["a", "b"]),
+        DeltaBuilder::orset_remove(["a"]),
+        DeltaBuilder::lww_set_add(["a"], 100),
+        DeltaBuilder::lww_set_remove(["a"], 200),
+        DeltaBuilder::lww_register("v", 100),
+        DeltaBuilder::fww_register("v", 100),
+        DeltaBuilder::mv_register("v"),
+        DeltaBuilder::lww_map_set([("k", "v")], 100),
+        DeltaBuilder::lww_map_remove(["k"], 200),
+        DeltaBuilder::or_map_set([("k", "v")]),
+        DeltaBuilder::or_map_remove(["k"]),
+    ];
+    for builder in &cases {
+        let crdt_type = builder.crdt_type();
+        let state = SerdeCapnpBridge::apply_capnp_delta(crdt_type, None, &builder.build(), "node1")
+            .unwrap_or_else(|e| panic!("{:?} delta rejected: {}", crdt_type, e));
+        SerdeCapnpBridge::capnp_bytes_to_json(crdt_type, &state).unwrap();
+
+        // The payload is tied to its type
+        let other = if crdt_type == CrdtType::GCounter { CrdtType::PNCounter } else { CrdtType::GCounter };
+        assert!(SerdeCapnpBridge::apply_capnp_delta(other, None, &builder.build(), "node1").is_err());
+    }
+
+    let state = SerdeCapnpBridge::apply_capnp_delta(CrdtType::GCounter, None, &cases[0].build(), "node1").unwrap();
+    let json = SerdeCapnpBridge::capnp_bytes_to_json(CrdtType::GCounter, &state).unwrap();
+    assert_eq!(json["counters"]["node1"], 10);
+
+    let map = SerdeCapnpBridge::apply_capnp_delta(CrdtType::LWWMap, None, &cases[10].build(), "node1").unwrap();
+    let map = SerdeCapnpBridge::apply_capnp_delta(CrdtType::LWWMap, Some(&map), &cases[11].build(), "node1").unwrap();
+    let json = SerdeCapnpBridge::capnp_bytes_to_json(CrdtType::LWWMap, &map).unwrap();
+    assert!(json["entries"].as_object().unwrap().is_empty());
+
+    // Dotted deltas are accepted by the idempotent path, once
+    let dotted = DeltaBuilder::gcounter(5).with_dot("node1", 1).build();
+    let once = SerdeCapnpBridge::apply_capnp_delta_idempotent(CrdtType::GCounter, None, &dotted, "node1").unwrap();
+    let twice = SerdeCapnpBridge::apply_capnp_delta_idempotent(CrdtType::GCounter, Some(&once), &dotted, "node1").unwrap();
+    let json = SerdeCapnpBridge::capnp_bytes_to_json(CrdtType::GCounter, &twice).unwrap();
+    assert_eq!(json["counters"]["node1"], 5);
+
+    // A two-item delta consumes counters 2 and 3, so the next dot is 4
+    let first = DeltaBuilder::orset_add(["a"]).with_dot("node1", 1).build();
+    let second = DeltaBuilder::orset_add(["b", "c"]).with_dot("node1", 2).build();
+    let state = SerdeCapnpBridge::apply_capnp_delta_idempotent(CrdtType::ORSet, None, &first, "node1").unwrap();
+    let state = SerdeCapnpBridge::apply_capnp_delta_idempotent(CrdtType::ORSet, Some(&state), &second, "node1").unwrap();
+    let replayed = SerdeCapnpBridge::apply_capnp_delta_idempotent(CrdtType::ORSet, Some(&state), &second, "node1").unwrap();
+    assert_eq!(replayed, state);
+    let json = SerdeCapnpBridge::capnp_bytes_to_json(CrdtType::ORSet, &state).unwrap();
+    assert_eq!(json["elements"].as_array().unwrap().len(), 3);
+
+    // A delta that ticks nothing still consumes its dot
+    let noop = DeltaBuilder::orset_remove(["z"]).with_dot("node1", 4).build();
+    let state = SerdeCapnpBridge::apply_capnp_delta_idempotent(CrdtType::ORSet, Some(&state), &noop, "node1").unwrap();
+    let replayed = SerdeCapnpBridge::apply_capnp_delta_idempotent(CrdtType::ORSet, Some(&state), &noop, "node1").unwrap();
+    assert_eq!(replayed, state);
+    let third = DeltaBuilder::orset_add(["d"]).with_dot("node1", 5).build();
+    assert!(SerdeCapnpBridge::apply_capnp_delta_idempotent(CrdtType::ORSet, Some(&state), &third, "node1").is_ok());
+}
@@ -214,3 +214,49 @@ fn test_delta_bytes_lwwmap() {
     let entry = entries.get("k1").unwrap().as_array().unwrap();
     assert_eq!(entry[0].as_str(), Some("v1")); 
 }
+
+#[test]
+fn test_delta_pncounter_increment_and_decrement() {
+    let state = SerdeCapnpBridge::apply_json_delta(
+        CrdtType::PNCounter,
+        None,
+        &json!({"increment": 10}),
+        "node_a"
+    ).unwrap();
+    assert_eq!(state["positive"]["counters"]["node_a"], 10);
+
+    let state2 = SerdeCapnpBridge::apply_json_delta(
+        CrdtType::PNCounter,
+        Some(&state),
+        &json!({"decrement": 4}),
+        "node_a"
+    ).unwrap();
+    assert_eq!(state2["positive"]["counters"]["node_a"], 10);
+    assert_eq!(state2["negative"]["counters"]["node_a"], 4);
+
+    // A negative decrement is ambiguous and rejected
+    assert!(SerdeCapnpBridge::apply_json_delta(
+        CrdtType::PNCounter,
+        Some(&state2),
+        &json!({"decrement": -4}),
+        "node_a"
+    ).is_err());
+
+    // i64::MIN has no representable magnitude
+    assert!(SerdeCapnpBridge::apply_json_delta(
+        CrdtType::PNCounter,
+        Some(&state2),
+        &json!(i64::MIN),
+        "node_a"
+    ).is_err());
+
+    // Mixed or misspelled fields are rejected instead of silently dropped
+    for delta in [json!({"increment": 5, "decrement": 3}), json!({"increment": 5, "decremnt": 3})] {
+        assert!(SerdeCapnpBridge::apply_json_delta(
+            CrdtType::PNCounter,
+            Some(&state2),
+            &delta,
+            "node_a"
+        ).is_err());
+    }
+}
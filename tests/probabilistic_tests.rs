@@ -3,7 +3,7 @@
 
 #[cfg(feature = "probabilistic")]
 mod tests {
-    use crdt_data_types::{CountMinSketch, HyperLogLog, RoaringBitmap, TDigest, TopK, Crdt};
+    use crdt_data_types::{MergeExt, CountMinSketch, CountMinSketchReader, CrdtError, HyperLogLog, RoaringBitmap, RoaringBitmapReader, TDigest, TDigestReader, TopK, Crdt, CrdtReader};
 
     #[test]
     fn test_count_min_sketch_basic() {
@@ -55,7 +55,7 @@ mod tests {
         let count = hll.cardinality();
         // HLL is probabilistic, but for small numbers with 14 bits precision it might be exact or close.
         // With 3 unique elements, it should be very close.
-        assert!(count >= 2 && count <= 4);
+        assert!((2..=4).contains(&count));
     }
 
     #[test]
@@ -71,7 +71,7 @@ mod tests {
         hll1.merge(&hll2);
 
         let count = hll1.cardinality();
-        assert!(count >= 3 && count <= 4); // Should be around 3
+        assert!((3..=4).contains(&count)); // Should be around 3
     }
 
     #[test]
@@ -234,4 +234,707 @@ mod tests {
         assert_eq!(top[0].0, "banana");
         assert_eq!(top[1].0, "apple");
     }
+
+    #[test]
+    fn test_topk_estimate_non_top_item() {
+        let mut topk = TopK::new(2, 1000, 5);
+        topk.increment("apple", 50);
+        topk.increment("banana", 40);
+        topk.increment("cherry", 3);
+
+        let top = topk.top_k();
+        assert!(top.iter().all(|(k, _)| k != "cherry"));
+
+        // The sketch still knows about cherry even though it fell out of the heap
+        assert!(topk.estimate("cherry") >= 3);
+        assert_eq!(topk.estimate("cherry"), topk.sketch().estimate("cherry"));
+        assert_eq!(topk.sketch().width, 1000);
+    }
+
+    #[test]
+    fn test_roaring_bitmap_delta_since() {
+        let mut base = RoaringBitmap::new(10000);
+        for i in 0..100 {
+            base.insert(i);
+        }
+        let mut current = base.clone();
+        current.insert(500);
+        current.insert(9000);
+
+        let delta = current.delta_since(&base);
+        assert_eq!(delta.cardinality(), 2);
+
+        // Delta survives the wire format
+        let delta = RoaringBitmap::from_capnp_bytes(&delta.to_capnp_bytes()).unwrap();
+
+        let mut applied = base.clone();
+        applied.apply_delta(&delta);
+        let mut merged = base.clone();
+        merged.merge(&current);
+        assert_eq!(applied, merged);
+    }
+
+    #[test]
+    fn test_count_min_sketch_with_error_bounds() {
+        // 2% error with 99% confidence
+        let cms = CountMinSketch::with_error_bounds(0.02, 0.01).unwrap();
+        assert_eq!(cms.width, (std::f64::consts::E / 0.02).ceil() as usize); // 136
+        assert_eq!(cms.depth, (1.0f64 / 0.01).ln().ceil() as usize); // 5
+
+        let cms = CountMinSketch::with_error_bounds(0.001, 0.0001).unwrap();
+        assert_eq!(cms.width, 2719);
+        assert_eq!(cms.depth, 10);
+
+        let topk = TopK::with_error_bounds(5, 0.02, 0.01).unwrap();
+        assert_eq!(topk.sketch().width, 136);
+        assert_eq!(topk.sketch().depth, 5);
+
+        assert!(CountMinSketch::with_error_bounds(0.0, 0.01).is_err());
+        assert!(CountMinSketch::with_error_bounds(0.02, 1.0).is_err());
+        assert!(TopK::with_error_bounds(5, f64::NAN, 0.01).is_err());
+    }
+
+    #[test]
+    fn test_roaring_bitmap_reader_cardinality() {
+        let mut rb = RoaringBitmap::new(1000);
+        rb.insert(1);
+        rb.insert(2);
+        rb.insert(999);
+        let bytes = rb.to_capnp_bytes();
+
+        let reader = RoaringBitmapReader::new(&bytes);
+        assert_eq!(reader.cardinality().unwrap(), 3);
+        assert!(!reader.is_empty().unwrap());
+
+        let empty_bytes = RoaringBitmap::new(1000).to_capnp_bytes();
+        let empty_reader = RoaringBitmapReader::new(&empty_bytes);
+        assert_eq!(empty_reader.cardinality().unwrap(), 0);
+        assert!(empty_reader.is_empty().unwrap());
+    }
+
+    #[test]
+    fn test_count_min_sketch_ingest_lines() {
+        let input = std::io::Cursor::new("apple\nbanana\napple\n\napple\n");
+        let mut cms = CountMinSketch::new(100, 5);
+        let ingested = cms.ingest_lines(input).unwrap();
+
+        assert_eq!(ingested, 4);
+        assert!(cms.estimate("apple") >= 3);
+        assert!(cms.estimate("banana") >= 1);
+    }
+
+    #[test]
+    fn test_tdigest_quantiles_batch() {
+        let mut td = TDigest::new(100);
+        for i in 1..=1000 {
+            td.insert((i % 97) as f64 * 1.5 + i as f64);
+        }
+
+        let qs = [0.99, 0.5, 0.0, 0.9, 0.95, 0.25, 1.0, 0.01];
+        let batch = td.quantiles(&qs);
+        assert_eq!(batch.len(), qs.len());
+        for (q, value) in qs.iter().zip(&batch) {
+            assert!((td.quantile(*q) - value).abs() < 1e-9, "q={} batch={} single={}", q, value, td.quantile(*q));
+        }
+
+        assert!(TDigest::new(100).quantiles(&[0.5]).iter().all(|v| *v == 0.0));
+    }
+
+    #[test]
+    fn test_tdigest_trimmed_mean() {
+        let mut td = TDigest::new(100);
+        for i in 1..=100 {
+            td.insert(i as f64);
+        }
+
+        // Full range is the plain mean
+        assert!((td.trimmed_mean(0.0, 1.0) - 50.5).abs() < 1e-9);
+        // Symmetric trimming of a uniform distribution keeps the centre
+        assert!((td.trimmed_mean(0.1, 0.9) - 50.5).abs() < 1.0);
+        assert!(td.trimmed_mean(0.0, 0.5) < td.trimmed_mean(0.5, 1.0));
+        assert_eq!(td.trimmed_mean(0.6, 0.4), 0.0);
+    }
+
+    #[test]
+    fn test_roaring_bitmap_iteration() {
+        let mut rb = RoaringBitmap::new(100_000);
+        for v in [42, 7, 99_999, 1000, 7] {
+            rb.insert(v);
+        }
+
+        assert_eq!(rb.to_vec(), vec![7, 42, 1000, 99_999]);
+        assert_eq!(rb.iter().count(), 4);
+        assert_eq!(rb.min(), Some(7));
+        assert_eq!(rb.max(), Some(99_999));
+
+        let empty = RoaringBitmap::new(10);
+        assert_eq!(empty.min(), None);
+        assert!(empty.to_vec().is_empty());
+    }
+
+    #[test]
+    fn test_hyperloglog_estimated_capnp_size() {
+        let mut hll = HyperLogLog::new();
+        hll.add("apple");
+        let actual = hll.to_capnp_bytes().len();
+        let estimate = hll.estimated_capnp_size();
+        assert!(estimate.abs_diff(actual) <= 16, "estimate {} vs actual {}", estimate, actual);
+    }
+
+    #[test]
+    fn test_approx_set_large_cardinality_and_recent_window() {
+        use crdt_data_types::ApproxSet;
+
+        let window = 1000;
+        let mut set = ApproxSet::new(window);
+        let n = 100_000u64;
+        for i in 0..n {
+            set.insert("node1", i);
+        }
+
+        let estimate = set.cardinality() as f64;
+        let error = (estimate - n as f64).abs() / n as f64;
+        assert!(error < 0.03, "estimate {} too far from {}", estimate, n);
+
+        // The most recent `window` elements are always exact
+        for i in (n - window as u64)..n {
+            assert!(set.contains(&i), "recent element {} missing", i);
+        }
+        // Old elements have been evicted from the exact window
+        assert!(!set.contains(&0));
+        assert!(set.recent().count() <= window * 2);
+    }
+
+    #[test]
+    fn test_approx_set_merge() {
+        use crdt_data_types::ApproxSet;
+
+        let mut a = ApproxSet::new(10);
+        let mut b = ApproxSet::new(10);
+        a.insert("node_a", "x".to_string());
+        b.insert("node_b", "y".to_string());
+        b.insert("node_b", "x".to_string());
+
+        let mut ab = a.clone();
+        ab.merge(&b);
+        let mut ba = b.clone();
+        ba.merge(&a);
+
+        assert_eq!(ab, ba);
+        assert_eq!(ab.cardinality(), 2);
+        assert!(ab.contains(&"x".to_string()) && ab.contains(&"y".to_string()));
+    }
+
+    #[test]
+    fn test_trait_from_capnp_bytes_uses_inherent_decoder() {
+        fn roundtrip<C: Crdt>(crdt: &C) -> C {
+            C::from_capnp_bytes(&crdt.to_capnp_bytes()).unwrap()
+        }
+
+        let mut hll = HyperLogLog::new();
+        hll.add("a");
+        assert_eq!(roundtrip(&hll), hll);
+
+        let mut rb = RoaringBitmap::new(100);
+        rb.insert(42);
+        assert_eq!(roundtrip(&rb).to_vec(), vec![42]);
+    }
+
+    #[test]
+    fn test_tdigest_centroids_weights_sum_to_count() {
+        let mut td = TDigest::new(50);
+        for i in 0..1000 {
+            td.insert((i % 97) as f64);
+        }
+
+        let centroids = td.centroids();
+        assert!(!centroids.is_empty());
+        assert!(centroids.windows(2).all(|w| w[0].0 <= w[1].0));
+        let total: f64 = centroids.iter().map(|(_, w)| w).sum();
+        assert_eq!(total, td.count as f64);
+        assert!(TDigest::new(50).centroids().is_empty());
+    }
+
+    #[test]
+    fn test_topk_merge_different_k_keeps_larger() {
+        let mut small = TopK::new(3, 100, 5);
+        for (i, item) in ["a", "b", "c"].iter().enumerate() {
+            small.increment(item, 10 + i as u64);
+        }
+        let mut large = TopK::new(5, 100, 5);
+        for (i, item) in ["d", "e", "f", "g", "h"].iter().enumerate() {
+            large.increment(item, 100 + i as u64);
+        }
+
+        let mut merged = small.clone();
+        merged.merge(&large);
+        assert_eq!(merged.k(), 5);
+        assert_eq!(merged.top_k().len(), 5);
+        assert_eq!(merged.top_k()[0].0, "h");
+
+        let mut reversed = large.clone();
+        reversed.merge(&small);
+        assert_eq!(reversed.top_k(), merged.top_k());
+
+        assert!(small.clone().merge_strict(&large).is_err());
+        assert!(small.clone().merge_strict(&TopK::new(3, 50, 5)).is_err());
+    }
+
+    #[test]
+    fn test_bridge_roundtrips_and_merges_sketches() {
+        use crdt_data_types::{CrdtType, SerdeCapnpBridge};
+        use std::str::FromStr;
+
+        let mut hll1 = HyperLogLog::new();
+        let mut hll2 = HyperLogLog::new();
+        for i in 0..500 {
+            hll1.add(&i);
+            hll2.add(&(i + 250));
+        }
+        let json1 = serde_json::to_value(&hll1).unwrap();
+        let bytes = SerdeCapnpBridge::json_to_capnp_bytes(CrdtType::HyperLogLog, json1.clone()).unwrap();
+        assert_eq!(SerdeCapnpBridge::capnp_bytes_to_json(CrdtType::HyperLogLog, &bytes).unwrap(), json1);
+
+        let json2 = serde_json::to_value(&hll2).unwrap();
+        let merged = SerdeCapnpBridge::merge_json_values(CrdtType::HyperLogLog, &[json1, json2]).unwrap();
+        let merged: HyperLogLog = serde_json::from_value(merged).unwrap();
+        let estimate = merged.cardinality() as f64;
+        assert!((estimate - 750.0).abs() < 750.0 * 0.05, "estimate {}", estimate);
+
+        let mut cms1 = CountMinSketch::new(100, 5);
+        cms1.increment("apple", 3);
+        let mut cms2 = CountMinSketch::new(100, 5);
+        cms2.increment("apple", 4);
+        let json1 = serde_json::to_value(&cms1).unwrap();
+        let bytes = SerdeCapnpBridge::json_to_capnp_bytes(CrdtType::CountMinSketch, json1.clone()).unwrap();
+        assert_eq!(SerdeCapnpBridge::capnp_bytes_to_json(CrdtType::CountMinSketch, &bytes).unwrap(), json1);
+
+        let json2 = serde_json::to_value(&cms2).unwrap();
+        let merged = SerdeCapnpBridge::merge_json_values(CrdtType::CountMinSketch, &[json1.clone(), json2]).unwrap();
+        let merged: CountMinSketch = serde_json::from_value(merged).unwrap();
+        assert_eq!(merged.estimate("apple"), 7);
+
+        // Mismatched dimensions are an error, not a panic
+        let other = serde_json::to_value(CountMinSketch::new(50, 5)).unwrap();
+        assert!(SerdeCapnpBridge::merge_json_values(CrdtType::CountMinSketch, &[json1, other]).is_err());
+
+        assert_eq!(CrdtType::from_str("hyper_log_log").unwrap(), CrdtType::HyperLogLog);
+        assert!(SerdeCapnpBridge::apply_json_delta(CrdtType::TopK, None, &serde_json::json!(1), "node_a").is_err());
+    }
+
+    #[test]
+    fn test_tdigest_json_roundtrip_keeps_centroids() {
+        let mut td = TDigest::new(100);
+        for i in 1..=100 {
+            td.insert(i as f64);
+        }
+        let json = serde_json::to_value(&td).unwrap();
+        let decoded: TDigest = serde_json::from_value(json).unwrap();
+        assert_eq!(decoded.centroids(), td.centroids());
+        assert_eq!(decoded.quantile(0.5), td.quantile(0.5));
+
+        let empty: TDigest = serde_json::from_value(serde_json::to_value(TDigest::new(100)).unwrap()).unwrap();
+        assert_eq!(empty, TDigest::new(100));
+    }
+
+    #[test]
+    fn test_tdigest_to_buckets_uniform() {
+        let mut td = TDigest::new(100);
+        for i in 0..10_000 {
+            td.insert(i as f64 / 10.0); // Uniform over [0, 1000)
+        }
+
+        let buckets = td.to_buckets(&[100.0, 250.0, 500.0, 900.0]).unwrap();
+        assert_eq!(buckets.len(), 5);
+        for (&(le, count), expected) in buckets.iter().zip([1_000u64, 2_500, 5_000, 9_000]) {
+            assert!(count.abs_diff(expected) <= 50, "le={} count={} expected={}", le, count, expected);
+        }
+        assert_eq!(buckets[4], (f64::INFINITY, 10_000));
+        assert!(buckets.windows(2).all(|w| w[0].1 <= w[1].1));
+
+        assert_eq!(td.to_buckets(&[-1.0, f64::INFINITY]).unwrap(), vec![(-1.0, 0), (f64::INFINITY, 10_000)]);
+        assert!(td.to_buckets(&[500.0, 100.0]).is_err());
+        assert!(td.to_buckets(&[f64::NAN]).is_err());
+    }
+
+    #[test]
+    fn test_roaring_bitmap_merge_reconciles_max_value() {
+        let mut small = RoaringBitmap::new(100);
+        small.insert(50);
+        let mut large = RoaringBitmap::new(1_000);
+        large.insert(500);
+
+        let mut merged = small.clone();
+        merged.merge(&large);
+        assert_eq!(merged.max_value, 1_000);
+        assert!(merged.contains(500));
+        merged.validate().unwrap();
+
+        let (a, b) = (small.to_capnp_bytes(), large.to_capnp_bytes());
+        let from_readers =
+            RoaringBitmap::merge_from_readers(&[RoaringBitmapReader::new(&a), RoaringBitmapReader::new(&b)]).unwrap();
+        assert_eq!(from_readers.max_value, 1_000);
+        assert_eq!(from_readers.to_vec(), vec![50, 500]);
+        from_readers.validate().unwrap();
+    }
+
+    #[test]
+    fn test_tdigest_estimated_error_smaller_at_tails() {
+        // Merge several shards so the median centroids are coarse
+        let mut td = TDigest::new(100);
+        for shard in 0..4 {
+            let mut part = TDigest::new(100);
+            for i in 0..2_500 {
+                let x = ((i * 4 + shard) * 7_919 % 10_000) as f64;
+                part.insert(x);
+            }
+            td.merge(&part);
+        }
+
+        let median = td.estimated_error(0.5);
+        assert!(median > 0.0);
+        assert!(median < 0.05);
+        assert!(td.estimated_error(0.01) < median);
+        assert!(td.estimated_error(0.99) < median);
+        assert!(td.estimated_error(0.999) <= td.estimated_error(0.99));
+        assert_eq!(TDigest::new(100).estimated_error(0.5), 0.0);
+    }
+
+    #[test]
+    fn test_topk_serialization_independent_of_insertion_order() {
+        let mut a = TopK::new(3, 100, 5);
+        for (item, count) in [("apple", 10), ("banana", 20), ("cherry", 5), ("date", 10)] {
+            a.increment(item, count);
+        }
+        let mut b = TopK::new(3, 100, 5);
+        for (item, count) in [("date", 10), ("cherry", 5), ("banana", 20), ("apple", 10)] {
+            b.increment(item, count);
+        }
+
+        // Same logical top-K, different internal heap order
+        assert_ne!(a, b);
+        assert_eq!(a.to_capnp_bytes(), b.to_capnp_bytes());
+        assert_eq!(TopK::from_capnp_bytes(&a.to_capnp_bytes()).unwrap().top_k()[0], ("banana".to_string(), 20));
+    }
+
+    #[test]
+    fn test_tdigest_merge_preserving_compresses_once() {
+        // 32 shards of a skewed distribution, fanned into one digest
+        let shards: Vec<Vec<f64>> = (0..32)
+            .map(|shard| {
+                (0..500)
+                    .map(|i| {
+                        let u = ((shard * 500 + i) * 7_919 % 16_000) as f64 / 16_000.0;
+                        u * u * 1_000.0
+                    })
+                    .collect()
+            })
+            .collect();
+        let mut all: Vec<f64> = shards.iter().flatten().copied().collect();
+        all.sort_by(f64::total_cmp);
+
+        let mut incremental = TDigest::new(50);
+        let mut once = TDigest::new(50);
+        for values in &shards {
+            let mut part = TDigest::new(50);
+            for &v in values {
+                part.insert(v);
+            }
+            incremental.merge(&part);
+            once.merge_preserving(&part);
+        }
+        // Queries before compressing already see every shard
+        assert_eq!(once.count, 16_000);
+        let before = once.quantile(0.5);
+        once.compress();
+        assert_eq!(once.quantile(0.5), before);
+
+        // Mean rank error across quantiles, as a fraction of the data
+        let rank_error = |td: &TDigest| {
+            let qs = [0.05, 0.1, 0.25, 0.5, 0.75, 0.9, 0.95, 0.99];
+            qs.iter()
+                .map(|&q| {
+                    let estimate = td.quantile(q);
+                    let rank = all.partition_point(|&v| v <= estimate) as f64 / all.len() as f64;
+                    (rank - q).abs()
+                })
+                .sum::<f64>()
+                / qs.len() as f64
+        };
+        let (err_once, err_incremental) = (rank_error(&once), rank_error(&incremental));
+        assert!(
+            err_once < err_incremental,
+            "once={} incremental={}",
+            err_once,
+            err_incremental
+        );
+        assert_eq!(once, incremental);
+    }
+
+    #[test]
+    fn test_equal_sketches_serialize_identically() {
+        // Same elements, different insertion and merge order
+        let mut hll1 = HyperLogLog::new();
+        for i in 0..1000 {
+            hll1.add(&i);
+        }
+        let mut hll2 = HyperLogLog::new();
+        let mut other = HyperLogLog::new();
+        for i in (0..500).rev() {
+            hll2.add(&i);
+        }
+        for i in 500..1000 {
+            other.add(&i);
+        }
+        hll2.merge(&other);
+
+        assert_eq!(hll1, hll2);
+        assert_eq!(hll1.to_capnp_bytes(), hll2.to_capnp_bytes());
+        assert_eq!(hll1.canonical_bytes().unwrap(), hll2.canonical_bytes().unwrap());
+        assert_eq!(HyperLogLog::from_capnp_bytes(&hll1.canonical_bytes().unwrap()).unwrap(), hll1);
+
+        let mut cms1 = CountMinSketch::new(64, 4);
+        let mut cms2 = CountMinSketch::new(64, 4);
+        cms1.increment("apple", 2);
+        cms1.increment("banana", 1);
+        cms2.increment("banana", 1);
+        cms2.increment("apple", 1);
+        cms2.increment("apple", 1);
+        assert_eq!(cms1.canonical_bytes().unwrap(), cms2.canonical_bytes().unwrap());
+        assert_eq!(CountMinSketch::from_capnp_bytes(&cms1.canonical_bytes().unwrap()).unwrap(), cms1);
+    }
+
+    #[test]
+    fn test_roaring_bitmap_optimize_shrinks_contiguous_range() {
+        let mut rb = RoaringBitmap::new(u32::MAX);
+        for value in 10_000..200_000 {
+            rb.insert(value);
+        }
+        let plain = rb.to_capnp_bytes();
+        let optimized = rb.optimized_bytes();
+        assert!(optimized.len() * 10 < plain.len(), "{} vs {}", optimized.len(), plain.len());
+
+        let decoded = RoaringBitmap::from_capnp_bytes(&optimized).unwrap();
+        assert_eq!(decoded, rb);
+        assert_eq!(decoded.to_vec(), rb.to_vec());
+
+        let mut in_place = rb.clone();
+        assert!(in_place.optimize());
+        assert_eq!(in_place, rb);
+        assert_eq!(in_place.to_capnp_bytes(), optimized);
+    }
+
+    #[test]
+    fn test_hyperloglog_cardinality_is_cached_until_change() {
+        let mut hll = HyperLogLog::new();
+        for i in 0..100 {
+            hll.add(&i);
+        }
+        assert_eq!(hll.cached_cardinality(), None);
+        let estimate = hll.cardinality();
+        assert_eq!(hll.cached_cardinality(), Some(estimate));
+        assert_eq!(hll.cardinality(), estimate);
+
+        // A duplicate changes no register, so the cache survives
+        assert!(!hll.add(&0));
+        assert_eq!(hll.cached_cardinality(), Some(estimate));
+
+        assert!(hll.add(&"new element"));
+        assert_eq!(hll.cached_cardinality(), None);
+        assert!(hll.cardinality() >= estimate);
+
+        // The cache is local: it affects neither equality nor the encoding
+        let fresh = HyperLogLog::from_capnp_bytes(&hll.to_capnp_bytes()).unwrap();
+        assert_eq!(fresh.cached_cardinality(), None);
+        assert_eq!(fresh, hll);
+        assert_eq!(fresh.to_capnp_bytes(), hll.to_capnp_bytes());
+    }
+
+    #[test]
+    fn test_tdigest_merge_keeps_higher_compression() {
+        let mut coarse = TDigest::new(50);
+        let mut fine = TDigest::new(200);
+        for i in 0..5_000 {
+            coarse.insert(i as f64);
+            fine.insert((i * 7 % 10_000) as f64);
+        }
+        let (coarse_bytes, fine_bytes) = (coarse.to_capnp_bytes(), fine.to_capnp_bytes());
+
+        let forward = TDigest::merge_from_readers(&[
+            TDigestReader::new(&coarse_bytes),
+            TDigestReader::new(&fine_bytes),
+        ])
+        .unwrap();
+        let backward = TDigest::merge_from_readers(&[
+            TDigestReader::new(&fine_bytes),
+            TDigestReader::new(&coarse_bytes),
+        ])
+        .unwrap();
+        assert_eq!(forward.compression(), 200);
+        assert_eq!(forward, backward);
+        assert_eq!(forward.centroids(), backward.centroids());
+
+        let mut in_memory = coarse.clone();
+        in_memory.merge(&fine);
+        let mut reversed = fine.clone();
+        reversed.merge(&coarse);
+        assert_eq!(in_memory.compression(), 200);
+        assert_eq!(in_memory.centroids(), reversed.centroids());
+        assert_eq!(in_memory.quantile(0.5), forward.quantile(0.5));
+    }
+
+    #[test]
+    fn test_tdigest_approx_eq() {
+        let values: Vec<f64> = (1..=1_000).map(|i| i as f64 * 0.1).collect();
+        let mut direct = TDigest::new(100);
+        for v in &values {
+            direct.insert(*v);
+        }
+        // Same data, summed in a different order through per-shard digests
+        let mut sharded = TDigest::new(100);
+        for shard in values.chunks(7).rev() {
+            let mut part = TDigest::new(100);
+            for v in shard {
+                part.insert(*v);
+            }
+            sharded.merge(&part);
+        }
+
+        assert!(direct.approx_eq(&sharded, 1e-9));
+        assert!(sharded.approx_eq(&direct, 1e-9));
+        let mut extra = sharded.clone();
+        extra.insert(0.1);
+        assert!(!direct.approx_eq(&extra, 1e-9));
+        let mut shifted = TDigest::new(100);
+        for v in &values {
+            shifted.insert(v * 1.01);
+        }
+        assert!(!direct.approx_eq(&shifted, 1e-3));
+        assert!(TDigest::new(100).approx_eq(&TDigest::new(100), 0.0));
+    }
+
+    #[test]
+    fn test_count_min_sketch_u32_counters_saturate_on_merge() {
+        let mut a = CountMinSketch::<u32>::with_dimensions(64, 4);
+        a.increment("hot", u32::MAX as u64 - 10);
+        let mut b = CountMinSketch::<u32>::with_dimensions(64, 4);
+        b.increment("hot", 100);
+        b.increment("cold", 3);
+
+        let (a_bytes, b_bytes) = (a.to_capnp_bytes(), b.to_capnp_bytes());
+        let merged = CountMinSketch::<u32>::merge_from_readers(&[
+            CountMinSketchReader::new(&a_bytes),
+            CountMinSketchReader::new(&b_bytes),
+        ])
+        .unwrap();
+        assert_eq!(merged.estimate("hot"), u32::MAX as u64);
+        assert!(merged.estimate("cold") >= 3);
+
+        a.merge(&b);
+        assert_eq!(a, merged);
+        let decoded = <CountMinSketch<u32> as Crdt>::from_capnp_bytes(&a.to_capnp_bytes()).unwrap();
+        assert_eq!(decoded, a);
+
+        // The counter width is part of the schema: widths never mix
+        let wide = CountMinSketch::new(64, 4).to_capnp_bytes();
+        assert!(matches!(
+            CountMinSketch::<u32>::merge_from_readers(&[
+                CountMinSketchReader::new(&a_bytes),
+                CountMinSketchReader::new(&wide),
+            ]),
+            Err(CrdtError::SchemaMismatch(_))
+        ));
+        assert!(CountMinSketch::from_capnp_bytes(&a_bytes).is_err());
+    }
+
+    #[test]
+    fn test_hyperloglog_dominates_by_registers() {
+        let mut small = HyperLogLog::new();
+        let mut large = HyperLogLog::new();
+        for i in 0..100u32 {
+            large.add(&i);
+            if i < 10 {
+                small.add(&i);
+            }
+        }
+        assert!(large.dominates(&small));
+        assert!(large.dominates(&large.clone()));
+        assert!(!small.dominates(&large));
+
+        let mut other = HyperLogLog::new();
+        other.add(&"not in large");
+        assert!(large.clone().merged(&other).dominates(&large));
+
+        let cms = CountMinSketch::new(16, 4);
+        assert!(!cms.dominates(&cms.clone()));
+    }
+
+    fn hll_message(registers: &[u8], version: Option<u8>) -> Vec<u8> {
+        let mut message = capnp::message::Builder::new_default();
+        let mut root = message.init_root::<crdt_data_types::hyperloglog_capnp::hyper_log_log::Builder>();
+        root.set_registers(registers);
+        if let Some(version) = version {
+            root.set_format_version(version);
+        }
+        let mut bytes = Vec::new();
+        capnp::serialize::write_message(&mut bytes, &message).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn test_hll_migrates_legacy_registers() {
+        let mut hll = HyperLogLog::new();
+        for i in 0..1000 {
+            hll.add(&i);
+        }
+        let json = serde_json::to_value(&hll).unwrap();
+        let registers: Vec<u8> = serde_json::from_value(json["registers"].clone()).unwrap();
+
+        // Version 0 ranks sat PRECISION (14) above the current ones
+        let legacy: Vec<u8> = registers.iter().map(|&r| if r == 0 { 0 } else { r + 14 }).collect();
+        let from_capnp = HyperLogLog::from_capnp_bytes(&hll_message(&legacy, None)).unwrap();
+        assert_eq!(from_capnp, hll);
+        let from_json: HyperLogLog =
+            serde_json::from_value(serde_json::json!({ "registers": legacy })).unwrap();
+        assert_eq!(from_json, hll);
+
+        // Legacy and current blobs merge on the same scale
+        let legacy_bytes = hll_message(&legacy, None);
+        let current_bytes = hll.to_capnp_bytes();
+        let merged = HyperLogLog::merge_from_readers(&[
+            crdt_data_types::HyperLogLogReader::new(&legacy_bytes),
+            crdt_data_types::HyperLogLogReader::new(&current_bytes),
+        ])
+        .unwrap();
+        assert_eq!(merged, hll);
+        assert_eq!(HyperLogLog::from_capnp_bytes(&current_bytes).unwrap(), hll);
+
+        // Versions from the future are rejected rather than misread
+        assert!(matches!(
+            HyperLogLog::from_capnp_bytes(&hll_message(&registers, Some(2))),
+            Err(CrdtError::Deserialization(_))
+        ));
+    }
+
+    #[test]
+    fn test_tdigest_decodes_json_without_centroids() {
+        // The shape written before centroids and compression were serialized
+        let json = serde_json::json!({ "min": 1.0, "max": 9.0, "sum": 15.0, "count": 3 });
+        let td: TDigest = serde_json::from_value(json).unwrap();
+        assert_eq!((td.min, td.max, td.sum, td.count), (1.0, 9.0, 15.0, 3));
+        assert_eq!(serde_json::from_value::<TDigest>(serde_json::to_value(&td).unwrap()).unwrap(), td);
+    }
+
+    #[test]
+    fn test_cms_lenient_merge_reports_dimension_mismatch() {
+        let narrow = CountMinSketch::new(100, 4).to_capnp_bytes();
+        let wide = CountMinSketch::new(200, 4).to_capnp_bytes();
+        let readers = [CountMinSketchReader::new(&narrow), CountMinSketchReader::new(&wide)];
+
+        let (merged, errors) = <CountMinSketch>::merge_from_readers_lenient(&readers).unwrap();
+        assert_eq!(merged.width, 100);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].0, 1);
+        assert!(matches!(errors[0].1, CrdtError::Merge(_)));
+    }
 }
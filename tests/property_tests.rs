@@ -411,6 +411,42 @@ proptest! {
     }
 }
 
+// Semantic Equality (wall-clock independent convergence)
+fn skew_wall_clock(vc: &mut VectorClock, offset: u64) {
+    for (_, ts) in vc.clocks.values_mut() {
+        *ts = ts.wrapping_add(offset);
+    }
+}
+
+macro_rules! test_semantic_convergence {
+    ($type:ident, $arb:expr) => {
+        paste::paste! {
+            proptest! {
+                #[test]
+                fn [< $type:lower _semantically_eq_ignores_wall_clock >](a in $arb, b in $arb, offset in 1u64..1_000_000u64) {
+                    let mut ab = a.clone();
+                    ab.merge(&b);
+                    let mut ba = b.clone();
+                    ba.merge(&a);
+
+                    skew_wall_clock(&mut ba.vclock, offset);
+                    prop_assert!(ab.semantically_eq(&ba));
+                    prop_assert!(ba.semantically_eq(&ab));
+                }
+            }
+        }
+    };
+}
+
+test_semantic_convergence!(GSet, arb_gset());
+test_semantic_convergence!(ORSet, arb_orset());
+test_semantic_convergence!(ORMap, arb_ormap());
+test_semantic_convergence!(MVRegister, arb_mvreg());
+test_semantic_convergence!(LWWRegister, arb_lwwreg());
+test_semantic_convergence!(FWWRegister, arb_fwwreg());
+test_semantic_convergence!(LWWMap, arb_lwwmap());
+test_semantic_convergence!(LWWSet, arb_lwwset());
+
 // Delta Equivalence Tests
 use crdt_data_types::deltas_capnp::delta;
 use capnp::serialize;
@@ -454,3 +490,35 @@ proptest! {
         prop_assert_eq!(res_json, res_capnp_as_json);
     }
 }
+
+// GCountedSet count convergence
+proptest! {
+    #[test]
+    fn gcounted_set_counts_converge(
+        ops in prop::collection::vec((0usize..3, "[a-c]"), 0..30),
+    ) {
+        // Each of three replicas applies its own adds, then they exchange state
+        let nodes = ["node1", "node2", "node3"];
+        let mut replicas: Vec<GCountedSet<String>> = vec![GCountedSet::new(); 3];
+        for (replica, element) in &ops {
+            replicas[*replica].insert(nodes[*replica], element.clone());
+        }
+
+        let mut forward = replicas[0].clone();
+        forward.merge(&replicas[1]);
+        forward.merge(&replicas[2]);
+
+        let mut backward = replicas[2].clone();
+        backward.merge(&replicas[1]);
+        backward.merge(&replicas[0]);
+        backward.merge(&replicas[0]);
+
+        prop_assert_eq!(&forward, &backward);
+        for element in ["a", "b", "c"] {
+            let element = element.to_string();
+            let expected = ops.iter().filter(|(_, e)| *e == element).count() as i64;
+            prop_assert_eq!(forward.count(&element), expected);
+            prop_assert_eq!(forward.contains(&element), expected > 0);
+        }
+    }
+}
@@ -0,0 +1,48 @@
+// Copyright (c) 2026 Adrian Robinson. All rights reserved.
+// Licensed under the MIT License. See LICENSE file in the project root for full license information.
+
+use crdt_data_types::replay::{replay, GCounterOp, LWWMapOp, ORSetOp, OpLog};
+use crdt_data_types::*;
+
+#[test]
+fn test_replay_reproduces_recorded_state() {
+    let mut counter = OpLog::attach(GCounter::new());
+    counter.apply("node_a", GCounterOp::Increment(3));
+    counter.apply("node_b", GCounterOp::Increment(4));
+    counter.apply("node_a", GCounterOp::Increment(5));
+    let rebuilt: GCounter = replay(counter.entries()).unwrap();
+    assert_eq!(rebuilt.counters, counter.state().counters);
+
+    let mut set = OpLog::attach(ORSet::new());
+    set.apply("node_a", ORSetOp::Insert("apple".to_string()));
+    set.apply("node_b", ORSetOp::Insert("banana".to_string()));
+    set.apply("node_b", ORSetOp::Remove("apple".to_string()));
+    set.apply("node_a", ORSetOp::Insert("cherry".to_string()));
+    let rebuilt: ORSet<String> = replay(set.entries()).unwrap();
+    assert_eq!(rebuilt.elements, set.state().elements);
+
+    let mut map = OpLog::attach(LWWMap::new());
+    map.apply("node_a", LWWMapOp::Insert { key: "k1".to_string(), value: 1, timestamp: 10 });
+    map.apply("node_b", LWWMapOp::Insert { key: "k1".to_string(), value: 2, timestamp: 20 });
+    map.apply("node_a", LWWMapOp::Insert { key: "k2".to_string(), value: 3, timestamp: 30 });
+    map.apply("node_b", LWWMapOp::Remove("k2".to_string()));
+    let (state, entries) = map.into_parts();
+    let rebuilt: LWWMap<String, i32> = replay(&entries).unwrap();
+    assert_eq!(rebuilt.entries, state.entries);
+    assert_eq!(rebuilt.get(&"k1".to_string()), Some(&2));
+}
+
+#[test]
+fn test_replay_reports_divergent_entries() {
+    let mut log = OpLog::attach(GCounter::new());
+    log.apply("node_a", GCounterOp::Increment(1));
+    log.apply("node_a", GCounterOp::Increment(1));
+
+    // Entries swapped out of causal order
+    let mut swapped = log.entries().to_vec();
+    swapped.swap(0, 1);
+    assert!(matches!(replay::<GCounter>(&swapped), Err(CrdtError::Validation(_))));
+
+    // A dropped entry means the next op no longer lands on its recorded dot
+    assert!(matches!(replay::<GCounter>(&log.entries()[1..]), Err(CrdtError::Validation(_))));
+}
@@ -0,0 +1,65 @@
+// Copyright (c) 2026 Adrian Robinson. All rights reserved.
+// Licensed under the MIT License. See LICENSE file in the project root for full license information.
+
+use crdt_data_types::*;
+use std::thread;
+
+#[test]
+fn test_shared_concurrent_merges_converge() {
+    let shared = Shared::new(GCounter::new());
+
+    let handles: Vec<_> = (0..8)
+        .map(|i| {
+            let shared = shared.clone();
+            thread::spawn(move || {
+                let node = format!("node{}", i);
+                let mut local = GCounter::new();
+                for _ in 0..50 {
+                    local.increment(&node, 1);
+                    shared.merge_bytes(&local.to_capnp_bytes()).unwrap();
+                }
+            })
+        })
+        .collect();
+
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    assert_eq!(shared.read(|gc| gc.value()), 8 * 50);
+
+    // Snapshot round-trips to the same state
+    let bytes = shared.snapshot();
+    let restored = GCounter::merge_from_readers(&[GCounterReader::new(&bytes)]).unwrap();
+    assert_eq!(restored.value(), 400);
+}
+
+#[test]
+fn test_shared_update_and_invalid_bytes() {
+    let shared = Shared::new(ORSet::<String>::new());
+    shared.update(|set| set.insert("node1", "apple".to_string()));
+    assert!(shared.read(|set| set.contains(&"apple".to_string())));
+
+    assert!(shared.merge_bytes(&[1, 2, 3]).is_err());
+    // A failed merge leaves the value untouched
+    assert_eq!(shared.read(|set| set.len()), 1);
+}
+
+#[test]
+fn test_frozen_view_unaffected_by_later_mutations() {
+    let shared = Shared::new(LWWMap::<String, i64>::new());
+    shared.update(|map| map.insert("node_a", "score".to_string(), 10, 1));
+
+    let view = shared.freeze();
+    let reader_view = view.clone();
+    shared.update(|map| {
+        map.insert("node_a", "score".to_string(), 20, 2);
+        map.insert("node_a", "level".to_string(), 3, 3);
+    });
+
+    let frozen = view.try_get().unwrap();
+    assert_eq!(frozen.get(&"score".to_string()), Some(&10));
+    assert!(!frozen.contains_key(&"level".to_string()));
+    assert!(thread::spawn(move || reader_view.try_get().unwrap().len() == 1).join().unwrap());
+    assert_eq!(shared.read(|map| map.len()), 2);
+}
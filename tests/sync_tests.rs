@@ -0,0 +1,78 @@
+// Copyright (c) 2026 Adrian Robinson. All rights reserved.
+// Licensed under the MIT License. See LICENSE file in the project root for full license information.
+
+use crdt_data_types::sync::{Coalescer, DeltaSince};
+use crdt_data_types::*;
+use std::time::{Duration, Instant};
+
+#[test]
+fn test_coalescer_burst_becomes_single_delta() {
+    let interval = Duration::from_millis(100);
+    let mut coalescer = Coalescer::new(interval);
+    let start = Instant::now();
+
+    let mut counter = GCounter::new();
+    for _ in 0..100 {
+        counter.increment("node1", 1);
+        assert!(coalescer.offer(&counter));
+    }
+
+    // One combined delta covering the net change
+    let delta = coalescer.poll_at(start).expect("pending delta");
+    assert_eq!(delta.value(), 100);
+    assert_eq!(delta, counter);
+    assert!(coalescer.poll_at(start).is_none());
+
+    // Re-offering an already emitted state is a no-op
+    assert!(!coalescer.offer(&counter));
+    assert!(!coalescer.has_pending());
+
+    // Further updates wait for the interval to elapse, and only they are emitted
+    let emitted = counter.clone();
+    counter.increment("node2", 5);
+    assert!(coalescer.offer(&counter));
+    assert!(coalescer.poll_at(start + interval / 2).is_none());
+    let delta = coalescer.poll_at(start + interval).expect("interval elapsed");
+    assert_eq!(delta.counters, vec![("node2".to_string(), 5)]);
+    assert_eq!(delta.vclock.clocks.keys().collect::<Vec<_>>(), vec!["node2"]);
+    assert_eq!(emitted.merged(&delta), counter);
+    assert_eq!(coalescer.watermark(), &counter.vclock);
+}
+
+#[test]
+fn test_coalescer_joins_updates_from_multiple_nodes() {
+    let mut coalescer = Coalescer::new(Duration::from_secs(1));
+
+    let mut a = GSet::new();
+    a.insert("node_a", "apple".to_string());
+    let mut b = GSet::new();
+    b.insert("node_b", "banana".to_string());
+
+    coalescer.offer(&a);
+    coalescer.offer(&b);
+    let delta = coalescer.flush().unwrap();
+    assert!(delta.contains(&"apple".to_string()));
+    assert!(delta.contains(&"banana".to_string()));
+}
+
+#[test]
+fn test_delta_since_carries_only_changes() {
+    let mut base = LWWSet::new();
+    base.insert("node_a", "apple".to_string(), 1);
+    base.insert("node_a", "pear".to_string(), 2);
+    let mut set = base.clone();
+    set.remove("node_b", "apple".to_string(), 3);
+    set.insert("node_b", "plum".to_string(), 4);
+
+    let delta = set.delta_since(&base);
+    assert_eq!(delta.add_set, vec![("plum".to_string(), (4, "node_b".to_string()))]);
+    assert_eq!(delta.remove_set, vec![("apple".to_string(), (3, "node_b".to_string()))]);
+    assert_eq!(base.merged(&delta), set);
+
+    let mut gset = GSet::new();
+    gset.insert("node_a", 1);
+    let mut grown = gset.clone();
+    grown.insert("node_a", 2);
+    assert_eq!(grown.delta_since(&gset).elements, vec![2]);
+    assert!(gset.delta_since(&gset).elements.is_empty());
+}
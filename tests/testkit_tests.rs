@@ -0,0 +1,134 @@
+// Copyright (c) 2026 Adrian Robinson. All rights reserved.
+// Licensed under the MIT License. See LICENSE file in the project root for full license information.
+
+#![cfg(feature = "testkit")]
+
+use crdt_data_types::testkit::{
+    assert_converges, assert_serialize_merge_equivalent, assert_would_converge, Op,
+};
+use crdt_data_types::*;
+
+enum SetOp {
+    Insert(&'static str),
+    Remove(&'static str),
+    RemoveBy(&'static str),
+}
+
+impl Op<ORSet<String>> for SetOp {
+    fn apply(&self, replica: &mut ORSet<String>, node_id: &str) {
+        match self {
+            SetOp::Insert(el) => replica.insert(node_id, el.to_string()),
+            SetOp::Remove(el) => replica.remove(&el.to_string()),
+            SetOp::RemoveBy(el) => replica.remove_by(node_id, &el.to_string()),
+        }
+    }
+}
+
+enum MapOp {
+    Insert(&'static str, i64, u64),
+    Remove(&'static str),
+}
+
+impl Op<LWWMap<String, i64>> for MapOp {
+    fn apply(&self, replica: &mut LWWMap<String, i64>, node_id: &str) {
+        match self {
+            MapOp::Insert(key, value, ts) => replica.insert(node_id, key.to_string(), *value, *ts),
+            MapOp::Remove(key) => replica.remove(&key.to_string()),
+        }
+    }
+}
+
+#[test]
+fn test_orset_converges_across_five_replicas() {
+    let ops = vec![
+        SetOp::Insert("apple"),
+        SetOp::Insert("banana"),
+        SetOp::Remove("apple"),
+        SetOp::Insert("cherry"),
+        SetOp::Insert("apple"),
+        SetOp::Remove("banana"),
+        SetOp::Insert("date"),
+        SetOp::Remove("cherry"),
+        SetOp::Insert("banana"),
+    ];
+    assert_converges(vec![ORSet::new(); 5], ops);
+}
+
+#[test]
+fn test_remove_wins_orset_converges_across_five_replicas() {
+    let ops = vec![
+        SetOp::Insert("apple"),
+        SetOp::Insert("banana"),
+        SetOp::RemoveBy("apple"),
+        SetOp::Insert("apple"),
+        SetOp::RemoveBy("banana"),
+        SetOp::Insert("cherry"),
+        SetOp::Remove("cherry"),
+        SetOp::Insert("banana"),
+        SetOp::RemoveBy("apple"),
+        SetOp::Insert("apple"),
+    ];
+    let set = ORSet::new().with_policy(ConflictPolicy::RemoveWins);
+    assert_converges(vec![set; 5], ops);
+}
+
+#[test]
+fn test_lwwmap_converges_across_five_replicas() {
+    let ops = vec![
+        MapOp::Insert("a", 1, 10),
+        MapOp::Insert("b", 2, 11),
+        MapOp::Insert("a", 3, 12),
+        MapOp::Remove("b"),
+        MapOp::Insert("c", 4, 12),
+        MapOp::Insert("c", 5, 12),
+        MapOp::Insert("b", 6, 13),
+        MapOp::Remove("a"),
+    ];
+    assert_converges(vec![LWWMap::new(); 5], ops);
+}
+
+#[test]
+fn test_serialize_merge_equivalence_for_gcounter_and_orset() {
+    let mut a = GCounter::new();
+    a.increment("node_a", 3);
+    a.increment("node_b", 1);
+    let mut b = GCounter::new();
+    b.increment("node_b", 4);
+    b.increment("node_c", 2);
+    assert_eq!(assert_serialize_merge_equivalent(a, b).value(), 9);
+
+    let mut a = ORSet::new();
+    a.insert("node_a", "apple".to_string());
+    a.insert("node_a", "banana".to_string());
+    a.remove(&"banana".to_string());
+    let mut b = ORSet::new();
+    b.insert("node_b", "banana".to_string());
+    b.insert("node_b", "cherry".to_string());
+    let merged = assert_serialize_merge_equivalent(a, b);
+    assert_eq!(merged.len(), 3);
+
+    assert_serialize_merge_equivalent(GCounter::new(), GCounter::new());
+}
+
+#[test]
+fn test_would_converge_reports_divergent_keys() {
+    let mut a = ORSet::new();
+    a.insert("node_a", "apple".to_string());
+    a.insert("node_a", "banana".to_string());
+    let mut b = a.clone();
+    a.remove(&"banana".to_string());
+    b.insert("node_b", "cherry".to_string());
+    assert_eq!(assert_would_converge(&a, &b), Ok(()));
+
+    // Two writes sharing a timestamp and node id: FWW keeps whichever side merges into it.
+    let value = |pairs: [(&str, i64); 2]| -> std::collections::BTreeMap<String, i64> {
+        pairs.into_iter().map(|(k, v)| (k.to_string(), v)).collect()
+    };
+    let a = FWWRegister::new(value([("x", 1), ("y", 2)]), 10, "node_a");
+    let b = FWWRegister::new(value([("x", 1), ("z", 3)]), 10, "node_a");
+    let report = assert_would_converge(&a, &b).unwrap_err();
+    assert!(report.contains("2 location(s)"), "{report}");
+    assert!(report.contains("/value/y: only in a.merge(b) = 2"), "{report}");
+    assert!(report.contains("/value/z: only in b.merge(a) = 3"), "{report}");
+    assert!(!report.contains("/value/x"), "{report}");
+}
@@ -0,0 +1,82 @@
+// Copyright (c) 2026 Adrian Robinson. All rights reserved.
+// Licensed under the MIT License. See LICENSE file in the project root for full license information.
+
+#![cfg(feature = "tracing")]
+
+use crdt_data_types::compaction::compact_capnp_bytes;
+use crdt_data_types::{Crdt, CrdtType, GCounter, SerdeCapnpBridge};
+use serde_json::json;
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber};
+
+/// Collects the fields of every event as `name=value` strings.
+#[derive(Clone, Default)]
+struct Capture {
+    events: Arc<Mutex<Vec<Vec<String>>>>,
+}
+
+struct Fields(Vec<String>);
+
+impl Visit for Fields {
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        self.0.push(format!("{}={:?}", field.name(), value));
+    }
+}
+
+impl Subscriber for Capture {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+    fn new_span(&self, _: &Attributes<'_>) -> Id {
+        Id::from_u64(1)
+    }
+    fn record(&self, _: &Id, _: &Record<'_>) {}
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+    fn event(&self, event: &Event<'_>) {
+        let mut fields = Fields(Vec::new());
+        event.record(&mut fields);
+        self.events.lock().unwrap().push(fields.0);
+    }
+    fn enter(&self, _: &Id) {}
+    fn exit(&self, _: &Id) {}
+}
+
+#[test]
+fn test_compact_capnp_bytes_emits_merge_event() {
+    let mut a = GCounter::new();
+    a.increment("node_a", 1);
+    let mut b = GCounter::new();
+    b.increment("node_b", 2);
+    let (a_bytes, b_bytes) = (a.to_capnp_bytes(), b.to_capnp_bytes());
+
+    let capture = Capture::default();
+    let merged = tracing::subscriber::with_default(capture.clone(), || {
+        compact_capnp_bytes(CrdtType::GCounter, &[&a_bytes, &b_bytes]).unwrap()
+    });
+
+    let events = capture.events.lock().unwrap();
+    assert_eq!(events.len(), 1);
+    let fields = &events[0];
+    assert!(fields.contains(&"op=\"compact\"".to_string()));
+    assert!(fields.contains(&"crdt_type=GCounter".to_string()));
+    assert!(fields.contains(&"input_count=2".to_string()));
+    assert!(fields.contains(&"output_elements=2".to_string()));
+    assert!(fields.contains(&format!("output_bytes={}", merged.len())));
+    assert!(fields.iter().any(|f| f.starts_with("elapsed_us=")));
+}
+
+#[test]
+fn test_json_to_capnp_bytes_emits_conversion_event() {
+    let capture = Capture::default();
+    tracing::subscriber::with_default(capture.clone(), || {
+        SerdeCapnpBridge::json_to_capnp_bytes(CrdtType::GSet, json!({"elements": ["a", "b", "c"]})).unwrap()
+    });
+
+    let events = capture.events.lock().unwrap();
+    assert_eq!(events.len(), 1);
+    assert!(events[0].contains(&"op=\"json_to_capnp_bytes\"".to_string()));
+    assert!(events[0].contains(&"output_elements=3".to_string()));
+}